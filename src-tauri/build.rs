@@ -1,3 +1,30 @@
+/// Dependencies whose resolved versions `build_info` reports.
+const REPORTED_DEPENDENCIES: &[(&str, &str)] = &[
+    ("sled", "INGAT_DEP_SLED"),
+    ("fastembed", "INGAT_DEP_FASTEMBED"),
+    ("rmcp", "INGAT_DEP_RMCP"),
+    ("tauri", "INGAT_DEP_TAURI"),
+];
+
 fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let lock = std::fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for (name, var) in REPORTED_DEPENDENCIES {
+        let version = locked_version(&lock, name).unwrap_or("unknown");
+        println!("cargo:rustc-env={var}={version}");
+    }
+
     tauri_build::build()
 }
+
+/// Version of the first `[[package]]` entry named `name` in `Cargo.lock`.
+fn locked_version<'a>(lock: &'a str, name: &str) -> Option<&'a str> {
+    let wanted = format!("name = \"{name}\"");
+    let mut lines = lock.lines();
+    lines.by_ref().find(|line| line.trim() == wanted)?;
+    lines
+        .next()?
+        .trim()
+        .strip_prefix("version = \"")?
+        .strip_suffix('"')
+}
@@ -4,12 +4,29 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::domain::{ContextKind, ContextSummary, QueryFilters, RetrievalQuery};
+use crate::domain::{
+    normalize_tag, parse_filter_expr, ContextKind, ContextSummary, DiffLine, DomainError,
+    EmbeddingComposition, HistoryOrder, QueryFilters, RetrievalQuery, SearchScope, SourceMeta,
+};
+
+/// Upper bound on results returned by a single semantic search.
+pub const MAX_SEARCH_LIMIT: usize = 32;
+
+/// Hard safety cap on results for searches sent with `export_mode`.
+pub const MAX_EXPORT_LIMIT: usize = 1_000;
+
+/// Upper bound on ids accepted by a single batch delete.
+pub const MAX_DELETE_BATCH: usize = 1_000;
+
+/// Upper bound on ids accepted by a single batch get.
+pub const MAX_FETCH_BATCH: usize = 1_000;
 
 /// Payload accepted from MCP clients or the UI when persisting a new context item.
 #[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IngestContextRequest {
+    /// Required by the service; MCP clients may omit it and fall back to their workspace root.
+    #[serde(default)]
     pub project: String,
     pub ide: String,
     pub file_path: Option<String>,
@@ -19,6 +36,21 @@ pub struct IngestContextRequest {
     pub tags: Vec<String>,
     #[serde(default)]
     pub kind: ContextKind,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Embed with this model instead of the active one (must match its dimensions).
+    #[serde(default)]
+    pub model_override: Option<String>,
+    /// Structured provenance. When absent, extra lines of a multi-line `ide`
+    /// are read as version and workspace.
+    #[serde(default)]
+    pub source: Option<SourceMeta>,
+    /// `true` returns only once the write is on disk. Left out, the store may
+    /// batch the flush with other writes. Bulk importers may pass `false` on
+    /// all but the last call; deferred writes become durable with the next
+    /// flushing write or the store's background flush.
+    #[serde(default)]
+    pub flush: Option<bool>,
 }
 
 /// DTO bridging the UI search form and the application layer.
@@ -30,16 +62,399 @@ pub struct SearchRequest {
     pub filters: QueryFilters,
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Drop results scoring below this similarity.
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Field to match against; non-default scopes require field embeddings.
+    #[serde(default)]
+    pub scope: SearchScope,
+    /// Embed the prompt with this model instead of the active one (must match its dimensions).
+    #[serde(default)]
+    pub model_override: Option<String>,
+    /// Filter expression such as `tag:rust|go tag:async`; replaces `filters` when set.
+    #[serde(default)]
+    pub filter_expr: Option<String>,
+    /// Treat tag filters as ranking hints: records carrying a requested tag get
+    /// a score bonus per matching tag instead of non-matches being excluded.
+    /// Boosted scores may exceed 1.0.
+    #[serde(default)]
+    pub soft_filter: bool,
+    /// Only match records created after this instant, e.g. the time of the
+    /// previous poll. A future instant matches nothing.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Bulk retrieval for reports and exports: `limit` may go up to
+    /// [`MAX_EXPORT_LIMIT`] instead of [`MAX_SEARCH_LIMIT`]. Slower, and
+    /// rate-limited by mcp-service; not meant for interactive use.
+    #[serde(default)]
+    pub export_mode: bool,
+    /// File path the query is about (e.g. `src/auth.rs`). Added to the
+    /// embedded query only when the service embeds file paths.
+    #[serde(default)]
+    pub path_hint: Option<String>,
+    /// Keep only the best-scoring hit per chunked record (the record itself
+    /// or one of its chunks), annotated with how many of its chunks matched.
+    #[serde(default)]
+    pub collapse_by_parent: bool,
+    /// At most this many results from any one project, keeping score order;
+    /// unset means no cap.
+    #[serde(default)]
+    pub max_per_project: Option<usize>,
+    /// How `created_at`/`updated_at` are written in the response (REST and MCP).
+    #[serde(default)]
+    pub timestamp_format: TimestampFormat,
+}
+
+impl SearchRequest {
+    pub fn builder(prompt: impl Into<String>) -> SearchRequestBuilder {
+        SearchRequestBuilder::new(prompt)
+    }
+
+    /// Largest `limit` honoured for this request.
+    pub fn max_limit(&self) -> usize {
+        if self.export_mode {
+            MAX_EXPORT_LIMIT
+        } else {
+            MAX_SEARCH_LIMIT
+        }
+    }
+
+    /// Compile `filter_expr`, if any, into `filters`.
+    pub fn resolve_filter_expr(mut self) -> Result<Self, DomainError> {
+        if let Some(expr) = self.filter_expr.take() {
+            self.filters = parse_filter_expr(&expr)?;
+        }
+        Ok(self)
+    }
 }
 
 impl From<SearchRequest> for RetrievalQuery {
-    fn from(value: SearchRequest) -> Self {
+    fn from(mut value: SearchRequest) -> Self {
+        value.filters.since = value.since.or(value.filters.since);
         Self {
             prompt: value.prompt,
             filters: value.filters,
             limit: value.limit,
+            min_score: value.min_score,
+            scope: value.scope,
+        }
+    }
+}
+
+/// Chainable constructor producing a validated [`SearchRequest`].
+///
+/// ```
+/// use ingat_lib::application::SearchRequestBuilder;
+/// use ingat_lib::domain::ContextKind;
+///
+/// let request = SearchRequestBuilder::new("retry failed http calls")
+///     .project("ingat")
+///     .kind(ContextKind::FixHistory)
+///     .tag("HTTP Client")
+///     .limit(5)
+///     .min_score(0.25)
+///     .build()
+///     .expect("valid search request");
+///
+/// assert_eq!(request.filters.tag.as_deref(), Some("http-client"));
+/// assert_eq!(request.limit, 5);
+///
+/// let request = SearchRequestBuilder::new("async runtimes")
+///     .filter_expr("tag:rust|go tag:async")
+///     .build()
+///     .expect("valid filter expression");
+/// assert_eq!(request.filters.groups.len(), 2);
+///
+/// assert!(SearchRequestBuilder::new("   ").build().is_err());
+/// assert!(SearchRequestBuilder::new("query").min_score(1.5).build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchRequestBuilder {
+    prompt: String,
+    filters: QueryFilters,
+    limit: usize,
+    min_score: Option<f32>,
+    scope: SearchScope,
+    model_override: Option<String>,
+    filter_expr: Option<String>,
+    soft_filter: bool,
+    since: Option<DateTime<Utc>>,
+    export_mode: bool,
+    path_hint: Option<String>,
+    collapse_by_parent: bool,
+    max_per_project: Option<usize>,
+    timestamp_format: TimestampFormat,
+}
+
+impl SearchRequestBuilder {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            filters: QueryFilters::default(),
+            limit: default_limit(),
+            min_score: None,
+            scope: SearchScope::default(),
+            model_override: None,
+            filter_expr: None,
+            soft_filter: false,
+            since: None,
+            export_mode: false,
+            path_hint: None,
+            collapse_by_parent: false,
+            max_per_project: None,
+            timestamp_format: TimestampFormat::default(),
+        }
+    }
+
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.filters.project = Some(project.into());
+        self
+    }
+
+    pub fn kind(mut self, kind: ContextKind) -> Self {
+        self.filters.kind = Some(kind);
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.filters.tag = Some(tag.into());
+        self
+    }
+
+    pub fn ide(mut self, ide: impl Into<String>) -> Self {
+        self.filters.ide = Some(ide.into());
+        self
+    }
+
+    pub fn ide_version(mut self, version: impl Into<String>) -> Self {
+        self.filters.ide_version = Some(version.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    pub fn scope(mut self, scope: SearchScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn model_override(mut self, model: impl Into<String>) -> Self {
+        self.model_override = Some(model.into());
+        self
+    }
+
+    /// Replace the structured filters with a parsed filter expression.
+    pub fn filter_expr(mut self, expr: impl Into<String>) -> Self {
+        self.filter_expr = Some(expr.into());
+        self
+    }
+
+    /// Boost records matching the tag filters instead of excluding the rest.
+    pub fn soft_filter(mut self, soft: bool) -> Self {
+        self.soft_filter = soft;
+        self
+    }
+
+    /// Only match records created after `since`.
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Allow limits up to [`MAX_EXPORT_LIMIT`] for bulk export callers.
+    pub fn export_mode(mut self, export: bool) -> Self {
+        self.export_mode = export;
+        self
+    }
+
+    /// Steer the query toward records from this file path.
+    pub fn path_hint(mut self, path: impl Into<String>) -> Self {
+        self.path_hint = Some(path.into());
+        self
+    }
+
+    /// Return one hit per chunked record instead of every matching chunk.
+    pub fn collapse_by_parent(mut self, collapse: bool) -> Self {
+        self.collapse_by_parent = collapse;
+        self
+    }
+
+    /// Spread results across projects: at most `max` from any one.
+    pub fn max_per_project(mut self, max: usize) -> Self {
+        self.max_per_project = Some(max);
+        self
+    }
+
+    pub fn timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.timestamp_format = format;
+        self
+    }
+
+    /// Validate the accumulated options and produce the request.
+    pub fn build(self) -> Result<SearchRequest, DomainError> {
+        let Self {
+            prompt,
+            filters,
+            limit,
+            min_score,
+            scope,
+            model_override,
+            filter_expr,
+            soft_filter,
+            since,
+            export_mode,
+            path_hint,
+            collapse_by_parent,
+            max_per_project,
+            timestamp_format,
+        } = self;
+
+        if prompt.trim().is_empty() {
+            return Err(DomainError::validation("prompt cannot be empty"));
+        }
+        let max_limit = if export_mode {
+            MAX_EXPORT_LIMIT
+        } else {
+            MAX_SEARCH_LIMIT
+        };
+        if !(1..=max_limit).contains(&limit) {
+            return Err(DomainError::validation(format!(
+                "limit must be between 1 and {max_limit}"
+            )));
+        }
+        if let Some(score) = min_score {
+            if !score.is_finite() || !(-1.0..=1.0).contains(&score) {
+                return Err(DomainError::validation(
+                    "min_score must be a finite value between -1.0 and 1.0",
+                ));
+            }
+        }
+        if max_per_project == Some(0) {
+            return Err(DomainError::validation(
+                "max_per_project must be at least 1",
+            ));
         }
+        let filters = match filter_expr {
+            Some(expr) => parse_filter_expr(&expr)?,
+            None => validate_filters(filters)?,
+        };
+
+        Ok(SearchRequest {
+            prompt,
+            filters,
+            limit,
+            min_score,
+            scope,
+            model_override,
+            filter_expr: None,
+            soft_filter,
+            since,
+            export_mode,
+            path_hint,
+            collapse_by_parent,
+            max_per_project,
+            timestamp_format,
+        })
+    }
+}
+
+/// Reject empty filter values and normalize the tag filter like stored tags.
+pub fn validate_filters(mut filters: QueryFilters) -> Result<QueryFilters, DomainError> {
+    if filters
+        .project
+        .as_deref()
+        .is_some_and(|project| project.trim().is_empty())
+    {
+        return Err(DomainError::validation("project filter cannot be empty"));
     }
+    if filters
+        .ide
+        .as_deref()
+        .is_some_and(|ide| ide.trim().is_empty())
+    {
+        return Err(DomainError::validation("ide filter cannot be empty"));
+    }
+    if let Some(tag) = filters.tag.take() {
+        let normalized =
+            normalize_tag(tag).ok_or_else(|| DomainError::validation("tag cannot be empty"))?;
+        filters.tag = Some(normalized);
+    }
+    filters.kind = filters.kind.map(ContextKind::normalized).transpose()?;
+    Ok(filters)
+}
+
+/// Ids to remove in one atomic batch; see `ContextService::delete_many`.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteContextsRequest {
+    #[cfg_attr(feature = "mcp-server", schemars(with = "Vec<String>"))]
+    pub ids: Vec<Uuid>,
+}
+
+/// Id of one context to remove; see `ContextService::delete`.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteContextRequest {
+    #[cfg_attr(feature = "mcp-server", schemars(with = "String"))]
+    pub id: Uuid,
+}
+
+/// Ids of full records to load in one call; see `ContextService::get_many`.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchContextsRequest {
+    #[cfg_attr(feature = "mcp-server", schemars(with = "Vec<String>"))]
+    pub ids: Vec<Uuid>,
+}
+
+/// A search result that was used; see `ContextService::record_feedback`.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackRequest {
+    /// `query_id` of the search response the result came from.
+    #[cfg_attr(feature = "mcp-server", schemars(with = "String"))]
+    pub query_id: Uuid,
+    #[cfg_attr(feature = "mcp-server", schemars(with = "String"))]
+    pub chosen_id: Uuid,
+}
+
+/// Readout of the search analytics collected so far.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedbackStats {
+    /// Whether new searches are being recorded.
+    pub enabled: bool,
+    pub searches: usize,
+    /// Searches followed by at least one used result.
+    pub with_feedback: usize,
+    /// Used results across all searches.
+    pub choices: usize,
+    /// How often the result at each position was used (index 0 = top result).
+    pub rank_counts: Vec<usize>,
+    /// Used results the search had not returned.
+    pub outside_results: usize,
+    /// Mean of `1 / (rank + 1)` over searches with feedback, taking the best
+    /// ranked result used; a result that wasn't returned counts as 0.
+    pub mean_reciprocal_rank: f64,
+}
+
+/// Narrow an earlier result set; see `ContextService::refine`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefineSearchRequest {
+    pub previous: Vec<Uuid>,
+    #[serde(default)]
+    pub filters: QueryFilters,
+    /// Re-rank against this prompt; omitted keeps the previous order.
+    #[serde(default)]
+    pub prompt: Option<String>,
 }
 
 /// Result row returned from semantic retrieval.
@@ -55,6 +470,19 @@ pub struct SearchResultDto {
     pub kind: ContextKind,
     pub score: f32,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceMeta>,
+    /// Set when this hit is a chunk: `body` holds only the matched window of
+    /// the record with this id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "mcp-server", schemars(with = "Option<String>"))]
+    pub parent_id: Option<Uuid>,
+    /// Hits of this record and its chunks folded into this one by
+    /// `collapse_by_parent`; only set on chunked records.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_matches: Option<usize>,
 }
 
 /// Response envelope for search operations.
@@ -63,6 +491,33 @@ pub struct SearchResultDto {
 pub struct SearchResponse {
     pub query: String,
     pub results: Vec<SearchResultDto>,
+    /// Records the semantic search read; 0 for searches that don't scan (keyword).
+    #[serde(default)]
+    pub scanned: usize,
+    /// Scanned records that passed the filters before scoring and `limit`.
+    #[serde(default)]
+    pub matched_filters: usize,
+    /// Results the search could have returned before `limit` cut the list;
+    /// compare with `results.len()` for a "showing 8 of 142" label. Keyword
+    /// searches stop counting one past `limit`, and searches that collapse
+    /// chunks or cap projects only count their overfetched candidates, so
+    /// there it is a lower bound.
+    #[serde(default)]
+    pub total_matched: usize,
+    /// More matches exist beyond `limit`; refine the query or raise the limit.
+    #[serde(default)]
+    pub has_more: bool,
+    /// Whether any result was returned; check this before citing memory.
+    #[serde(default)]
+    pub found: bool,
+    /// Guidance for agents when nothing was found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// With search analytics on, pass this to `record_feedback` along with
+    /// the id of the result that was used.
+    #[cfg_attr(feature = "mcp-server", schemars(with = "Option<String>"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_id: Option<Uuid>,
 }
 
 /// Simple projection for timeline/history listings.
@@ -72,6 +527,196 @@ pub struct SummaryListResponse {
     pub items: Vec<ContextSummary>,
 }
 
+/// Parameters for recency listings, optionally scoped to a project and/or tag.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentContextsRequest {
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Only contexts created after this instant.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Sort field and direction; newest first when omitted.
+    #[serde(default, flatten)]
+    pub order: HistoryOrder,
+    #[serde(default)]
+    pub timestamp_format: TimestampFormat,
+}
+
+/// Wire format of `created_at`/`updated_at` in responses, for clients that
+/// can't easily parse RFC 3339.
+///
+/// ```
+/// use ingat_lib::application::TimestampFormat;
+///
+/// let mut value = serde_json::json!({
+///     "results": [{ "created_at": "2024-05-01T12:00:00.250Z", "summary": "kept" }],
+///     "updated_at": null,
+/// });
+/// TimestampFormat::EpochMillis.apply(&mut value);
+/// assert_eq!(value["results"][0]["created_at"], 1_714_564_800_250_i64);
+/// assert!(value["updated_at"].is_null());
+/// ```
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    #[default]
+    Rfc3339,
+    EpochMillis,
+    EpochSecs,
+}
+
+impl TimestampFormat {
+    /// Rewrite every RFC 3339 `created_at`/`updated_at` string in `value`,
+    /// at any depth. `Rfc3339` leaves it untouched.
+    pub fn apply(self, value: &mut serde_json::Value) {
+        if self == Self::Rfc3339 {
+            return;
+        }
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (key, field) in fields.iter_mut() {
+                    let instant = matches!(key.as_str(), "created_at" | "updated_at")
+                        .then(|| field.as_str())
+                        .flatten()
+                        .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok());
+                    match instant {
+                        Some(instant) if self == Self::EpochSecs => {
+                            *field = instant.timestamp().into();
+                        }
+                        Some(instant) => *field = instant.timestamp_millis().into(),
+                        None => self.apply(field),
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.apply(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Occurrence count for a kind or tag within an aggregate.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelCount {
+    pub label: String,
+    pub count: usize,
+}
+
+/// Landing-page aggregate for a single project.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectOverview {
+    pub project: String,
+    pub total: usize,
+    pub kinds: Vec<LabelCount>,
+    pub top_tags: Vec<LabelCount>,
+    pub earliest: Option<DateTime<Utc>>,
+    pub latest: Option<DateTime<Utc>>,
+    pub recent: Vec<ContextSummary>,
+}
+
+/// A project with its record count and most recent ingest, for recency-sorted listings.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub count: usize,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// Side-by-side comparison of two contexts; see `ContextService::diff`.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextDiff {
+    pub a: ContextSummary,
+    pub b: ContextSummary,
+    /// Cosine similarity of the stored vectors; `None` when either is archived
+    /// or their dimensions differ.
+    pub similarity: Option<f32>,
+    pub same_project: bool,
+    pub same_kind: bool,
+    pub shared_tags: Vec<String>,
+    pub tags_only_in_a: Vec<String>,
+    pub tags_only_in_b: Vec<String>,
+    /// Line diff from `a`'s body to `b`'s.
+    pub body: Vec<DiffLine>,
+}
+
+/// A project whose tags overlap with another one's; see `ContextService::related_projects`.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedProject {
+    pub name: String,
+    /// Jaccard similarity of the two projects' tag sets, in `(0, 1]`.
+    pub score: f32,
+    /// Tags both projects use, sorted.
+    pub shared_tags: Vec<String>,
+}
+
+/// How much space the store takes; see `ContextService::storage_stats`.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageStats {
+    /// Sum of file sizes under the store directory; `None` in remote mode.
+    pub disk_bytes: Option<u64>,
+    pub records: usize,
+    /// Average on-disk bytes per record, including index and log overhead.
+    pub bytes_per_record: Option<u64>,
+}
+
+/// Progress of `ContextService::reembed_all`, reported after every batch.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReembedProgress {
+    /// Records visited so far, including ones already on the active model.
+    pub processed: usize,
+    pub total: usize,
+    /// Records whose vectors were actually replaced.
+    pub reembedded: usize,
+    pub cancelled: bool,
+}
+
+/// Effective configuration of the running process, for diagnostics and bug reports.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeConfigResponse {
+    pub version: String,
+    /// `local` (embedded store) or `remote` (proxying to mcp-service).
+    pub mode: String,
+    pub data_dir: String,
+    /// Embedded store location; `None` in remote mode.
+    pub store_dir: Option<String>,
+    pub active_backend: String,
+    pub embedding_model: String,
+    pub dimensions: Option<usize>,
+    pub service_host: String,
+    pub service_port: u16,
+    /// Cargo features compiled into this binary.
+    pub features: Vec<String>,
+}
+
+/// What a binary was built with, for bug reports.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,
+    /// Cargo features compiled into this binary.
+    pub features: Vec<String>,
+    /// Resolved versions of key dependencies, from `Cargo.lock` at build time.
+    /// Listed even when the dependency's feature is off.
+    pub dependencies: std::collections::BTreeMap<String, String>,
+    /// `debug` or `release`.
+    pub profile: String,
+    pub target_os: String,
+    pub target_arch: String,
+}
+
 /// Health/readiness report for diagnostics.
 #[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +724,250 @@ pub struct HealthStatusResponse {
     pub ok: bool,
     pub message: String,
     pub details: Option<String>,
+    #[serde(default)]
+    pub composition: Vec<EmbeddingComposition>,
+    /// `local` or `remote`; filled in by the desktop app, which can switch at runtime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Records re-embedded in the background by searches (`lazy_reembed`).
+    #[serde(default)]
+    pub lazy_reembedded: usize,
+    #[serde(default)]
+    pub state: ServiceState,
+}
+
+/// Whether the service accepts writes. During maintenance, writes fail fast
+/// with [`DomainError::Busy`] while reads keep working.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ServiceState {
+    #[default]
+    Idle,
+    Maintenance {
+        op: MaintenanceOp,
+    },
+}
+
+/// Long-running operation that holds the store.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceOp {
+    Reembed,
+    RebuildIndex,
+    RebuildTermStats,
+}
+
+impl MaintenanceOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reembed => "reembed",
+            Self::RebuildIndex => "rebuild_index",
+            Self::RebuildTermStats => "rebuild_term_stats",
+        }
+    }
+}
+
+/// Breakdown of the embedding models present in the store versus the active one.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreCompositionResponse {
+    pub active_model: String,
+    pub active_dimensions: Option<usize>,
+    pub total: usize,
+    pub entries: Vec<EmbeddingComposition>,
+}
+
+/// Integrity problem found on one stored embedding.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum VerifyProblem {
+    WrongDimensions { expected: usize, actual: usize },
+    ZeroVector,
+    NonFinite,
+}
+
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyIssue {
+    #[cfg_attr(feature = "mcp-server", schemars(with = "String"))]
+    pub id: Uuid,
+    /// `embedding`, `summary_embedding` or `body_embedding`.
+    pub field: String,
+    #[serde(flatten)]
+    pub problem: VerifyProblem,
+}
+
+/// Outcome of a full-store integrity pass.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub total: usize,
+    pub healthy: usize,
+    /// Records that could not be decoded at all.
+    pub unreadable: usize,
+    pub issues: Vec<VerifyIssue>,
+}
+
+/// Result of moving the data directory; the old directory is kept as a backup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataMigrationResponse {
+    pub from: String,
+    pub to: String,
+    /// Records verified in the new store.
+    pub records: usize,
+}
+
+/// Latency/throughput measurements for the active embedding backend.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingBenchmark {
+    pub model: String,
+    pub samples: usize,
+    pub dimensions: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub embeddings_per_second: f64,
+}
+
+/// Outcome of exercising an embedding backend in isolation, before it
+/// replaces the active one.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingSelfTest {
+    pub backend: String,
+    pub model: String,
+    pub dimensions: usize,
+    /// Dimension of the active backend; a different value means switching
+    /// needs a re-embed before old records are searchable.
+    pub active_dimensions: Option<usize>,
+    pub init_ms: f64,
+    /// Mean latency of the probe embeddings.
+    pub embed_ms: f64,
+    /// Embedding the same text twice gave the same vector.
+    pub deterministic: bool,
+    /// Cosine between two phrasings of the same sentence.
+    pub related_score: f32,
+    /// Cosine between that sentence and an unrelated one.
+    pub unrelated_score: f32,
+    /// Problems found on the probe vectors themselves.
+    pub problems: Vec<VerifyProblem>,
+    /// No problems, deterministic, and related text outscores unrelated text.
+    pub passed: bool,
+}
+
+/// Scripted ingests and searches run by `ContextService::simulate_session`.
+/// The default scenario exercises the ingest -> search -> recall loop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationScenario {
+    /// Project every simulated record is filed under.
+    #[serde(default = "default_simulation_project")]
+    pub project: String,
+    pub steps: Vec<SimulationStep>,
+    /// Delete the simulated records afterwards.
+    #[serde(default = "default_true")]
+    pub cleanup: bool,
+}
+
+/// One step of a [`SimulationScenario`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SimulationStep {
+    Ingest {
+        summary: String,
+        body: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        kind: ContextKind,
+    },
+    Search {
+        prompt: String,
+        #[serde(default)]
+        limit: Option<usize>,
+        /// Indexes of earlier `ingest` steps whose records should come back.
+        #[serde(default)]
+        expect: Vec<usize>,
+    },
+}
+
+fn default_simulation_project() -> String {
+    "ingat-simulation".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SimulationScenario {
+    fn default() -> Self {
+        let ingest =
+            |summary: &str, body: &str, tags: &[&str], kind: ContextKind| SimulationStep::Ingest {
+                summary: summary.to_string(),
+                body: body.to_string(),
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                kind,
+            };
+        let search = |prompt: &str, expect: Vec<usize>| SimulationStep::Search {
+            prompt: prompt.to_string(),
+            limit: Some(5),
+            expect,
+        };
+        Self {
+            project: default_simulation_project(),
+            steps: vec![
+                ingest(
+                    "Retry HTTP calls with exponential backoff",
+                    "fn fetch() { for attempt in 0..5 { sleep(2u64.pow(attempt) * 100); } }",
+                    &["http", "retry"],
+                    ContextKind::CodeSnippet,
+                ),
+                ingest(
+                    "Fixed sled lock error on startup",
+                    "Another process held the database lock; wait for it or stop mcp-service.",
+                    &["sled", "startup"],
+                    ContextKind::FixHistory,
+                ),
+                search("retry http requests with backoff", vec![0]),
+                search("database lock when starting", vec![1]),
+            ],
+            cleanup: true,
+        }
+    }
+}
+
+/// Outcome of one [`SimulationStep`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationStepResult {
+    pub index: usize,
+    /// `ingest` or `search`.
+    pub action: String,
+    pub elapsed_ms: f64,
+    /// Record created by an ingest step.
+    pub id: Option<Uuid>,
+    /// Results returned by a search step.
+    pub results: Option<usize>,
+    /// 1-based rank of each expected record, `None` when it was not returned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_ranks: Vec<Option<usize>>,
+    pub error: Option<String>,
+}
+
+/// Transcript of a simulated session with recall metrics over every
+/// expectation of its search steps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationTranscript {
+    pub steps: Vec<SimulationStepResult>,
+    pub failed_steps: usize,
+    pub expected: usize,
+    pub recalled: usize,
+    /// Mean of `1 / rank` over expectations, counting misses as 0.
+    pub mean_reciprocal_rank: f64,
+    pub total_ms: f64,
+    /// Simulated records deleted afterwards.
+    pub cleaned_up: usize,
 }
 
 #[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
@@ -97,6 +986,20 @@ pub struct EmbeddingBackendOption {
 pub struct EmbeddingBackendListResponse {
     pub active: String,
     pub options: Vec<EmbeddingBackendOption>,
+    /// Set when stored vectors of the active model have a different dimension.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimension_mismatch: Option<DimensionMismatch>,
+}
+
+/// Records of the active model stored at a dimension other than the one now
+/// configured. They can no longer be compared against new queries.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DimensionMismatch {
+    pub model: String,
+    pub configured: usize,
+    pub stored: Vec<usize>,
+    pub affected_records: usize,
 }
 
 #[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
@@ -4,8 +4,14 @@ pub mod dtos;
 pub mod services;
 
 pub use dtos::{
-    EmbeddingBackendListResponse, EmbeddingBackendOption, HealthStatusResponse,
-    IngestContextRequest, SearchRequest, SearchResponse, SummaryListResponse,
-    UpdateEmbeddingBackendRequest,
+    BuildInfo, ContextDiff, DataMigrationResponse, DeleteContextRequest, DeleteContextsRequest,
+    DimensionMismatch, EmbeddingBackendListResponse, EmbeddingBackendOption, EmbeddingBenchmark,
+    EmbeddingSelfTest, FeedbackRequest, FeedbackStats, FetchContextsRequest, HealthStatusResponse,
+    IngestContextRequest, LabelCount, MaintenanceOp, ProjectInfo, ProjectOverview,
+    RecentContextsRequest, ReembedProgress, RefineSearchRequest, RelatedProject,
+    RuntimeConfigResponse, SearchRequest, SearchRequestBuilder, SearchResponse, ServiceState,
+    SimulationScenario, SimulationStep, SimulationStepResult, SimulationTranscript, StorageStats,
+    StoreCompositionResponse, SummaryListResponse, TimestampFormat, UpdateEmbeddingBackendRequest,
+    VerifyIssue, VerifyProblem, VerifyReport,
 };
 pub use services::ContextService;
@@ -1,26 +1,275 @@
-use std::sync::Arc;
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::{
     application::dtos::{
-        HealthStatusResponse, IngestContextRequest, SearchRequest, SearchResponse, SearchResultDto,
-        SummaryListResponse,
+        validate_filters, ContextDiff, DimensionMismatch, EmbeddingBenchmark, EmbeddingSelfTest,
+        FeedbackStats, HealthStatusResponse, IngestContextRequest, LabelCount, MaintenanceOp,
+        ProjectInfo, ProjectOverview, ReembedProgress, RelatedProject, SearchRequest,
+        SearchResponse, SearchResultDto, ServiceState, SimulationScenario, SimulationStep,
+        SimulationStepResult, SimulationTranscript, StorageStats, StoreCompositionResponse,
+        SummaryListResponse, VerifyIssue, VerifyProblem, VerifyReport, MAX_DELETE_BATCH,
+        MAX_FETCH_BATCH, MAX_SEARCH_LIMIT,
     },
     domain::{
-        ContextEmbedding, ContextKind, ContextRecord, ContextSummary, DomainError, QueryFilters,
-        RetrievalQuery,
+        diff_lines, normalize_tag, sanitize_project, strip_invisible, tokenize_terms, ChangeEvent,
+        ContextEmbedding, ContextKind, ContextRecord, ContextSummary, DomainError,
+        EmbeddingComposition, FilterField, HistoryOrder, QueryFilters, QueryHistoryEntry,
+        RetrievalQuery, SearchEvent, SearchScope, SourceMeta,
     },
 };
 
 const MAX_BODY_CHARS: usize = 16_000;
 const MAX_SUMMARY_CHARS: usize = 640;
+/// Length cap for summaries derived from the body; well under the hard limit.
+const AUTO_SUMMARY_CHARS: usize = 200;
+/// Summary words that mark an unspecified ingest as a fix when inferring kinds.
+const FIX_WORDS: &[&str] = &[
+    "fix",
+    "fixed",
+    "fixes",
+    "bug",
+    "bugs",
+    "bugfix",
+    "error",
+    "errors",
+    "crash",
+    "regression",
+];
+/// First summary words that make it read as a question.
+const QUESTION_WORDS: &[&str] = &[
+    "how", "why", "what", "when", "where", "which", "who", "should", "can", "could", "does", "do",
+    "is", "are",
+];
+/// Soft-filtered searches rank this many candidates per requested result.
+const SOFT_FILTER_OVERFETCH: usize = 4;
+/// Score bonus per matching tag in soft-filtered searches.
+pub const DEFAULT_TAG_BOOST: f32 = 0.1;
+/// `message` of a search that found nothing, steering agents away from
+/// claiming the answer came from memory.
+pub const DEFAULT_EMPTY_RESULTS_MESSAGE: &str =
+    "No stored memories matched; answer from your own knowledge and consider saving the solution";
+/// Searches kept in the query history before the oldest is evicted.
+pub const DEFAULT_QUERY_HISTORY_CAPACITY: usize = 200;
+/// Subtracted from legacy index scores before they are merged with the
+/// active model's.
+pub const DEFAULT_LEGACY_PENALTY: f32 = 0.1;
+/// Days a deletion stays in the change feed before compaction drops it.
+pub const DEFAULT_TOMBSTONE_RETENTION_DAYS: u32 = 30;
+/// Legacy query embeddings kept before the cache starts over.
+const LEGACY_QUERY_CACHE: usize = 256;
+/// Search analytics events a store keeps before evicting the oldest.
+pub const SEARCH_EVENT_CAPACITY: usize = 10_000;
+/// Entries returned by `recent_queries` when no limit is given.
+const DEFAULT_RECENT_QUERIES: usize = 20;
+const MAX_BENCHMARK_SAMPLES: usize = 256;
+const OVERVIEW_TOP_TAGS: usize = 10;
+const MAX_RELATED_PROJECTS: usize = 50;
+/// Records `top_similar_pairs` will compare; the scan is quadratic.
+const MAX_SIMILAR_PAIR_RECORDS: usize = 5_000;
+const MAX_SIMILAR_PAIRS: usize = 500;
+/// Records a lazy re-embed works on at once; further mismatches wait for a later search.
+const LAZY_REEMBED_MAX_IN_FLIGHT: usize = 32;
+/// Records `reembed_all` persists per write (and between progress reports).
+const REEMBED_BATCH: usize = 32;
+/// Most chunks one record is split into; longer bodies get wider windows.
+const MAX_CHUNKS: usize = 64;
+/// Extra candidates per requested result when chunk hits may roll up.
+const CHUNK_OVERFETCH: usize = 4;
+/// Extra candidates per requested result when `max_per_project` may drop hits.
+const PROJECT_CAP_OVERFETCH: usize = 4;
+
+/// Which part of an over-long text is kept for embedding. Only the embedded
+/// text is cut; the stored body is never modified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    /// Keep the beginning (what most models do silently).
+    #[default]
+    Head,
+    /// Keep the end, e.g. for logs where the conclusion matters most.
+    Tail,
+    /// Keep the beginning and end halves; usually best for long documents.
+    HeadTail,
+}
+
+impl TruncationStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TruncationStrategy::Head => "head",
+            TruncationStrategy::Tail => "tail",
+            TruncationStrategy::HeadTail => "head_tail",
+        }
+    }
+
+    /// Cut `text` to at most `max_chars` characters; borrowed when it already fits.
+    pub fn apply<'a>(&self, text: &'a str, max_chars: usize) -> Cow<'a, str> {
+        let total = text.chars().count();
+        if total <= max_chars {
+            return Cow::Borrowed(text);
+        }
+        let byte_at = |chars: usize| {
+            text.char_indices()
+                .nth(chars)
+                .map_or(text.len(), |(i, _)| i)
+        };
+        match self {
+            TruncationStrategy::Head => Cow::Borrowed(&text[..byte_at(max_chars)]),
+            TruncationStrategy::Tail => Cow::Borrowed(&text[byte_at(total - max_chars)..]),
+            TruncationStrategy::HeadTail => {
+                let head = max_chars.div_ceil(2);
+                let tail = max_chars - head;
+                Cow::Owned(format!(
+                    "{}{}",
+                    &text[..byte_at(head)],
+                    &text[byte_at(total - tail)..]
+                ))
+            }
+        }
+    }
+}
+
+/// Which fields make up the vector of a record. The stored body is kept
+/// either way; switching scope requires re-embedding existing records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbedScope {
+    #[default]
+    SummaryPlusBody,
+    /// Summary and tags only, for stores with large bodies that need not be searchable.
+    SummaryOnly,
+}
+
+/// Normalization applied, in order, to text before it is embedded. Like
+/// truncation it only shapes the embedder input, never the stored text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreprocessStep {
+    Lowercase,
+    /// Drop ```/~~~ fence lines but keep the code between them.
+    StripCodeFences,
+    CollapseWhitespace,
+    /// Drop heading, quote and list markers plus bold/italic/inline-code markup.
+    TrimMarkdown,
+}
+
+impl PreprocessStep {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            PreprocessStep::Lowercase => text.to_lowercase(),
+            PreprocessStep::StripCodeFences => text
+                .lines()
+                .filter(|line| {
+                    let line = line.trim_start();
+                    !(line.starts_with("```") || line.starts_with("~~~"))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            PreprocessStep::CollapseWhitespace => {
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+            PreprocessStep::TrimMarkdown => text
+                .lines()
+                .map(|line| {
+                    strip_block_marker(line)
+                        .replace("**", "")
+                        .replace("__", "")
+                        .replace('`', "")
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Run `steps` in order; borrowed when there is nothing to do.
+pub fn preprocess<'a>(steps: &[PreprocessStep], text: &'a str) -> Cow<'a, str> {
+    steps.iter().fold(Cow::Borrowed(text), |text, step| {
+        Cow::Owned(step.apply(&text))
+    })
+}
+
+/// Leading `#`, `>` and list markers of one markdown line.
+fn strip_block_marker(line: &str) -> &str {
+    let line = line
+        .trim_start()
+        .trim_start_matches(['#', '>'])
+        .trim_start();
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return rest;
+        }
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    match line[digits..].strip_prefix(". ") {
+        Some(rest) if digits > 0 => rest,
+        _ => line,
+    }
+}
 
 /// High level configuration shared by the service and its adapters.
 #[derive(Debug, Clone)]
 pub struct ServiceConfig {
     pub embedding_model: String,
     pub default_limit: usize,
+    /// Store separate summary/body vectors so searches can target one field.
+    pub field_embeddings: bool,
+    /// Reuse identical search results for this long; `None` disables the cache.
+    pub search_cache_ttl: Option<Duration>,
+    /// Longest text (in chars) handed to the embedder; `None` leaves it to the model.
+    pub max_embed_chars: Option<usize>,
+    pub truncation: TruncationStrategy,
+    pub embed_scope: EmbedScope,
+    /// Append the record's file path to its embedded text, and a search's
+    /// `path_hint` to the query.
+    pub embed_file_path: bool,
+    /// Applied to ingest and query text before truncation; empty is a no-op.
+    pub preprocess: Vec<PreprocessStep>,
+    /// Fill an empty summary from the body instead of rejecting the ingest.
+    pub auto_summary: bool,
+    /// Bonus added per requested tag a record carries in soft-filtered searches.
+    pub tag_boost: f32,
+    /// Strip control and zero-width characters from summary, body and tags at ingest.
+    pub sanitize_unicode: bool,
+    /// Add the normalized `language` as a tag at ingest, room permitting.
+    pub auto_tag_language: bool,
+    /// Guess a kind from the content of ingests that leave it unspecified.
+    pub infer_kind: bool,
+    /// Skip records whose dimension doesn't match the query and re-embed them
+    /// in the background instead of failing the search.
+    pub lazy_reembed: bool,
+    /// Kind for ingests that leave it unspecified, keyed by lowercased IDE name.
+    pub ide_default_kind: HashMap<String, ContextKind>,
+    /// Split bodies longer than this many chars into separately embedded
+    /// chunks; `None` stores one vector per record.
+    pub chunk_size: Option<usize>,
+    /// Chars shared by neighbouring chunks.
+    pub chunk_overlap: usize,
+    /// Attached to searches returning no results; `None` leaves it out.
+    pub empty_results_message: Option<String>,
+    /// Keep every search's returned ids so `record_feedback` can note which
+    /// result was used. Off by default.
+    pub search_analytics: bool,
+    /// Score penalty for hits from the legacy index; see
+    /// [`ContextService::with_legacy_index`].
+    pub legacy_penalty: f32,
+    /// Sync horizon: deletions older than this many days are dropped from
+    /// the change feed when indexes are rebuilt. Consumers that last synced
+    /// before it need a full resync.
+    pub tombstone_retention_days: u32,
 }
 
 impl Default for ServiceConfig {
@@ -28,6 +277,26 @@ impl Default for ServiceConfig {
         Self {
             embedding_model: "ingat/simple-hash".into(),
             default_limit: 8,
+            field_embeddings: false,
+            search_cache_ttl: None,
+            max_embed_chars: None,
+            truncation: TruncationStrategy::default(),
+            embed_scope: EmbedScope::default(),
+            embed_file_path: false,
+            preprocess: Vec::new(),
+            auto_summary: false,
+            tag_boost: DEFAULT_TAG_BOOST,
+            sanitize_unicode: true,
+            auto_tag_language: false,
+            infer_kind: false,
+            lazy_reembed: false,
+            ide_default_kind: HashMap::new(),
+            chunk_size: None,
+            chunk_overlap: 0,
+            empty_results_message: Some(DEFAULT_EMPTY_RESULTS_MESSAGE.to_string()),
+            search_analytics: false,
+            legacy_penalty: DEFAULT_LEGACY_PENALTY,
+            tombstone_retention_days: DEFAULT_TOMBSTONE_RETENTION_DAYS,
         }
     }
 }
@@ -37,9 +306,114 @@ impl ServiceConfig {
         Self {
             embedding_model: embedding_model.into(),
             default_limit: default_limit.max(1),
+            ..Self::default()
         }
     }
 
+    pub fn with_field_embeddings(mut self, enabled: bool) -> Self {
+        self.field_embeddings = enabled;
+        self
+    }
+
+    /// Cache search responses for `ttl_ms` milliseconds; `0` turns caching off.
+    pub fn with_search_cache_ttl_ms(mut self, ttl_ms: u64) -> Self {
+        self.search_cache_ttl = (ttl_ms > 0).then(|| Duration::from_millis(ttl_ms));
+        self
+    }
+
+    /// Cut embedding input longer than `max_chars` using `strategy`; `0` disables truncation.
+    pub fn with_truncation(mut self, strategy: TruncationStrategy, max_chars: usize) -> Self {
+        self.truncation = strategy;
+        self.max_embed_chars = (max_chars > 0).then_some(max_chars);
+        self
+    }
+
+    pub fn with_embed_scope(mut self, scope: EmbedScope) -> Self {
+        self.embed_scope = scope;
+        self
+    }
+
+    pub fn with_embed_file_path(mut self, enabled: bool) -> Self {
+        self.embed_file_path = enabled;
+        self
+    }
+
+    /// Chunk long bodies into `size`-char windows overlapping by `overlap`;
+    /// a `size` of `0` turns chunking off.
+    pub fn with_chunking(mut self, size: usize, overlap: usize) -> Self {
+        self.chunk_size = (size > 0).then_some(size);
+        self.chunk_overlap = overlap;
+        self
+    }
+
+    /// Message for empty search results; blank omits it.
+    pub fn with_empty_results_message(mut self, message: &str) -> Self {
+        let message = message.trim();
+        self.empty_results_message = (!message.is_empty()).then(|| message.to_string());
+        self
+    }
+
+    pub fn with_preprocess(mut self, steps: Vec<PreprocessStep>) -> Self {
+        self.preprocess = steps;
+        self
+    }
+
+    pub fn with_auto_summary(mut self, enabled: bool) -> Self {
+        self.auto_summary = enabled;
+        self
+    }
+
+    pub fn with_tag_boost(mut self, boost: f32) -> Self {
+        self.tag_boost = boost;
+        self
+    }
+
+    pub fn with_sanitize_unicode(mut self, enabled: bool) -> Self {
+        self.sanitize_unicode = enabled;
+        self
+    }
+
+    pub fn with_auto_tag_language(mut self, enabled: bool) -> Self {
+        self.auto_tag_language = enabled;
+        self
+    }
+
+    pub fn with_infer_kind(mut self, enabled: bool) -> Self {
+        self.infer_kind = enabled;
+        self
+    }
+
+    pub fn with_lazy_reembed(mut self, enabled: bool) -> Self {
+        self.lazy_reembed = enabled;
+        self
+    }
+
+    pub fn with_search_analytics(mut self, enabled: bool) -> Self {
+        self.search_analytics = enabled;
+        self
+    }
+
+    pub fn with_legacy_penalty(mut self, penalty: f32) -> Self {
+        self.legacy_penalty = penalty;
+        self
+    }
+
+    pub fn with_tombstone_retention_days(mut self, days: u32) -> Self {
+        self.tombstone_retention_days = days;
+        self
+    }
+
+    pub fn with_ide_default_kinds<'a>(
+        mut self,
+        kinds: impl IntoIterator<Item = (&'a String, &'a ContextKind)>,
+    ) -> Self {
+        self.ide_default_kind = kinds
+            .into_iter()
+            .map(|(ide, kind)| (ide.trim().to_lowercase(), kind.clone()))
+            .collect();
+        self
+    }
+
     pub fn with_model(embedding_model: impl Into<String>) -> Self {
         Self::new(embedding_model, Self::default().default_limit)
     }
@@ -56,27 +430,323 @@ pub trait EmbeddingEngine: Send + Sync {
     fn dims(&self, _model: &str) -> Option<usize> {
         None
     }
+
+    /// Version tag recorded alongside vectors so incompatible upgrades can be detected.
+    fn model_version(&self, _model: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Ranked semantic matches plus how selective the filters were.
+#[derive(Debug, Default)]
+pub struct SearchHits {
+    pub matches: Vec<(ContextRecord, f32)>,
+    /// Records read from storage (kind filters can narrow this via the index).
+    pub scanned: usize,
+    /// Scanned records that passed the filters, before scoring and truncation.
+    pub matched_filters: usize,
+    /// Filter-matching records whose vector has another dimension than the
+    /// query; they are left out of `matches`.
+    pub mismatched: Vec<Uuid>,
+}
+
+/// Ranked search matches, each turned into a [`SearchResultDto`] only as the
+/// iterator reaches it.
+pub struct RankedResults {
+    matches: std::vec::IntoIter<(ContextRecord, f32)>,
+    min_score: Option<f32>,
+    /// Hits folded into each collapsed record, by record id.
+    chunk_matches: HashMap<Uuid, usize>,
+}
+
+impl RankedResults {
+    fn new(
+        matches: Vec<(ContextRecord, f32)>,
+        min_score: Option<f32>,
+        chunk_matches: HashMap<Uuid, usize>,
+    ) -> Self {
+        Self {
+            matches: matches.into_iter(),
+            min_score,
+            chunk_matches,
+        }
+    }
+
+    fn qualifies(min_score: Option<f32>, score: f32) -> bool {
+        min_score.map_or(true, |min| score >= min)
+    }
+
+    /// Ids of the results not yielded yet, in order.
+    pub fn ids(&self) -> Vec<Uuid> {
+        self.matches
+            .as_slice()
+            .iter()
+            .filter(|(_, score)| Self::qualifies(self.min_score, *score))
+            .map(|(record, _)| record.id)
+            .collect()
+    }
+}
+
+impl Iterator for RankedResults {
+    type Item = SearchResultDto;
+
+    fn next(&mut self) -> Option<SearchResultDto> {
+        let min_score = self.min_score;
+        let (record, score) = self
+            .matches
+            .find(|(_, score)| Self::qualifies(min_score, *score))?;
+        Some(SearchResultDto {
+            chunk_matches: self.chunk_matches.get(&record.id).copied(),
+            id: record.id,
+            project: record.project,
+            summary: record.summary,
+            body: record.body,
+            tags: record.tags,
+            kind: record.kind,
+            score,
+            created_at: record.created_at,
+            pinned: record.pinned,
+            source: record.source,
+            parent_id: record.parent_id,
+        })
+    }
+}
+
+/// A validated search request, ready to rank.
+struct PreparedSearch {
+    prompt: String,
+    /// Prompt as embedded, with the path hint appended when enabled.
+    query_text: String,
+    model: String,
+    filters: QueryFilters,
+    /// Tag filters relaxed into score boosts by `soft_filter`.
+    boosted_tags: Vec<String>,
+    limit: usize,
+    min_score: Option<f32>,
+    scope: SearchScope,
+    collapse_by_parent: bool,
+    max_per_project: Option<usize>,
+}
+
+/// Matches of a [`PreparedSearch`], best first and cut to its limit.
+struct RankedMatches {
+    matches: Vec<(ContextRecord, f32)>,
+    chunk_matches: HashMap<Uuid, usize>,
+    scanned: usize,
+    matched_filters: usize,
+    /// Matches left after collapsing and capping, before the cut.
+    available: usize,
+    /// The store filled the whole candidate pool, so more may exist.
+    saturated: bool,
+    /// No chunks or project cap were involved.
+    plain: bool,
 }
 
 /// Contract for the embedded vector storage engine.
 pub trait VectorStore: Send + Sync {
     fn persist(&self, record: &ContextRecord) -> Result<(), DomainError>;
 
+    /// Like `persist`, but the store may postpone making the write durable.
+    fn persist_deferred(&self, record: &ContextRecord) -> Result<(), DomainError> {
+        self.persist(record)
+    }
+
+    /// Persist many records at once. Stores that support atomic batches should
+    /// override this to avoid a flush per record.
+    fn persist_batch(&self, records: &[ContextRecord]) -> Result<(), DomainError> {
+        records.iter().try_for_each(|record| self.persist(record))
+    }
+
     fn search(
         &self,
         embedding: &ContextEmbedding,
         limit: usize,
         filters: &QueryFilters,
-    ) -> Result<Vec<(ContextRecord, f32)>, DomainError>;
+        scope: SearchScope,
+    ) -> Result<SearchHits, DomainError>;
 
+    /// Summaries matching `project` and `tag` created after `since`, sorted by
+    /// `order`, first `limit` only.
     fn recent(
         &self,
         project: Option<&str>,
+        tag: Option<&str>,
+        since: Option<DateTime<Utc>>,
         limit: usize,
+        order: HistoryOrder,
     ) -> Result<Vec<ContextSummary>, DomainError>;
 
     fn projects(&self) -> Result<Vec<String>, DomainError>;
 
+    /// Make every acknowledged write durable. Stores without local state need not override.
+    fn flush(&self) -> Result<(), DomainError> {
+        Ok(())
+    }
+
+    /// Every stored summary in storage order, decoded lazily one record at a time.
+    fn iter_summaries(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<ContextSummary, DomainError>> + Send + '_>;
+
+    /// Flip the pinned flag of a stored record without touching its embedding.
+    fn set_pinned(&self, id: Uuid, pinned: bool) -> Result<ContextSummary, DomainError>;
+
+    /// Whether the records live in another process (mcp-service). `persist`
+    /// ingests there as a new record, so fetched records must never be written
+    /// back through it; in-place changes use the dedicated calls below.
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    /// Archive or unarchive `id` on the service that owns the records. Only
+    /// remote stores implement this; local records are rewritten by
+    /// `ContextService` itself.
+    fn set_archived_remote(
+        &self,
+        _id: Uuid,
+        _archived: bool,
+    ) -> Result<ContextSummary, DomainError> {
+        Err(DomainError::other(
+            "archiving through the store is only available in remote mode",
+        ))
+    }
+
+    /// Integrity report computed by the service that owns the records. Only
+    /// remote stores implement this; local stores are checked by
+    /// `ContextService::verify` itself.
+    fn verify_remote(&self) -> Result<VerifyReport, DomainError> {
+        Err(DomainError::other(
+            "verifying through the store is only available in remote mode",
+        ))
+    }
+
+    /// Re-embed `project` on the service that owns the records, returning how
+    /// many were re-embedded. Only remote stores implement this; local
+    /// records are re-embedded by `ContextService::reembed_project` itself.
+    fn reembed_project_remote(&self, _project: &str) -> Result<usize, DomainError> {
+        Err(DomainError::other(
+            "re-embedding through the store is only available in remote mode",
+        ))
+    }
+
+    /// Overview of `project` built by the service that owns the records. Only
+    /// remote stores implement this; local stores are summarized by
+    /// `ContextService::project_overview` itself.
+    fn project_overview_remote(
+        &self,
+        _project: &str,
+        _recent_limit: Option<usize>,
+    ) -> Result<ProjectOverview, DomainError> {
+        Err(DomainError::other(
+            "project overviews through the store are only available in remote mode",
+        ))
+    }
+
+    /// Inserts, updates and (optionally) deletions recorded after `since`,
+    /// oldest first. Chunks are left out.
+    fn changes_since(
+        &self,
+        since: DateTime<Utc>,
+        include_deleted: bool,
+    ) -> Result<Vec<ChangeEvent>, DomainError>;
+
+    /// Visit every stored record, including embeddings, one at a time. Records
+    /// that fail to decode are passed as errors so callers can count them.
+    fn scan_records(
+        &self,
+        visit: &mut dyn FnMut(Result<ContextRecord, DomainError>),
+    ) -> Result<(), DomainError>;
+
+    /// Full records (including embeddings) for `ids`, in the same order, in
+    /// one call; multi-record operations build on this instead of looping
+    /// over single reads. Ids that are no longer stored are skipped.
+    fn fetch_records(&self, ids: &[Uuid]) -> Result<Vec<ContextRecord>, DomainError>;
+
+    /// Full records (including embeddings) belonging to `project`.
+    fn project_records(&self, project: &str) -> Result<Vec<ContextRecord>, DomainError>;
+
+    /// Recompute document frequencies for every term in the corpus, returning
+    /// the number of documents indexed.
+    fn rebuild_term_stats(&self) -> Result<usize, DomainError>;
+
+    /// Count one more document containing each of `terms`. Cheap, approximate
+    /// upkeep between full rebuilds.
+    fn record_term_stats(&self, terms: &BTreeSet<String>) -> Result<(), DomainError>;
+
+    /// Records matching `filters`, ranked by TF-IDF against `terms`.
+    fn keyword_search(
+        &self,
+        terms: &[String],
+        limit: usize,
+        filters: &QueryFilters,
+    ) -> Result<Vec<(ContextRecord, f32)>, DomainError>;
+
+    /// Remove the records with these ids in one atomic write, leaving a
+    /// tombstone for each one that is not a chunk and taking it out of the
+    /// term statistics. Unknown ids are skipped; returns how many were removed.
+    fn delete_batch(&self, ids: &[Uuid]) -> Result<usize, DomainError>;
+
+    /// Ids of the chunks split from `parents`. Stores with a parent index
+    /// should override this full scan.
+    fn chunk_ids(&self, parents: &[Uuid]) -> Result<Vec<Uuid>, DomainError> {
+        let parents: HashSet<&Uuid> = parents.iter().collect();
+        let mut ids = Vec::new();
+        for summary in self.iter_summaries() {
+            let summary = summary?;
+            if summary
+                .parent_id
+                .is_some_and(|parent| parents.contains(&parent))
+            {
+                ids.push(summary.id);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Drop and rebuild the secondary indexes from the primary records,
+    /// returning the number of contexts indexed; chunks are not counted.
+    fn rebuild_index(&self) -> Result<usize, DomainError>;
+
+    /// Forget deletions recorded before `before`, returning how many
+    /// tombstones were dropped. Stores without tombstones need not override.
+    fn prune_tombstones(&self, _before: DateTime<Utc>) -> Result<usize, DomainError> {
+        Ok(0)
+    }
+
+    /// Record count per kind key (see `ContextKind::key`).
+    fn kind_counts(&self) -> Result<Vec<(String, usize)>, DomainError>;
+
+    /// Histogram of (model, version, dimension) combinations present in the store.
+    fn store_composition(&self) -> Result<Vec<EmbeddingComposition>, DomainError>;
+
+    /// Remember a search, evicting the oldest entry once the history is full.
+    fn record_query(&self, entry: &QueryHistoryEntry) -> Result<(), DomainError>;
+
+    /// Remembered searches, most recent first.
+    fn recent_queries(&self, limit: usize) -> Result<Vec<QueryHistoryEntry>, DomainError>;
+
+    /// Forget every remembered search, returning how many were dropped.
+    fn clear_query_history(&self) -> Result<usize, DomainError>;
+
+    /// Keep a search for relevance analytics, evicting the oldest events
+    /// beyond [`SEARCH_EVENT_CAPACITY`].
+    fn record_search_event(&self, event: &SearchEvent) -> Result<(), DomainError>;
+
+    /// Note that `chosen_id` was used after the search `query_id`, returning
+    /// the updated event. Unknown (or evicted) searches are `NotFound`.
+    fn record_feedback(&self, query_id: Uuid, chosen_id: Uuid) -> Result<SearchEvent, DomainError>;
+
+    /// Every kept search event, oldest first.
+    fn search_events(&self) -> Result<Vec<SearchEvent>, DomainError>;
+
+    /// Drop every search event, returning how many were dropped.
+    fn clear_search_events(&self) -> Result<usize, DomainError>;
+
+    /// Bytes the store occupies on disk; `None` for stores without local files.
+    fn disk_usage(&self) -> Result<Option<u64>, DomainError> {
+        Ok(None)
+    }
+
     fn ping(&self) -> Result<(), DomainError>;
 }
 
@@ -85,6 +755,51 @@ pub struct ContextService {
     embedder: Arc<dyn EmbeddingEngine>,
     store: Arc<dyn VectorStore>,
     config: ServiceConfig,
+    /// Normalized search request -> response, valid only at the store version
+    /// it was computed for.
+    search_cache: Mutex<HashMap<String, CachedSearch>>,
+    /// Bumped on every write made through this service (or its background
+    /// workers); cached searches from an older version are never served.
+    store_version: Arc<AtomicU64>,
+    /// Last store composition with the store version it was computed at, so
+    /// health polls don't rescan an unchanged store.
+    composition_cache: Arc<Mutex<Option<(u64, Vec<EmbeddingComposition>)>>>,
+    lazy_reembed: Arc<LazyReembed>,
+    /// Maintenance operation currently holding the store, if any.
+    maintenance: Arc<Mutex<Option<MaintenanceOp>>>,
+    /// Records still embedded by the previous model, searched alongside.
+    legacy: Option<Arc<LegacyIndex>>,
+}
+
+/// The model a store is migrating away from, with the engine that still
+/// embeds queries into its space.
+struct LegacyIndex {
+    engine: Arc<dyn EmbeddingEngine>,
+    model: String,
+    /// Query text -> legacy embedding, so repeated searches skip the engine.
+    queries: Mutex<HashMap<String, ContextEmbedding>>,
+}
+
+struct CachedSearch {
+    version: u64,
+    cached_at: Instant,
+    response: SearchResponse,
+}
+
+/// Clears the maintenance flag when the operation ends, even on error.
+struct MaintenanceGuard<'a>(&'a Mutex<Option<MaintenanceOp>>);
+
+impl Drop for MaintenanceGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.lock() = None;
+    }
+}
+
+/// Bookkeeping for background re-embeds queued by searches.
+#[derive(Default)]
+struct LazyReembed {
+    in_flight: Mutex<HashSet<Uuid>>,
+    completed: AtomicUsize,
 }
 
 impl ContextService {
@@ -97,19 +812,123 @@ impl ContextService {
             embedder,
             store,
             config,
+            search_cache: Mutex::new(HashMap::new()),
+            store_version: Arc::default(),
+            composition_cache: Arc::default(),
+            lazy_reembed: Arc::default(),
+            maintenance: Arc::default(),
+            legacy: None,
+        }
+    }
+
+    /// Keep records embedded with `model` searchable during a gradual
+    /// migration: searches also embed the query with `engine`, score those
+    /// records in their own space and merge the hits, lowered by
+    /// `legacy_penalty`, with the active model's.
+    pub fn with_legacy_index(
+        mut self,
+        engine: Arc<dyn EmbeddingEngine>,
+        model: impl Into<String>,
+    ) -> Self {
+        self.legacy = Some(Arc::new(LegacyIndex {
+            engine,
+            model: model.into(),
+            queries: Mutex::new(HashMap::new()),
+        }));
+        self
+    }
+
+    /// Start with `entries` as the store composition, when the caller has
+    /// just scanned the store anyway; the next write invalidates it.
+    pub fn with_composition(self, entries: Vec<EmbeddingComposition>) -> Self {
+        *self.composition_cache.lock() = Some((self.store_version(), entries));
+        self
+    }
+
+    /// Model of the attached legacy index, if any.
+    pub fn legacy_model(&self) -> Option<&str> {
+        self.legacy.as_ref().map(|legacy| legacy.model.as_str())
+    }
+
+    pub fn state(&self) -> ServiceState {
+        match *self.maintenance.lock() {
+            Some(op) => ServiceState::Maintenance { op },
+            None => ServiceState::Idle,
+        }
+    }
+
+    /// Mark `op` as running until the guard drops. Only one maintenance
+    /// operation runs at a time.
+    fn begin_maintenance(&self, op: MaintenanceOp) -> Result<MaintenanceGuard<'_>, DomainError> {
+        let mut current = self.maintenance.lock();
+        if let Some(running) = *current {
+            return Err(DomainError::busy(running.as_str()));
         }
+        *current = Some(op);
+        Ok(MaintenanceGuard(&self.maintenance))
     }
 
-    pub fn ingest(&self, payload: IngestContextRequest) -> Result<ContextSummary, DomainError> {
+    /// Writes fail fast during maintenance instead of queueing behind it.
+    fn ensure_writable(&self) -> Result<(), DomainError> {
+        match *self.maintenance.lock() {
+            Some(op) => Err(DomainError::busy(op.as_str())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn ingest(&self, mut payload: IngestContextRequest) -> Result<ContextSummary, DomainError> {
+        self.ensure_writable()?;
+        if self.config.sanitize_unicode {
+            payload.project = strip_invisible(&payload.project);
+            payload.ide = strip_invisible(&payload.ide);
+            payload.summary = strip_invisible(&payload.summary);
+            payload.body = strip_invisible(&payload.body);
+            for tag in &mut payload.tags {
+                *tag = strip_invisible(tag);
+            }
+            if let Some(source) = payload.source.as_mut() {
+                source.ide = strip_invisible(&source.ide);
+                for value in [&mut source.ide_version, &mut source.workspace]
+                    .into_iter()
+                    .flatten()
+                {
+                    *value = strip_invisible(value);
+                }
+            }
+        }
+        if self.config.auto_summary && payload.summary.trim().is_empty() {
+            payload.summary = derive_summary(&payload.body);
+        }
         self.validate_payload(&payload)?;
+        if self.config.auto_tag_language {
+            tag_language(&mut payload.tags, payload.language.as_deref());
+        }
+        if payload.kind == ContextKind::default() {
+            if let Some(kind) = self.default_kind_for(&payload.ide) {
+                payload.kind = kind.clone();
+            } else if self.config.infer_kind {
+                if let Some(kind) = infer_kind(&payload) {
+                    payload.kind = kind;
+                }
+            }
+        }
+        payload.kind = payload.kind.normalized()?;
 
-        let text_to_embed = format!("{}\n{}", payload.summary.trim(), payload.body.trim());
-        let vector = self
-            .embedder
-            .embed(&self.config.embedding_model, &text_to_embed)?;
-        let embedding = ContextEmbedding::new(&self.config.embedding_model, vector);
+        let model = self.resolve_model(payload.model_override.as_deref())?;
+        let embedding = self.embed_content(
+            &model,
+            &payload.summary,
+            &payload.body,
+            &payload.tags,
+            payload.file_path.as_deref(),
+        )?;
 
-        let record = ContextRecord::new(
+        let flush = payload.flush;
+        let source = payload
+            .source
+            .or_else(|| SourceMeta::from_ide_lines(&payload.ide))
+            .map(SourceMeta::sanitized);
+        let mut record = ContextRecord::new(
             payload.project,
             payload.ide,
             payload.file_path,
@@ -120,129 +939,3439 @@ impl ContextService {
             payload.kind,
             embedding,
         );
+        record.pinned = payload.pinned;
+        record.source = source;
+        self.attach_field_embeddings(&model, &mut record)?;
+        let mut chunks = self.chunk_records(&model, &record)?;
 
-        self.store.persist(&record)?;
+        if !chunks.is_empty() {
+            chunks.insert(0, record.clone());
+            self.store.persist_batch(&chunks)?;
+        } else {
+            match flush {
+                None => self.store.persist(&record)?,
+                Some(false) => self.store.persist_deferred(&record)?,
+                Some(true) => {
+                    self.store.persist(&record)?;
+                    self.store.flush()?;
+                }
+            }
+        }
+        self.bump_store_version();
+        // Statistics only weight keyword scores; the record is already stored.
+        if let Err(err) = self
+            .store
+            .record_term_stats(&Self::record_terms(&record).collect())
+        {
+            tracing::warn!(target: "ingat::storage", "failed to update term stats: {err}");
+        }
 
         Ok(record.as_summary())
     }
 
-    pub fn search(&self, request: SearchRequest) -> Result<SearchResponse, DomainError> {
-        if request.prompt.trim().is_empty() {
-            return Err(DomainError::validation("prompt cannot be empty"));
-        }
-
-        let RetrievalQuery {
-            prompt,
-            filters,
-            limit,
-        } = RetrievalQuery::from(request);
-
-        let effective_limit = limit.clamp(1, 32);
-
-        let query_vector = self
-            .embedder
-            .embed(&self.config.embedding_model, prompt.trim())?;
-        let query_embedding = ContextEmbedding::new(&self.config.embedding_model, query_vector);
+    /// Configured kind for `ide`; only the first line names the IDE.
+    fn default_kind_for(&self, ide: &str) -> Option<&ContextKind> {
+        let name = ide.lines().next().unwrap_or_default().trim().to_lowercase();
+        self.config.ide_default_kind.get(&name)
+    }
 
-        let matches = self
-            .store
-            .search(&query_embedding, effective_limit, &filters)?;
+    /// Search-only copies of `parent`, each holding and embedding one window
+    /// of its body. Empty unless chunking is on and the body is longer than a
+    /// chunk.
+    fn chunk_records(
+        &self,
+        model: &str,
+        parent: &ContextRecord,
+    ) -> Result<Vec<ContextRecord>, DomainError> {
+        let Some(size) = self.config.chunk_size else {
+            return Ok(Vec::new());
+        };
+        if parent.body.chars().count() <= size {
+            return Ok(Vec::new());
+        }
 
-        let results = matches
+        chunk_windows(&parent.body, size, self.config.chunk_overlap)
             .into_iter()
-            .map(|(record, score)| SearchResultDto {
-                id: record.id,
-                project: record.project,
-                summary: record.summary,
-                body: record.body,
-                tags: record.tags,
-                kind: record.kind,
-                score,
-                created_at: record.created_at,
+            .map(|window| {
+                let embedding = self.embed_content(
+                    model,
+                    &parent.summary,
+                    window,
+                    &parent.tags,
+                    parent.file_path.as_deref(),
+                )?;
+                let mut chunk = ContextRecord::new(
+                    parent.project.clone(),
+                    parent.ide.clone(),
+                    parent.file_path.clone(),
+                    parent.language.clone(),
+                    parent.summary.clone(),
+                    window,
+                    parent.tags.clone(),
+                    parent.kind.clone(),
+                    embedding,
+                );
+                chunk.created_at = parent.created_at;
+                chunk.pinned = parent.pinned;
+                chunk.source = parent.source.clone();
+                chunk.parent_id = Some(parent.id);
+                self.attach_field_embeddings(model, &mut chunk)?;
+                Ok(chunk)
             })
-            .collect();
+            .collect()
+    }
 
-        Ok(SearchResponse {
-            query: prompt,
-            results,
-        })
+    /// Full records for `ids` (embeddings included) in one store pass, in
+    /// the order asked for. Repeated ids are returned once and ids that are
+    /// not stored are skipped.
+    pub fn get_many(&self, mut ids: Vec<Uuid>) -> Result<Vec<ContextRecord>, DomainError> {
+        if ids.len() > MAX_FETCH_BATCH {
+            return Err(DomainError::limit(format!(
+                "batch get accepts at most {MAX_FETCH_BATCH} ids"
+            )));
+        }
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+        self.store.fetch_records(&ids)
     }
 
-    pub fn history(
-        &self,
-        project: Option<String>,
-        limit: Option<usize>,
-    ) -> Result<SummaryListResponse, DomainError> {
-        let capped_limit = limit.unwrap_or(self.config.default_limit).clamp(1, 50);
-        let summaries = self.store.recent(project.as_deref(), capped_limit)?;
+    /// Delete every listed context, and the chunks split from it, in one
+    /// atomic write, returning how many of the listed ids existed. Keyword
+    /// statistics drop the deleted records in the same write.
+    pub fn delete_many(&self, mut ids: Vec<Uuid>) -> Result<usize, DomainError> {
+        if ids.len() > MAX_DELETE_BATCH {
+            return Err(DomainError::limit(format!(
+                "delete accepts at most {MAX_DELETE_BATCH} ids"
+            )));
+        }
+        self.ensure_writable()?;
+        ids.sort_unstable();
+        ids.dedup();
 
-        Ok(SummaryListResponse { items: summaries })
-    }
+        let mut chunks = self.store.chunk_ids(&ids)?;
+        chunks.retain(|chunk| ids.binary_search(chunk).is_err());
+        let chunk_count = chunks.len();
+        ids.extend(chunks);
 
-    pub fn projects(&self) -> Result<Vec<String>, DomainError> {
-        self.store.projects()
+        let deleted = self.store.delete_batch(&ids)?;
+        self.bump_store_version();
+        Ok(deleted.saturating_sub(chunk_count))
     }
 
-    pub fn embedding_dimensions(&self) -> Option<usize> {
-        self.embedder.dims(self.config.embedding_model())
+    /// Delete one context and its chunks; `NotFound` if no such context exists.
+    pub fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        match self.delete_many(vec![id])? {
+            0 => Err(DomainError::not_found(format!("context {id} not found"))),
+            _ => Ok(()),
+        }
     }
 
-    pub fn health(&self) -> Result<HealthStatusResponse, DomainError> {
-        self.store.ping()?;
+    /// Re-embed every searchable record of `project` with the active model,
+    /// returning how many were updated. Archived records stay archived.
+    pub fn reembed_project(&self, project: &str) -> Result<usize, DomainError> {
+        // Match the name the records were stored under.
+        let project = sanitize_project(project);
+        if project.is_empty() {
+            return Err(DomainError::validation("project is required"));
+        }
+        if self.store.is_remote() {
+            self.ensure_writable()?;
+            let count = self.store.reembed_project_remote(&project)?;
+            self.bump_store_version();
+            return Ok(count);
+        }
+        let _maintenance = self.begin_maintenance(MaintenanceOp::Reembed)?;
 
-        let status = HealthStatusResponse {
-            ok: true,
-            message: "ready".into(),
-            details: Some(format!(
-                "model: {}, checked_at: {}",
-                self.config.embedding_model,
-                Utc::now()
-            )),
-        };
+        let mut records = self.store.project_records(&project)?;
+        records.retain(|record| record.searchable);
+        for record in &mut records {
+            let model = &self.config.embedding_model;
+            record.embedding = self.embed_content(
+                model,
+                &record.summary,
+                &record.body,
+                &record.tags,
+                record.file_path.as_deref(),
+            )?;
+            self.attach_field_embeddings(model, record)?;
+            record.touch();
+        }
 
-        Ok(status)
+        self.store.persist_batch(&records)?;
+        self.bump_store_version();
+        Ok(records.len())
     }
 
-    fn validate_payload(&self, payload: &IngestContextRequest) -> Result<(), DomainError> {
-        if payload.project.trim().is_empty() {
-            return Err(DomainError::validation("project is required"));
-        }
-        if payload.ide.trim().is_empty() {
-            return Err(DomainError::validation("ide is required"));
-        }
-        if payload.summary.trim().is_empty() {
-            return Err(DomainError::validation("summary is required"));
+    /// Re-embed every searchable record with the active model, reporting
+    /// progress after each batch. `cancel` is checked between records and
+    /// batches already written stay migrated. Records already embedded by the
+    /// active model and version are skipped, so a cancelled run resumes where
+    /// it stopped.
+    pub fn reembed_all(
+        &self,
+        cancel: &AtomicBool,
+        mut on_progress: impl FnMut(ReembedProgress),
+    ) -> Result<ReembedProgress, DomainError> {
+        if self.store.is_remote() {
+            return Err(DomainError::other(
+                "re-embedding the store is not available in remote mode; run this operation on mcp-service",
+            ));
         }
-        if payload.summary.chars().count() > MAX_SUMMARY_CHARS {
-            return Err(DomainError::limit(format!(
-                "summary cannot exceed {} characters",
-                MAX_SUMMARY_CHARS
-            )));
+        let _maintenance = self.begin_maintenance(MaintenanceOp::Reembed)?;
+        let ids = self
+            .store
+            .iter_summaries()
+            .filter_map(|summary| match summary {
+                Ok(summary) => summary.searchable.then_some(Ok(summary.id)),
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<Result<Vec<Uuid>, DomainError>>()?;
+        let model = &self.config.embedding_model;
+        let version = self.embedder.model_version(model);
+        let mut progress = ReembedProgress {
+            total: ids.len(),
+            ..ReembedProgress::default()
+        };
+        on_progress(progress);
+
+        for chunk in ids.chunks(REEMBED_BATCH) {
+            let mut updated = Vec::with_capacity(chunk.len());
+            let mut visited = 0;
+            for mut record in self.store.fetch_records(chunk)? {
+                if cancel.load(Ordering::Relaxed) {
+                    progress.cancelled = true;
+                    break;
+                }
+                visited += 1;
+                if record.embedding.model == *model && record.embedding.model_version == version {
+                    continue;
+                }
+                record.embedding = self.embed_content(
+                    model,
+                    &record.summary,
+                    &record.body,
+                    &record.tags,
+                    record.file_path.as_deref(),
+                )?;
+                self.attach_field_embeddings(model, &mut record)?;
+                record.touch();
+                updated.push(record);
+            }
+
+            if !updated.is_empty() {
+                self.store.persist_batch(&updated)?;
+                self.bump_store_version();
+            }
+            progress.reembedded += updated.len();
+            // Ids deleted since the scan count as done.
+            progress.processed += if progress.cancelled {
+                visited
+            } else {
+                chunk.len()
+            };
+            on_progress(progress);
+            if progress.cancelled {
+                break;
+            }
         }
-        if payload.body.trim().is_empty() {
-            return Err(DomainError::validation("body is required"));
+        Ok(progress)
+    }
+
+    pub fn search(&self, request: SearchRequest) -> Result<SearchResponse, DomainError> {
+        let search = self.prepare_search(request)?;
+
+        let cache_key = self.config.search_cache_ttl.and_then(|_| {
+            serde_json::to_string(&(
+                &search.query_text,
+                &search.filters,
+                &search.boosted_tags,
+                search.limit,
+                search.min_score,
+                search.scope,
+                &search.model,
+                search.collapse_by_parent,
+                search.max_per_project,
+            ))
+            .ok()
+        });
+        // Read before searching: a write landing mid-search leaves this
+        // response cached under an already stale version.
+        let version = self.store_version();
+        if let Some(mut hit) = cache_key
+            .as_deref()
+            .and_then(|key| self.cached_search(key, version))
+        {
+            self.remember_query(&mut hit);
+            return Ok(hit);
         }
-        if payload.body.chars().count() > MAX_BODY_CHARS {
-            return Err(DomainError::limit(format!(
-                "body cannot exceed {} characters",
-                MAX_BODY_CHARS
-            )));
+
+        let ranked = self.rank(&search)?;
+        let returned = ranked.matches.len();
+        let results: Vec<SearchResultDto> =
+            RankedResults::new(ranked.matches, search.min_score, ranked.chunk_matches).collect();
+        let total_matched = if results.len() < returned {
+            // Matches come best first, so once `min_score` drops one
+            // nothing past `limit` could have qualified either.
+            results.len()
+        } else if ranked.saturated && ranked.plain {
+            ranked.matched_filters
+        } else {
+            // Exact when the pool held every match; otherwise collapsing and
+            // capping leave only a lower bound.
+            ranked.available
+        };
+        let mut response = self.search_response(
+            search.prompt,
+            results,
+            ranked.scanned,
+            ranked.matched_filters,
+            total_matched,
+        );
+
+        if let (Some(key), Some(ttl)) = (cache_key, self.config.search_cache_ttl) {
+            let current = self.store_version();
+            let mut cache = self.search_cache.lock();
+            cache.retain(|_, entry| entry.version == current && entry.cached_at.elapsed() < ttl);
+            cache.insert(
+                key,
+                CachedSearch {
+                    version,
+                    cached_at: Instant::now(),
+                    response: response.clone(),
+                },
+            );
         }
-        if payload.tags.len() > crate::domain::models::MAX_TAGS {
-            return Err(DomainError::limit(format!(
-                "tags cannot exceed {} entries",
-                crate::domain::models::MAX_TAGS
+
+        self.remember_query(&mut response);
+        Ok(response)
+    }
+
+    /// The results [`Self::search`] would return, built one at a time as the
+    /// iterator is consumed, for exports written out result by result.
+    /// Bypasses the response cache; the query is still recorded.
+    pub fn search_ranked(&self, request: SearchRequest) -> Result<RankedResults, DomainError> {
+        let search = self.prepare_search(request)?;
+        let ranked = self.rank(&search)?;
+        let results = RankedResults::new(ranked.matches, search.min_score, ranked.chunk_matches);
+        self.record_query(&search.prompt, results.ids());
+        Ok(results)
+    }
+
+    fn prepare_search(&self, request: SearchRequest) -> Result<PreparedSearch, DomainError> {
+        if request.prompt.trim().is_empty() {
+            return Err(DomainError::validation("prompt cannot be empty"));
+        }
+        let request = request.resolve_filter_expr()?;
+
+        let model = self.resolve_model(request.model_override.as_deref())?;
+        let soft_filter = request.soft_filter;
+        let max_limit = request.max_limit();
+        let path_hint = request.path_hint.clone();
+        let collapse_by_parent = request.collapse_by_parent;
+        let max_per_project = request.max_per_project;
+        if max_per_project == Some(0) {
+            return Err(DomainError::validation(
+                "max_per_project must be at least 1",
+            ));
+        }
+
+        let RetrievalQuery {
+            prompt,
+            mut filters,
+            limit,
+            min_score,
+            scope,
+        } = RetrievalQuery::from(request);
+        let boosted_tags = if soft_filter {
+            take_tag_filters(&mut filters)
+        } else {
+            Vec::new()
+        };
+
+        if scope != SearchScope::Both && !self.config.field_embeddings {
+            return Err(DomainError::validation(
+                "search scope requires field embeddings to be enabled in settings",
+            ));
+        }
+
+        let mut query_text = prompt.trim().to_string();
+        if let Some(path) = path_hint.as_deref().filter(|_| self.config.embed_file_path) {
+            append_path(&mut query_text, path);
+        }
+
+        Ok(PreparedSearch {
+            prompt,
+            query_text,
+            model,
+            filters,
+            boosted_tags,
+            limit: limit.clamp(1, max_limit),
+            min_score,
+            scope,
+            collapse_by_parent,
+            max_per_project,
+        })
+    }
+
+    /// Embed the query and rank the store's matches, best first and cut to
+    /// the search's limit.
+    fn rank(&self, search: &PreparedSearch) -> Result<RankedMatches, DomainError> {
+        let query_embedding = self
+            .embed_raw(&search.model, &search.query_text)
+            .map_err(|err| err.with_context("failed to embed query"))?;
+
+        let mut candidates = if search.boosted_tags.is_empty() {
+            search.limit
+        } else {
+            search.limit * SOFT_FILTER_OVERFETCH
+        };
+        if search.collapse_by_parent && self.config.chunk_size.is_some() {
+            candidates *= CHUNK_OVERFETCH;
+        }
+        if search.max_per_project.is_some() {
+            candidates *= PROJECT_CAP_OVERFETCH;
+        }
+        let mut hits = self.search_indexes(
+            &search.query_text,
+            &query_embedding,
+            candidates,
+            &search.filters,
+            search.scope,
+        )?;
+        if !hits.mismatched.is_empty() {
+            if !self.config.lazy_reembed {
+                // One mismatched record is enough to name the stored dimension.
+                let stored = self
+                    .store
+                    .fetch_records(&hits.mismatched[..1])
+                    .ok()
+                    .and_then(|records| records.first().map(|record| record.embedding.dims()))
+                    .map_or_else(|| "a different size".to_string(), |dims| dims.to_string());
+                return Err(DomainError::embedding(format!(
+                    "embedding dimension mismatch: store has {stored}, model produces {}",
+                    query_embedding.dims()
+                )));
+            }
+            self.schedule_lazy_reembed(std::mem::take(&mut hits.mismatched));
+        }
+        if !search.boosted_tags.is_empty() {
+            boost_tag_matches(
+                &mut hits.matches,
+                &search.boosted_tags,
+                self.config.tag_boost,
+            );
+        }
+        // A full candidate pool means matches may exist past it.
+        let saturated = hits.matches.len() >= candidates;
+        // Without chunks or a project cap every filter match is a result.
+        let plain = search.max_per_project.is_none()
+            && !hits.matches.iter().any(|(record, _)| record.is_chunk());
+        let (mut matches, chunk_matches) =
+            self.resolve_chunk_hits(hits.matches, search.collapse_by_parent, candidates)?;
+        if let Some(max) = search.max_per_project {
+            cap_per_project(&mut matches, max);
+        }
+        let available = matches.len();
+        matches.truncate(search.limit);
+
+        Ok(RankedMatches {
+            matches,
+            chunk_matches,
+            scanned: hits.scanned,
+            matched_filters: hits.matched_filters,
+            available,
+            saturated,
+            plain,
+        })
+    }
+
+    /// Store hits for `query_embedding`, merged with the legacy index's when
+    /// one is attached. Legacy models differing by name are kept apart with a
+    /// model filter; a same-named one (e.g. the simple backend at another
+    /// dimension) is told apart by dimension alone.
+    fn search_indexes(
+        &self,
+        query_text: &str,
+        query_embedding: &ContextEmbedding,
+        limit: usize,
+        filters: &QueryFilters,
+        scope: SearchScope,
+    ) -> Result<SearchHits, DomainError> {
+        let read = |embedding: &ContextEmbedding, filters: &QueryFilters| {
+            self.store
+                .search(embedding, limit, filters, scope)
+                .map_err(|err| err.with_context("failed to read store"))
+        };
+        // Overridden models search on their own.
+        let Some(legacy) = self
+            .legacy
+            .as_deref()
+            .filter(|_| query_embedding.model == self.config.embedding_model)
+        else {
+            return read(query_embedding, filters);
+        };
+
+        let by_model = !legacy.model.eq_ignore_ascii_case(&query_embedding.model);
+        let scoped = |model: &str| {
+            let mut filters = filters.clone();
+            if by_model {
+                filters.model = Some(model.to_string());
+            }
+            filters
+        };
+        let mut hits = read(query_embedding, &scoped(&query_embedding.model))?;
+        let legacy_embedding = match self.legacy_query_embedding(legacy, query_text) {
+            Ok(embedding) => embedding,
+            Err(err) => {
+                tracing::warn!(target: "ingat::embed", "legacy index skipped: {err}");
+                return Ok(hits);
+            }
+        };
+        let legacy_hits = read(&legacy_embedding, &scoped(&legacy.model))?;
+
+        if by_model {
+            hits.matched_filters += legacy_hits.matched_filters;
+            hits.mismatched.extend(legacy_hits.mismatched);
+        } else {
+            // Each pass reports the other's records as mismatched; only
+            // those neither could score are.
+            let unscored: HashSet<Uuid> = legacy_hits.mismatched.into_iter().collect();
+            hits.mismatched.retain(|id| unscored.contains(id));
+        }
+        hits.scanned = hits.scanned.max(legacy_hits.scanned);
+        hits.matches.extend(
+            legacy_hits
+                .matches
+                .into_iter()
+                .map(|(record, score)| (record, score - self.config.legacy_penalty)),
+        );
+        hits.matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        hits.matches.truncate(limit);
+        Ok(hits)
+    }
+
+    fn legacy_query_embedding(
+        &self,
+        legacy: &LegacyIndex,
+        query_text: &str,
+    ) -> Result<ContextEmbedding, DomainError> {
+        if let Some(cached) = legacy.queries.lock().get(query_text) {
+            return Ok(cached.clone());
+        }
+        let embedding = self.embed_with(legacy.engine.as_ref(), &legacy.model, query_text)?;
+        let mut queries = legacy.queries.lock();
+        if queries.len() >= LEGACY_QUERY_CACHE {
+            queries.clear();
+        }
+        queries.insert(query_text.to_string(), embedding.clone());
+        Ok(embedding)
+    }
+
+    /// Records re-embedded in the background because a search found them
+    /// with the wrong dimension, since the service started.
+    pub fn lazy_reembedded(&self) -> usize {
+        self.lazy_reembed.completed.load(Ordering::Relaxed)
+    }
+
+    /// Re-embed `ids` on the active model on a background thread. Records
+    /// already queued are skipped, and at most [`LAZY_REEMBED_MAX_IN_FLIGHT`]
+    /// are in progress at once; the rest are picked up by later searches.
+    fn schedule_lazy_reembed(&self, ids: Vec<Uuid>) {
+        // Written back with `persist`, a remote record would be ingested anew.
+        if self.store.is_remote() {
+            return;
+        }
+        let batch: Vec<Uuid> = {
+            let mut in_flight = self.lazy_reembed.in_flight.lock();
+            let room = LAZY_REEMBED_MAX_IN_FLIGHT.saturating_sub(in_flight.len());
+            ids.into_iter()
+                .filter(|id| in_flight.insert(*id))
+                .take(room)
+                .collect()
+        };
+        if batch.is_empty() {
+            return;
+        }
+
+        let worker = ContextService {
+            embedder: Arc::clone(&self.embedder),
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+            search_cache: Mutex::new(HashMap::new()),
+            store_version: Arc::clone(&self.store_version),
+            composition_cache: Arc::clone(&self.composition_cache),
+            lazy_reembed: Arc::clone(&self.lazy_reembed),
+            maintenance: Arc::clone(&self.maintenance),
+            legacy: self.legacy.clone(),
+        };
+        let queued = batch.clone();
+        let spawned = std::thread::Builder::new()
+            .name("ingat-lazy-reembed".into())
+            .spawn(move || worker.run_lazy_reembed(batch));
+        if let Err(err) = spawned {
+            tracing::warn!(target: "ingat::embed", "failed to start lazy re-embed: {err}");
+            let mut in_flight = self.lazy_reembed.in_flight.lock();
+            for id in &queued {
+                in_flight.remove(id);
+            }
+        }
+    }
+
+    /// Best-effort: a failure is logged and the records are retried the next
+    /// time a search runs into them.
+    fn run_lazy_reembed(&self, ids: Vec<Uuid>) {
+        let model = &self.config.embedding_model;
+        let result = self.store.fetch_records(&ids).and_then(|mut records| {
+            records.retain(|record| record.searchable);
+            for record in &mut records {
+                record.embedding = self.embed_content(
+                    model,
+                    &record.summary,
+                    &record.body,
+                    &record.tags,
+                    record.file_path.as_deref(),
+                )?;
+                self.attach_field_embeddings(model, record)?;
+                record.touch();
+            }
+            self.store.persist_batch(&records)?;
+            self.bump_store_version();
+            Ok(records.len())
+        });
+        match result {
+            Ok(count) => {
+                self.lazy_reembed
+                    .completed
+                    .fetch_add(count, Ordering::Relaxed);
+                tracing::debug!(target: "ingat::embed", count, "lazily re-embedded records");
+            }
+            Err(err) => tracing::warn!(target: "ingat::embed", "lazy re-embed failed: {err}"),
+        }
+
+        let mut in_flight = self.lazy_reembed.in_flight.lock();
+        for id in &ids {
+            in_flight.remove(id);
+        }
+    }
+
+    /// Remembered searches, most recent first.
+    pub fn recent_queries(
+        &self,
+        limit: Option<usize>,
+    ) -> Result<Vec<QueryHistoryEntry>, DomainError> {
+        self.store
+            .recent_queries(limit.unwrap_or(DEFAULT_RECENT_QUERIES).max(1))
+    }
+
+    pub fn clear_query_history(&self) -> Result<usize, DomainError> {
+        self.store.clear_query_history()
+    }
+
+    /// History is a convenience; failing to record must not fail the search.
+    /// With analytics on, also keeps the returned ids and tags `response`
+    /// with the `query_id` feedback refers to.
+    fn remember_query(&self, response: &mut SearchResponse) {
+        let returned = response.results.iter().map(|result| result.id).collect();
+        response.query_id = self.record_query(&response.query, returned);
+    }
+
+    /// Add `prompt` to the query history and, with analytics on, record the
+    /// search event feedback refers to; returns that event's id.
+    fn record_query(&self, prompt: &str, returned: Vec<Uuid>) -> Option<Uuid> {
+        let entry = QueryHistoryEntry {
+            prompt: prompt.to_string(),
+            results: returned.len(),
+            searched_at: Utc::now(),
+        };
+        if let Err(err) = self.store.record_query(&entry) {
+            tracing::warn!(target: "ingat::queries", "failed to record query: {err}");
+        }
+        if !self.config.search_analytics {
+            return None;
+        }
+        let event = SearchEvent {
+            query_id: Uuid::new_v4(),
+            prompt: prompt.to_string(),
+            returned,
+            searched_at: entry.searched_at,
+            chosen: Vec::new(),
+        };
+        match self.store.record_search_event(&event) {
+            Ok(()) => Some(event.query_id),
+            Err(err) => {
+                tracing::warn!(target: "ingat::feedback", "failed to record search event: {err}");
+                None
+            }
+        }
+    }
+
+    /// Note that `chosen_id` was used after the search `query_id`.
+    pub fn record_feedback(
+        &self,
+        query_id: Uuid,
+        chosen_id: Uuid,
+    ) -> Result<SearchEvent, DomainError> {
+        self.require_search_analytics()?;
+        self.store.record_feedback(query_id, chosen_id)
+    }
+
+    /// Keep a search event recorded by a remote-mode client, whose searches
+    /// carry its own `query_id`.
+    pub fn record_search_event(&self, event: SearchEvent) -> Result<(), DomainError> {
+        self.require_search_analytics()?;
+        self.store.record_search_event(&event)
+    }
+
+    /// Recorded search events, oldest first.
+    pub fn search_events(&self) -> Result<Vec<SearchEvent>, DomainError> {
+        self.store.search_events()
+    }
+
+    fn require_search_analytics(&self) -> Result<(), DomainError> {
+        if self.config.search_analytics {
+            Ok(())
+        } else {
+            Err(DomainError::validation(
+                "search analytics are disabled; enable `search.search_analytics` first",
+            ))
+        }
+    }
+
+    /// How often searches led to a chosen result, and at which rank.
+    pub fn feedback_stats(&self) -> Result<FeedbackStats, DomainError> {
+        let events = self.store.search_events()?;
+        let mut stats = FeedbackStats {
+            enabled: self.config.search_analytics,
+            searches: events.len(),
+            ..FeedbackStats::default()
+        };
+        let mut reciprocal_sum = 0.0;
+        for event in &events {
+            if event.chosen.is_empty() {
+                continue;
+            }
+            stats.with_feedback += 1;
+            stats.choices += event.chosen.len();
+            for choice in &event.chosen {
+                match choice.rank {
+                    Some(rank) => {
+                        if stats.rank_counts.len() <= rank {
+                            stats.rank_counts.resize(rank + 1, 0);
+                        }
+                        stats.rank_counts[rank] += 1;
+                    }
+                    None => stats.outside_results += 1,
+                }
+            }
+            // Best rank per search; a choice outside the results scores 0.
+            if let Some(best) = event.chosen.iter().filter_map(|choice| choice.rank).min() {
+                reciprocal_sum += 1.0 / (best + 1) as f64;
+            }
+        }
+        if stats.with_feedback > 0 {
+            stats.mean_reciprocal_rank = reciprocal_sum / stats.with_feedback as f64;
+        }
+        Ok(stats)
+    }
+
+    pub fn clear_feedback(&self) -> Result<usize, DomainError> {
+        self.store.clear_search_events()
+    }
+
+    fn cached_search(&self, key: &str, version: u64) -> Option<SearchResponse> {
+        let ttl = self.config.search_cache_ttl?;
+        let cache = self.search_cache.lock();
+        cache
+            .get(key)
+            .filter(|entry| entry.version == version && entry.cached_at.elapsed() < ttl)
+            .map(|entry| entry.response.clone())
+    }
+
+    /// Counter of writes made through this service since it started.
+    pub fn store_version(&self) -> u64 {
+        self.store_version.load(Ordering::Acquire)
+    }
+
+    /// Run `request` as a semantic search and format the results as a Markdown
+    /// document for sharing.
+    pub fn export_results_markdown(&self, request: SearchRequest) -> Result<String, DomainError> {
+        Ok(render_markdown(&self.search(request)?))
+    }
+
+    /// Lexical search ranked by TF-IDF; complements semantic search for exact identifiers.
+    pub fn keyword_search(&self, request: SearchRequest) -> Result<SearchResponse, DomainError> {
+        let collapse_by_parent = request.collapse_by_parent;
+        let RetrievalQuery {
+            prompt,
+            filters,
+            limit,
+            min_score,
+            ..
+        } = RetrievalQuery::from(request.resolve_filter_expr()?);
+
+        let terms = tokenize_terms(&prompt);
+        if terms.is_empty() {
+            return Err(DomainError::validation(
+                "prompt must contain at least one searchable term",
+            ));
+        }
+
+        let limit = limit.clamp(1, MAX_SEARCH_LIMIT);
+        // One extra match tells whether the list was cut at `limit`.
+        let matches = self
+            .store
+            .keyword_search(&terms, limit + 1, &filters)
+            .map_err(|err| err.with_context("failed to read store"))?;
+        let (mut matches, chunk_matches) =
+            self.resolve_chunk_hits(matches, collapse_by_parent, limit + 1)?;
+        // Counted after collapsing, so chunks of a returned record don't
+        // count as more.
+        let more_than_limit = matches.len() > limit;
+        matches.truncate(limit);
+
+        let returned = matches.len();
+        let results: Vec<SearchResultDto> =
+            RankedResults::new(matches, min_score, chunk_matches).collect();
+        let total_matched = if more_than_limit && results.len() == returned {
+            results.len() + 1
+        } else {
+            results.len()
+        };
+        let mut response = self.search_response(prompt, results, 0, 0, total_matched);
+        self.remember_query(&mut response);
+        Ok(response)
+    }
+
+    /// Narrow a previous result set without rescanning the store: `previous`
+    /// ids are filtered in memory and, when `prompt` is given, re-ranked by
+    /// similarity to it. Without a prompt the previous order is kept and
+    /// scores are reported as 0.
+    pub fn refine(
+        &self,
+        previous: Vec<Uuid>,
+        filters: QueryFilters,
+        prompt: Option<String>,
+    ) -> Result<SearchResponse, DomainError> {
+        if previous.len() > MAX_SEARCH_LIMIT {
+            return Err(DomainError::validation(format!(
+                "refine accepts at most {MAX_SEARCH_LIMIT} ids"
             )));
         }
-        Self::validate_kind(&payload.kind)
+        let prompt = prompt.filter(|prompt| !prompt.trim().is_empty());
+        let filters = validate_filters(filters)?;
+
+        let fetched = self.store.fetch_records(&previous)?;
+        let scanned = fetched.len();
+        let records: Vec<ContextRecord> = fetched
+            .into_iter()
+            .filter(|record| record.matches_filters(&filters))
+            .collect();
+        let matched_filters = records.len();
+        let records = records.into_iter();
+
+        let mut matches: Vec<(ContextRecord, f32)> = match &prompt {
+            Some(prompt) => {
+                let query = self.embed_raw(&self.config.embedding_model, prompt.trim())?;
+                records
+                    .map(|record| {
+                        let score = cosine(&query.vector, &record.embedding.vector);
+                        (record, score)
+                    })
+                    .collect()
+            }
+            None => records.map(|record| (record, 0.0)).collect(),
+        };
+        if prompt.is_some() {
+            matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        }
+
+        Ok(self.search_response(
+            prompt.unwrap_or_default(),
+            RankedResults::new(matches, None, HashMap::new()).collect(),
+            scanned,
+            matched_filters,
+            matched_filters,
+        ))
+    }
+
+    /// Stored kinds by index key with their record counts, most common first.
+    pub fn context_kinds(&self) -> Result<Vec<LabelCount>, DomainError> {
+        let counts = self.store.kind_counts()?.into_iter().collect();
+        Ok(ranked_counts(counts, usize::MAX))
     }
 
-    fn validate_kind(kind: &ContextKind) -> Result<(), DomainError> {
-        match kind {
-            ContextKind::Other(label) if label.trim().is_empty() => {
-                Err(DomainError::validation("custom kind label cannot be empty"))
+    /// Check every stored embedding for wrong dimensions and zero or
+    /// non-finite components. In remote mode the service runs the check.
+    pub fn verify(&self) -> Result<VerifyReport, DomainError> {
+        if self.store.is_remote() {
+            return self.store.verify_remote();
+        }
+        let active_model = self.config.embedding_model.clone();
+        let expected_dims = self.embedding_dimensions();
+        let mut report = VerifyReport::default();
+
+        self.store.scan_records(&mut |record| {
+            report.total += 1;
+            let record = match record {
+                Ok(record) => record,
+                Err(_) => {
+                    report.unreadable += 1;
+                    return;
+                }
+            };
+
+            // Archived records have no vectors by design.
+            if !record.searchable {
+                report.healthy += 1;
+                return;
             }
-            _ => Ok(()),
+
+            let fields = [
+                ("embedding", Some(&record.embedding)),
+                ("summary_embedding", record.summary_embedding.as_ref()),
+                ("body_embedding", record.body_embedding.as_ref()),
+            ];
+
+            let mut healthy = true;
+            for (field, embedding) in fields {
+                let Some(embedding) = embedding else {
+                    continue;
+                };
+                // Vectors from other models legitimately have other sizes.
+                let expected = expected_dims.filter(|_| embedding.model == active_model);
+                if let Some(problem) = check_vector(&embedding.vector, expected) {
+                    healthy = false;
+                    report.issues.push(VerifyIssue {
+                        id: record.id,
+                        field: field.to_string(),
+                        problem,
+                    });
+                }
+            }
+
+            if healthy {
+                report.healthy += 1;
+            }
+        })?;
+
+        Ok(report)
+    }
+
+    /// Recompute term document frequencies across the whole store.
+    pub fn rebuild_term_stats(&self) -> Result<usize, DomainError> {
+        let _maintenance = self.begin_maintenance(MaintenanceOp::RebuildTermStats)?;
+        self.store.rebuild_term_stats()
+    }
+
+    /// Make deferred writes durable, e.g. before the process exits.
+    pub fn flush(&self) -> Result<(), DomainError> {
+        self.store.flush()
+    }
+
+    /// Recovery path for index drift after a crash or an external import.
+    /// Also compacts the change feed: tombstones older than the sync horizon
+    /// ([`ServiceConfig::tombstone_retention_days`]) are dropped.
+    pub fn rebuild_index(&self) -> Result<usize, DomainError> {
+        let _maintenance = self.begin_maintenance(MaintenanceOp::RebuildIndex)?;
+        let indexed = self.store.rebuild_index()?;
+        let horizon =
+            Utc::now() - chrono::Duration::days(self.config.tombstone_retention_days.into());
+        let pruned = self.store.prune_tombstones(horizon)?;
+        if pruned > 0 {
+            tracing::info!(target: "ingat::storage", "pruned {pruned} tombstones older than {horizon}");
+        }
+        self.bump_store_version();
+        Ok(indexed)
+    }
+
+    pub fn history(
+        &self,
+        project: Option<String>,
+        tag: Option<String>,
+        since: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+        order: HistoryOrder,
+    ) -> Result<SummaryListResponse, DomainError> {
+        let capped_limit = limit.unwrap_or(self.config.default_limit).clamp(1, 50);
+        let tag = tag.and_then(normalize_tag);
+        let summaries = self.store.recent(
+            project.as_deref(),
+            tag.as_deref(),
+            since,
+            capped_limit,
+            order,
+        )?;
+
+        Ok(SummaryListResponse { items: summaries })
+    }
+
+    /// Pin or unpin a record together with its chunks.
+    pub fn set_pinned(&self, id: &str, pinned: bool) -> Result<ContextSummary, DomainError> {
+        self.ensure_writable()?;
+        let id = parse_context_id(id)?;
+        let summary = self.store.set_pinned(id, pinned)?;
+        for chunk in self.store.chunk_ids(&[id])? {
+            self.store.set_pinned(chunk, pinned)?;
+        }
+        self.bump_store_version();
+        Ok(summary)
+    }
+
+    /// Drop the vectors of a record (and its chunks) that should stay
+    /// readable in history but no longer match searches. Archiving an
+    /// archived record is a no-op.
+    pub fn archive(&self, id: &str) -> Result<ContextSummary, DomainError> {
+        self.ensure_writable()?;
+        if self.store.is_remote() {
+            return self.archive_remote(id, true);
+        }
+        let mut records = self.fetch_with_chunks(id)?;
+        if !records[0].searchable {
+            return Ok(records[0].as_summary());
+        }
+
+        for record in &mut records {
+            record.embedding.vector = Vec::new();
+            record.summary_embedding = None;
+            record.body_embedding = None;
+            record.searchable = false;
+            record.touch();
+        }
+
+        self.store.persist_batch(&records)?;
+        self.bump_store_version();
+        Ok(records[0].as_summary())
+    }
+
+    /// Re-embed an archived record (and its chunks) with the active model and
+    /// make it searchable again.
+    pub fn unarchive(&self, id: &str) -> Result<ContextSummary, DomainError> {
+        self.ensure_writable()?;
+        if self.store.is_remote() {
+            return self.archive_remote(id, false);
+        }
+        let mut records = self.fetch_with_chunks(id)?;
+        if records[0].searchable {
+            return Ok(records[0].as_summary());
+        }
+
+        let model = &self.config.embedding_model;
+        for record in &mut records {
+            record.embedding = self.embed_content(
+                model,
+                &record.summary,
+                &record.body,
+                &record.tags,
+                record.file_path.as_deref(),
+            )?;
+            self.attach_field_embeddings(model, record)?;
+            record.searchable = true;
+            record.touch();
+        }
+
+        self.store.persist_batch(&records)?;
+        self.bump_store_version();
+        Ok(records[0].as_summary())
+    }
+
+    /// The service owning a remote store archives (or re-embeds) the record.
+    fn archive_remote(&self, id: &str, archived: bool) -> Result<ContextSummary, DomainError> {
+        let summary = self
+            .store
+            .set_archived_remote(parse_context_id(id)?, archived)?;
+        self.bump_store_version();
+        Ok(summary)
+    }
+
+    fn fetch_one(&self, id: &str) -> Result<ContextRecord, DomainError> {
+        let id = parse_context_id(id)?;
+        self.store
+            .fetch_records(&[id])?
+            .pop()
+            .ok_or_else(|| DomainError::not_found(format!("context {id} not found")))
+    }
+
+    /// The record `id` followed by its chunks.
+    fn fetch_with_chunks(&self, id: &str) -> Result<Vec<ContextRecord>, DomainError> {
+        let record = self.fetch_one(id)?;
+        let chunks = self.store.chunk_ids(&[record.id])?;
+        let mut records = vec![record];
+        records.extend(self.store.fetch_records(&chunks)?);
+        Ok(records)
+    }
+
+    /// Compare two stored contexts: body line diff, tag and metadata
+    /// differences, and the similarity of their vectors.
+    pub fn diff(&self, a: Uuid, b: Uuid) -> Result<ContextDiff, DomainError> {
+        let mut records = self.store.fetch_records(&[a, b])?.into_iter();
+        let (a, b) = match (records.next(), records.next()) {
+            (Some(first), Some(second)) => (first, second),
+            (Some(found), None) => {
+                let missing = if found.id == a { b } else { a };
+                return Err(DomainError::not_found(format!(
+                    "context {missing} not found"
+                )));
+            }
+            _ => return Err(DomainError::not_found(format!("context {a} not found"))),
+        };
+
+        let tags_a: BTreeSet<String> = a.tags.iter().cloned().collect();
+        let tags_b: BTreeSet<String> = b.tags.iter().cloned().collect();
+        let (vector_a, vector_b) = (&a.embedding.vector, &b.embedding.vector);
+        let similarity = (!vector_a.is_empty() && vector_a.len() == vector_b.len())
+            .then(|| cosine(vector_a, vector_b));
+
+        Ok(ContextDiff {
+            similarity,
+            same_project: a.project == b.project,
+            same_kind: a.kind == b.kind,
+            shared_tags: tags_a.intersection(&tags_b).cloned().collect(),
+            tags_only_in_a: tags_a.difference(&tags_b).cloned().collect(),
+            tags_only_in_b: tags_b.difference(&tags_a).cloned().collect(),
+            body: diff_lines(&a.body, &b.body),
+            a: a.as_summary(),
+            b: b.as_summary(),
+        })
+    }
+
+    pub fn changes_since(
+        &self,
+        since: &str,
+        include_deleted: bool,
+    ) -> Result<Vec<ChangeEvent>, DomainError> {
+        let since = DateTime::parse_from_rfc3339(since.trim())
+            .map_err(|err| DomainError::validation(format!("invalid RFC 3339 timestamp: {err}")))?
+            .with_timezone(&Utc);
+        self.store.changes_since(since, include_deleted)
+    }
+
+    /// Aggregate counts by kind, top tags, time span and latest summaries for `project`.
+    pub fn project_overview(
+        &self,
+        project: &str,
+        recent_limit: Option<usize>,
+    ) -> Result<ProjectOverview, DomainError> {
+        // Match the name the records were stored under.
+        let project = sanitize_project(project);
+        if project.is_empty() {
+            return Err(DomainError::validation("project is required"));
+        }
+        // The service caps remote listings, so it counts the project itself.
+        if self.store.is_remote() {
+            return self.store.project_overview_remote(&project, recent_limit);
+        }
+
+        // Summaries come back newest first.
+        let summaries = self.store.recent(
+            Some(&project),
+            None,
+            None,
+            usize::MAX,
+            HistoryOrder::default(),
+        )?;
+
+        let mut kinds: HashMap<String, usize> = HashMap::new();
+        let mut tags: HashMap<String, usize> = HashMap::new();
+        for summary in &summaries {
+            *kinds.entry(summary.kind.label().to_string()).or_default() += 1;
+            for tag in &summary.tags {
+                *tags.entry(tag.clone()).or_default() += 1;
+            }
+        }
+
+        let recent_limit = recent_limit
+            .unwrap_or(self.config.default_limit)
+            .clamp(1, 50);
+
+        Ok(ProjectOverview {
+            project,
+            total: summaries.len(),
+            kinds: ranked_counts(kinds, usize::MAX),
+            top_tags: ranked_counts(tags, OVERVIEW_TOP_TAGS),
+            earliest: summaries.last().map(|summary| summary.created_at),
+            latest: summaries.first().map(|summary| summary.created_at),
+            recent: summaries.into_iter().take(recent_limit).collect(),
+        })
+    }
+
+    pub fn projects(&self) -> Result<Vec<String>, DomainError> {
+        self.store.projects()
+    }
+
+    /// Every project with its record count and latest `created_at`, most
+    /// recently active first, gathered in a single pass over the summaries.
+    pub fn projects_with_activity(&self) -> Result<Vec<ProjectInfo>, DomainError> {
+        let mut projects: HashMap<String, ProjectInfo> = HashMap::new();
+        for summary in self.store.iter_summaries() {
+            let summary = summary?;
+            if summary.parent_id.is_some() {
+                continue;
+            }
+            let info = projects
+                .entry(summary.project.clone())
+                .or_insert_with(|| ProjectInfo {
+                    name: summary.project,
+                    count: 0,
+                    last_activity: summary.created_at,
+                });
+            info.count += 1;
+            info.last_activity = info.last_activity.max(summary.created_at);
         }
+
+        let mut projects: Vec<ProjectInfo> = projects.into_values().collect();
+        projects.sort_by(|a, b| {
+            b.last_activity
+                .cmp(&a.last_activity)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(projects)
+    }
+
+    /// The `k` (at most [`MAX_SIMILAR_PAIRS`]) most similar pairs of searchable
+    /// records on the active model, by cosine of their main vectors, best first.
+    /// Fails with `LimitExceeded` above [`MAX_SIMILAR_PAIR_RECORDS`] records.
+    pub fn top_similar_pairs(&self, k: usize) -> Result<Vec<(Uuid, Uuid, f32)>, DomainError> {
+        let k = k.min(MAX_SIMILAR_PAIRS);
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let model = self.config.embedding_model.as_str();
+        let mut vectors: Vec<(Uuid, Vec<f32>)> = Vec::new();
+        let mut too_many = false;
+        self.store.scan_records(&mut |record| {
+            let Ok(record) = record else {
+                return;
+            };
+            if too_many
+                || !record.searchable
+                || record.is_chunk()
+                || record.embedding.model != model
+            {
+                return;
+            }
+            if vectors.len() == MAX_SIMILAR_PAIR_RECORDS {
+                too_many = true;
+                return;
+            }
+            let norm = record
+                .embedding
+                .vector
+                .iter()
+                .map(|x| x * x)
+                .sum::<f32>()
+                .sqrt();
+            if norm > 0.0 && norm.is_finite() {
+                let unit = record.embedding.vector.iter().map(|x| x / norm).collect();
+                vectors.push((record.id, unit));
+            }
+        })?;
+        if too_many {
+            return Err(DomainError::limit(format!(
+                "top_similar_pairs compares at most {MAX_SIMILAR_PAIR_RECORDS} records; \
+                 narrow the store (e.g. archive old records) first"
+            )));
+        }
+
+        // Min-heap of the best `k` so far; the root is the pair to evict.
+        let mut best: BinaryHeap<Reverse<SimilarPair>> = BinaryHeap::with_capacity(k + 1);
+        for (i, (left, a)) in vectors.iter().enumerate() {
+            for (right, b) in &vectors[i + 1..] {
+                if a.len() != b.len() {
+                    continue;
+                }
+                let score = a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+                if !score.is_finite() {
+                    continue;
+                }
+                if best.len() == k {
+                    match best.peek() {
+                        Some(Reverse(worst)) if worst.score >= score => continue,
+                        _ => {
+                            best.pop();
+                        }
+                    }
+                }
+                best.push(Reverse(SimilarPair {
+                    score: score.clamp(-1.0, 1.0),
+                    left: *left,
+                    right: *right,
+                }));
+            }
+        }
+
+        Ok(best
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(pair)| (pair.left, pair.right, pair.score))
+            .collect())
+    }
+
+    /// Projects ranked by the Jaccard similarity of their tag set to
+    /// `project`'s, best first. Projects sharing no tag are left out.
+    pub fn related_projects(
+        &self,
+        project: &str,
+        limit: usize,
+    ) -> Result<Vec<RelatedProject>, DomainError> {
+        // Match the name the records were stored under.
+        let project = sanitize_project(project);
+        if project.is_empty() {
+            return Err(DomainError::validation("project is required"));
+        }
+
+        let mut tag_sets: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for summary in self.store.iter_summaries() {
+            let summary = summary?;
+            if summary.parent_id.is_some() {
+                continue;
+            }
+            tag_sets
+                .entry(summary.project)
+                .or_default()
+                .extend(summary.tags);
+        }
+        let target = tag_sets
+            .remove(&project)
+            .ok_or_else(|| DomainError::not_found(format!("project {project} not found")))?;
+
+        let mut related: Vec<RelatedProject> = tag_sets
+            .into_iter()
+            .filter_map(|(name, tags)| {
+                let shared_tags: Vec<String> = target.intersection(&tags).cloned().collect();
+                if shared_tags.is_empty() {
+                    return None;
+                }
+                let union = target.union(&tags).count();
+                Some(RelatedProject {
+                    name,
+                    score: shared_tags.len() as f32 / union as f32,
+                    shared_tags,
+                })
+            })
+            .collect();
+        related.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        related.truncate(limit.clamp(1, MAX_RELATED_PROJECTS));
+        Ok(related)
+    }
+
+    /// Embed `samples` synthetic strings and report latency percentiles and throughput.
+    pub fn benchmark_embedding(&self, samples: usize) -> Result<EmbeddingBenchmark, DomainError> {
+        let samples = samples.clamp(1, MAX_BENCHMARK_SAMPLES);
+        let mut latencies = Vec::with_capacity(samples);
+        let mut dimensions = 0;
+
+        let started = Instant::now();
+        for index in 0..samples {
+            let text = format!(
+                "benchmark sample {index}: retry the failing request with exponential backoff \
+                 and log the response body when the status code is not successful"
+            );
+            let call_started = Instant::now();
+            let vector = self.embedder.embed(&self.config.embedding_model, &text)?;
+            latencies.push(call_started.elapsed().as_secs_f64() * 1000.0);
+            dimensions = vector.len();
+        }
+        let total_secs = started.elapsed().as_secs_f64();
+
+        latencies.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = ((latencies.len() - 1) as f64 * p).round() as usize;
+            latencies[rank]
+        };
+
+        Ok(EmbeddingBenchmark {
+            model: self.config.embedding_model.clone(),
+            samples,
+            dimensions,
+            p50_ms: percentile(0.50),
+            p95_ms: percentile(0.95),
+            embeddings_per_second: if total_secs > 0.0 {
+                samples as f64 / total_secs
+            } else {
+                0.0
+            },
+        })
+    }
+
+    /// Run `scenario` against this service through the regular ingest and
+    /// search paths, timing every step and checking that expected records
+    /// come back. A failing step is recorded and the session continues.
+    pub fn simulate_session(
+        &self,
+        scenario: SimulationScenario,
+    ) -> Result<SimulationTranscript, DomainError> {
+        let project = scenario.project.trim().to_string();
+        if project.is_empty() {
+            return Err(DomainError::validation("simulation project is required"));
+        }
+
+        let mut transcript = SimulationTranscript::default();
+        let mut ingested: HashMap<usize, Uuid> = HashMap::new();
+        let mut reciprocal_ranks = 0.0;
+        let started = Instant::now();
+        for (index, step) in scenario.steps.into_iter().enumerate() {
+            let step_started = Instant::now();
+            let mut result = SimulationStepResult {
+                index,
+                action: String::new(),
+                elapsed_ms: 0.0,
+                id: None,
+                results: None,
+                expected_ranks: Vec::new(),
+                error: None,
+            };
+            match step {
+                SimulationStep::Ingest {
+                    summary,
+                    body,
+                    tags,
+                    kind,
+                } => {
+                    result.action = "ingest".into();
+                    let outcome = self.ingest(IngestContextRequest {
+                        project: project.clone(),
+                        ide: "ingat-simulation".into(),
+                        file_path: None,
+                        language: None,
+                        summary,
+                        body,
+                        tags,
+                        kind,
+                        pinned: false,
+                        model_override: None,
+                        source: None,
+                        flush: Some(false),
+                    });
+                    match outcome {
+                        Ok(summary) => {
+                            ingested.insert(index, summary.id);
+                            result.id = Some(summary.id);
+                        }
+                        Err(err) => result.error = Some(err.to_string()),
+                    }
+                }
+                SimulationStep::Search {
+                    prompt,
+                    limit,
+                    expect,
+                } => {
+                    result.action = "search".into();
+                    let outcome = SearchRequest::builder(prompt)
+                        .project(project.clone())
+                        .limit(limit.unwrap_or(self.config.default_limit))
+                        .build()
+                        .and_then(|request| self.search(request));
+                    match outcome {
+                        Ok(response) => {
+                            result.results = Some(response.results.len());
+                            for step in expect {
+                                let rank = ingested.get(&step).and_then(|id| {
+                                    response
+                                        .results
+                                        .iter()
+                                        .position(|hit| hit.id == *id)
+                                        .map(|position| position + 1)
+                                });
+                                transcript.expected += 1;
+                                if let Some(rank) = rank {
+                                    transcript.recalled += 1;
+                                    reciprocal_ranks += 1.0 / rank as f64;
+                                }
+                                result.expected_ranks.push(rank);
+                            }
+                        }
+                        Err(err) => result.error = Some(err.to_string()),
+                    }
+                }
+            }
+            result.elapsed_ms = step_started.elapsed().as_secs_f64() * 1000.0;
+            if result.error.is_some() {
+                transcript.failed_steps += 1;
+            }
+            transcript.steps.push(result);
+        }
+        transcript.total_ms = started.elapsed().as_secs_f64() * 1000.0;
+        if transcript.expected > 0 {
+            transcript.mean_reciprocal_rank = reciprocal_ranks / transcript.expected as f64;
+        }
+
+        if scenario.cleanup && !ingested.is_empty() {
+            transcript.cleaned_up = self.delete_many(ingested.into_values().collect())?;
+        }
+        Ok(transcript)
+    }
+
+    pub fn embedding_model(&self) -> &str {
+        self.config.embedding_model()
+    }
+
+    pub fn embedding_dimensions(&self) -> Option<usize> {
+        self.embedder.dims(self.config.embedding_model())
+    }
+
+    /// Compare the active model's dimension with what is already stored for it.
+    pub fn dimension_mismatch(&self) -> Result<Option<DimensionMismatch>, DomainError> {
+        let Some(configured) = self.embedding_dimensions() else {
+            return Ok(None);
+        };
+        let model = &self.config.embedding_model;
+        let mismatched: Vec<EmbeddingComposition> = self
+            .composition()?
+            .into_iter()
+            .filter(|entry| entry.model == *model && entry.dimensions != configured)
+            .collect();
+        if mismatched.is_empty() {
+            return Ok(None);
+        }
+
+        let mut stored: Vec<usize> = mismatched.iter().map(|entry| entry.dimensions).collect();
+        stored.sort_unstable();
+        stored.dedup();
+        Ok(Some(DimensionMismatch {
+            model: model.clone(),
+            configured,
+            stored,
+            affected_records: mismatched.iter().map(|entry| entry.count).sum(),
+        }))
+    }
+
+    /// On-disk footprint next to the logical record count.
+    pub fn storage_stats(&self) -> Result<StorageStats, DomainError> {
+        let disk_bytes = self.store.disk_usage()?;
+        let records: usize = self
+            .store
+            .kind_counts()?
+            .iter()
+            .map(|(_, count)| count)
+            .sum();
+        Ok(StorageStats {
+            disk_bytes,
+            records,
+            bytes_per_record: disk_bytes
+                .filter(|_| records > 0)
+                .map(|bytes| bytes / records as u64),
+        })
+    }
+
+    pub fn store_composition(&self) -> Result<StoreCompositionResponse, DomainError> {
+        let entries = self.composition()?;
+        let total = entries.iter().map(|entry| entry.count).sum();
+        Ok(StoreCompositionResponse {
+            active_model: self.config.embedding_model.clone(),
+            active_dimensions: self.embedding_dimensions(),
+            total,
+            entries,
+        })
+    }
+
+    /// The store's embedding composition, rescanned only after a write made
+    /// through this service. A remote store is asked every time, since other
+    /// clients write to it too.
+    fn composition(&self) -> Result<Vec<EmbeddingComposition>, DomainError> {
+        if self.store.is_remote() {
+            return self.store.store_composition();
+        }
+        let version = self.store_version();
+        if let Some((cached_at, entries)) = &*self.composition_cache.lock() {
+            if *cached_at == version {
+                return Ok(entries.clone());
+            }
+        }
+        let entries = self.store.store_composition()?;
+        *self.composition_cache.lock() = Some((version, entries.clone()));
+        Ok(entries)
+    }
+
+    pub fn health(&self) -> Result<HealthStatusResponse, DomainError> {
+        self.store.ping()?;
+
+        let state = self.state();
+        let status = HealthStatusResponse {
+            ok: true,
+            message: match state {
+                ServiceState::Idle => "ready".into(),
+                ServiceState::Maintenance { op } => format!("maintenance: {}", op.as_str()),
+            },
+            details: Some(format!(
+                "model: {}, checked_at: {}",
+                self.config.embedding_model,
+                Utc::now()
+            )),
+            composition: self.composition()?,
+            mode: None,
+            lazy_reembedded: self.lazy_reembedded(),
+            state,
+        };
+
+        Ok(status)
+    }
+
+    /// Embed arbitrary text with the active engine without storing anything.
+    pub fn embed_text(&self, text: String) -> Result<ContextEmbedding, DomainError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(DomainError::validation("text is required"));
+        }
+        if text.chars().count() > MAX_BODY_CHARS {
+            return Err(DomainError::limit(format!(
+                "text cannot exceed {} characters",
+                MAX_BODY_CHARS
+            )));
+        }
+
+        self.embed_raw(&self.config.embedding_model, text)
+    }
+
+    /// Model to embed with: the per-request override when given, otherwise the active one.
+    /// Overrides must produce vectors of the active dimension so results stay comparable.
+    fn resolve_model(&self, model_override: Option<&str>) -> Result<String, DomainError> {
+        let active = &self.config.embedding_model;
+        let Some(model) = model_override
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+        else {
+            return Ok(active.clone());
+        };
+
+        let active_dims = self.embedder.dims(active);
+        let override_dims = self.embedder.dims(model);
+        if active_dims.is_some() && override_dims != active_dims {
+            return Err(DomainError::embedding(format!(
+                "model `{}` is not compatible with the active engine (`{}`, {} dimensions)",
+                model,
+                active,
+                active_dims.unwrap_or_default()
+            )));
+        }
+
+        Ok(model.to_string())
+    }
+
+    /// Make every cached search response stale; call after any write to the store.
+    fn bump_store_version(&self) {
+        self.store_version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Response envelope; `found` and `message` tell agents plainly when
+    /// nothing matched.
+    /// `total_matched` counts every result the search could have returned
+    /// (at least `results.len()`); `has_more` is set when it exceeds them.
+    fn search_response(
+        &self,
+        query: String,
+        results: Vec<SearchResultDto>,
+        scanned: usize,
+        matched_filters: usize,
+        total_matched: usize,
+    ) -> SearchResponse {
+        let found = !results.is_empty();
+        let total_matched = total_matched.max(results.len());
+        SearchResponse {
+            query,
+            has_more: total_matched > results.len(),
+            total_matched,
+            results,
+            scanned,
+            matched_filters,
+            found,
+            message: if found {
+                None
+            } else {
+                self.config.empty_results_message.clone()
+            },
+            query_id: None,
+        }
+    }
+
+    /// Drop chunk hits whose record was since deleted or archived, then, when
+    /// `collapse` is set, keep only the best hit per chunked record (the record
+    /// itself or one of its chunks). Returns the first `limit` hits and, for
+    /// collapsed groups, how many hits each one stands for.
+    fn resolve_chunk_hits(
+        &self,
+        mut matches: Vec<(ContextRecord, f32)>,
+        collapse: bool,
+        limit: usize,
+    ) -> Result<(Vec<(ContextRecord, f32)>, HashMap<Uuid, usize>), DomainError> {
+        if !matches.iter().any(|(record, _)| record.is_chunk()) {
+            matches.truncate(limit);
+            return Ok((matches, HashMap::new()));
+        }
+
+        let parent_ids: BTreeSet<Uuid> = matches
+            .iter()
+            .filter_map(|(record, _)| record.parent_id)
+            .collect();
+        let parent_ids: Vec<Uuid> = parent_ids.into_iter().collect();
+        let live: HashSet<Uuid> = self
+            .store
+            .fetch_records(&parent_ids)?
+            .into_iter()
+            .filter(|parent| parent.searchable)
+            .map(|parent| parent.id)
+            .collect();
+        matches.retain(|(record, _)| {
+            record
+                .parent_id
+                .map_or(true, |parent| live.contains(&parent))
+        });
+
+        let mut counts: HashMap<Uuid, usize> = HashMap::new();
+        if collapse {
+            matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+            // Chunked record id -> id of its best hit.
+            let mut kept: HashMap<Uuid, Uuid> = HashMap::new();
+            matches.retain(|(record, _)| {
+                let group = record.parent_id.unwrap_or(record.id);
+                let best = *kept.entry(group).or_insert(record.id);
+                if live.contains(&group) {
+                    *counts.entry(best).or_default() += 1;
+                }
+                best == record.id
+            });
+        }
+        matches.truncate(limit);
+        Ok((matches, counts))
+    }
+
+    /// Distinct terms of a record's summary and body.
+    fn record_terms(record: &ContextRecord) -> impl Iterator<Item = String> {
+        tokenize_terms(&record.summary)
+            .into_iter()
+            .chain(tokenize_terms(&record.body))
+    }
+
+    fn embed_content(
+        &self,
+        model: &str,
+        summary: &str,
+        body: &str,
+        tags: &[String],
+        file_path: Option<&str>,
+    ) -> Result<ContextEmbedding, DomainError> {
+        let mut text = match self.config.embed_scope {
+            EmbedScope::SummaryPlusBody => format!("{}\n{}", summary.trim(), body.trim()),
+            EmbedScope::SummaryOnly => format!("{}\n{}", summary.trim(), tags.join(" ")),
+        };
+        if let Some(path) = file_path.filter(|_| self.config.embed_file_path) {
+            append_path(&mut text, path);
+        }
+        self.embed_raw(model, text.trim_end())
+    }
+
+    fn embed_raw(&self, model: &str, text: &str) -> Result<ContextEmbedding, DomainError> {
+        self.embed_with(self.embedder.as_ref(), model, text)
+    }
+
+    fn embed_with(
+        &self,
+        engine: &dyn EmbeddingEngine,
+        model: &str,
+        text: &str,
+    ) -> Result<ContextEmbedding, DomainError> {
+        let text = preprocess(&self.config.preprocess, text);
+        let text = match self.config.max_embed_chars {
+            Some(max_chars) => {
+                let cut = self.config.truncation.apply(&text, max_chars);
+                if cut.len() < text.len() {
+                    tracing::debug!(
+                        target: "ingat::embed",
+                        strategy = self.config.truncation.as_str(),
+                        original_chars = text.chars().count(),
+                        max_chars,
+                        "truncated embedding input"
+                    );
+                }
+                cut
+            }
+            None => Cow::Borrowed(text.as_ref()),
+        };
+        let vector = engine.embed(model, &text)?;
+        ensure_finite(&vector)?;
+        Ok(ContextEmbedding::new(model, vector).with_version(engine.model_version(model)))
+    }
+
+    /// Populate (or clear) the per-field vectors according to the service config.
+    fn attach_field_embeddings(
+        &self,
+        model: &str,
+        record: &mut ContextRecord,
+    ) -> Result<(), DomainError> {
+        if self.config.field_embeddings {
+            record.summary_embedding = Some(self.embed_raw(model, record.summary.trim())?);
+            record.body_embedding = Some(self.embed_raw(model, record.body.trim())?);
+        } else {
+            record.summary_embedding = None;
+            record.body_embedding = None;
+        }
+        Ok(())
+    }
+
+    fn validate_payload(&self, payload: &IngestContextRequest) -> Result<(), DomainError> {
+        if payload.project.trim().is_empty() {
+            return Err(DomainError::validation("project is required"));
+        }
+        if payload.ide.trim().is_empty() {
+            return Err(DomainError::validation("ide is required"));
+        }
+        if payload.summary.trim().is_empty() {
+            return Err(DomainError::validation("summary is required"));
+        }
+        if payload.summary.chars().count() > MAX_SUMMARY_CHARS {
+            return Err(DomainError::limit(format!(
+                "summary cannot exceed {} characters",
+                MAX_SUMMARY_CHARS
+            )));
+        }
+        if payload.body.trim().is_empty() {
+            return Err(DomainError::validation("body is required"));
+        }
+        if payload.body.chars().count() > MAX_BODY_CHARS {
+            return Err(DomainError::limit(format!(
+                "body cannot exceed {} characters",
+                MAX_BODY_CHARS
+            )));
+        }
+        if payload.tags.len() > crate::domain::models::MAX_TAGS {
+            return Err(DomainError::limit(format!(
+                "tags cannot exceed {} entries",
+                crate::domain::models::MAX_TAGS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Reject vectors with NaN/inf components before they reach storage or scoring.
+fn ensure_finite(vector: &[f32]) -> Result<(), DomainError> {
+    if vector.iter().all(|value| value.is_finite()) {
+        Ok(())
+    } else {
+        Err(DomainError::embedding(
+            "embedding contains NaN or infinite values",
+        ))
+    }
+}
+
+/// First integrity problem with `vector`, if any.
+fn check_vector(vector: &[f32], expected_dims: Option<usize>) -> Option<VerifyProblem> {
+    if let Some(expected) = expected_dims {
+        if vector.len() != expected {
+            return Some(VerifyProblem::WrongDimensions {
+                expected,
+                actual: vector.len(),
+            });
+        }
+    }
+    if vector.iter().any(|value| !value.is_finite()) {
+        return Some(VerifyProblem::NonFinite);
+    }
+
+    if vector.iter().all(|value| *value == 0.0) {
+        return Some(VerifyProblem::ZeroVector);
+    }
+
+    None
+}
+
+/// Probe texts for [`embedding_selftest`]: a sentence, a paraphrase of it and
+/// an unrelated one.
+const SELFTEST_ANCHOR: &str = "retry failed HTTP requests with exponential backoff";
+const SELFTEST_RELATED: &str = "retry the failing HTTP request using exponential backoff";
+const SELFTEST_UNRELATED: &str = "bake the chocolate cake until the frosting sets";
+
+/// Embed a few probe texts with `engine` and check the vectors are sound,
+/// stable, and rank a paraphrase above unrelated text. `backend` only labels
+/// the report; timing of the engine's start-up is left to the caller.
+pub fn embedding_selftest(
+    backend: &str,
+    engine: &dyn EmbeddingEngine,
+    model: &str,
+) -> Result<EmbeddingSelfTest, DomainError> {
+    let mut latencies = Vec::with_capacity(4);
+    let mut embed = |text: &str| {
+        let started = Instant::now();
+        let vector = engine.embed(model, text)?;
+        latencies.push(started.elapsed().as_secs_f64() * 1000.0);
+        Ok::<_, DomainError>(vector)
+    };
+    let anchor = embed(SELFTEST_ANCHOR)?;
+    let repeat = embed(SELFTEST_ANCHOR)?;
+    let related = embed(SELFTEST_RELATED)?;
+    let unrelated = embed(SELFTEST_UNRELATED)?;
+
+    let expected = engine.dims(model).or(Some(anchor.len()));
+    let mut problems: Vec<VerifyProblem> = [&anchor, &related, &unrelated]
+        .into_iter()
+        .filter_map(|vector| check_vector(vector, expected))
+        .collect();
+    problems.dedup();
+
+    let related_score = cosine(&anchor, &related);
+    let unrelated_score = cosine(&anchor, &unrelated);
+    let deterministic = anchor == repeat;
+    Ok(EmbeddingSelfTest {
+        backend: backend.to_string(),
+        model: model.to_string(),
+        dimensions: anchor.len(),
+        active_dimensions: None,
+        init_ms: 0.0,
+        embed_ms: latencies.iter().sum::<f64>() / latencies.len() as f64,
+        deterministic,
+        related_score,
+        unrelated_score,
+        passed: problems.is_empty() && deterministic && related_score > unrelated_score,
+        problems,
+    })
+}
+
+/// Windows of `size` chars, each starting `size - overlap` chars after the
+/// previous one. Windows are widened so at most [`MAX_CHUNKS`] are produced.
+fn chunk_windows(text: &str, size: usize, overlap: usize) -> Vec<&str> {
+    let bounds: Vec<usize> = text
+        .char_indices()
+        .map(|(at, _)| at)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let chars = bounds.len() - 1;
+    let overlap = overlap.min(size.saturating_sub(1));
+    let size = size.max(chars.div_ceil(MAX_CHUNKS) + overlap).max(1);
+    let step = size - overlap;
+
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + size).min(chars);
+        windows.push(&text[bounds[start]..bounds[end]]);
+        if end == chars {
+            return windows;
+        }
+        start += step;
+    }
+}
+
+/// Append `path` on its own line with forward slashes and no leading `./`,
+/// so the same file embeds alike whichever OS recorded it.
+fn append_path(text: &mut String, path: &str) {
+    let path = path.trim().replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    if !path.is_empty() {
+        text.push('\n');
+        text.push_str(path);
+    }
+}
+
+/// Candidate of [`ContextService::top_similar_pairs`], ordered by score.
+#[derive(Debug, Clone, Copy)]
+struct SimilarPair {
+    score: f32,
+    left: Uuid,
+    right: Uuid,
+}
+
+impl PartialEq for SimilarPair {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for SimilarPair {}
+
+impl PartialOrd for SimilarPair {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SimilarPair {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| (self.left, self.right).cmp(&(other.left, other.right)))
+    }
+}
+
+/// Cosine similarity for in-memory re-ranking; vectors that can't be compared
+/// (other dimensions, zero norm, non-finite) score 0.
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms =
+        a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|y| y * y).sum::<f32>().sqrt();
+    let score = dot / norms;
+    if score.is_finite() {
+        score.clamp(-1.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// One section per result; code-like kinds keep their body verbatim in a fence.
+fn render_markdown(response: &SearchResponse) -> String {
+    let mut out = format!("# Ingat results: {}\n", response.query.trim());
+    if response.results.is_empty() {
+        out.push_str("\n_No matching contexts._\n");
+        return out;
+    }
+
+    for (index, result) in response.results.iter().enumerate() {
+        let title = result
+            .summary
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let tags = if result.tags.is_empty() {
+            "_none_".to_string()
+        } else {
+            result
+                .tags
+                .iter()
+                .map(|tag| format!("`{tag}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        out.push_str(&format!(
+            "\n## {}. {title}\n\n- **Project:** {}\n- **Kind:** {}\n- **Tags:** {tags}\n- **Score:** {:.3}\n- **Id:** `{}`\n\n",
+            index + 1,
+            result.project,
+            result.kind.label(),
+            result.score,
+            result.id,
+        ));
+
+        let body = result.body.trim_end();
+        if matches!(result.kind, ContextKind::CodeSnippet | ContextKind::ToolLog) {
+            // Outlast any backtick run in the body so it can't close the fence early.
+            let longest_run = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+            let fence = "`".repeat(longest_run.max(2) + 1);
+            out.push_str(&format!("{fence}\n{body}\n{fence}\n"));
+        } else {
+            out.push_str(body);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Move every tag condition out of `filters` (the `tag` field and tag groups),
+/// returning the requested tags for boosting.
+fn take_tag_filters(filters: &mut QueryFilters) -> Vec<String> {
+    let mut tags: Vec<String> = filters.tag.take().into_iter().collect();
+    filters.groups.retain(|group| {
+        if group.field != FilterField::Tag {
+            return true;
+        }
+        tags.extend(group.any_of.iter().cloned());
+        false
+    });
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Add `boost` per requested tag each record carries, then re-rank.
+fn boost_tag_matches(matches: &mut [(ContextRecord, f32)], tags: &[String], boost: f32) {
+    for (record, score) in matches.iter_mut() {
+        let matched = record.tags.iter().filter(|tag| tags.contains(tag)).count();
+        *score += boost * matched as f32;
+    }
+    matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+}
+
+/// Drop hits past the `max`-th of each project from `matches` (best first),
+/// keeping the order of the rest.
+fn cap_per_project(matches: &mut Vec<(ContextRecord, f32)>, max: usize) {
+    let mut per_project: HashMap<String, usize> = HashMap::new();
+    matches.retain(|(record, _)| {
+        let taken = per_project.entry(record.project.clone()).or_default();
+        *taken += 1;
+        *taken <= max
+    });
+}
+
+/// Append `language` as a normalized tag unless it is already tagged or the
+/// record is at [`MAX_TAGS`](crate::domain::models::MAX_TAGS).
+fn tag_language(tags: &mut Vec<String>, language: Option<&str>) {
+    let Some(language) = language.and_then(normalize_tag) else {
+        return;
+    };
+    let tagged = tags
+        .iter()
+        .any(|tag| normalize_tag(tag.as_str()).as_deref() == Some(language.as_str()));
+    if !tagged && tags.len() < crate::domain::models::MAX_TAGS {
+        tags.push(language);
+    }
+}
+
+fn parse_context_id(id: &str) -> Result<Uuid, DomainError> {
+    Uuid::parse_str(id.trim())
+        .map_err(|err| DomainError::validation(format!("invalid context id: {err}")))
+}
+
+/// Kind suggested by an ingest's content, checked in order: fix words in
+/// the summary, then a code fence, `language` or `file_path`, then a
+/// question-like summary.
+fn infer_kind(payload: &IngestContextRequest) -> Option<ContextKind> {
+    let words: Vec<String> = payload
+        .summary
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.iter().any(|word| FIX_WORDS.contains(&word.as_str())) {
+        return Some(ContextKind::FixHistory);
+    }
+    let has_code = payload.body.contains("```") || payload.body.contains("~~~");
+    let has_location = [&payload.language, &payload.file_path]
+        .into_iter()
+        .any(|field| {
+            field
+                .as_deref()
+                .is_some_and(|value| !value.trim().is_empty())
+        });
+    if has_code || has_location {
+        return Some(ContextKind::CodeSnippet);
+    }
+    let question = payload.summary.trim_end().ends_with('?')
+        || words
+            .first()
+            .is_some_and(|word| QUESTION_WORDS.contains(&word.as_str()));
+    question.then_some(ContextKind::Discussion)
+}
+
+/// First sentence of the first non-blank body line, cut at a word boundary
+/// with an ellipsis when it runs past [`AUTO_SUMMARY_CHARS`].
+fn derive_summary(body: &str) -> String {
+    let line = body
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let sentence = line
+        .char_indices()
+        .find(|&(at, ch)| {
+            matches!(ch, '.' | '!' | '?')
+                && line[at + ch.len_utf8()..]
+                    .chars()
+                    .next()
+                    .map_or(true, char::is_whitespace)
+        })
+        .map_or(line, |(at, ch)| &line[..at + ch.len_utf8()]);
+
+    if sentence.chars().count() <= AUTO_SUMMARY_CHARS {
+        return sentence.to_string();
+    }
+    let cut = sentence
+        .char_indices()
+        .nth(AUTO_SUMMARY_CHARS - 1)
+        .map_or(sentence.len(), |(at, _)| at);
+    let head = &sentence[..cut];
+    let head = head
+        .rfind(char::is_whitespace)
+        .map_or(head, |at| &head[..at]);
+    format!("{}…", head.trim_end())
+}
+
+/// Sort label counts by frequency (ties alphabetically) and keep the first `limit`.
+fn ranked_counts(counts: HashMap<String, usize>, limit: usize) -> Vec<LabelCount> {
+    let mut ranked: Vec<LabelCount> = counts
+        .into_iter()
+        .map(|(label, count)| LabelCount { label, count })
+        .collect();
+    ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::infrastructure::{MemoryVectorStore, SimpleEmbedEngine};
+
+    /// A service over `store` that embeds with the 64-dimension hash engine.
+    pub(crate) fn service_over(
+        store: Arc<dyn VectorStore>,
+        config: ServiceConfig,
+    ) -> ContextService {
+        ContextService::new(
+            Arc::new(SimpleEmbedEngine::try_new("ingat/simple-hash", 64).unwrap()),
+            store,
+            config,
+        )
+    }
+
+    /// [`service_over`] an empty in-memory store.
+    pub(crate) fn memory_service_with(config: ServiceConfig) -> ContextService {
+        service_over(Arc::new(MemoryVectorStore::new()), config)
+    }
+
+    pub(crate) fn memory_service() -> ContextService {
+        memory_service_with(ServiceConfig::with_model("ingat/simple-hash"))
+    }
+
+    /// An `ingat`/`vscode` note with no tags, path or kind; override the rest
+    /// with struct update syntax.
+    pub(crate) fn ingest_request(summary: &str, body: &str) -> IngestContextRequest {
+        IngestContextRequest {
+            project: "ingat".into(),
+            ide: "vscode".into(),
+            file_path: None,
+            language: None,
+            summary: summary.into(),
+            body: body.into(),
+            tags: Vec::new(),
+            kind: ContextKind::default(),
+            pinned: false,
+            model_override: None,
+            source: None,
+            flush: None,
+        }
+    }
+
+    #[test]
+    fn test_check_vector_flags_anomalies() {
+        assert_eq!(check_vector(&[0.6, 0.8], Some(2)), None);
+        assert_eq!(
+            check_vector(&[0.6, 0.8], Some(3)),
+            Some(VerifyProblem::WrongDimensions {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            check_vector(&[0.0, 0.0], None),
+            Some(VerifyProblem::ZeroVector)
+        );
+        assert_eq!(
+            check_vector(&[f32::NAN, 1.0], None),
+            Some(VerifyProblem::NonFinite)
+        );
+    }
+
+    #[test]
+    fn test_ensure_finite_rejects_nan_vectors() {
+        assert!(ensure_finite(&[0.1, 0.2]).is_ok());
+        assert!(matches!(
+            ensure_finite(&[0.1, f32::NAN]),
+            Err(DomainError::Embedding(_))
+        ));
+        assert!(ensure_finite(&[f32::INFINITY]).is_err());
+    }
+
+    #[test]
+    fn test_custom_kinds_are_normalized_and_checked_for_collisions() {
+        assert_eq!(
+            ContextKind::Other("  Release Notes_v2 ".into())
+                .normalized()
+                .unwrap(),
+            ContextKind::Other("release-notes-v2".into())
+        );
+        assert!(ContextKind::Other("code_snippet".into())
+            .normalized()
+            .is_err());
+        assert!(ContextKind::Other("ToolLog".into()).normalized().is_err());
+        assert!(ContextKind::Other(" - ".into()).normalized().is_err());
+    }
+
+    #[test]
+    fn test_markdown_export_fences_code_kinds() {
+        let result = |kind, body: &str| SearchResultDto {
+            id: Uuid::nil(),
+            project: "ingat".into(),
+            summary: "Retry\nhelper".into(),
+            body: body.into(),
+            tags: vec!["http".into()],
+            kind,
+            score: 0.5,
+            created_at: Utc::now(),
+            pinned: false,
+            source: None,
+            parent_id: None,
+            chunk_matches: None,
+        };
+        let markdown = render_markdown(&SearchResponse {
+            query: "retry".into(),
+            results: vec![
+                result(ContextKind::CodeSnippet, "let s = \"```\";"),
+                result(ContextKind::Discussion, "Use backoff."),
+            ],
+            scanned: 2,
+            matched_filters: 2,
+            total_matched: 2,
+            has_more: false,
+            found: true,
+            message: None,
+            query_id: None,
+        });
+
+        assert!(markdown.starts_with("# Ingat results: retry\n"));
+        assert!(markdown.contains("## 1. Retry helper\n"));
+        assert!(markdown.contains("- **Tags:** `http`\n- **Score:** 0.500\n"));
+        assert!(markdown.contains("````\nlet s = \"```\";\n````\n"));
+        assert!(markdown.contains("`\n\nUse backoff.\n"));
+    }
+
+    #[test]
+    fn test_cosine_scores_incomparable_vectors_as_zero() {
+        assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0, 0.0], &[1.0, 0.0, 0.0]), 0.0);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_truncation_strategies_keep_expected_window() {
+        let text = "abcdefghij";
+        assert_eq!(TruncationStrategy::Head.apply(text, 4), "abcd");
+        assert_eq!(TruncationStrategy::Tail.apply(text, 4), "ghij");
+        assert_eq!(TruncationStrategy::HeadTail.apply(text, 5), "abcij");
+        assert_eq!(TruncationStrategy::HeadTail.apply("héllo", 10), "héllo");
+        assert_eq!(TruncationStrategy::Tail.apply("héllo wörld", 5), "wörld");
+    }
+
+    #[test]
+    fn test_preprocess_steps_run_in_order() {
+        let text = "## Fix **Retry**\n\n```rust\nlet  x = `y`;\n```\n- Use   backoff\n2. Done";
+        assert_eq!(preprocess(&[], text), text);
+        assert_eq!(
+            preprocess(
+                &[
+                    PreprocessStep::StripCodeFences,
+                    PreprocessStep::TrimMarkdown,
+                    PreprocessStep::CollapseWhitespace,
+                    PreprocessStep::Lowercase,
+                ],
+                text
+            ),
+            "fix retry let x = y; use backoff done"
+        );
+    }
+
+    #[test]
+    fn test_derived_summary_takes_first_sentence() {
+        assert_eq!(
+            derive_summary("\n  Retry with backoff. Cap at 5 tries.\nMore"),
+            "Retry with backoff."
+        );
+        assert_eq!(derive_summary("Bump v1.2 then ship"), "Bump v1.2 then ship");
+        assert_eq!(derive_summary("   "), "");
+
+        let long = derive_summary(&"word ".repeat(100));
+        assert!(long.ends_with("word…"));
+        assert!(long.chars().count() <= AUTO_SUMMARY_CHARS);
+    }
+
+    #[test]
+    fn test_infer_kind_from_content() {
+        let request = |summary: &str, body: &str, file_path: Option<&str>| IngestContextRequest {
+            file_path: file_path.map(String::from),
+            flush: Some(false),
+            ..ingest_request(summary, body)
+        };
+        let infer = |summary, body, file_path| infer_kind(&request(summary, body, file_path));
+
+        assert_eq!(
+            infer(
+                "Fixed sled lock error",
+                "Close the handle.",
+                Some("src/lib.rs")
+            ),
+            Some(ContextKind::FixHistory)
+        );
+        assert_eq!(
+            infer("Retry helper", "```rust\nretry()\n```", None),
+            Some(ContextKind::CodeSnippet)
+        );
+        assert_eq!(
+            infer("Retry helper", "Wraps calls.", Some("src/retry.rs")),
+            Some(ContextKind::CodeSnippet)
+        );
+        assert_eq!(
+            infer("Should we shard the store", "Pros and cons.", None),
+            Some(ContextKind::Discussion)
+        );
+        assert_eq!(
+            infer("Sharding, revisited?", "Pros and cons.", None),
+            Some(ContextKind::Discussion)
+        );
+        // "prefix" and "debugger" only contain fix words.
+        assert_eq!(infer("Prefix debugger notes", "Plain text.", None), None);
+
+        let service = memory_service_with(
+            ServiceConfig::with_model("ingat/simple-hash").with_infer_kind(true),
+        );
+        let inferred = service
+            .ingest(request(
+                "Retry helper",
+                "Wraps calls.",
+                Some("src/retry.rs"),
+            ))
+            .unwrap();
+        assert_eq!(inferred.kind, ContextKind::CodeSnippet);
+        let explicit = service
+            .ingest(IngestContextRequest {
+                kind: ContextKind::Discussion,
+                ..request("Fixed retry bug", "```rust\nretry()\n```", None)
+            })
+            .unwrap();
+        assert_eq!(explicit.kind, ContextKind::Discussion);
+    }
+
+    #[test]
+    fn test_ingest_search_and_delete_on_memory_store() {
+        let store = Arc::new(MemoryVectorStore::new());
+        let service = service_over(
+            Arc::clone(&store) as Arc<dyn VectorStore>,
+            ServiceConfig::with_model("ingat/simple-hash").with_auto_summary(true),
+        );
+
+        let summary = service
+            .ingest(IngestContextRequest {
+                tags: vec!["http".into()],
+                kind: ContextKind::FixHistory,
+                ..ingest_request(
+                    "",
+                    "Retry HTTP calls with exponential backoff. Cap at five tries.",
+                )
+            })
+            .unwrap();
+        assert_eq!(
+            summary.summary,
+            "Retry HTTP calls with exponential backoff."
+        );
+
+        let response = service
+            .search(
+                crate::application::SearchRequestBuilder::new("exponential backoff")
+                    .project("ingat")
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].id, summary.id);
+        assert_eq!((response.scanned, response.matched_filters), (1, 1));
+
+        let queries = service.recent_queries(None).unwrap();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].prompt, "exponential backoff");
+        assert_eq!(queries[0].results, 1);
+        assert_eq!(service.clear_query_history().unwrap(), 1);
+        assert!(service.recent_queries(None).unwrap().is_empty());
+
+        let fetched = service
+            .get_many(vec![Uuid::new_v4(), summary.id, summary.id])
+            .unwrap();
+        assert_eq!(fetched.len(), 1);
+        assert_eq!(fetched[0].id, summary.id);
+        assert_eq!(fetched[0].embedding.vector.len(), 64);
+        assert!(matches!(
+            service.get_many(vec![summary.id; MAX_FETCH_BATCH + 1]),
+            Err(DomainError::LimitExceeded(_))
+        ));
+
+        assert_eq!(
+            service.delete_many(vec![summary.id, summary.id]).unwrap(),
+            1
+        );
+        assert!(store.is_empty());
+        assert!(matches!(
+            service.delete(summary.id),
+            Err(DomainError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_lazy_reembed_skips_and_repairs_mismatched_records() {
+        let stale = ContextRecord::new(
+            "ingat",
+            "vscode",
+            None::<String>,
+            None::<String>,
+            "Sled lock handling",
+            "Only one process may open the sled database at a time.",
+            Vec::<String>::new(),
+            ContextKind::Discussion,
+            ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+        );
+        let store = Arc::new(MemoryVectorStore::new());
+        store.persist(&stale).unwrap();
+        let service = |lazy| {
+            service_over(
+                Arc::clone(&store) as Arc<dyn VectorStore>,
+                ServiceConfig::with_model("ingat/simple-hash").with_lazy_reembed(lazy),
+            )
+        };
+        let request = || {
+            crate::application::SearchRequestBuilder::new("sled database lock")
+                .build()
+                .unwrap()
+        };
+
+        match service(false).search(request()) {
+            Err(DomainError::Embedding(message)) => assert_eq!(
+                message,
+                "embedding dimension mismatch: store has 2, model produces 64"
+            ),
+            other => panic!("expected a dimension mismatch, got {other:?}"),
+        }
+
+        let service = service(true);
+        let response = service.search(request()).unwrap();
+        assert!(response.results.is_empty());
+        assert_eq!(response.matched_filters, 1);
+
+        let started = Instant::now();
+        while service.lazy_reembedded() == 0 && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(service.lazy_reembedded(), 1);
+        assert_eq!(service.search(request()).unwrap().results.len(), 1);
+    }
+
+    #[test]
+    fn test_reembed_all_stops_on_cancel_and_resumes() {
+        let store = Arc::new(MemoryVectorStore::new());
+        for i in 0..REEMBED_BATCH + 8 {
+            let record = ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                format!("Note {i}"),
+                "Embedded by a backend that is no longer active.",
+                Vec::<String>::new(),
+                ContextKind::Discussion,
+                ContextEmbedding::new("old/model", vec![0.6, 0.8]),
+            );
+            store.persist(&record).unwrap();
+        }
+        let service = service_over(
+            Arc::clone(&store) as Arc<dyn VectorStore>,
+            ServiceConfig::with_model("ingat/simple-hash"),
+        );
+
+        let cancel = AtomicBool::new(false);
+        let first = service
+            .reembed_all(&cancel, |progress| {
+                if progress.processed > 0 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            })
+            .unwrap();
+        assert!(first.cancelled);
+        assert_eq!(first.processed, REEMBED_BATCH);
+        assert_eq!(first.reembedded, REEMBED_BATCH);
+
+        let mut reports = Vec::new();
+        let resumed = service
+            .reembed_all(&AtomicBool::new(false), |progress| reports.push(progress))
+            .unwrap();
+        assert!(!resumed.cancelled);
+        assert_eq!(resumed.processed, REEMBED_BATCH + 8);
+        assert_eq!(resumed.reembedded, 8);
+        assert_eq!(reports.len(), 3);
+
+        let again = service
+            .reembed_all(&AtomicBool::new(false), |_| {})
+            .unwrap();
+        assert_eq!(again.reembedded, 0);
+    }
+
+    #[test]
+    fn test_writes_fail_fast_during_maintenance() {
+        let service = memory_service();
+        let request = || IngestContextRequest {
+            kind: ContextKind::Discussion,
+            ..ingest_request("Retry with backoff", "Wrap the call in a retry loop.")
+        };
+        service.ingest(request()).unwrap();
+
+        let mut checked = false;
+        service
+            .reembed_all(&AtomicBool::new(false), |_| {
+                if checked {
+                    return;
+                }
+                checked = true;
+                assert!(matches!(
+                    service.ingest(request()),
+                    Err(DomainError::Busy(op)) if op == "reembed"
+                ));
+                assert!(matches!(service.rebuild_index(), Err(DomainError::Busy(_))));
+                let health = service.health().unwrap();
+                assert_eq!(
+                    health.state,
+                    ServiceState::Maintenance {
+                        op: MaintenanceOp::Reembed
+                    }
+                );
+                let search = crate::application::SearchRequestBuilder::new("retry")
+                    .build()
+                    .unwrap();
+                assert_eq!(service.search(search).unwrap().results.len(), 1);
+            })
+            .unwrap();
+        assert!(checked);
+
+        assert_eq!(service.state(), ServiceState::Idle);
+        service.ingest(request()).unwrap();
+    }
+
+    #[test]
+    fn test_export_mode_lifts_the_interactive_limit() {
+        let service = memory_service();
+        for index in 0..40 {
+            service
+                .ingest(IngestContextRequest {
+                    kind: ContextKind::Discussion,
+                    flush: Some(false),
+                    ..ingest_request(
+                        &format!("Retry note {index}"),
+                        "Retry failed requests with backoff.",
+                    )
+                })
+                .unwrap();
+        }
+
+        let search = |export| {
+            let mut request = crate::application::SearchRequestBuilder::new("retry backoff")
+                .export_mode(export)
+                .build()
+                .unwrap();
+            request.limit = 100;
+            service.search(request).unwrap().results.len()
+        };
+        assert_eq!(search(false), MAX_SEARCH_LIMIT);
+        assert_eq!(search(true), 40);
+
+        assert!(crate::application::SearchRequestBuilder::new("retry")
+            .limit(100)
+            .build()
+            .is_err());
+        assert!(crate::application::SearchRequestBuilder::new("retry")
+            .limit(100)
+            .export_mode(true)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_search_ranked_yields_search_results_lazily() {
+        let service = memory_service();
+        for index in 0..5 {
+            service
+                .ingest(ingest_request(
+                    &format!("Retry note {index}"),
+                    "Retry failed requests with backoff.",
+                ))
+                .unwrap();
+        }
+        let request = || {
+            crate::application::SearchRequestBuilder::new("retry backoff")
+                .export_mode(true)
+                .build()
+                .unwrap()
+        };
+
+        let expected: Vec<Uuid> = service
+            .search(request())
+            .unwrap()
+            .results
+            .iter()
+            .map(|result| result.id)
+            .collect();
+        let mut ranked = service.search_ranked(request()).unwrap();
+        assert_eq!(ranked.ids(), expected);
+        let first = ranked.next().unwrap();
+        assert_eq!(first.id, expected[0]);
+        assert_eq!(ranked.ids(), expected[1..]);
+        assert_eq!(ranked.count(), expected.len() - 1);
+    }
+
+    #[test]
+    fn test_projects_with_activity_lists_most_recent_first() {
+        let record = |project: &str, minutes_ago: i64| {
+            let mut record = ContextRecord::new(
+                project,
+                "vscode",
+                None::<String>,
+                None::<String>,
+                "summary",
+                "body",
+                Vec::<String>::new(),
+                ContextKind::Discussion,
+                ContextEmbedding::new("ingat/simple-hash", vec![1.0, 0.0]),
+            );
+            record.created_at = Utc::now() - chrono::Duration::minutes(minutes_ago);
+            record
+        };
+        let store = Arc::new(MemoryVectorStore::new());
+        for record in [record("alpha", 30), record("alpha", 90), record("beta", 5)] {
+            store.persist(&record).unwrap();
+        }
+        let service = ContextService::new(
+            Arc::new(crate::infrastructure::NoOpEmbeddingEngine::for_remote_mode()),
+            store,
+            ServiceConfig::default(),
+        );
+
+        let projects = service.projects_with_activity().unwrap();
+        let names: Vec<(&str, usize)> = projects
+            .iter()
+            .map(|project| (project.name.as_str(), project.count))
+            .collect();
+        assert_eq!(names, vec![("beta", 1), ("alpha", 2)]);
+        assert!(projects[1].last_activity > Utc::now() - chrono::Duration::minutes(31));
+    }
+
+    #[test]
+    fn test_project_overview_matches_the_stored_project_name() {
+        let service = memory_service();
+        service
+            .ingest(IngestContextRequest {
+                project: "team/api".into(),
+                ..ingest_request("Overview note", "body")
+            })
+            .unwrap();
+
+        let overview = service.project_overview(" team/api\n", None).unwrap();
+        assert_eq!(overview.project, "team-api");
+        assert_eq!(overview.total, 1);
+    }
+
+    #[test]
+    fn test_related_projects_ranks_by_tag_overlap() {
+        let record = |project: &str, tags: &[&str]| {
+            ContextRecord::new(
+                project,
+                "vscode",
+                None::<String>,
+                None::<String>,
+                "summary",
+                "body",
+                tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>(),
+                ContextKind::Discussion,
+                ContextEmbedding::new("ingat/simple-hash", vec![1.0, 0.0]),
+            )
+        };
+        let store = Arc::new(MemoryVectorStore::new());
+        for record in [
+            record("api", &["rust", "axum"]),
+            record("api", &["sled"]),
+            record("worker", &["rust", "sled", "tokio"]),
+            record("cli", &["rust"]),
+            record("site", &["css"]),
+            record("team/web", &["css"]),
+        ] {
+            store.persist(&record).unwrap();
+        }
+        let service = ContextService::new(
+            Arc::new(crate::infrastructure::NoOpEmbeddingEngine::for_remote_mode()),
+            store,
+            ServiceConfig::default(),
+        );
+
+        let related = service.related_projects("api", 10).unwrap();
+        let names: Vec<&str> = related
+            .iter()
+            .map(|project| project.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["worker", "cli"]);
+        assert_eq!(related[0].shared_tags, vec!["rust", "sled"]);
+        assert!((related[0].score - 0.5).abs() < f32::EPSILON);
+
+        assert!(matches!(
+            service.related_projects("missing", 10),
+            Err(DomainError::NotFound(_))
+        ));
+
+        // Looked up under the name ingest stored it as.
+        let related = service.related_projects("team/web", 10).unwrap();
+        assert_eq!(related[0].name, "site");
+    }
+
+    #[test]
+    fn test_summary_only_scope_leaves_body_out_of_the_vector() {
+        let request = |body: &str| IngestContextRequest {
+            tags: vec!["ci".into()],
+            kind: ContextKind::ToolLog,
+            ..ingest_request("Nightly build log", body)
+        };
+        let vectors = |scope| {
+            let service = memory_service_with(
+                ServiceConfig::with_model("ingat/simple-hash").with_embed_scope(scope),
+            );
+            let [a, b] = ["linker error in crate foo", "all 312 tests passed"].map(|body| {
+                let summary = service.ingest(request(body)).unwrap();
+                service.fetch_one(&summary.id.to_string()).unwrap()
+            });
+            assert_eq!(b.body, "all 312 tests passed");
+            (a.embedding.vector, b.embedding.vector)
+        };
+
+        let (a, b) = vectors(EmbedScope::SummaryOnly);
+        assert_eq!(a, b);
+        let (a, b) = vectors(EmbedScope::SummaryPlusBody);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_diff_reports_body_and_tag_changes() {
+        use crate::domain::DiffOp;
+
+        let request = |body: &str, tags: &[&str]| IngestContextRequest {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            kind: ContextKind::CodeSnippet,
+            ..ingest_request("Retry helper", body)
+        };
+        let service = memory_service();
+        let a = service
+            .ingest(request("loop {\n    sleep(100);\n}", &["rust", "retry"]))
+            .unwrap();
+        let b = service
+            .ingest(request(
+                "loop {\n    backoff.wait();\n}",
+                &["rust", "backoff"],
+            ))
+            .unwrap();
+
+        let diff = service.diff(a.id, b.id).unwrap();
+        assert!(diff.same_project && diff.same_kind);
+        assert_eq!(diff.shared_tags, vec!["rust"]);
+        assert_eq!(diff.tags_only_in_a, vec!["retry"]);
+        assert_eq!(diff.tags_only_in_b, vec!["backoff"]);
+        let ops: Vec<DiffOp> = diff.body.iter().map(|line| line.op).collect();
+        assert_eq!(
+            ops,
+            vec![DiffOp::Same, DiffOp::Removed, DiffOp::Added, DiffOp::Same]
+        );
+        assert!(diff.similarity.is_some_and(|score| score < 1.0));
+
+        assert!(matches!(
+            service.diff(a.id, Uuid::new_v4()),
+            Err(DomainError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_embed_file_path_separates_records_by_location() {
+        let request = |path: &str| IngestContextRequest {
+            file_path: Some(path.into()),
+            language: Some("rust".into()),
+            kind: ContextKind::CodeSnippet,
+            ..ingest_request(
+                "Map errors to HTTP status codes",
+                "match err { NotFound => 404, _ => 500 }",
+            )
+        };
+        let service = |enabled| {
+            memory_service_with(
+                ServiceConfig::with_model("ingat/simple-hash").with_embed_file_path(enabled),
+            )
+        };
+        let vector = |service: &ContextService, path: &str| {
+            let summary = service.ingest(request(path)).unwrap();
+            service
+                .fetch_one(&summary.id.to_string())
+                .unwrap()
+                .embedding
+                .vector
+        };
+
+        let plain = service(false);
+        assert_eq!(
+            vector(&plain, "src/auth.rs"),
+            vector(&plain, "src/billing.rs")
+        );
+
+        let located = service(true);
+        assert_ne!(
+            vector(&located, "src/auth.rs"),
+            vector(&located, "src/billing.rs")
+        );
+        assert_eq!(
+            vector(&located, "src\\auth.rs"),
+            vector(&located, "./src/auth.rs")
+        );
+    }
+
+    #[test]
+    fn test_chunk_windows_overlap_and_cap() {
+        assert_eq!(
+            chunk_windows("abcdefghij", 4, 1),
+            vec!["abcd", "defg", "ghij"]
+        );
+        assert_eq!(chunk_windows("añb", 8, 2), vec!["añb"]);
+
+        let long = "x".repeat(10_000);
+        let windows = chunk_windows(&long, 10, 5);
+        assert!(windows.len() <= MAX_CHUNKS);
+        assert!(windows[0].len() > 10);
+        assert!(windows.iter().map(|window| window.len()).sum::<usize>() >= long.len());
+    }
+
+    #[test]
+    fn test_chunked_ingest_collapses_search_hits_by_parent() {
+        let store = Arc::new(MemoryVectorStore::new());
+        let service = service_over(
+            Arc::clone(&store) as Arc<dyn VectorStore>,
+            ServiceConfig::with_model("ingat/simple-hash").with_chunking(200, 20),
+        );
+        let body = format!(
+            "{}The ingress controller drops websocket upgrades after sixty seconds.",
+            "Meeting notes about quarterly planning and budgets. ".repeat(12)
+        );
+        let parent = service
+            .ingest(IngestContextRequest {
+                tags: vec!["notes".into()],
+                kind: ContextKind::Discussion,
+                ..ingest_request("Planning notes", &body)
+            })
+            .unwrap();
+        assert!(store.iter_summaries().count() > 1);
+
+        let history = service
+            .history(None, None, None, None, HistoryOrder::default())
+            .unwrap();
+        assert_eq!(history.items.len(), 1);
+
+        let search = |collapse| {
+            service
+                .search(
+                    crate::application::SearchRequestBuilder::new("ingress websocket upgrades")
+                        .collapse_by_parent(collapse)
+                        .build()
+                        .unwrap(),
+                )
+                .unwrap()
+                .results
+        };
+        let all = search(false);
+        assert!(all.len() > 1);
+        assert!(all.iter().any(|hit| hit.parent_id == Some(parent.id)
+            && hit.body.contains("websocket")
+            && hit.body.len() < body.len()));
+        assert!(all.iter().all(|hit| hit.chunk_matches.is_none()));
+
+        let collapsed = search(true);
+        assert_eq!(collapsed.len(), 1);
+        assert_eq!(collapsed[0].parent_id.unwrap_or(collapsed[0].id), parent.id);
+        assert_eq!(collapsed[0].chunk_matches, Some(all.len()));
+
+        let chunks = store.chunk_ids(&[parent.id]).unwrap();
+        assert!(!chunks.is_empty());
+        service.set_pinned(&parent.id.to_string(), true).unwrap();
+        service.archive(&parent.id.to_string()).unwrap();
+        let stored = store.fetch_records(&chunks).unwrap();
+        assert!(stored.iter().all(|chunk| chunk.pinned && !chunk.searchable));
+        assert!(search(false).is_empty());
+
+        assert_eq!(service.delete_many(vec![parent.id]).unwrap(), 1);
+        assert_eq!(store.iter_summaries().count(), 0);
+    }
+
+    #[test]
+    fn test_ingest_strips_invisible_characters() {
+        let request = || IngestContextRequest {
+            ide: "vscode\u{200B}".into(),
+            tags: vec!["tokio\u{200B}rt".into()],
+            kind: ContextKind::CodeSnippet,
+            ..ingest_request(
+                "\u{FEFF}Retry with backoff",
+                "let x = 1;\u{0007}\n\tlet y = 2; // \u{1F469}\u{200D}\u{1F4BB}",
+            )
+        };
+        let service = |sanitize| {
+            memory_service_with(
+                ServiceConfig::with_model("ingat/simple-hash").with_sanitize_unicode(sanitize),
+            )
+        };
+
+        let sanitized = service(true);
+        let summary = sanitized.ingest(request()).unwrap();
+        assert_eq!(summary.summary, "Retry with backoff");
+        assert_eq!(summary.tags, vec!["tokiort".to_string()]);
+        let stored = sanitized.fetch_one(&summary.id.to_string()).unwrap();
+        // The joiner inside the emoji sequence survives.
+        assert_eq!(
+            stored.body,
+            "let x = 1;\n\tlet y = 2; // \u{1F469}\u{200D}\u{1F4BB}"
+        );
+        assert_eq!(stored.ide, "vscode");
+
+        let raw = service(false);
+        let summary = raw.ingest(request()).unwrap();
+        assert_eq!(summary.summary, "\u{FEFF}Retry with backoff");
+        assert_eq!(summary.tags, vec!["tokio\u{200B}rt".to_string()]);
+        let stored = raw.fetch_one(&summary.id.to_string()).unwrap();
+        assert_eq!(stored.ide, "vscode\u{200B}");
+    }
+
+    #[test]
+    fn test_embedding_selftest_checks_engine_in_isolation() {
+        use crate::infrastructure::NoOpEmbeddingEngine;
+
+        let engine = SimpleEmbedEngine::try_new("ingat/simple-hash", 64).unwrap();
+        let report = embedding_selftest("simple", &engine, "ingat/simple-hash").unwrap();
+        assert_eq!(report.backend, "simple");
+        assert_eq!(report.dimensions, 64);
+        assert!(report.deterministic);
+        assert!(report.problems.is_empty());
+        assert!(report.related_score > report.unrelated_score);
+        assert!(report.passed);
+
+        let broken = NoOpEmbeddingEngine::for_remote_mode();
+        assert!(embedding_selftest("noop", &broken, "remote-proxy").is_err());
+    }
+
+    #[test]
+    fn test_top_similar_pairs_returns_best_first() {
+        let service = memory_service();
+        let ingest =
+            |summary: &str, body: &str| service.ingest(ingest_request(summary, body)).unwrap().id;
+        let a = ingest(
+            "retry with exponential backoff",
+            "retry with exponential backoff",
+        );
+        let b = ingest(
+            "retry with exponential backoff",
+            "retry with exponential backoff!",
+        );
+        ingest("sled tree compaction", "notes on compacting sled trees");
+        ingest("tauri window state", "persist window size between launches");
+
+        let pairs = service.top_similar_pairs(2).unwrap();
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs[0].2 >= pairs[1].2);
+        let (left, right, _) = pairs[0];
+        assert_eq!(BTreeSet::from([left, right]), BTreeSet::from([a, b]));
+
+        assert!(service.top_similar_pairs(0).unwrap().is_empty());
+        assert_eq!(service.top_similar_pairs(100).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_tag_language_normalizes_and_respects_existing_tags() {
+        let mut tags = vec!["async".to_string()];
+        tag_language(&mut tags, Some(" Rust "));
+        assert_eq!(tags, vec!["async", "rust"]);
+
+        tag_language(&mut tags, Some("RUST"));
+        tag_language(&mut tags, None);
+        assert_eq!(tags, vec!["async", "rust"]);
+
+        let mut full: Vec<String> = (0..crate::domain::models::MAX_TAGS)
+            .map(|i| format!("t{i}"))
+            .collect();
+        tag_language(&mut full, Some("go"));
+        assert_eq!(full.len(), crate::domain::models::MAX_TAGS);
+        assert!(!full.contains(&"go".to_string()));
+    }
+
+    #[test]
+    fn test_ide_default_kind_fills_unspecified_kind_only() {
+        let request = |ide: &str, kind: ContextKind| IngestContextRequest {
+            ide: ide.into(),
+            kind,
+            ..ingest_request("cargo test output", "test result: ok. 42 passed")
+        };
+        let defaults =
+            std::collections::BTreeMap::from([("WezTerm".to_string(), ContextKind::ToolLog)]);
+        let service = memory_service_with(
+            ServiceConfig::with_model("ingat/simple-hash").with_ide_default_kinds(&defaults),
+        );
+
+        let filled = service
+            .ingest(request("wezterm\n20240203", ContextKind::default()))
+            .unwrap();
+        assert_eq!(filled.kind, ContextKind::ToolLog);
+
+        let explicit = service
+            .ingest(request("wezterm", ContextKind::Discussion))
+            .unwrap();
+        assert_eq!(explicit.kind, ContextKind::Discussion);
+
+        let other_ide = service
+            .ingest(request("vscode", ContextKind::default()))
+            .unwrap();
+        assert_eq!(other_ide.kind, ContextKind::default().normalized().unwrap());
+    }
+
+    #[test]
+    fn test_archive_hides_from_search_until_unarchived() {
+        let service = memory_service();
+        let summary = service
+            .ingest(IngestContextRequest {
+                kind: ContextKind::Discussion,
+                ..ingest_request(
+                    "Sled lock handling",
+                    "Only one process may open the sled database at a time.",
+                )
+            })
+            .unwrap();
+        let search = || {
+            service
+                .search(
+                    crate::application::SearchRequestBuilder::new("sled database lock")
+                        .build()
+                        .unwrap(),
+                )
+                .unwrap()
+                .results
+                .len()
+        };
+        assert_eq!(search(), 1);
+
+        let archived = service.archive(&summary.id.to_string()).unwrap();
+        assert!(!archived.searchable);
+        assert_eq!(search(), 0);
+        let history = service
+            .history(None, None, None, None, HistoryOrder::default())
+            .unwrap();
+        assert_eq!(history.items.len(), 1);
+        assert!(!history.items[0].searchable);
+        assert_eq!(service.store_composition().unwrap().entries.len(), 0);
+
+        let restored = service.unarchive(&summary.id.to_string()).unwrap();
+        assert!(restored.searchable);
+        assert_eq!(search(), 1);
+
+        assert!(matches!(
+            service.archive(&Uuid::new_v4().to_string()),
+            Err(DomainError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_soft_filter_boosts_tag_matches_instead_of_excluding() {
+        let record = |tags: &[&str], vector: Vec<f32>| {
+            ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                "summary",
+                "body",
+                tags.iter().copied(),
+                ContextKind::CodeSnippet,
+                ContextEmbedding::new("ingat/simple-hash", vector),
+            )
+        };
+        let closest = record(&[], vec![1.0, 0.0]);
+        let tagged = record(&["rust"], vec![0.9, 0.1]);
+        let mut tags = vec!["rust".to_string()];
+        let mut matches = vec![(closest.clone(), 1.0), (tagged.clone(), 0.95)];
+        boost_tag_matches(&mut matches, &tags, DEFAULT_TAG_BOOST);
+        assert_eq!(matches[0].0.id, tagged.id);
+        assert_eq!(matches[1].0.id, closest.id);
+
+        let mut filters =
+            crate::domain::parse_filter_expr("tag:rust|go kind:code-snippet").unwrap();
+        filters.tag = Some("rust".into());
+        tags.push("go".into());
+        tags.sort();
+        assert_eq!(take_tag_filters(&mut filters), tags);
+        assert!(filters.tag.is_none());
+        assert_eq!(filters.groups.len(), 1);
+    }
+
+    #[test]
+    fn test_default_simulation_recalls_and_cleans_up() {
+        let service = memory_service();
+
+        let transcript = service
+            .simulate_session(SimulationScenario::default())
+            .unwrap();
+        assert_eq!(transcript.steps.len(), 4);
+        assert_eq!(transcript.failed_steps, 0);
+        assert_eq!(transcript.expected, 2);
+        assert_eq!(transcript.recalled, 2);
+        assert!(transcript.mean_reciprocal_rank > 0.0);
+        assert_eq!(transcript.cleaned_up, 2);
+        assert!(service.projects().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cached_search_misses_after_a_write() {
+        let service = memory_service_with(
+            ServiceConfig::with_model("ingat/simple-hash").with_search_cache_ttl_ms(60_000),
+        );
+        let ingest = |summary: &str| {
+            service
+                .ingest(ingest_request(
+                    summary,
+                    &format!("{summary} in the search cache"),
+                ))
+                .unwrap()
+        };
+        let search = || {
+            service
+                .search(
+                    SearchRequest::builder("stale entries")
+                        .limit(10)
+                        .build()
+                        .unwrap(),
+                )
+                .unwrap()
+                .results
+                .len()
+        };
+
+        ingest("stale entries");
+        let before = service.store_version();
+        assert_eq!(search(), 1);
+        assert_eq!(search(), 1);
+
+        ingest("stale entries again");
+        assert!(service.store_version() > before);
+        assert_eq!(search(), 2);
+    }
+
+    #[test]
+    fn test_max_per_project_spreads_results_across_projects() {
+        let service = memory_service();
+        let ingest = |project: &str, summary: &str| {
+            service
+                .ingest(IngestContextRequest {
+                    project: project.into(),
+                    ..ingest_request(summary, &format!("{summary} with exponential backoff"))
+                })
+                .unwrap();
+        };
+        for n in 0..4 {
+            ingest("noisy", &format!("retry http request {n}"));
+        }
+        ingest("quiet", "retry database connection");
+
+        let search = |cap: Option<usize>| {
+            let builder = SearchRequest::builder("retry with exponential backoff").limit(3);
+            let builder = match cap {
+                Some(cap) => builder.max_per_project(cap),
+                None => builder,
+            };
+            let results = service.search(builder.build().unwrap()).unwrap().results;
+            assert!(results
+                .windows(2)
+                .all(|pair| pair[0].score >= pair[1].score));
+            results
+                .into_iter()
+                .map(|hit| hit.project)
+                .collect::<Vec<_>>()
+        };
+
+        let capped = search(Some(1));
+        assert_eq!(capped.len(), 2);
+        assert!(capped.contains(&"noisy".to_string()));
+        assert!(capped.contains(&"quiet".to_string()));
+        assert_eq!(search(None).len(), 3);
+        assert!(SearchRequest::builder("retry")
+            .max_per_project(0)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_empty_search_says_nothing_was_found() {
+        let service = memory_service();
+        let request = SearchRequest::builder("anything at all").build().unwrap();
+
+        let empty = service.search(request.clone()).unwrap();
+        assert!(!empty.found);
+        assert_eq!(
+            empty.message.as_deref(),
+            Some(DEFAULT_EMPTY_RESULTS_MESSAGE)
+        );
+
+        let quiet = memory_service_with(
+            ServiceConfig::with_model("ingat/simple-hash").with_empty_results_message(" "),
+        );
+        assert_eq!(quiet.search(request).unwrap().message, None);
+    }
+
+    #[test]
+    fn test_search_cache_ttl_zero_disables_cache() {
+        let config = ServiceConfig::default();
+        assert_eq!(config.search_cache_ttl, None);
+        assert_eq!(
+            config.clone().with_search_cache_ttl_ms(0).search_cache_ttl,
+            None
+        );
+        assert_eq!(
+            config.with_search_cache_ttl_ms(250).search_cache_ttl,
+            Some(Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    fn test_search_reports_matches_beyond_limit() {
+        let service = memory_service();
+        for n in 0..5 {
+            service
+                .ingest(ingest_request(
+                    &format!("retry http request {n}"),
+                    "retry with exponential backoff",
+                ))
+                .unwrap();
+        }
+        let request = |limit: usize| {
+            SearchRequest::builder("retry with backoff")
+                .limit(limit)
+                .build()
+                .unwrap()
+        };
+
+        let truncated = service.search(request(2)).unwrap();
+        assert_eq!(truncated.results.len(), 2);
+        assert_eq!(truncated.total_matched, 5);
+        assert!(truncated.has_more);
+
+        let complete = service.search(request(10)).unwrap();
+        assert_eq!(complete.total_matched, 5);
+        assert!(!complete.has_more);
+
+        let keyword = service.keyword_search(request(2)).unwrap();
+        assert_eq!(keyword.results.len(), 2);
+        assert!(keyword.has_more);
+        assert!(!service.keyword_search(request(10)).unwrap().has_more);
+
+        // The project cap drops four of the five, so they are not "more".
+        let capped = service
+            .search(
+                SearchRequest::builder("retry with backoff")
+                    .limit(2)
+                    .max_per_project(1)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(capped.results.len(), 1);
+        assert_eq!(capped.total_matched, 1);
+        assert!(!capped.has_more);
+    }
+
+    #[test]
+    fn test_search_analytics_record_chosen_ranks() {
+        let service = memory_service_with(
+            ServiceConfig::with_model("ingat/simple-hash").with_search_analytics(true),
+        );
+        for summary in ["retry http request", "cache invalidation notes"] {
+            service
+                .ingest(ingest_request(summary, &format!("{summary} body")))
+                .unwrap();
+        }
+        let search = |prompt: &str| {
+            service
+                .search(SearchRequest::builder(prompt).limit(2).build().unwrap())
+                .unwrap()
+        };
+
+        let first = search("retry http");
+        let query_id = first.query_id.expect("analytics tag the response");
+        let event = service
+            .record_feedback(query_id, first.results[1].id)
+            .unwrap();
+        assert_eq!(event.chosen[0].rank, Some(1));
+        // Choosing the same result twice counts once.
+        service
+            .record_feedback(query_id, first.results[1].id)
+            .unwrap();
+        service.record_feedback(query_id, Uuid::new_v4()).unwrap();
+        assert!(matches!(
+            service.record_feedback(Uuid::new_v4(), first.results[0].id),
+            Err(DomainError::NotFound(_))
+        ));
+
+        let second = search("cache invalidation");
+        service
+            .record_feedback(second.query_id.unwrap(), second.results[0].id)
+            .unwrap();
+        search("unused query");
+
+        let stats = service.feedback_stats().unwrap();
+        assert!(stats.enabled);
+        assert_eq!((stats.searches, stats.with_feedback), (3, 2));
+        assert_eq!(stats.choices, 3);
+        assert_eq!(stats.rank_counts, vec![1, 1]);
+        assert_eq!(stats.outside_results, 1);
+        assert!((stats.mean_reciprocal_rank - 0.75).abs() < 1e-9);
+
+        assert_eq!(service.clear_feedback().unwrap(), 3);
+        assert_eq!(service.feedback_stats().unwrap().searches, 0);
+    }
+
+    #[test]
+    fn test_legacy_index_keeps_old_model_searchable() {
+        let legacy_engine = Arc::new(SimpleEmbedEngine::try_new("ingat/legacy-hash", 32).unwrap());
+        let body = "Only one process may open the sled database at a time.";
+        let stale = ContextRecord::new(
+            "ingat",
+            "vscode",
+            None::<String>,
+            None::<String>,
+            "Sled lock handling",
+            body,
+            Vec::<String>::new(),
+            ContextKind::Discussion,
+            ContextEmbedding::new(
+                "ingat/legacy-hash",
+                legacy_engine
+                    .embed("ingat/legacy-hash", &format!("Sled lock handling {body}"))
+                    .unwrap(),
+            ),
+        );
+        let store = Arc::new(MemoryVectorStore::new());
+        store.persist(&stale).unwrap();
+        let service = |penalty: f32| {
+            service_over(
+                Arc::clone(&store) as Arc<dyn VectorStore>,
+                ServiceConfig::with_model("ingat/simple-hash").with_legacy_penalty(penalty),
+            )
+            .with_legacy_index(
+                Arc::clone(&legacy_engine) as Arc<dyn EmbeddingEngine>,
+                "ingat/legacy-hash",
+            )
+        };
+        service(0.0)
+            .ingest(ingest_request(
+                "Sled database lock",
+                "Close the other process holding the sled lock.",
+            ))
+            .unwrap();
+        let request = || {
+            SearchRequest::builder("sled database lock")
+                .build()
+                .unwrap()
+        };
+
+        let plain = service(0.0).search(request()).unwrap();
+        assert_eq!(plain.results.len(), 2);
+        assert_eq!(plain.matched_filters, 2);
+        let legacy_score = |response: &SearchResponse| {
+            response
+                .results
+                .iter()
+                .find(|result| result.id == stale.id)
+                .map(|result| result.score)
+                .unwrap()
+        };
+        let penalized = service(0.25).search(request()).unwrap();
+        assert!((legacy_score(&plain) - legacy_score(&penalized) - 0.25).abs() < 1e-5);
+
+        // Without the legacy index the old records can't be scored at all.
+        let active_only = service_over(
+            Arc::clone(&store) as Arc<dyn VectorStore>,
+            ServiceConfig::with_model("ingat/simple-hash"),
+        );
+        assert!(matches!(
+            active_only.search(request()),
+            Err(DomainError::Embedding(_))
+        ));
     }
 }
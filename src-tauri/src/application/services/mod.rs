@@ -2,4 +2,13 @@
 
 mod context_service;
 
-pub use context_service::{ContextService, EmbeddingEngine, ServiceConfig, VectorStore};
+pub use context_service::{
+    embedding_selftest, preprocess, ContextService, EmbedScope, EmbeddingEngine, PreprocessStep,
+    RankedResults, SearchHits, ServiceConfig, TruncationStrategy, VectorStore,
+    DEFAULT_EMPTY_RESULTS_MESSAGE, DEFAULT_LEGACY_PENALTY, DEFAULT_QUERY_HISTORY_CAPACITY,
+    DEFAULT_TAG_BOOST, DEFAULT_TOMBSTONE_RETENTION_DAYS, SEARCH_EVENT_CAPACITY,
+};
+
+/// Fixtures shared by tests across the crate.
+#[cfg(test)]
+pub(crate) use context_service::tests::{ingest_request, memory_service, service_over};
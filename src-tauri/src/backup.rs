@@ -0,0 +1,285 @@
+//! Single-file backups of the store as a logical JSONL dump.
+//!
+//! The archive holds a header line, one full [`ContextRecord`] (embeddings
+//! included) per line, and a footer with the record count. Being independent
+//! of sled's on-disk format, it restores across sled versions and into any
+//! store backend. The footer doubles as a truncation check.
+
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{application::services::VectorStore, domain::ContextRecord};
+
+/// Value of the header's `format` field.
+const BACKUP_FORMAT: &str = "ingat-backup";
+
+/// Bumped when the line layout changes; older versions stay readable.
+const BACKUP_VERSION: u32 = 1;
+
+/// Records written per `persist_batch` call during a restore.
+const RESTORE_BATCH: usize = 256;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupHeader {
+    format: String,
+    version: u32,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BackupFooter {
+    records: usize,
+}
+
+/// Result of [`backup_store`].
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupReport {
+    pub path: String,
+    pub records: usize,
+    /// Records that failed to decode and were left out of the archive.
+    pub skipped: usize,
+    pub bytes: u64,
+}
+
+/// Result of [`restore_store`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub path: String,
+    pub records: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Flush `store` and dump every record to `dest`. The archive is written
+/// next to `dest` and renamed into place, so a failed backup never leaves a
+/// half-written file behind.
+pub fn backup_store(store: &dyn VectorStore, dest: &Path) -> Result<BackupReport> {
+    if !dest.is_absolute() {
+        bail!("backup path must be absolute");
+    }
+    if dest.is_dir() {
+        bail!("backup path {} is a directory", dest.display());
+    }
+    store
+        .flush()
+        .map_err(|err| anyhow!(err.to_string()))
+        .context("failed to flush store before backup")?;
+
+    let staging = staging_path(dest)?;
+    let written = write_archive(store, &staging);
+    let (records, skipped) = match written {
+        Ok(counts) => counts,
+        Err(err) => {
+            let _ = fs::remove_file(&staging);
+            return Err(err);
+        }
+    };
+    if let Err(err) = fs::rename(&staging, dest) {
+        let _ = fs::remove_file(&staging);
+        return Err(err).context("failed to move backup into place");
+    }
+
+    let bytes = fs::metadata(dest)?.len();
+    Ok(BackupReport {
+        path: dest.display().to_string(),
+        records,
+        skipped,
+        bytes,
+    })
+}
+
+/// Validate the archive at `src` end to end, then load it into `store`, which
+/// must hold no records. Nothing is written if validation fails.
+pub fn restore_store(store: &dyn VectorStore, src: &Path) -> Result<RestoreReport> {
+    let (header, expected) =
+        read_archive(src, |_| Ok(())).context("backup archive failed validation")?;
+    if store.iter_summaries().next().is_some() {
+        bail!("restore needs an empty store; point INGAT_DATA_DIR at a fresh directory first");
+    }
+
+    let mut batch = Vec::with_capacity(RESTORE_BATCH);
+    let persist = |batch: &mut Vec<ContextRecord>| -> Result<()> {
+        store
+            .persist_batch(batch)
+            .map_err(|err| anyhow!(err.to_string()))
+            .context("failed to write restored records")?;
+        batch.clear();
+        Ok(())
+    };
+    read_archive(src, |record| {
+        batch.push(record);
+        if batch.len() == RESTORE_BATCH {
+            persist(&mut batch)?;
+        }
+        Ok(())
+    })?;
+    if !batch.is_empty() {
+        persist(&mut batch)?;
+    }
+    store.flush().map_err(|err| anyhow!(err.to_string()))?;
+
+    Ok(RestoreReport {
+        path: src.display().to_string(),
+        records: expected,
+        created_at: header.created_at,
+    })
+}
+
+fn staging_path(dest: &Path) -> Result<PathBuf> {
+    let name = dest
+        .file_name()
+        .ok_or_else(|| anyhow!("backup path has no file name"))?
+        .to_string_lossy();
+    Ok(dest.with_file_name(format!(".{name}.partial-{}", Uuid::new_v4())))
+}
+
+fn write_archive(store: &dyn VectorStore, path: &Path) -> Result<(usize, usize)> {
+    let file = File::create(path)
+        .with_context(|| format!("backup location {} is not writable", path.display()))?;
+    let mut out = BufWriter::new(file);
+    write_line(
+        &mut out,
+        &BackupHeader {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            created_at: Utc::now(),
+        },
+    )?;
+
+    let (mut records, mut skipped) = (0, 0);
+    let mut failure: Option<anyhow::Error> = None;
+    store
+        .scan_records(&mut |record| {
+            if failure.is_some() {
+                return;
+            }
+            match record {
+                Ok(record) => match write_line(&mut out, &record) {
+                    Ok(()) => records += 1,
+                    Err(err) => failure = Some(err),
+                },
+                Err(_) => skipped += 1,
+            }
+        })
+        .map_err(|err| anyhow!(err.to_string()))?;
+    if let Some(err) = failure {
+        return Err(err).context("failed to write backup");
+    }
+
+    write_line(&mut out, &BackupFooter { records })?;
+    out.into_inner()
+        .map_err(|err| err.into_error())?
+        .sync_all()?;
+    Ok((records, skipped))
+}
+
+fn write_line(out: &mut impl Write, value: &impl Serialize) -> Result<()> {
+    serde_json::to_writer(&mut *out, value)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Stream the records of the archive at `path` through `visit`, checking the
+/// header, every line and the footer count.
+fn read_archive(
+    path: &Path,
+    mut visit: impl FnMut(ContextRecord) -> Result<()>,
+) -> Result<(BackupHeader, usize)> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines().enumerate().peekable();
+
+    let header: BackupHeader = match lines.next() {
+        Some((_, line)) => serde_json::from_str(&line?).context("missing backup header")?,
+        None => bail!("backup archive is empty"),
+    };
+    if header.format != BACKUP_FORMAT {
+        bail!("not an ingat backup (format `{}`)", header.format);
+    }
+    if header.version > BACKUP_VERSION {
+        bail!(
+            "backup version {} is newer than this build supports ({BACKUP_VERSION})",
+            header.version
+        );
+    }
+
+    let mut records = 0;
+    while let Some((index, line)) = lines.next() {
+        let line = line?;
+        if lines.peek().is_none() {
+            let footer: BackupFooter = serde_json::from_str(&line)
+                .context("backup archive is truncated (missing footer)")?;
+            if footer.records != records {
+                bail!(
+                    "backup footer lists {} records but {records} were read",
+                    footer.records
+                );
+            }
+            return Ok((header, records));
+        }
+        let record: ContextRecord = serde_json::from_str(&line)
+            .with_context(|| format!("malformed record on line {}", index + 1))?;
+        visit(record)?;
+        records += 1;
+    }
+    bail!("backup archive is truncated (missing footer)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::{ingest_request, service_over, ServiceConfig};
+    use crate::application::IngestContextRequest;
+    use crate::infrastructure::MemoryVectorStore;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_backup_round_trips_and_rejects_truncated_archives() {
+        let dir = std::env::temp_dir().join(format!("ingat-backup-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("ingat.jsonl");
+
+        let source: Arc<dyn VectorStore> = Arc::new(MemoryVectorStore::new());
+        let service = service_over(
+            Arc::clone(&source),
+            ServiceConfig::with_model("ingat/simple-hash"),
+        );
+        for summary in ["retry with backoff", "cache invalidation notes"] {
+            service
+                .ingest(IngestContextRequest {
+                    tags: vec!["backup".into()],
+                    flush: Some(false),
+                    ..ingest_request(summary, &format!("{summary} body"))
+                })
+                .unwrap();
+        }
+
+        let report = backup_store(source.as_ref(), &archive).unwrap();
+        assert_eq!((report.records, report.skipped), (2, 0));
+        assert_eq!(report.bytes, fs::metadata(&archive).unwrap().len());
+
+        let target: Arc<dyn VectorStore> = Arc::new(MemoryVectorStore::new());
+        let restored = restore_store(target.as_ref(), &archive).unwrap();
+        assert_eq!(restored.records, 2);
+        assert_eq!(target.iter_summaries().count(), 2);
+        // Restoring over existing records is refused.
+        assert!(restore_store(target.as_ref(), &archive).is_err());
+
+        let raw = fs::read_to_string(&archive).unwrap();
+        let truncated = dir.join("truncated.jsonl");
+        let without_footer: Vec<&str> = raw.lines().take(raw.lines().count() - 1).collect();
+        fs::write(&truncated, without_footer.join("\n")).unwrap();
+        let empty: Arc<dyn VectorStore> = Arc::new(MemoryVectorStore::new());
+        assert!(restore_store(empty.as_ref(), &truncated).is_err());
+        assert_eq!(empty.iter_summaries().count(), 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -32,9 +32,33 @@
 /// # Endpoints
 ///
 /// - `POST /api/contexts` - Save a context
-/// - `GET /api/contexts` - List contexts
-/// - `POST /api/search` - Search contexts
-/// - `GET /api/stats` - Get statistics
+/// - `GET /api/contexts` - List contexts (optional `project`, `tag`, `since`, `limit`, `order_by`, `direction`, `timestamp_format`)
+/// - `GET /api/contexts/stream` - Every summary as NDJSON in a single pass (for full sync)
+/// - `POST|DELETE /api/contexts/:id/pin` - Pin or unpin a context
+/// - `POST|DELETE /api/contexts/:id/archive` - Drop a context's vectors (kept in history, not searchable) or re-embed it
+/// - `POST /api/contexts/delete` - Delete a list of contexts atomically (`{"ids": [...]}`)
+/// - `POST /api/contexts/batch-get` - Full records (with embeddings) for a list of ids (`{"ids": [...]}`)
+/// - `GET /api/tags/:tag/contexts` - List recent contexts carrying a tag
+/// - `GET /api/changes?since=<rfc3339>&include_deleted=<bool>` - Change feed for sync
+/// - `POST /api/search` - Search contexts (`Accept: application/x-ndjson` returns one result per line;
+///   `export_mode` raises the limit cap for bulk exports, one such search every 5 seconds)
+/// - `POST /api/search/keyword` - TF-IDF keyword search over summaries and bodies
+/// - `POST /api/term-stats/rebuild` - Recompute keyword statistics from the stored records
+/// - `GET /api/stats` - Get statistics, including store size on disk
+/// - `GET /api/config` - Effective runtime configuration (paths, backend, mode, features)
+/// - `GET /api/build` - Crate version, compiled features and key dependency versions
+/// - `GET /api/store/composition` - Histogram of stored embedding models/dimensions
+/// - `GET /api/store/verify` - Integrity check of every stored embedding
+/// - `GET /api/kinds` - Stored context kinds (normalized keys) with counts
+/// - `GET|DELETE /api/queries` - Recent searches, newest first (optional `limit`), or clear them
+/// - `POST|DELETE /api/feedback` - Record which result of a search was used (`{"query_id", "chosen_id"}`), or clear all
+/// - `GET /api/feedback/stats` - Chosen-result counts by rank and mean reciprocal rank
+/// - `GET|POST /api/feedback/events` - Raw search events, or record one made by a remote-mode client
+/// - `POST /api/index/rebuild` - Rebuild secondary indexes from the primary records
+/// - `GET /api/projects` - Projects with record count and latest activity, most recent first
+/// - `GET /api/projects/:project/overview` - Counts by kind, top tags, and latest summaries
+/// - `GET /api/projects/:project/related?limit=10` - Projects ranked by shared tags (Jaccard)
+/// - `POST /api/projects/:project/reembed` - Re-embed one project on the active model
 /// - `GET /sse` - MCP SSE transport
 /// - `POST /message` - MCP message endpoint
 /// - `POST /mcp-stdio` - MCP stdio-over-HTTP transport
@@ -45,12 +69,20 @@
 /// - `INGAT_DATA_DIR`: Override data directory location
 /// - `INGAT_SERVICE_PORT`: Default port (default: 3200)
 /// - `INGAT_SERVICE_HOST`: Bind address (default: 127.0.0.1)
+/// - `INGAT_MCP_RATE_LIMIT`: Requests per second allowed per client address (unset = unlimited)
+/// - `INGAT_FASTEMBED_INSTANCES`: FastEmbed model copies embedding in parallel (default: 1)
+/// - `INGAT_ERROR_FORMAT`: Error body shape, `error-code` (default, `{error, code}`)
+///   or `message-status` (`{message, status}`)
+/// - `INGAT_STATIC_DIR`: Serve a bundled web UI from this directory for non-API paths (unset = off)
+/// - `INGAT_CORS_ORIGINS`: Comma-separated origins allowed to call the API from a browser,
+///   or `*` for any (unset = no CORS headers)
 ///
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
 use axum::{
-    extract::{Query, State},
+    extract::{ConnectInfo, Path, Query, Request, State},
     http::{HeaderMap, StatusCode},
+    middleware::Next,
     response::{
         sse::{Event, KeepAlive},
         IntoResponse, Response, Sse,
@@ -61,17 +93,35 @@ use axum::{
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
 use ingat_lib::application::{
-    services::VectorStore, ContextService, IngestContextRequest, SearchRequest, SearchResponse,
+    services::{RankedResults, VectorStore},
+    BuildInfo, ContextService, DeleteContextsRequest, FeedbackRequest, FeedbackStats,
+    FetchContextsRequest, IngestContextRequest, LabelCount, ProjectInfo, ProjectOverview,
+    RelatedProject, RuntimeConfigResponse, SearchRequest, SearchResponse, StorageStats,
+    StoreCompositionResponse, TimestampFormat, VerifyReport,
 };
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
-use ingat_lib::domain::ContextSummary;
+use ingat_lib::domain::{
+    ChangeEvent, ContextSummary, DomainError, HistoryOrder, QueryHistoryEntry, SearchEvent,
+};
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+use ingat_lib::infrastructure::http_client::CLIENT_ID_HEADER;
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
 use ingat_lib::settings::ConfigManager;
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
-use serde::Serialize;
+use ingat_lib::{describe_build, describe_runtime, RuntimeMode};
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+use serde::{Deserialize, Serialize};
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+use ingat_lib::interfaces::{
+    rate_limit::{export_search_bucket, rate_limit_from_env, KeyedRateLimiter, TokenBucket},
+    request_log::RequestLogger,
+};
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
 use std::{net::SocketAddr, sync::Arc};
@@ -79,6 +129,12 @@ use std::{net::SocketAddr, sync::Arc};
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
 use tokio::sync::RwLock;
 
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    services::{ServeDir, ServeFile},
+};
+
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
 use tracing::{error, info};
 
@@ -96,6 +152,10 @@ struct AppState {
     store: Arc<dyn VectorStore>,
     config: Arc<ConfigManager>,
     data_dir: std::path::PathBuf,
+    mode: RuntimeMode,
+    request_log: Option<RequestLogger>,
+    /// Shared budget for `export_mode` searches, which can scan and return far more.
+    export_searches: Arc<parking_lot::Mutex<TokenBucket>>,
 }
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
@@ -112,24 +172,100 @@ struct StatsResponse {
     data_dir: String,
     version: String,
     uptime_seconds: u64,
+    storage: StorageStats,
 }
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct ErrorResponse {
     error: String,
     code: String,
 }
 
+/// Error response for a failed service call, tagged with the handler's
+/// `code`: unknown ids are 404, bad input 400, a store in maintenance 503 and
+/// everything else 500.
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+fn service_error(e: DomainError, code: &str) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match &e {
+        DomainError::NotFound(_) => StatusCode::NOT_FOUND,
+        DomainError::Validation(_) | DomainError::LimitExceeded(_) => StatusCode::BAD_REQUEST,
+        DomainError::Busy(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (
+        status,
+        Json(ErrorResponse {
+            error: e.to_string(),
+            code: code.to_string(),
+        }),
+    )
+}
+
+/// Shape of error bodies, selected with `INGAT_ERROR_FORMAT`.
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorFormat {
+    /// `{"error": "...", "code": "SEARCH_FAILED"}` — the default, parsed by `RemoteVectorStore`.
+    ErrorCode,
+    /// `{"message": "...", "status": 500}` for browser-style clients.
+    MessageStatus,
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+impl ErrorFormat {
+    fn from_env() -> Self {
+        match std::env::var("INGAT_ERROR_FORMAT").as_deref() {
+            Ok("message-status") => ErrorFormat::MessageStatus,
+            _ => ErrorFormat::ErrorCode,
+        }
+    }
+}
+
+/// CORS policy from `INGAT_CORS_ORIGINS`, or `None` when browser access is not enabled.
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+fn cors_layer_from_env() -> Option<CorsLayer> {
+    let origins = std::env::var("INGAT_CORS_ORIGINS").ok()?;
+    let origins = origins.trim();
+    if origins.is_empty() {
+        return None;
+    }
+
+    let allow_origin = if origins == "*" {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .filter_map(|origin| origin.parse().ok()),
+        )
+    };
+
+    Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([
+                axum::http::Method::GET,
+                axum::http::Method::POST,
+                axum::http::Method::DELETE,
+            ])
+            .allow_headers([axum::http::header::CONTENT_TYPE]),
+    )
+}
+
 // ============================================================================
 // HTTP Handlers
 // ============================================================================
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
-async fn health_check() -> impl IntoResponse {
+async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
+    let service = state.service.read().await;
     Json(serde_json::json!({
         "status": "healthy",
-        "service": "ingat-backend"
+        "service": "ingat-backend",
+        "state": service.state()
     }))
 }
 
@@ -138,6 +274,9 @@ async fn save_context(
     State(state): State<AppState>,
     Json(payload): Json<IngestContextRequest>,
 ) -> Result<Json<ContextSummary>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(log) = &state.request_log {
+        log.ingest("rest", &payload);
+    }
     let service = state.service.read().await;
     let service = Arc::clone(&service);
 
@@ -148,40 +287,153 @@ async fn save_context(
         }
         Err(e) => {
             error!("Failed to save context: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+            Err(service_error(e, "SAVE_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn list_contexts(
+    State(state): State<AppState>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    let limit = params.get("limit").and_then(|s| s.parse().ok());
+
+    let project = params.get("project").cloned();
+    let tag = params.get("tag").cloned();
+    let timestamp_format = match params.get("timestamp_format") {
+        Some(value) => serde_json::from_value(serde_json::json!(value.trim())).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
-                    error: e.to_string(),
-                    code: "SAVE_FAILED".to_string(),
+                    error: format!("invalid timestamp_format: {e}"),
+                    code: "INVALID_TIMESTAMP_FORMAT".to_string(),
                 }),
-            ))
+            )
+        })?,
+        None => TimestampFormat::default(),
+    };
+    let order = parse_history_order(&params).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("invalid ordering: {e}"),
+                code: "INVALID_ORDER".to_string(),
+            }),
+        )
+    })?;
+
+    let since = match params.get("since") {
+        Some(value) => Some(
+            chrono::DateTime::parse_from_rfc3339(value.trim())
+                .map_err(|e| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(ErrorResponse {
+                            error: format!("invalid since timestamp: {e}"),
+                            code: "INVALID_SINCE".to_string(),
+                        }),
+                    )
+                })?
+                .with_timezone(&chrono::Utc),
+        ),
+        None => None,
+    };
+
+    match service.history(project, tag, since, limit, order) {
+        Ok(response) => Ok(timestamped_json(&response.items, timestamp_format)),
+        Err(e) => {
+            error!("Failed to list contexts: {}", e);
+            Err(service_error(e, "LIST_FAILED"))
         }
     }
 }
 
+/// `order_by` / `direction` query parameters, spelled like their JSON values
+/// (`order_by=Project&direction=Asc`).
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
-async fn list_contexts(
+fn parse_history_order(
+    params: &std::collections::HashMap<String, String>,
+) -> Result<HistoryOrder, serde_json::Error> {
+    let fields: serde_json::Map<String, serde_json::Value> = ["order_by", "direction"]
+        .into_iter()
+        .filter_map(|key| {
+            params
+                .get(key)
+                .map(|value| (key.to_string(), serde_json::json!(value)))
+        })
+        .collect();
+    serde_json::from_value(serde_json::Value::Object(fields))
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+const STREAM_BUFFER: usize = 64;
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn stream_contexts(State(state): State<AppState>) -> Response {
+    let store = Arc::clone(&state.store);
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<Vec<u8>, DomainError>>(STREAM_BUFFER);
+
+    // sled iteration is blocking; feed lines through a bounded channel so memory
+    // stays flat no matter how large the store is.
+    tokio::task::spawn_blocking(move || {
+        let summaries = store
+            .iter_summaries()
+            .filter(|item| !matches!(item, Ok(summary) if summary.parent_id.is_some()));
+        for item in summaries {
+            let line = item.and_then(|summary| {
+                let mut line = serde_json::to_vec(&summary)
+                    .map_err(|e| DomainError::other(format!("serialization error: {e}")))?;
+                line.push(b'\n');
+                Ok(line)
+            });
+
+            let failed = line.is_err();
+            if tx.blocking_send(line).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let body = async_stream::stream! {
+        while let Some(line) = rx.recv().await {
+            match line {
+                Ok(line) => yield Ok(line),
+                Err(e) => {
+                    error!("Failed to stream contexts: {}", e);
+                    yield Err(std::io::Error::other(e.to_string()));
+                }
+            }
+        }
+    };
+
+    (
+        [(axum::http::header::CONTENT_TYPE, "application/x-ndjson")],
+        axum::body::Body::from_stream(body),
+    )
+        .into_response()
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn list_tag_contexts(
     State(state): State<AppState>,
+    Path(tag): Path<String>,
     Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Result<Json<Vec<ContextSummary>>, (StatusCode, Json<ErrorResponse>)> {
     let service = state.service.read().await;
     let service = Arc::clone(&service);
 
     let limit = params.get("limit").and_then(|s| s.parse().ok());
-
     let project = params.get("project").cloned();
 
-    match service.history(project, limit) {
+    match service.history(project, Some(tag), None, limit, HistoryOrder::default()) {
         Ok(response) => Ok(Json(response.items)),
         Err(e) => {
-            error!("Failed to list contexts: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                    code: "LIST_FAILED".to_string(),
-                }),
-            ))
+            error!("Failed to list tag contexts: {}", e);
+            Err(service_error(e, "LIST_FAILED"))
         }
     }
 }
@@ -189,42 +441,606 @@ async fn list_contexts(
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
 async fn search_contexts(
     State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
     Json(payload): Json<SearchRequest>,
-) -> Result<Json<SearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(log) = &state.request_log {
+        log.search("rest", &payload);
+    }
+    if payload.export_mode && !state.export_searches.lock().try_acquire() {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ErrorResponse {
+                error: "export searches are rate-limited; retry in a few seconds".to_string(),
+                code: "EXPORT_RATE_LIMITED".to_string(),
+            }),
+        ));
+    }
+    let timestamp_format = payload.timestamp_format;
     let service = state.service.read().await;
     let service = Arc::clone(&service);
 
+    // Export searches always stream; they are what NDJSON is for.
+    if payload.export_mode || wants_ndjson(&headers) {
+        return match service.search_ranked(payload) {
+            Ok(results) => Ok(ndjson_stream(results, timestamp_format)),
+            Err(e) => {
+                error!("Search failed: {}", e);
+                Err(service_error(e, "SEARCH_FAILED"))
+            }
+        };
+    }
     match service.search(payload) {
-        Ok(response) => Ok(Json(response)),
+        Ok(response) => Ok(timestamped_json(&response, timestamp_format)),
         Err(e) => {
             error!("Search failed: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                    code: "SEARCH_FAILED".to_string(),
-                }),
-            ))
+            Err(service_error(e, "SEARCH_FAILED"))
         }
     }
 }
 
 #[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
-async fn get_stats(State(state): State<AppState>) -> Result<Json<StatsResponse>, StatusCode> {
+async fn keyword_search(
+    State(state): State<AppState>,
+    Json(payload): Json<SearchRequest>,
+) -> Result<Json<SearchResponse>, (StatusCode, Json<ErrorResponse>)> {
     let service = state.service.read().await;
     let service = Arc::clone(&service);
 
-    // Use history with large limit to count contexts
-    match service.history(None, Some(10000)) {
-        Ok(response) => Ok(Json(StatsResponse {
-            total_contexts: response.items.len(),
+    match service.keyword_search(payload) {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Keyword search failed: {}", e);
+            Err(service_error(e, "SEARCH_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+fn wants_ndjson(headers: &axum::http::HeaderMap) -> bool {
+    headers
+        .get_all(axum::http::header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media| media.split(';').next().map(str::trim) == Some("application/x-ndjson"))
+}
+
+/// `value` as a JSON response with its timestamps written in `format`.
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+fn timestamped_json(value: &impl Serialize, format: TimestampFormat) -> Response {
+    if format == TimestampFormat::Rfc3339 {
+        return Json(value).into_response();
+    }
+    match serde_json::to_value(value) {
+        Ok(mut value) => {
+            format.apply(&mut value);
+            Json(value).into_response()
+        }
+        Err(e) => {
+            error!("Failed to serialize response: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Stream ranked search results as NDJSON, one `SearchResultDto` per line,
+/// each built and serialized only when the client is ready for it.
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+fn ndjson_stream(results: RankedResults, format: TimestampFormat) -> Response {
+    let body = async_stream::stream! {
+        for result in results {
+            let line = serde_json::to_value(&result).and_then(|mut value| {
+                format.apply(&mut value);
+                serde_json::to_vec(&value)
+            });
+            match line {
+                Ok(mut line) => {
+                    line.push(b'\n');
+                    yield Ok(line);
+                }
+                Err(e) => {
+                    error!("Failed to format search result: {}", e);
+                    yield Err(std::io::Error::other(e.to_string()));
+                    break;
+                }
+            }
+        }
+    };
+
+    (
+        [(axum::http::header::CONTENT_TYPE, "application/x-ndjson")],
+        axum::body::Body::from_stream(body),
+    )
+        .into_response()
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn get_stats(
+    State(state): State<AppState>,
+) -> Result<Json<StatsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.storage_stats() {
+        Ok(storage) => Ok(Json(StatsResponse {
+            total_contexts: storage.records,
             data_dir: state.data_dir.display().to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             uptime_seconds: 0, // TODO: track service start time
+            storage,
         })),
         Err(e) => {
             error!("Failed to get stats: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(service_error(e, "STATS_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn store_composition(
+    State(state): State<AppState>,
+) -> Result<Json<StoreCompositionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.store_composition() {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to compute store composition: {}", e);
+            Err(service_error(e, "COMPOSITION_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn verify_store(
+    State(state): State<AppState>,
+) -> Result<Json<VerifyReport>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match tokio::task::spawn_blocking(move || service.verify())
+        .await
+        .expect("Failed to spawn verify task")
+    {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            error!("Failed to verify store: {}", e);
+            Err(service_error(e, "VERIFY_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn list_kinds(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LabelCount>>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.context_kinds() {
+        Ok(kinds) => Ok(Json(kinds)),
+        Err(e) => {
+            error!("Failed to list kinds: {}", e);
+            Err(service_error(e, "KINDS_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn recent_queries(
+    State(state): State<AppState>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Json<Vec<QueryHistoryEntry>>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    let limit = params.get("limit").and_then(|s| s.parse().ok());
+
+    match service.recent_queries(limit) {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e) => {
+            error!("Failed to list recent queries: {}", e);
+            Err(service_error(e, "QUERIES_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn clear_query_history(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.clear_query_history() {
+        Ok(cleared) => Ok(Json(serde_json::json!({ "cleared": cleared }))),
+        Err(e) => {
+            error!("Failed to clear query history: {}", e);
+            Err(service_error(e, "QUERIES_CLEAR_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn record_feedback(
+    State(state): State<AppState>,
+    Json(request): Json<FeedbackRequest>,
+) -> Result<Json<SearchEvent>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.record_feedback(request.query_id, request.chosen_id) {
+        Ok(event) => Ok(Json(event)),
+        Err(e) => {
+            error!("Failed to record feedback: {}", e);
+            Err(service_error(e, "FEEDBACK_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn feedback_stats(
+    State(state): State<AppState>,
+) -> Result<Json<FeedbackStats>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.feedback_stats() {
+        Ok(stats) => Ok(Json(stats)),
+        Err(e) => {
+            error!("Failed to compute feedback stats: {}", e);
+            Err(service_error(e, "FEEDBACK_STATS_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn list_search_events(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SearchEvent>>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.search_events() {
+        Ok(events) => Ok(Json(events)),
+        Err(e) => {
+            error!("Failed to list search events: {}", e);
+            Err(service_error(e, "SEARCH_EVENTS_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn record_search_event(
+    State(state): State<AppState>,
+    Json(event): Json<SearchEvent>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.record_search_event(event) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to record search event: {}", e);
+            Err(service_error(e, "SEARCH_EVENT_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn clear_feedback(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.clear_feedback() {
+        Ok(cleared) => Ok(Json(serde_json::json!({ "cleared": cleared }))),
+        Err(e) => {
+            error!("Failed to clear feedback: {}", e);
+            Err(service_error(e, "FEEDBACK_CLEAR_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn list_projects(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ProjectInfo>>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.projects_with_activity() {
+        Ok(projects) => Ok(Json(projects)),
+        Err(e) => {
+            error!("Failed to list projects: {}", e);
+            Err(service_error(e, "PROJECTS_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn project_overview(
+    State(state): State<AppState>,
+    Path(project): Path<String>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Json<ProjectOverview>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    let recent_limit = params.get("recent").and_then(|s| s.parse().ok());
+
+    match service.project_overview(&project, recent_limit) {
+        Ok(overview) => Ok(Json(overview)),
+        Err(e) => {
+            error!("Failed to build project overview: {}", e);
+            Err(service_error(e, "OVERVIEW_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn related_projects(
+    State(state): State<AppState>,
+    Path(project): Path<String>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Json<Vec<RelatedProject>>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    let limit = params
+        .get("limit")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(10);
+
+    match service.related_projects(&project, limit) {
+        Ok(related) => Ok(Json(related)),
+        Err(e) => {
+            error!("Failed to find related projects: {}", e);
+            Err(service_error(e, "RELATED_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn runtime_config(State(state): State<AppState>) -> Json<RuntimeConfigResponse> {
+    let service = state.service.read().await;
+    Json(describe_runtime(
+        &service,
+        &state.config,
+        &state.data_dir,
+        state.mode,
+    ))
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn build_info() -> Json<BuildInfo> {
+    Json(describe_build())
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn rebuild_term_stats(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match tokio::task::spawn_blocking(move || service.rebuild_term_stats())
+        .await
+        .expect("Failed to spawn term stats rebuild task")
+    {
+        Ok(documents) => {
+            info!("Rebuilt term statistics over {} contexts", documents);
+            Ok(Json(serde_json::json!({ "documents": documents })))
+        }
+        Err(e) => {
+            error!("Failed to rebuild term stats: {}", e);
+            Err(service_error(e, "REBUILD_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn rebuild_index(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match tokio::task::spawn_blocking(move || service.rebuild_index())
+        .await
+        .expect("Failed to spawn index rebuild task")
+    {
+        Ok(indexed) => {
+            info!("Rebuilt secondary indexes over {} contexts", indexed);
+            Ok(Json(serde_json::json!({ "indexed": indexed })))
+        }
+        Err(e) => {
+            error!("Failed to rebuild index: {}", e);
+            Err(service_error(e, "REBUILD_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn delete_contexts(
+    State(state): State<AppState>,
+    Json(payload): Json<DeleteContextsRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match tokio::task::spawn_blocking(move || service.delete_many(payload.ids))
+        .await
+        .expect("Failed to spawn delete task")
+    {
+        Ok(deleted) => {
+            info!("Deleted {} contexts", deleted);
+            Ok(Json(serde_json::json!({ "deleted": deleted })))
+        }
+        Err(e) => {
+            error!("Failed to delete contexts: {}", e);
+            Err(service_error(e, "DELETE_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn batch_get_contexts(
+    State(state): State<AppState>,
+    Json(payload): Json<FetchContextsRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match tokio::task::spawn_blocking(move || service.get_many(payload.ids))
+        .await
+        .expect("Failed to spawn batch get task")
+    {
+        Ok(records) => Ok(Json(serde_json::json!({ "records": records }))),
+        Err(e) => {
+            error!("Failed to fetch contexts: {}", e);
+            Err(service_error(e, "BATCH_GET_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn reembed_project(
+    State(state): State<AppState>,
+    Path(project): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match tokio::task::spawn_blocking(move || {
+        service
+            .reembed_project(&project)
+            .map(|count| (project, count))
+    })
+    .await
+    .expect("Failed to spawn re-embed task")
+    {
+        Ok((project, count)) => {
+            info!("Re-embedded {} contexts for project {}", count, project);
+            Ok(Json(serde_json::json!({
+                "project": project,
+                "reembedded": count
+            })))
+        }
+        Err(e) => {
+            error!("Failed to re-embed project: {}", e);
+            Err(service_error(e, "REEMBED_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn changes_since(
+    State(state): State<AppState>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Json<Vec<ChangeEvent>>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    let since = params.get("since").cloned().unwrap_or_default();
+    let include_deleted = params
+        .get("include_deleted")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false);
+
+    match service.changes_since(&since, include_deleted) {
+        Ok(events) => Ok(Json(events)),
+        Err(e) => {
+            error!("Failed to list changes: {}", e);
+            Err(service_error(e, "CHANGES_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn update_pin(
+    state: AppState,
+    id: String,
+    pinned: bool,
+) -> Result<Json<ContextSummary>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    match service.set_pinned(&id, pinned) {
+        Ok(summary) => {
+            info!("Context {} pinned={}", summary.id, pinned);
+            Ok(Json(summary))
+        }
+        Err(e) => {
+            error!("Failed to update pin: {}", e);
+            Err(service_error(e, "PIN_FAILED"))
+        }
+    }
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn pin_context(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ContextSummary>, (StatusCode, Json<ErrorResponse>)> {
+    update_pin(state, id, true).await
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn unpin_context(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ContextSummary>, (StatusCode, Json<ErrorResponse>)> {
+    update_pin(state, id, false).await
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn archive_context(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ContextSummary>, (StatusCode, Json<ErrorResponse>)> {
+    update_archive(state, id, true).await
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn unarchive_context(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ContextSummary>, (StatusCode, Json<ErrorResponse>)> {
+    update_archive(state, id, false).await
+}
+
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn update_archive(
+    state: AppState,
+    id: String,
+    archived: bool,
+) -> Result<Json<ContextSummary>, (StatusCode, Json<ErrorResponse>)> {
+    let service = state.service.read().await;
+    let service = Arc::clone(&service);
+
+    // Unarchiving embeds the record again.
+    let result = tokio::task::spawn_blocking(move || {
+        if archived {
+            service.archive(&id)
+        } else {
+            service.unarchive(&id)
+        }
+    })
+    .await
+    .expect("Failed to spawn archive task");
+
+    match result {
+        Ok(summary) => {
+            info!("Context {} archived={}", summary.id, archived);
+            Ok(Json(summary))
+        }
+        Err(e) => {
+            error!("Failed to update archive state: {}", e);
+            Err(service_error(e, "ARCHIVE_FAILED"))
         }
     }
 }
@@ -280,6 +1096,78 @@ async fn mcp_message_handler(
     Json(response).into_response()
 }
 
+// ============================================================================
+// Rate Limiting
+// ============================================================================
+
+/// Paths the REST limiter leaves alone: health probes, and the MCP endpoints,
+/// whose tool calls are charged by the MCP server itself (handshakes are free).
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+const RATE_LIMIT_EXEMPT: [&str; 3] = ["/health", "/sse", "/message"];
+
+/// Applies the per-client budget from `INGAT_MCP_RATE_LIMIT`. Clients are told
+/// apart by their `x-ingat-client` header, falling back to the source address.
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn enforce_rate_limit(
+    State(limiter): State<Arc<KeyedRateLimiter<String>>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if RATE_LIMIT_EXEMPT.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+    let client = request
+        .headers()
+        .get(CLIENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128)
+        .map_or_else(|| addr.ip().to_string(), |id| format!("client:{id}"));
+    if limiter.try_acquire(client.clone()) {
+        return next.run(request).await;
+    }
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(ErrorResponse {
+            error: format!("rate limit exceeded for {client}"),
+            code: "RATE_LIMITED".to_string(),
+        }),
+    )
+        .into_response()
+}
+
+// ============================================================================
+// Error Body Formatting
+// ============================================================================
+
+/// Rewrites `{error, code}` bodies of failed responses into `{message, status}`.
+#[cfg(all(feature = "mcp-server", feature = "tauri-plugin"))]
+async fn reshape_error_body(response: Response) -> Response {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, 64 * 1024).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    let message = match serde_json::from_slice::<ErrorResponse>(&bytes) {
+        Ok(err) => err.error,
+        Err(_) => return Response::from_parts(parts, axum::body::Body::from(bytes)),
+    };
+
+    parts.headers.remove(axum::http::header::CONTENT_LENGTH);
+    let reshaped = serde_json::json!({
+        "message": message,
+        "status": status.as_u16(),
+    });
+    Response::from_parts(parts, axum::body::Body::from(reshaped.to_string()))
+}
+
 // ============================================================================
 // Service Setup and Main
 // ============================================================================
@@ -292,7 +1180,13 @@ async fn run_service() -> anyhow::Result<()> {
             EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| EnvFilter::new("info,ingat_lib=debug")),
         )
-        .with(fmt::layer().with_target(true).with_thread_ids(true))
+        .with(
+            fmt::layer()
+                .with_target(true)
+                .with_thread_ids(true)
+                // Plain text when redirected to INGAT_SERVICE_LOG_FILE.
+                .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stdout())),
+        )
         .init();
 
     info!(
@@ -308,6 +1202,15 @@ async fn run_service() -> anyhow::Result<()> {
         .expect("Failed to spawn initialization task")
         .expect("Failed to initialize application");
 
+    if app_handles.mode == RuntimeMode::Remote {
+        // Another service (or the desktop app in local mode) owns the store;
+        // proxying to it from here would only loop back to ourselves.
+        anyhow::bail!(
+            "another Ingat process already holds the database in {}; stop it before starting mcp-service",
+            app_handles.data_dir.display()
+        );
+    }
+
     info!("Data directory: {}", app_handles.data_dir.display());
 
     let state = AppState {
@@ -315,23 +1218,96 @@ async fn run_service() -> anyhow::Result<()> {
         store: app_handles.store,
         config: app_handles.config,
         data_dir: app_handles.data_dir,
+        mode: app_handles.mode,
+        request_log: RequestLogger::from_env(),
+        export_searches: export_search_bucket(),
     };
 
     info!("Application initialized successfully");
+    let store = Arc::clone(&state.store);
 
     // Build router
-    let app = Router::new()
+    let mut app = Router::new()
         // Health check
         .route("/health", get(health_check))
         // REST API
         .route("/api/contexts", post(save_context).get(list_contexts))
+        .route("/api/contexts/stream", get(stream_contexts))
+        .route("/api/contexts/delete", post(delete_contexts))
+        .route("/api/contexts/batch-get", post(batch_get_contexts))
+        .route(
+            "/api/contexts/:id/pin",
+            post(pin_context).delete(unpin_context),
+        )
+        .route(
+            "/api/contexts/:id/archive",
+            post(archive_context).delete(unarchive_context),
+        )
+        .route("/api/tags/:tag/contexts", get(list_tag_contexts))
+        .route("/api/changes", get(changes_since))
         .route("/api/search", post(search_contexts))
+        .route("/api/search/keyword", post(keyword_search))
+        .route("/api/term-stats/rebuild", post(rebuild_term_stats))
         .route("/api/stats", get(get_stats))
+        .route("/api/config", get(runtime_config))
+        .route("/api/build", get(build_info))
+        .route("/api/store/composition", get(store_composition))
+        .route("/api/store/verify", get(verify_store))
+        .route("/api/kinds", get(list_kinds))
+        .route(
+            "/api/queries",
+            get(recent_queries).delete(clear_query_history),
+        )
+        .route(
+            "/api/feedback",
+            post(record_feedback).delete(clear_feedback),
+        )
+        .route("/api/feedback/stats", get(feedback_stats))
+        .route(
+            "/api/feedback/events",
+            get(list_search_events).post(record_search_event),
+        )
+        .route("/api/index/rebuild", post(rebuild_index))
+        .route("/api/projects", get(list_projects))
+        .route("/api/projects/:project/overview", get(project_overview))
+        .route("/api/projects/:project/related", get(related_projects))
+        .route("/api/projects/:project/reembed", post(reembed_project))
         // MCP endpoints
         .route("/sse", get(mcp_sse_handler))
         .route("/message", post(mcp_message_handler))
         .with_state(state);
 
+    if let Some(rate) = rate_limit_from_env() {
+        let limiter = Arc::new(KeyedRateLimiter::new(rate));
+        app = app.layer(axum::middleware::from_fn_with_state(
+            limiter,
+            enforce_rate_limit,
+        ));
+        info!("Rate limit: {} requests/second per client", rate);
+    }
+
+    let error_format = ErrorFormat::from_env();
+    if error_format == ErrorFormat::MessageStatus {
+        app = app.layer(axum::middleware::map_response(reshape_error_body));
+    }
+    info!("Error body format: {:?}", error_format);
+
+    // Paths the router doesn't handle are files under the static dir; misses
+    // get `index.html` so client-side routing works.
+    if let Ok(dir) = std::env::var("INGAT_STATIC_DIR") {
+        let root = std::path::PathBuf::from(dir);
+        info!("Serving static files from {}", root.display());
+        app = app.fallback_service(
+            ServeDir::new(&root).fallback(ServeFile::new(root.join("index.html"))),
+        );
+    }
+
+    // Outermost so preflight requests are answered before rate limiting.
+    if let Some(cors) = cors_layer_from_env() {
+        app = app.layer(cors);
+        info!("CORS enabled for browser clients");
+    }
+
     // Determine bind address
     let host = std::env::var("INGAT_SERVICE_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
     let port = std::env::var("INGAT_SERVICE_PORT")
@@ -353,8 +1329,20 @@ async fn run_service() -> anyhow::Result<()> {
         .await
         .expect("Failed to bind to address");
 
-    axum::serve(listener, app).await.expect("Server failed");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await
+    .expect("Server failed");
 
+    // Make writes still waiting for a coalesced flush durable before exiting.
+    if let Err(err) = store.flush() {
+        error!("Failed to flush the store on shutdown: {}", err);
+    }
     Ok(())
 }
 
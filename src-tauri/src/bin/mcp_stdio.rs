@@ -1,7 +1,7 @@
 #![allow(clippy::expect_used)]
 
 #[cfg(feature = "mcp-server")]
-use ingat_lib::run_mcp_stdio;
+use ingat_lib::{check_mcp_stdio, run_mcp_stdio};
 
 #[cfg(feature = "mcp-server")]
 use tauri::async_runtime;
@@ -40,8 +40,29 @@ use tauri::async_runtime;
 /// - `INGAT_LOG`: Set logging level (trace, debug, info, warn, error)
 /// - `INGAT_DATA_DIR`: Override data directory location
 ///
+/// # Self-test
+///
+/// `mcp-stdio --self-test` runs `initialize`, `notifications/initialized`,
+/// `tools/list` and a `tools/call` through the stdio loop over in-memory pipes
+/// and exits non-zero if any response is malformed.
 #[cfg(feature = "mcp-server")]
 fn main() {
+    if std::env::args().skip(1).any(|arg| arg == "--self-test") {
+        match async_runtime::block_on(check_mcp_stdio()) {
+            Ok(report) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).expect("serializable report")
+                );
+                return;
+            }
+            Err(err) => {
+                eprintln!("[ingat::mcp-stdio] Self-test failed: {err:#}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Block on the async runtime
     if let Err(err) = async_runtime::block_on(run_mcp_stdio()) {
         eprintln!("[ingat::mcp-stdio] Runtime failed: {err:?}");
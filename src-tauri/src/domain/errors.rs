@@ -23,6 +23,15 @@ pub enum DomainError {
     #[error("embedding mismatch: {0}")]
     Embedding(String),
 
+    /// Configuration could not be read, parsed or written.
+    #[error("configuration error: {0}")]
+    Config(String),
+
+    /// A maintenance operation (named in the message) holds the store;
+    /// retry once it finishes.
+    #[error("service in maintenance: {0}")]
+    Busy(String),
+
     /// Any other unexpected failure.
     #[error("unexpected error: {0}")]
     Other(String),
@@ -49,7 +58,30 @@ impl DomainError {
         Self::Embedding(msg.into())
     }
 
+    pub fn config(msg: impl Into<String>) -> Self {
+        Self::Config(msg.into())
+    }
+
+    pub fn busy(operation: impl Into<String>) -> Self {
+        Self::Busy(operation.into())
+    }
+
     pub fn other(msg: impl Into<String>) -> Self {
         Self::Other(msg.into())
     }
+
+    /// Prefix the message with the stage that failed, keeping the variant.
+    pub fn with_context(self, context: &str) -> Self {
+        let prefix = |msg: String| format!("{context}: {msg}");
+        match self {
+            Self::Validation(msg) => Self::Validation(prefix(msg)),
+            Self::LimitExceeded(msg) => Self::LimitExceeded(prefix(msg)),
+            Self::NotFound(msg) => Self::NotFound(prefix(msg)),
+            Self::Storage(msg) => Self::Storage(prefix(msg)),
+            Self::Embedding(msg) => Self::Embedding(prefix(msg)),
+            Self::Config(msg) => Self::Config(prefix(msg)),
+            Self::Busy(msg) => Self::Busy(prefix(msg)),
+            Self::Other(msg) => Self::Other(prefix(msg)),
+        }
+    }
 }
@@ -0,0 +1,160 @@
+//! Compact filter expressions such as `tag:rust|go tag:async kind:fix-history`.
+//!
+//! Whitespace-separated terms are ANDed; `|` inside a term ORs its values.
+//! Each term compiles to a [`FilterGroup`] on [`QueryFilters`], so the example
+//! reads as `(tag rust OR tag go) AND tag async AND kind fix-history`.
+
+#[cfg(feature = "mcp-server")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{normalize_tag, ContextKind, ContextRecord, DomainError, QueryFilters};
+
+/// Record field a [`FilterGroup`] tests.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterField {
+    Project,
+    Kind,
+    Tag,
+    Ide,
+}
+
+impl FilterField {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "project" => Some(Self::Project),
+            "kind" => Some(Self::Kind),
+            "tag" => Some(Self::Tag),
+            "ide" => Some(Self::Ide),
+            _ => None,
+        }
+    }
+}
+
+/// Matches when the field equals any of the values. Tag values are stored
+/// normalized and kind values as [`ContextKind::key`].
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterGroup {
+    pub field: FilterField,
+    pub any_of: Vec<String>,
+}
+
+impl FilterGroup {
+    pub fn matches(&self, record: &ContextRecord) -> bool {
+        match self.field {
+            FilterField::Project => self.any_of.iter().any(|value| value == &record.project),
+            FilterField::Ide => self.any_of.iter().any(|value| value == &record.ide),
+            FilterField::Kind => {
+                let key = record.kind.key();
+                self.any_of.iter().any(|value| value == &key)
+            }
+            FilterField::Tag => record
+                .tags
+                .iter()
+                .any(|tag| self.any_of.iter().any(|value| value == tag)),
+        }
+    }
+}
+
+/// Compile `expr` into filters holding only AND-of-OR groups. Errors name
+/// the 1-based character position of the offending term or value.
+pub fn parse_filter_expr(expr: &str) -> Result<QueryFilters, DomainError> {
+    let mut groups = Vec::new();
+
+    for (offset, term) in terms(expr) {
+        let error = |at: usize, message: String| {
+            let position = expr[..at].chars().count() + 1;
+            DomainError::validation(format!("filter_expr: {message} at position {position}"))
+        };
+
+        let Some((name, values)) = term.split_once(':') else {
+            return Err(error(
+                offset,
+                format!("expected `field:value`, found `{term}`"),
+            ));
+        };
+        let field = FilterField::parse(name).ok_or_else(|| {
+            error(
+                offset,
+                format!("unknown field `{name}` (expected project, kind, tag or ide)"),
+            )
+        })?;
+
+        let mut any_of = Vec::new();
+        let mut value_offset = offset + name.len() + 1;
+        for raw in values.split('|') {
+            let value = match field {
+                FilterField::Tag => normalize_tag(raw),
+                FilterField::Kind => ContextKind::from_label(raw).map(|kind| kind.key()),
+                FilterField::Project | FilterField::Ide => {
+                    (!raw.is_empty()).then(|| raw.to_string())
+                }
+            }
+            .ok_or_else(|| error(value_offset, format!("empty value for `{name}`")))?;
+            if !any_of.contains(&value) {
+                any_of.push(value);
+            }
+            value_offset += raw.len() + 1;
+        }
+        groups.push(FilterGroup { field, any_of });
+    }
+
+    if groups.is_empty() {
+        return Err(DomainError::validation("filter_expr cannot be empty"));
+    }
+    Ok(QueryFilters {
+        groups,
+        ..QueryFilters::default()
+    })
+}
+
+/// Whitespace-separated terms with their byte offsets into `expr`.
+fn terms(expr: &str) -> impl Iterator<Item = (usize, &str)> {
+    expr.split_whitespace()
+        .map(move |term| (term.as_ptr() as usize - expr.as_ptr() as usize, term))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_expr_compiles_and_of_or_groups() {
+        let filters = parse_filter_expr("tag:Rust|go  tag:async kind:FixHistory").unwrap();
+        assert_eq!(
+            filters.groups,
+            vec![
+                FilterGroup {
+                    field: FilterField::Tag,
+                    any_of: vec!["rust".into(), "go".into()],
+                },
+                FilterGroup {
+                    field: FilterField::Tag,
+                    any_of: vec!["async".into()],
+                },
+                FilterGroup {
+                    field: FilterField::Kind,
+                    any_of: vec!["fix-history".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_expr_reports_offending_position() {
+        let err = parse_filter_expr("tag:rust colour:red").unwrap_err();
+        assert!(err.to_string().contains("unknown field `colour`"));
+        assert!(err.to_string().contains("position 10"));
+
+        let err = parse_filter_expr("tag:rust||go").unwrap_err();
+        assert!(err.to_string().contains("position 10"));
+
+        let err = parse_filter_expr("ide:vscode async").unwrap_err();
+        assert!(err.to_string().contains("position 12"));
+
+        assert!(parse_filter_expr("   ").is_err());
+    }
+}
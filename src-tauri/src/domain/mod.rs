@@ -1,9 +1,17 @@
 //! Domain layer: core business entities and value objects for Ingat.
 
 pub mod errors;
+pub mod filter_expr;
 pub mod models;
+pub mod text_diff;
 
 pub use errors::DomainError;
+pub use filter_expr::{parse_filter_expr, FilterField, FilterGroup};
 pub use models::{
-    ContextEmbedding, ContextKind, ContextRecord, ContextSummary, QueryFilters, RetrievalQuery,
+    infer_project_from_path, normalize_kind_label, normalize_tag, sanitize_project,
+    strip_invisible, tokenize_terms, ChangeEvent, ChangeKind, ChosenResult, ContextEmbedding,
+    ContextKind, ContextRecord, ContextSummary, EmbeddingComposition, HistoryOrder, OrderField,
+    QueryFilters, QueryHistoryEntry, RetrievalQuery, SearchEvent, SearchScope, SortDirection,
+    SourceMeta,
 };
+pub use text_diff::{diff_lines, DiffLine, DiffOp};
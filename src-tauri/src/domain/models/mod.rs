@@ -4,9 +4,14 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::domain::{DomainError, FilterGroup};
+
 /// Upper bound to keep tag arrays compact for storage and filtering.
 pub const MAX_TAGS: usize = 12;
 
+/// Longest custom kind key kept after normalization.
+pub const MAX_KIND_LABEL_CHARS: usize = 32;
+
 /// Core record representing a stored context chunk, embedding, and its metadata.
 #[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
 #[cfg_attr(feature = "mcp-server", schemars(rename_all = "camelCase"))]
@@ -24,6 +29,28 @@ pub struct ContextRecord {
     pub kind: ContextKind,
     pub embedding: ContextEmbedding,
     pub created_at: DateTime<Utc>,
+    /// User-set marker returned with results and history; it does not affect storage.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Last time the record was modified after ingest (pin changes, re-embeds).
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Summary-only vector, present when field embeddings are enabled.
+    #[serde(default)]
+    pub summary_embedding: Option<ContextEmbedding>,
+    /// Body-only vector, present when field embeddings are enabled.
+    #[serde(default)]
+    pub body_embedding: Option<ContextEmbedding>,
+    /// Structured provenance; `ide` above stays the flat, single-line name.
+    #[serde(default)]
+    pub source: Option<SourceMeta>,
+    /// Archived records keep their text but have no vectors and never match a search.
+    #[serde(default = "default_searchable")]
+    pub searchable: bool,
+    /// Set on the per-window chunks of a long body; points at the full record.
+    #[serde(default)]
+    #[cfg_attr(feature = "mcp-server", schemars(with = "Option<String>"))]
+    pub parent_id: Option<Uuid>,
 }
 
 impl ContextRecord {
@@ -51,17 +78,44 @@ impl ContextRecord {
             kind,
             embedding,
             created_at: Utc::now(),
+            pinned: false,
+            updated_at: None,
+            summary_embedding: None,
+            body_embedding: None,
+            source: None,
+            searchable: true,
+            parent_id: None,
         }
     }
 
+    /// Stamp the record as modified now.
+    pub fn touch(&mut self) {
+        self.updated_at = Some(Utc::now());
+    }
+
+    /// Vector to compare against for the given scope. Records stored without
+    /// per-field vectors fall back to the combined embedding.
+    pub fn embedding_for(&self, scope: SearchScope) -> &ContextEmbedding {
+        let field = match scope {
+            SearchScope::Both => None,
+            SearchScope::SummaryOnly => self.summary_embedding.as_ref(),
+            SearchScope::BodyOnly => self.body_embedding.as_ref(),
+        };
+        field.unwrap_or(&self.embedding)
+    }
+
+    /// Archived records never match, whatever the filters.
     pub fn matches_filters(&self, filters: &QueryFilters) -> bool {
+        if !self.searchable {
+            return false;
+        }
         if let Some(project) = &filters.project {
             if &self.project != project {
                 return false;
             }
         }
         if let Some(kind) = &filters.kind {
-            if &self.kind != kind {
+            if self.kind.key() != kind.key() {
                 return false;
             }
         }
@@ -75,7 +129,24 @@ impl ContextRecord {
                 return false;
             }
         }
-        true
+        if let Some(version) = &filters.ide_version {
+            let stored = self
+                .source
+                .as_ref()
+                .and_then(|source| source.ide_version.as_ref());
+            if stored != Some(version) {
+                return false;
+            }
+        }
+        if filters.since.is_some_and(|since| self.created_at <= since) {
+            return false;
+        }
+        if let Some(model) = &filters.model {
+            if &self.embedding.model != model {
+                return false;
+            }
+        }
+        filters.groups.iter().all(|group| group.matches(self))
     }
 
     pub fn as_summary(&self) -> ContextSummary {
@@ -85,7 +156,64 @@ impl ContextRecord {
             summary: self.summary.clone(),
             kind: self.kind.clone(),
             created_at: self.created_at,
+            updated_at: self.updated_at,
             tags: self.tags.clone(),
+            pinned: self.pinned,
+            searchable: self.searchable,
+            parent_id: self.parent_id,
+        }
+    }
+
+    /// Chunk records are search-only copies of part of a parent's body.
+    pub fn is_chunk(&self) -> bool {
+        self.parent_id.is_some()
+    }
+}
+
+/// Where a context came from, as reported by the client.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMeta {
+    #[serde(default)]
+    pub ide: String,
+    #[serde(default)]
+    pub ide_version: Option<String>,
+    #[serde(default)]
+    pub workspace: Option<String>,
+}
+
+impl SourceMeta {
+    /// Recover metadata from a multi-line `ide` string (`name`, `version`,
+    /// `workspace` on successive lines). `None` for a plain single-line value.
+    pub fn from_ide_lines(raw: &str) -> Option<Self> {
+        let mut lines = raw.lines().map(str::trim);
+        let ide = lines.next().unwrap_or_default().to_string();
+        let ide_version = lines
+            .next()
+            .filter(|line| !line.is_empty())
+            .map(String::from);
+        let workspace = lines
+            .next()
+            .filter(|line| !line.is_empty())
+            .map(String::from);
+        (ide_version.is_some() || workspace.is_some()).then_some(Self {
+            ide,
+            ide_version,
+            workspace,
+        })
+    }
+
+    /// Trim every field to a single line and drop empty optionals.
+    pub fn sanitized(self) -> Self {
+        let optional = |value: Option<String>| {
+            value
+                .map(sanitize_single_line)
+                .filter(|value| !value.is_empty())
+        };
+        Self {
+            ide: sanitize_single_line(self.ide),
+            ide_version: optional(self.ide_version),
+            workspace: optional(self.workspace),
         }
     }
 }
@@ -102,6 +230,141 @@ pub struct ContextSummary {
     pub kind: ContextKind,
     pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default = "default_searchable")]
+    pub searchable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "mcp-server", schemars(with = "Option<String>"))]
+    pub parent_id: Option<Uuid>,
+}
+
+/// One search remembered by the query history.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryHistoryEntry {
+    pub prompt: String,
+    /// Number of results the search returned.
+    pub results: usize,
+    pub searched_at: DateTime<Utc>,
+}
+
+/// A search kept for relevance analytics, with the results later acted on.
+/// Holds the prompt and ids only, never record text.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchEvent {
+    #[cfg_attr(feature = "mcp-server", schemars(with = "String"))]
+    pub query_id: Uuid,
+    pub prompt: String,
+    /// Result ids in the order they were returned.
+    #[cfg_attr(feature = "mcp-server", schemars(with = "Vec<String>"))]
+    pub returned: Vec<Uuid>,
+    pub searched_at: DateTime<Utc>,
+    #[serde(default)]
+    pub chosen: Vec<ChosenResult>,
+}
+
+/// A result the user or agent went on to use.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChosenResult {
+    #[cfg_attr(feature = "mcp-server", schemars(with = "String"))]
+    pub id: Uuid,
+    /// Position in `returned` (0 = top result); `None` when it wasn't returned.
+    pub rank: Option<usize>,
+    pub chosen_at: DateTime<Utc>,
+}
+
+impl SearchEvent {
+    /// Record that `id` was used, once per id. Returns whether it was new.
+    pub fn choose(&mut self, id: Uuid) -> bool {
+        if self.chosen.iter().any(|chosen| chosen.id == id) {
+            return false;
+        }
+        self.chosen.push(ChosenResult {
+            id,
+            rank: self.returned.iter().position(|returned| *returned == id),
+            chosen_at: Utc::now(),
+        });
+        true
+    }
+}
+
+/// Summary field a history listing is sorted by.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderField {
+    #[default]
+    CreatedAt,
+    /// Last modification; never-modified records count as modified at creation.
+    UpdatedAt,
+    Project,
+    /// Ordered by [`ContextKind::key`].
+    Kind,
+}
+
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Ordering of history listings; the default is newest first.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryOrder {
+    #[serde(default)]
+    pub order_by: OrderField,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+impl HistoryOrder {
+    pub fn compare(&self, a: &ContextSummary, b: &ContextSummary) -> std::cmp::Ordering {
+        let primary = match self.order_by {
+            OrderField::CreatedAt => a.created_at.cmp(&b.created_at),
+            OrderField::UpdatedAt => a
+                .updated_at
+                .unwrap_or(a.created_at)
+                .cmp(&b.updated_at.unwrap_or(b.created_at)),
+            OrderField::Project => a.project.cmp(&b.project),
+            OrderField::Kind => a.kind.key().cmp(&b.kind.key()),
+        };
+        let primary = match self.direction {
+            SortDirection::Asc => primary,
+            SortDirection::Desc => primary.reverse(),
+        };
+        // Ties list newest first, then by id, so repeated listings agree.
+        primary
+            .then_with(|| b.created_at.cmp(&a.created_at))
+            .then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+/// Kind of mutation reported by a change feed.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// Single entry of the change feed used for incremental sync.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    #[cfg_attr(feature = "mcp-server", schemars(with = "String"))]
+    pub id: Uuid,
+    pub change: ChangeKind,
+    pub at: DateTime<Utc>,
+    /// Current state for inserts/updates; `None` for deletions.
+    pub summary: Option<ContextSummary>,
 }
 
 /// Input for retrieval requests originating from the UI or MCP clients.
@@ -111,6 +374,10 @@ pub struct RetrievalQuery {
     pub prompt: String,
     pub filters: QueryFilters,
     pub limit: usize,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    #[serde(default)]
+    pub scope: SearchScope,
 }
 
 /// Supported filters for narrowing search results.
@@ -121,6 +388,18 @@ pub struct QueryFilters {
     pub kind: Option<ContextKind>,
     pub tag: Option<String>,
     pub ide: Option<String>,
+    #[serde(default)]
+    pub ide_version: Option<String>,
+    /// AND-of-OR groups, usually compiled from a filter expression.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<FilterGroup>,
+    /// Only records created strictly after this instant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+    /// Only records embedded with this model. Set internally when a legacy
+    /// index splits a search by model; never read from or sent in requests.
+    #[serde(skip)]
+    pub model: Option<String>,
 }
 
 #[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
@@ -134,18 +413,112 @@ pub enum ContextKind {
     Other(String),
 }
 
+impl ContextKind {
+    /// Human-readable label; custom kinds use their own label.
+    pub fn label(&self) -> &str {
+        match self {
+            ContextKind::CodeSnippet => "CodeSnippet",
+            ContextKind::FixHistory => "FixHistory",
+            ContextKind::ProjectSummary => "ProjectSummary",
+            ContextKind::Discussion => "Discussion",
+            ContextKind::ToolLog => "ToolLog",
+            ContextKind::Other(label) => label,
+        }
+    }
+
+    /// Index key: kebab-case for built-ins, the normalized label for custom
+    /// kinds. Custom labels stored before normalization map to the same key.
+    pub fn key(&self) -> String {
+        match self {
+            ContextKind::Other(label) => normalize_kind_label(label).unwrap_or_default(),
+            builtin => builtin.builtin_key().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Kind named by a free-form label; built-ins match loosely, so
+    /// `FixHistory`, `fix_history` and `fix-history` are all the same kind.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let key = normalize_kind_label(label)?;
+        let squashed = key.replace('-', "");
+        Some(
+            Self::BUILTINS
+                .into_iter()
+                .find(|builtin| {
+                    builtin
+                        .builtin_key()
+                        .is_some_and(|builtin| builtin.replace('-', "") == squashed)
+                })
+                .unwrap_or(ContextKind::Other(key)),
+        )
+    }
+
+    /// Normalize a custom label (see [`normalize_kind_label`]), rejecting labels
+    /// that are empty or would be mistaken for a built-in kind.
+    pub fn normalized(self) -> Result<Self, DomainError> {
+        let ContextKind::Other(label) = self else {
+            return Ok(self);
+        };
+        let key = normalize_kind_label(&label)
+            .ok_or_else(|| DomainError::validation("custom kind label cannot be empty"))?;
+        let squashed = key.replace('-', "");
+        if Self::BUILTINS
+            .iter()
+            .filter_map(ContextKind::builtin_key)
+            .any(|builtin| builtin.replace('-', "") == squashed)
+        {
+            return Err(DomainError::validation(format!(
+                "custom kind `{label}` collides with a built-in kind; use the built-in kind instead"
+            )));
+        }
+        Ok(ContextKind::Other(key))
+    }
+
+    const BUILTINS: [ContextKind; 5] = [
+        ContextKind::CodeSnippet,
+        ContextKind::FixHistory,
+        ContextKind::ProjectSummary,
+        ContextKind::Discussion,
+        ContextKind::ToolLog,
+    ];
+
+    fn builtin_key(&self) -> Option<&'static str> {
+        match self {
+            ContextKind::CodeSnippet => Some("code-snippet"),
+            ContextKind::FixHistory => Some("fix-history"),
+            ContextKind::ProjectSummary => Some("project-summary"),
+            ContextKind::Discussion => Some("discussion"),
+            ContextKind::ToolLog => Some("tool-log"),
+            ContextKind::Other(_) => None,
+        }
+    }
+}
+
 impl Default for ContextKind {
     fn default() -> Self {
         ContextKind::Other("unspecified".into())
     }
 }
 
+/// Which stored field a semantic search compares against.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SearchScope {
+    SummaryOnly,
+    BodyOnly,
+    /// Combined summary + body vector (the original behaviour).
+    #[default]
+    Both,
+}
+
 /// Vector representation of a context chunk.
 #[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContextEmbedding {
     pub model: String,
     pub vector: Vec<f32>,
+    /// Version of the engine that produced the vector, when known.
+    #[serde(default)]
+    pub model_version: Option<String>,
 }
 
 impl ContextEmbedding {
@@ -153,18 +526,38 @@ impl ContextEmbedding {
         Self {
             model: model.into(),
             vector,
+            model_version: None,
         }
     }
 
+    pub fn with_version(mut self, version: Option<String>) -> Self {
+        self.model_version = version;
+        self
+    }
+
     pub fn dims(&self) -> usize {
         self.vector.len()
     }
 }
 
-fn sanitize_project(input: impl Into<String>) -> String {
+/// Number of stored records sharing the same embedding model, version, and dimension.
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingComposition {
+    pub model: String,
+    pub model_version: Option<String>,
+    pub dimensions: usize,
+    pub count: usize,
+}
+
+/// Project name as records store it: one trimmed line with path separators
+/// replaced with `-`.
+pub fn sanitize_project(input: impl Into<String>) -> String {
     sanitize_single_line(input).replace(['\\', '/', ':'], "-")
 }
 
+/// The first line, trimmed. Invisible characters are left to [`strip_invisible`],
+/// which ingest only applies when unicode sanitizing is enabled.
 fn sanitize_single_line(input: impl Into<String>) -> String {
     input
         .into()
@@ -175,16 +568,76 @@ fn sanitize_single_line(input: impl Into<String>) -> String {
         .to_string()
 }
 
+/// Drop control characters, zero-width spaces, direction marks and BOMs that
+/// pasted text tends to carry. Newlines and tabs are kept so bodies keep their
+/// layout, and the zero-width (non-)joiners are kept because emoji sequences
+/// and several scripts need them.
+pub fn strip_invisible(text: &str) -> String {
+    text.chars()
+        .filter(|&ch| {
+            matches!(ch, '\n' | '\t')
+                || !(ch.is_control()
+                    || matches!(
+                        ch,
+                        '\u{200B}' | '\u{200E}' | '\u{200F}' | '\u{2060}' | '\u{FEFF}'
+                    ))
+        })
+        .collect()
+}
+
+/// Lowercase a custom kind label, join words with `-` and cap its length,
+/// returning `None` when nothing meaningful remains.
+pub fn normalize_kind_label(label: &str) -> Option<String> {
+    let normalized = label
+        .trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let capped: String = normalized.chars().take(MAX_KIND_LABEL_CHARS).collect();
+    let capped = capped.trim_end_matches('-');
+    (!capped.is_empty()).then(|| capped.to_string())
+}
+
+/// Normalizes a single tag the same way stored tags are normalized, returning
+/// `None` when nothing meaningful remains.
+pub fn normalize_tag(tag: impl Into<String>) -> Option<String> {
+    let normalized = tag.into().trim().to_lowercase().replace(' ', "-");
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+/// Project name implied by a workspace path or `file://` URI: its last
+/// non-empty component.
+pub fn infer_project_from_path(path: &str) -> Option<String> {
+    let path = path.trim();
+    let path = path.strip_prefix("file://").unwrap_or(path);
+    path.split(['/', '\\'])
+        .rev()
+        .map(str::trim)
+        .find(|component| !component.is_empty() && !component.ends_with(':'))
+        .map(str::to_string)
+}
+
+/// Lowercased alphanumeric terms (2+ chars) used for lexical scoring.
+pub fn tokenize_terms(text: &str) -> Vec<String> {
+    text.split(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+        .filter(|term| term.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
 fn normalize_tags(tags: impl IntoIterator<Item = impl Into<String>>) -> Vec<String> {
     tags.into_iter()
-        .filter_map(|tag| {
-            let normalized = tag.into().trim().to_lowercase().replace(' ', "-");
-            if normalized.is_empty() {
-                None
-            } else {
-                Some(normalized)
-            }
-        })
+        .filter_map(normalize_tag)
         .take(MAX_TAGS)
         .collect()
 }
+
+const fn default_searchable() -> bool {
+    true
+}
@@ -0,0 +1,152 @@
+//! Line-level diff for comparing two context bodies side by side.
+
+#[cfg(feature = "mcp-server")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Above this many line pairs the changed middle is reported as one
+/// removal followed by one addition instead of being aligned.
+const MAX_DIFF_CELLS: usize = 1_000_000;
+
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Same,
+    /// Only in the first text.
+    Removed,
+    /// Only in the second text.
+    Added,
+}
+
+#[cfg_attr(feature = "mcp-server", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+impl DiffLine {
+    fn new(op: DiffOp, text: &str) -> Self {
+        Self {
+            op,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Lines of `a` and `b` aligned on their longest common subsequence.
+pub fn diff_lines(a: &str, b: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = a.lines().collect();
+    let b: Vec<&str> = b.lines().collect();
+
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut out: Vec<DiffLine> = a[..prefix]
+        .iter()
+        .map(|line| DiffLine::new(DiffOp::Same, line))
+        .collect();
+    if a_mid.len().saturating_mul(b_mid.len()) > MAX_DIFF_CELLS {
+        out.extend(
+            a_mid
+                .iter()
+                .map(|line| DiffLine::new(DiffOp::Removed, line)),
+        );
+        out.extend(b_mid.iter().map(|line| DiffLine::new(DiffOp::Added, line)));
+    } else {
+        align(a_mid, b_mid, &mut out);
+    }
+    out.extend(
+        a[a.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::new(DiffOp::Same, line)),
+    );
+    out
+}
+
+fn align(a: &[&str], b: &[&str], out: &mut Vec<DiffLine>) {
+    // lcs[i][j]: common subsequence length of a[i..] and b[j..].
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(DiffLine::new(DiffOp::Same, a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            out.push(DiffLine::new(DiffOp::Removed, a[i]));
+            i += 1;
+        } else {
+            out.push(DiffLine::new(DiffOp::Added, b[j]));
+            j += 1;
+        }
+    }
+    out.extend(
+        a[i..]
+            .iter()
+            .map(|line| DiffLine::new(DiffOp::Removed, line)),
+    );
+    out.extend(b[j..].iter().map(|line| DiffLine::new(DiffOp::Added, line)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(lines: &[DiffLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                let sign = match line.op {
+                    DiffOp::Same => ' ',
+                    DiffOp::Removed => '-',
+                    DiffOp::Added => '+',
+                };
+                format!("{sign}{}", line.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_lines_aligns_changed_middle() {
+        let a = "fn retry() {\n    sleep(100);\n    call()\n}";
+        let b = "fn retry() {\n    backoff.wait();\n    call()\n    log();\n}";
+        assert_eq!(
+            render(&diff_lines(a, b)),
+            vec![
+                " fn retry() {",
+                "-    sleep(100);",
+                "+    backoff.wait();",
+                "     call()",
+                "+    log();",
+                " }",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_identical_and_empty() {
+        assert!(diff_lines("a\nb", "a\nb")
+            .iter()
+            .all(|line| line.op == DiffOp::Same));
+        assert_eq!(render(&diff_lines("", "x")), vec!["+x"]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Composite embedding engine that falls back through a priority list.
+//!
+//! Vectors from different backends are not comparable (and usually differ in
+//! dimension), so the chain only falls back until one member has served a
+//! call. From then on it is *settled*: every later call goes to that member and
+//! its errors are returned rather than masked by another backend. The tradeoff
+//! is that a transient failure of the settled backend surfaces as an error
+//! instead of silently mixing vector spaces in the store.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::{application::services::EmbeddingEngine, domain::DomainError};
+
+/// One initialised backend in the chain.
+pub struct ChainMember {
+    pub backend_id: String,
+    pub model: String,
+    pub engine: Arc<dyn EmbeddingEngine>,
+}
+
+/// Tries each member in order until one succeeds, then sticks with it.
+pub struct ChainedEmbeddingEngine {
+    members: Vec<ChainMember>,
+    settled: Mutex<Option<usize>>,
+}
+
+impl ChainedEmbeddingEngine {
+    pub fn try_new(members: Vec<ChainMember>) -> Result<Self, DomainError> {
+        if members.is_empty() {
+            return Err(DomainError::embedding(
+                "no backend in the embedding chain could be initialised",
+            ));
+        }
+
+        Ok(Self {
+            members,
+            settled: Mutex::new(None),
+        })
+    }
+
+    /// Embed a probe string so the chain settles before any record is written.
+    pub fn settle(&self) -> Result<&ChainMember, DomainError> {
+        self.embed_with_fallback("ingat embedding probe")?;
+        self.served_by()
+            .ok_or_else(|| DomainError::embedding("embedding chain did not settle"))
+    }
+
+    /// The member serving calls, once the chain has settled.
+    pub fn served_by(&self) -> Option<&ChainMember> {
+        (*self.settled.lock()).map(|index| &self.members[index])
+    }
+
+    /// The lock only guards the settled index; it is never held while a
+    /// backend embeds, so slow calls don't serialise each other.
+    fn embed_with_fallback(&self, text: &str) -> Result<Vec<f32>, DomainError> {
+        let settled = *self.settled.lock();
+        if let Some(index) = settled {
+            return self.embed_with(index, text);
+        }
+
+        let mut failures = Vec::new();
+        for (index, member) in self.members.iter().enumerate() {
+            match member.engine.embed(&member.model, text) {
+                Ok(vector) => {
+                    let winner = *self.settled.lock().get_or_insert(index);
+                    // A concurrent first call may have settled on another member.
+                    return if winner == index {
+                        Ok(vector)
+                    } else {
+                        self.embed_with(winner, text)
+                    };
+                }
+                Err(err) => failures.push(format!("{}: {err}", member.backend_id)),
+            }
+        }
+
+        Err(DomainError::embedding(format!(
+            "every backend in the embedding chain failed ({})",
+            failures.join("; ")
+        )))
+    }
+
+    fn embed_with(&self, index: usize, text: &str) -> Result<Vec<f32>, DomainError> {
+        let member = &self.members[index];
+        member
+            .engine
+            .embed(&member.model, text)
+            .map_err(|err| DomainError::embedding(format!("[{}] {err}", member.backend_id)))
+    }
+
+    fn member_for(&self, model: &str) -> Option<&ChainMember> {
+        self.served_by().or_else(|| {
+            self.members
+                .iter()
+                .find(|member| member.model.eq_ignore_ascii_case(model))
+        })
+    }
+}
+
+impl EmbeddingEngine for ChainedEmbeddingEngine {
+    fn embed(&self, model: &str, text: &str) -> Result<Vec<f32>, DomainError> {
+        if let Some(member) = self.served_by() {
+            if !model.eq_ignore_ascii_case(&member.model) {
+                return Err(DomainError::embedding(format!(
+                    "embedding chain settled on `{}` but `{}` requested",
+                    member.model, model
+                )));
+            }
+        }
+        self.embed_with_fallback(text)
+    }
+
+    fn dims(&self, model: &str) -> Option<usize> {
+        self.member_for(model)
+            .and_then(|member| member.engine.dims(&member.model))
+    }
+
+    fn model_version(&self, model: &str) -> Option<String> {
+        self.member_for(model)
+            .and_then(|member| member.engine.model_version(&member.model))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedEngine(Option<Vec<f32>>);
+
+    impl EmbeddingEngine for FixedEngine {
+        fn embed(&self, _model: &str, _text: &str) -> Result<Vec<f32>, DomainError> {
+            self.0
+                .clone()
+                .ok_or_else(|| DomainError::embedding("unavailable"))
+        }
+    }
+
+    fn member(id: &str, vector: Option<Vec<f32>>) -> ChainMember {
+        ChainMember {
+            backend_id: id.to_string(),
+            model: format!("{id}-model"),
+            engine: Arc::new(FixedEngine(vector)),
+        }
+    }
+
+    #[test]
+    fn test_chain_falls_back_then_sticks() {
+        let chain = ChainedEmbeddingEngine::try_new(vec![
+            member("broken", None),
+            member("simple", Some(vec![1.0, 0.0])),
+        ])
+        .unwrap();
+
+        assert_eq!(chain.settle().unwrap().backend_id, "simple");
+        assert_eq!(chain.embed("simple-model", "text").unwrap(), vec![1.0, 0.0]);
+        assert!(chain.embed("broken-model", "text").is_err());
+    }
+}
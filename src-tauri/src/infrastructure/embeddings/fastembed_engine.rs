@@ -1,21 +1,53 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use fastembed::{EmbeddingModel, TextEmbedding, TextInitOptions};
 use parking_lot::Mutex;
 
 use crate::{application::services::EmbeddingEngine, domain::DomainError};
 
+/// Major version of the fastembed runtime recorded alongside produced vectors.
+const FASTEMBED_VERSION: &str = "fastembed-5";
+
+/// Number of model instances to load for parallel embedding.
+pub const ENV_FASTEMBED_INSTANCES: &str = "INGAT_FASTEMBED_INSTANCES";
+
+/// Upper bound for [`ENV_FASTEMBED_INSTANCES`]; every instance holds its own
+/// copy of the model weights.
+const MAX_FASTEMBED_INSTANCES: usize = 16;
+
+/// Pool size from `INGAT_FASTEMBED_INSTANCES`, `1` when unset or invalid.
+pub fn instances_from_env() -> usize {
+    std::env::var(ENV_FASTEMBED_INSTANCES)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .map_or(1, |instances| instances.clamp(1, MAX_FASTEMBED_INSTANCES))
+}
+
 /// Embedding engine backed by `fastembed`'s `TextEmbedding`.
 ///
-/// The engine keeps a single `TextEmbedding` instance behind a `Mutex`, which
-/// allows us to reuse the loaded model without cloning heavyweight resources.
+/// Each loaded `TextEmbedding` sits behind its own `Mutex`, so concurrent
+/// `embed` calls run in parallel up to the pool size. One instance (the
+/// default) serializes inference; each extra one costs another copy of the
+/// model in memory (roughly 100 MB for bge-small, over 1 GB for large models).
 pub struct FastEmbedEngine {
     model_label: String,
     dimensions: usize,
-    inner: Mutex<TextEmbedding>,
+    instances: Vec<Mutex<TextEmbedding>>,
+    /// Slot a caller waits on when every instance is busy.
+    next: AtomicUsize,
 }
 
 impl FastEmbedEngine {
     /// Create a new engine for the given model (for example `BAAI/bge-small-en-v1.5`).
     pub fn try_new(model_name: impl AsRef<str>) -> Result<Self, DomainError> {
+        Self::try_new_pool(model_name, 1)
+    }
+
+    /// Like [`Self::try_new`], loading `instances` copies of the model.
+    pub fn try_new_pool(
+        model_name: impl AsRef<str>,
+        instances: usize,
+    ) -> Result<Self, DomainError> {
         let label = model_name.as_ref().trim();
         if label.is_empty() {
             return Err(DomainError::validation(
@@ -31,20 +63,62 @@ impl FastEmbedEngine {
             ))
         })?;
 
-        let init_options = TextInitOptions::new(embedding_model.clone());
-        let text_embedding = TextEmbedding::try_new(init_options).map_err(|err| {
-            DomainError::other(format!(
-                "failed to initialise fastembed model `{label}`: {err}"
-            ))
-        })?;
+        let instances = (0..instances.max(1))
+            .map(|_| {
+                let init_options = TextInitOptions::new(embedding_model.clone());
+                TextEmbedding::try_new(init_options)
+                    .map(Mutex::new)
+                    .map_err(|err| {
+                        DomainError::other(format!(
+                            "failed to initialise fastembed model `{label}`: {err}"
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Self {
             model_label: label.to_string(),
             dimensions: model_info.dim,
-            inner: Mutex::new(text_embedding),
+            instances,
+            next: AtomicUsize::new(0),
         })
     }
 
+    /// An idle instance if there is one, otherwise the next one in turn.
+    fn checkout(&self) -> parking_lot::MutexGuard<'_, TextEmbedding> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let len = self.instances.len();
+        (0..len)
+            .find_map(|offset| self.instances[(start + offset) % len].try_lock())
+            .unwrap_or_else(|| self.instances[start % len].lock())
+    }
+
+    /// Output dimension of a model accepted by [`Self::try_new`], known
+    /// without downloading or loading it.
+    pub fn known_dimensions(model_name: &str) -> Option<usize> {
+        let dimensions = match Self::parse_model(model_name.trim()).ok()? {
+            EmbeddingModel::BGESmallENV15
+            | EmbeddingModel::AllMiniLML6V2
+            | EmbeddingModel::AllMiniLML12V2
+            | EmbeddingModel::MultilingualE5Small => 384,
+            EmbeddingModel::ClipVitB32 | EmbeddingModel::BGESmallZHV15 => 512,
+            EmbeddingModel::BGEBaseENV15
+            | EmbeddingModel::ParaphraseMLMpnetBaseV2
+            | EmbeddingModel::NomicEmbedTextV1
+            | EmbeddingModel::NomicEmbedTextV15
+            | EmbeddingModel::MultilingualE5Base
+            | EmbeddingModel::GTEBaseENV15 => 768,
+            EmbeddingModel::MxbaiEmbedLargeV1
+            | EmbeddingModel::BGELargeENV15
+            | EmbeddingModel::BGELargeZHV15
+            | EmbeddingModel::ModernBertEmbedLarge
+            | EmbeddingModel::MultilingualE5Large
+            | EmbeddingModel::GTELargeENV15 => 1024,
+            _ => return None,
+        };
+        Some(dimensions)
+    }
+
     fn parse_model(s: &str) -> Result<EmbeddingModel, DomainError> {
         match s {
             "BAAI/bge-small-en-v1.5" | "bge-small-en-v1.5" | "BGESmallENV15" => {
@@ -122,7 +196,7 @@ impl EmbeddingEngine for FastEmbedEngine {
             return Err(DomainError::validation("text payload cannot be empty"));
         }
 
-        let mut embedder = self.inner.lock();
+        let mut embedder = self.checkout();
         let embeddings = embedder
             .embed(vec![text], None)
             .map_err(|err| DomainError::other(format!("fastembed inference failed: {err}")))?;
@@ -145,4 +219,8 @@ impl EmbeddingEngine for FastEmbedEngine {
     fn dims(&self, _model: &str) -> Option<usize> {
         Some(self.dimensions)
     }
+
+    fn model_version(&self, _model: &str) -> Option<String> {
+        Some(FASTEMBED_VERSION.to_string())
+    }
 }
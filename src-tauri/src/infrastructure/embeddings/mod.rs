@@ -1,9 +1,11 @@
+pub mod chain_engine;
 pub mod noop_engine;
 pub mod simple_engine;
 
 #[cfg(feature = "fastembed-engine")]
 pub mod fastembed_engine;
 
+pub use chain_engine::{ChainMember, ChainedEmbeddingEngine};
 #[cfg(feature = "fastembed-engine")]
 pub use fastembed_engine::FastEmbedEngine;
 pub use noop_engine::NoOpEmbeddingEngine;
@@ -6,6 +6,9 @@ use crate::{
     domain::{ContextEmbedding, DomainError},
 };
 
+/// Version of the token hashing scheme; bump whenever vectors stop being comparable.
+const SIMPLE_HASH_VERSION: &str = "ahash-v1";
+
 /// A lightweight, deterministic embedding engine that hashes tokens into a fixed-size vector.
 /// This is not meant for production-grade semantic search, but it keeps the application functional
 /// without downloading external models or shipping native dependencies.
@@ -102,4 +105,8 @@ impl EmbeddingEngine for SimpleEmbedEngine {
     fn dims(&self, _model: &str) -> Option<usize> {
         Some(self.dimensions)
     }
+
+    fn model_version(&self, _model: &str) -> Option<String> {
+        Some(SIMPLE_HASH_VERSION.to_string())
+    }
 }
@@ -0,0 +1,118 @@
+//! Detects a vanished mcp-service from consecutive transport failures.
+//!
+//! Only transport errors (connection refused, timeouts, resets) count; an HTTP
+//! error status still proves the service is alive and resets the streak. Once
+//! the threshold is reached the breaker trips exactly once and runs the
+//! registered callback, which is expected to rebuild the app in local mode.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc,
+};
+
+use parking_lot::Mutex;
+
+/// Consecutive failures before tripping when `INGAT_REMOTE_FAILOVER_AFTER` is unset.
+pub const DEFAULT_FAILOVER_THRESHOLD: u32 = 3;
+
+type TripCallback = Arc<dyn Fn() + Send + Sync>;
+
+pub struct CircuitBreaker {
+    /// Zero disables tripping.
+    threshold: u32,
+    failures: AtomicU32,
+    tripped: AtomicBool,
+    on_trip: Mutex<Option<TripCallback>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            failures: AtomicU32::new(0),
+            tripped: AtomicBool::new(false),
+            on_trip: Mutex::new(None),
+        }
+    }
+
+    /// Threshold from `INGAT_REMOTE_FAILOVER_AFTER`; `0` disables failover.
+    pub fn from_env() -> Self {
+        let threshold = std::env::var("INGAT_REMOTE_FAILOVER_AFTER")
+            .ok()
+            .and_then(|raw| raw.trim().parse().ok())
+            .unwrap_or(DEFAULT_FAILOVER_THRESHOLD);
+        Self::new(threshold)
+    }
+
+    /// Register the action to run when the breaker trips.
+    pub fn on_trip(&self, callback: impl Fn() + Send + Sync + 'static) {
+        *self.on_trip.lock() = Some(Arc::new(callback));
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::SeqCst)
+    }
+
+    /// Re-arm after a failed failover so a later streak can try again.
+    pub fn reset(&self) {
+        self.failures.store(0, Ordering::SeqCst);
+        self.tripped.store(false, Ordering::SeqCst);
+    }
+
+    /// Pass `result` through, updating the failure streak.
+    // The error type is ureq's own; callers map it immediately.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn record<T>(&self, result: Result<T, ureq::Error>) -> Result<T, ureq::Error> {
+        match &result {
+            Err(ureq::Error::Transport(_)) => {
+                let failures = self.failures.fetch_add(1, Ordering::SeqCst) + 1;
+                if self.threshold > 0
+                    && failures >= self.threshold
+                    && !self.tripped.swap(true, Ordering::SeqCst)
+                {
+                    // Clone out so the callback may re-register or reset freely.
+                    let callback = self.on_trip.lock().clone();
+                    if let Some(callback) = callback {
+                        callback();
+                    }
+                }
+            }
+            _ => self.failures.store(0, Ordering::SeqCst),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transport_error() -> ureq::Error {
+        // Nothing listens on port 9 (discard) on a test machine.
+        ureq::get("http://127.0.0.1:9/")
+            .timeout(std::time::Duration::from_millis(200))
+            .call()
+            .unwrap_err()
+    }
+
+    #[test]
+    fn test_breaker_trips_once_after_consecutive_transport_failures() {
+        let breaker = CircuitBreaker::new(2);
+        let trips = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&trips);
+        breaker.on_trip(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let _ = breaker.record::<()>(Err(transport_error()));
+        let _ = breaker.record(Ok(()));
+        let _ = breaker.record::<()>(Err(transport_error()));
+        assert!(!breaker.is_tripped(), "success must reset the streak");
+
+        for _ in 0..3 {
+            let _ = breaker.record::<()>(Err(transport_error()));
+        }
+        assert!(breaker.is_tripped());
+        assert_eq!(trips.load(Ordering::SeqCst), 1);
+    }
+}
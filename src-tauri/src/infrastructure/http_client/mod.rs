@@ -4,12 +4,18 @@
 //! operations to a running mcp-service instance via HTTP, eliminating the need
 //! for direct database access and avoiding lock conflicts.
 
+mod circuit_breaker;
 mod remote_store;
+mod store_compare;
 
+pub use circuit_breaker::{CircuitBreaker, DEFAULT_FAILOVER_THRESHOLD};
 pub use remote_store::RemoteVectorStore;
+pub use store_compare::{fetch_remote_summaries, StoreComparison};
+
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Default service host
 pub const DEFAULT_HOST: &str = "127.0.0.1";
@@ -17,6 +23,10 @@ pub const DEFAULT_HOST: &str = "127.0.0.1";
 /// Default service port
 pub const DEFAULT_PORT: u16 = 3200;
 
+/// Header naming the calling client, so mcp-service budgets each one
+/// separately even when they share an address.
+pub const CLIENT_ID_HEADER: &str = "x-ingat-client";
+
 /// Health check response from the service
 #[derive(Debug, Deserialize)]
 struct HealthResponse {
@@ -60,6 +70,130 @@ pub fn check_service_availability(host: &str, port: u16) -> bool {
     }
 }
 
+/// Layer at which a connection test failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionFailure {
+    /// Nothing answered: refused, timed out, DNS, TLS.
+    Transport,
+    /// Something answered, but not like an ingat service.
+    Protocol,
+}
+
+/// Outcome of [`test_service_connection`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionReport {
+    pub ok: bool,
+    pub url: String,
+    /// Round trip of the `/health` probe.
+    pub latency_ms: Option<u64>,
+    pub version: Option<String>,
+    pub total_contexts: Option<usize>,
+    pub failure: Option<ConnectionFailure>,
+    pub error: Option<String>,
+}
+
+/// Fields of `/api/stats` the connection test reports.
+#[derive(Debug, Deserialize)]
+struct StatsSnapshot {
+    /// Absent from services that predate the storage block.
+    #[serde(default)]
+    storage: Option<StorageSnapshot>,
+    #[serde(default)]
+    total_contexts: Option<usize>,
+    version: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageSnapshot {
+    records: usize,
+}
+
+/// Probe `/health`, then fetch `/api/stats`, without touching the active store.
+pub fn test_service_connection(host: &str, port: u16) -> ConnectionReport {
+    let url = get_service_url(host, port);
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(2))
+        .build();
+    let mut report = ConnectionReport {
+        ok: false,
+        url: url.clone(),
+        latency_ms: None,
+        version: None,
+        total_contexts: None,
+        failure: None,
+        error: None,
+    };
+
+    let started = Instant::now();
+    let health = agent
+        .get(&format!("{url}/health"))
+        .call()
+        .map_err(classify_error)
+        .and_then(|response| {
+            response.into_json::<HealthResponse>().map_err(|err| {
+                (
+                    ConnectionFailure::Protocol,
+                    format!("bad /health body: {err}"),
+                )
+            })
+        });
+    let health = match health {
+        Ok(health) => health,
+        Err((failure, error)) => return report.failed(failure, error),
+    };
+    report.latency_ms = Some(started.elapsed().as_millis() as u64);
+    if health.status != "healthy" {
+        return report.failed(
+            ConnectionFailure::Protocol,
+            format!("{} reported status '{}'", health.service, health.status),
+        );
+    }
+
+    let stats = agent
+        .get(&format!("{url}/api/stats"))
+        .call()
+        .map_err(classify_error)
+        .and_then(|response| {
+            response.into_json::<StatsSnapshot>().map_err(|err| {
+                (
+                    ConnectionFailure::Protocol,
+                    format!("bad /api/stats body: {err}"),
+                )
+            })
+        });
+    match stats {
+        Ok(stats) => {
+            report.ok = true;
+            report.version = Some(stats.version);
+            report.total_contexts = stats
+                .storage
+                .map(|storage| storage.records)
+                .or(stats.total_contexts);
+            report
+        }
+        Err((failure, error)) => report.failed(failure, error),
+    }
+}
+
+impl ConnectionReport {
+    fn failed(mut self, failure: ConnectionFailure, error: String) -> Self {
+        self.failure = Some(failure);
+        self.error = Some(error);
+        self
+    }
+}
+
+fn classify_error(error: ureq::Error) -> (ConnectionFailure, String) {
+    match error {
+        ureq::Error::Transport(transport) => (ConnectionFailure::Transport, transport.to_string()),
+        status @ ureq::Error::Status(..) => (
+            ConnectionFailure::Protocol,
+            handle_http_error(status).to_string(),
+        ),
+    }
+}
+
 /// Get the service base URL
 pub fn get_service_url(host: &str, port: u16) -> String {
     format!("http://{}:{}", host, port)
@@ -92,3 +226,21 @@ pub fn handle_http_error(error: ureq::Error) -> anyhow::Error {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_to_closed_port_is_a_transport_failure() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("reserve port")
+            .port();
+
+        let report = test_service_connection("127.0.0.1", port);
+        assert!(!report.ok);
+        assert_eq!(report.failure, Some(ConnectionFailure::Transport));
+        assert!(report.latency_ms.is_none());
+    }
+}
@@ -1,37 +1,66 @@
 //! Remote vector store implementation that proxies operations to mcp-service via HTTP.
 
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::application::services::VectorStore;
-use crate::domain::{ContextEmbedding, ContextRecord, ContextSummary, DomainError, QueryFilters};
+use crate::application::{
+    dtos::{MAX_FETCH_BATCH, MAX_SEARCH_LIMIT},
+    services::{SearchHits, VectorStore},
+    LabelCount, ProjectOverview, StoreCompositionResponse, VerifyReport,
+};
+use crate::domain::{
+    ChangeEvent, ContextEmbedding, ContextRecord, ContextSummary, DomainError,
+    EmbeddingComposition, HistoryOrder, QueryFilters, QueryHistoryEntry, SearchEvent, SearchScope,
+};
+
+use super::{get_service_url, CircuitBreaker, CLIENT_ID_HEADER};
 
-use super::get_service_url;
+/// Full exports can take far longer than a regular request.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(600);
 
 /// Vector store implementation that proxies all operations to a remote mcp-service
 pub struct RemoteVectorStore {
     base_url: String,
     agent: ureq::Agent,
+    breaker: Arc<CircuitBreaker>,
 }
 
 impl RemoteVectorStore {
     /// Create a new remote vector store client
     pub fn new(host: &str, port: u16) -> Self {
         let base_url = get_service_url(host, port);
+        // Gives this store its own rate-limit bucket on the service.
+        let client_id = format!("ingat-app-{}", Uuid::new_v4());
         let agent = ureq::AgentBuilder::new()
             .timeout(std::time::Duration::from_secs(30))
+            .middleware(move |request: ureq::Request, next: ureq::MiddlewareNext| {
+                next.handle(request.set(CLIENT_ID_HEADER, &client_id))
+            })
             .build();
 
-        Self { base_url, agent }
+        Self {
+            base_url,
+            agent,
+            breaker: Arc::new(CircuitBreaker::from_env()),
+        }
+    }
+
+    /// Failure tracker shared with whoever should react to the service going away.
+    pub fn breaker(&self) -> Arc<CircuitBreaker> {
+        Arc::clone(&self.breaker)
     }
 
     /// Get the API endpoint URL
     fn api_url(&self, path: &str) -> String {
         format!("{}/api/{}", self.base_url, path)
     }
-}
 
-impl VectorStore for RemoteVectorStore {
-    fn persist(&self, record: &ContextRecord) -> Result<(), DomainError> {
+    fn send_record(&self, record: &ContextRecord, flush: bool) -> Result<(), DomainError> {
         let url = self.api_url("contexts");
 
         // Convert ContextRecord to IngestContextRequest format
@@ -44,43 +73,22 @@ impl VectorStore for RemoteVectorStore {
             "body": record.body,
             "tags": record.tags,
             "kind": record.kind,
+            "pinned": record.pinned,
+            "flush": flush,
         });
 
-        self.agent
-            .post(&url)
-            .send_json(request_body)
+        self.breaker
+            .record(self.agent.post(&url).send_json(request_body))
             .map_err(|e| DomainError::storage(format!("Failed to save context: {}", e)))?;
 
         Ok(())
     }
 
-    fn search(
-        &self,
-        embedding: &ContextEmbedding,
-        limit: usize,
-        filters: &QueryFilters,
+    /// Records and scores from a `SearchResponse` body. Results carry no
+    /// embeddings, so the records are only good for display and ranking.
+    fn parse_matches(
+        search_response: &serde_json::Value,
     ) -> Result<Vec<(ContextRecord, f32)>, DomainError> {
-        let url = self.api_url("search");
-
-        let request_body = serde_json::json!({
-            "prompt": "",
-            "embedding": &embedding.vector,
-            "limit": limit,
-            "project": filters.project,
-            "kind": filters.kind,
-        });
-
-        let response = self
-            .agent
-            .post(&url)
-            .send_json(request_body)
-            .map_err(|e| DomainError::storage(format!("Search failed: {}", e)))?;
-
-        // Parse SearchResponse
-        let search_response: serde_json::Value = response
-            .into_json()
-            .map_err(|e| DomainError::storage(format!("Failed to parse search response: {}", e)))?;
-
         let results = search_response["results"]
             .as_array()
             .ok_or_else(|| DomainError::storage("Invalid search response format"))?;
@@ -112,6 +120,13 @@ impl VectorStore for RemoteVectorStore {
                     kind: serde_json::from_value(item["kind"].clone()).ok()?,
                     embedding: ContextEmbedding::new("remote", Vec::new()),
                     created_at: serde_json::from_value(item["created_at"].clone()).ok()?,
+                    pinned: item["pinned"].as_bool().unwrap_or(false),
+                    updated_at: None,
+                    summary_embedding: None,
+                    body_embedding: None,
+                    source: serde_json::from_value(item["source"].clone()).ok(),
+                    searchable: true,
+                    parent_id: None,
                 };
                 Some((record, score))
             })
@@ -119,11 +134,61 @@ impl VectorStore for RemoteVectorStore {
 
         Ok(records)
     }
+}
+
+impl VectorStore for RemoteVectorStore {
+    fn persist(&self, record: &ContextRecord) -> Result<(), DomainError> {
+        self.send_record(record, true)
+    }
+
+    fn persist_deferred(&self, record: &ContextRecord) -> Result<(), DomainError> {
+        self.send_record(record, false)
+    }
+
+    fn search(
+        &self,
+        embedding: &ContextEmbedding,
+        limit: usize,
+        filters: &QueryFilters,
+        scope: SearchScope,
+    ) -> Result<SearchHits, DomainError> {
+        let url = self.api_url("search");
+
+        let request_body = serde_json::json!({
+            "prompt": "",
+            "embedding": &embedding.vector,
+            "limit": limit,
+            "export_mode": limit > MAX_SEARCH_LIMIT,
+            "filters": filters,
+            "scope": scope,
+        });
+
+        let response = self
+            .breaker
+            .record(self.agent.post(&url).send_json(request_body))
+            .map_err(|e| DomainError::storage(format!("Search failed: {}", e)))?;
+
+        // Parse SearchResponse
+        let search_response: serde_json::Value = response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse search response: {}", e)))?;
+        let records = Self::parse_matches(&search_response)?;
+
+        Ok(SearchHits {
+            matches: records,
+            scanned: search_response["scanned"].as_u64().unwrap_or(0) as usize,
+            matched_filters: search_response["matched_filters"].as_u64().unwrap_or(0) as usize,
+            mismatched: Vec::new(),
+        })
+    }
 
     fn recent(
         &self,
         project: Option<&str>,
+        tag: Option<&str>,
+        since: Option<DateTime<Utc>>,
         limit: usize,
+        order: HistoryOrder,
     ) -> Result<Vec<ContextSummary>, DomainError> {
         let mut url = self.api_url("contexts");
 
@@ -132,16 +197,33 @@ impl VectorStore for RemoteVectorStore {
         if let Some(proj) = project {
             params.push(format!("project={}", urlencoding::encode(proj)));
         }
+        if let Some(tag) = tag {
+            params.push(format!("tag={}", urlencoding::encode(tag)));
+        }
+        if let Some(since) = since {
+            params.push(format!(
+                "since={}",
+                urlencoding::encode(&since.to_rfc3339())
+            ));
+        }
         params.push(format!("limit={}", limit));
+        if order != HistoryOrder::default() {
+            // Variant names as serde writes them, e.g. `order_by=Project&direction=Asc`.
+            for (key, value) in [
+                ("order_by", serde_json::json!(order.order_by)),
+                ("direction", serde_json::json!(order.direction)),
+            ] {
+                params.push(format!("{key}={}", value.as_str().unwrap_or_default()));
+            }
+        }
 
         if !params.is_empty() {
             url = format!("{}?{}", url, params.join("&"));
         }
 
         let response = self
-            .agent
-            .get(&url)
-            .call()
+            .breaker
+            .record(self.agent.get(&url).call())
             .map_err(|e| DomainError::storage(format!("Failed to list contexts: {}", e)))?;
 
         let summaries: Vec<ContextSummary> = response
@@ -157,13 +239,430 @@ impl VectorStore for RemoteVectorStore {
         Ok(Vec::new())
     }
 
+    fn iter_summaries(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<ContextSummary, DomainError>> + Send + '_> {
+        let url = self.api_url("contexts/stream");
+
+        let response = match self
+            .breaker
+            .record(self.agent.get(&url).timeout(STREAM_TIMEOUT).call())
+        {
+            Ok(response) => response,
+            Err(e) => {
+                return Box::new(std::iter::once(Err(DomainError::storage(format!(
+                    "Failed to stream contexts: {}",
+                    e
+                )))))
+            }
+        };
+
+        Box::new(
+            BufReader::new(response.into_reader())
+                .lines()
+                .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .map(|line| {
+                    let line = line.map_err(|e| {
+                        DomainError::storage(format!("Failed to read context stream: {}", e))
+                    })?;
+                    serde_json::from_str(&line).map_err(|e| {
+                        DomainError::storage(format!("Failed to parse streamed context: {}", e))
+                    })
+                }),
+        )
+    }
+
+    fn set_pinned(&self, id: Uuid, pinned: bool) -> Result<ContextSummary, DomainError> {
+        let url = self.api_url(&format!("contexts/{}/pin", id));
+
+        let request = if pinned {
+            self.agent.post(&url)
+        } else {
+            self.agent.delete(&url)
+        };
+
+        let response = self
+            .breaker
+            .record(request.call())
+            .map_err(|e| DomainError::storage(format!("Failed to update pin: {}", e)))?;
+
+        response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse pin response: {}", e)))
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    fn chunk_ids(&self, _parents: &[Uuid]) -> Result<Vec<Uuid>, DomainError> {
+        // The service expands chunks itself when deleting or pinning a parent.
+        Ok(Vec::new())
+    }
+
+    fn set_archived_remote(&self, id: Uuid, archived: bool) -> Result<ContextSummary, DomainError> {
+        let url = self.api_url(&format!("contexts/{}/archive", id));
+
+        let request = if archived {
+            self.agent.post(&url)
+        } else {
+            self.agent.delete(&url)
+        };
+
+        let response = self
+            .breaker
+            .record(request.call())
+            .map_err(|e| DomainError::storage(format!("Failed to update archive state: {}", e)))?;
+
+        response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse archive response: {}", e)))
+    }
+
+    fn verify_remote(&self) -> Result<VerifyReport, DomainError> {
+        let url = self.api_url("store/verify");
+
+        let response = self
+            .breaker
+            .record(self.agent.get(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to verify store: {}", e)))?;
+
+        response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse verify response: {}", e)))
+    }
+
+    fn reembed_project_remote(&self, project: &str) -> Result<usize, DomainError> {
+        let url = self.api_url(&format!(
+            "projects/{}/reembed",
+            urlencoding::encode(project)
+        ));
+
+        let response = self
+            .breaker
+            .record(self.agent.post(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to re-embed project: {}", e)))?;
+
+        let body: serde_json::Value = response.into_json().map_err(|e| {
+            DomainError::storage(format!("Failed to parse re-embed response: {}", e))
+        })?;
+
+        body["reembedded"]
+            .as_u64()
+            .map(|count| count as usize)
+            .ok_or_else(|| DomainError::storage("Invalid re-embed response format"))
+    }
+
+    fn project_overview_remote(
+        &self,
+        project: &str,
+        recent_limit: Option<usize>,
+    ) -> Result<ProjectOverview, DomainError> {
+        let mut url = self.api_url(&format!(
+            "projects/{}/overview",
+            urlencoding::encode(project)
+        ));
+        if let Some(recent) = recent_limit {
+            url = format!("{}?recent={}", url, recent);
+        }
+
+        let response = self
+            .breaker
+            .record(self.agent.get(&url).call())
+            .map_err(|e| {
+                DomainError::storage(format!("Failed to fetch project overview: {}", e))
+            })?;
+
+        response.into_json().map_err(|e| {
+            DomainError::storage(format!("Failed to parse project overview response: {}", e))
+        })
+    }
+
+    fn changes_since(
+        &self,
+        since: DateTime<Utc>,
+        include_deleted: bool,
+    ) -> Result<Vec<ChangeEvent>, DomainError> {
+        let url = format!(
+            "{}?since={}&include_deleted={}",
+            self.api_url("changes"),
+            urlencoding::encode(&since.to_rfc3339()),
+            include_deleted
+        );
+
+        let response = self
+            .breaker
+            .record(self.agent.get(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to fetch changes: {}", e)))?;
+
+        response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse changes response: {}", e)))
+    }
+
+    fn scan_records(
+        &self,
+        _visit: &mut dyn FnMut(Result<ContextRecord, DomainError>),
+    ) -> Result<(), DomainError> {
+        Err(DomainError::other(
+            "full record access is not available in remote mode; run this operation on mcp-service",
+        ))
+    }
+
+    fn fetch_records(&self, ids: &[Uuid]) -> Result<Vec<ContextRecord>, DomainError> {
+        let url = self.api_url("contexts/batch-get");
+        let mut records = Vec::with_capacity(ids.len());
+
+        for batch in ids.chunks(MAX_FETCH_BATCH) {
+            let response = self
+                .breaker
+                .record(
+                    self.agent
+                        .post(&url)
+                        .send_json(serde_json::json!({ "ids": batch })),
+                )
+                .map_err(|e| DomainError::storage(format!("Failed to fetch contexts: {}", e)))?;
+
+            let mut body: serde_json::Value = response.into_json().map_err(|e| {
+                DomainError::storage(format!("Failed to parse batch get response: {}", e))
+            })?;
+            let batch: Vec<ContextRecord> = serde_json::from_value(body["records"].take())
+                .map_err(|e| {
+                    DomainError::storage(format!("Invalid batch get response format: {}", e))
+                })?;
+            records.extend(batch);
+        }
+
+        Ok(records)
+    }
+
+    fn project_records(&self, _project: &str) -> Result<Vec<ContextRecord>, DomainError> {
+        Err(DomainError::other(
+            "full record access is not available in remote mode; run this operation on mcp-service",
+        ))
+    }
+
+    fn rebuild_term_stats(&self) -> Result<usize, DomainError> {
+        let url = self.api_url("term-stats/rebuild");
+
+        let response = self
+            .breaker
+            .record(self.agent.post(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to rebuild term stats: {}", e)))?;
+
+        let body: serde_json::Value = response.into_json().map_err(|e| {
+            DomainError::storage(format!("Failed to parse rebuild response: {}", e))
+        })?;
+
+        body["documents"]
+            .as_u64()
+            .map(|documents| documents as usize)
+            .ok_or_else(|| DomainError::storage("Invalid rebuild response format"))
+    }
+
+    fn record_term_stats(&self, _terms: &BTreeSet<String>) -> Result<(), DomainError> {
+        // The service keeps its own statistics up to date on ingest.
+        Ok(())
+    }
+
+    fn keyword_search(
+        &self,
+        terms: &[String],
+        limit: usize,
+        filters: &QueryFilters,
+    ) -> Result<Vec<(ContextRecord, f32)>, DomainError> {
+        let url = self.api_url("search/keyword");
+
+        let request_body = serde_json::json!({
+            "prompt": terms.join(" "),
+            "limit": limit.min(MAX_SEARCH_LIMIT),
+            "filters": filters,
+        });
+
+        let response = self
+            .breaker
+            .record(self.agent.post(&url).send_json(request_body))
+            .map_err(|e| DomainError::storage(format!("Keyword search failed: {}", e)))?;
+
+        let search_response: serde_json::Value = response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse search response: {}", e)))?;
+        Self::parse_matches(&search_response)
+    }
+
+    fn delete_batch(&self, ids: &[Uuid]) -> Result<usize, DomainError> {
+        let url = self.api_url("contexts/delete");
+
+        let response = self
+            .breaker
+            .record(
+                self.agent
+                    .post(&url)
+                    .send_json(serde_json::json!({ "ids": ids })),
+            )
+            .map_err(|e| DomainError::storage(format!("Failed to delete contexts: {}", e)))?;
+
+        let body: serde_json::Value = response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse delete response: {}", e)))?;
+
+        body["deleted"]
+            .as_u64()
+            .map(|deleted| deleted as usize)
+            .ok_or_else(|| DomainError::storage("Invalid delete response format"))
+    }
+
+    fn rebuild_index(&self) -> Result<usize, DomainError> {
+        let url = self.api_url("index/rebuild");
+
+        let response = self
+            .breaker
+            .record(self.agent.post(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to rebuild index: {}", e)))?;
+
+        let body: serde_json::Value = response.into_json().map_err(|e| {
+            DomainError::storage(format!("Failed to parse rebuild response: {}", e))
+        })?;
+
+        body["indexed"]
+            .as_u64()
+            .map(|indexed| indexed as usize)
+            .ok_or_else(|| DomainError::storage("Invalid rebuild response format"))
+    }
+
+    fn kind_counts(&self) -> Result<Vec<(String, usize)>, DomainError> {
+        let url = self.api_url("kinds");
+
+        let response = self
+            .breaker
+            .record(self.agent.get(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to fetch kinds: {}", e)))?;
+
+        let kinds: Vec<LabelCount> = response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse kinds response: {}", e)))?;
+
+        Ok(kinds
+            .into_iter()
+            .map(|entry| (entry.label, entry.count))
+            .collect())
+    }
+
+    fn store_composition(&self) -> Result<Vec<EmbeddingComposition>, DomainError> {
+        let url = self.api_url("store/composition");
+
+        let response = self
+            .breaker
+            .record(self.agent.get(&url).call())
+            .map_err(|e| {
+                DomainError::storage(format!("Failed to fetch store composition: {}", e))
+            })?;
+
+        let composition: StoreCompositionResponse = response.into_json().map_err(|e| {
+            DomainError::storage(format!("Failed to parse composition response: {}", e))
+        })?;
+
+        Ok(composition.entries)
+    }
+
+    fn record_query(&self, _entry: &QueryHistoryEntry) -> Result<(), DomainError> {
+        // The service records the searches it runs on our behalf.
+        Ok(())
+    }
+
+    fn recent_queries(&self, limit: usize) -> Result<Vec<QueryHistoryEntry>, DomainError> {
+        let url = self.api_url(&format!("queries?limit={}", limit));
+
+        let response = self
+            .breaker
+            .record(self.agent.get(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to fetch query history: {}", e)))?;
+
+        response.into_json().map_err(|e| {
+            DomainError::storage(format!("Failed to parse query history response: {}", e))
+        })
+    }
+
+    fn clear_query_history(&self) -> Result<usize, DomainError> {
+        let url = self.api_url("queries");
+
+        let response = self
+            .breaker
+            .record(self.agent.delete(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to clear query history: {}", e)))?;
+
+        let body: serde_json::Value = response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse clear response: {}", e)))?;
+
+        body["cleared"]
+            .as_u64()
+            .map(|cleared| cleared as usize)
+            .ok_or_else(|| DomainError::storage("Invalid clear response format"))
+    }
+
+    fn record_search_event(&self, event: &SearchEvent) -> Result<(), DomainError> {
+        let url = self.api_url("feedback/events");
+
+        self.breaker
+            .record(self.agent.post(&url).send_json(event))
+            .map_err(|e| DomainError::storage(format!("Failed to record search event: {}", e)))?;
+        Ok(())
+    }
+
+    fn record_feedback(&self, query_id: Uuid, chosen_id: Uuid) -> Result<SearchEvent, DomainError> {
+        let url = self.api_url("feedback");
+
+        let response = self
+            .breaker
+            .record(self.agent.post(&url).send_json(serde_json::json!({
+                "query_id": query_id,
+                "chosen_id": chosen_id,
+            })))
+            .map_err(|e| DomainError::storage(format!("Failed to record feedback: {}", e)))?;
+
+        response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse feedback response: {}", e)))
+    }
+
+    fn search_events(&self) -> Result<Vec<SearchEvent>, DomainError> {
+        let url = self.api_url("feedback/events");
+
+        let response = self
+            .breaker
+            .record(self.agent.get(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to fetch search events: {}", e)))?;
+
+        response.into_json().map_err(|e| {
+            DomainError::storage(format!("Failed to parse search events response: {}", e))
+        })
+    }
+
+    fn clear_search_events(&self) -> Result<usize, DomainError> {
+        let url = self.api_url("feedback");
+
+        let response = self
+            .breaker
+            .record(self.agent.delete(&url).call())
+            .map_err(|e| DomainError::storage(format!("Failed to clear search events: {}", e)))?;
+
+        let body: serde_json::Value = response
+            .into_json()
+            .map_err(|e| DomainError::storage(format!("Failed to parse clear response: {}", e)))?;
+
+        body["cleared"]
+            .as_u64()
+            .map(|cleared| cleared as usize)
+            .ok_or_else(|| DomainError::storage("Invalid clear response format"))
+    }
+
     fn ping(&self) -> Result<(), DomainError> {
         let url = format!("{}/health", self.base_url);
 
         let response = self
-            .agent
-            .get(&url)
-            .call()
+            .breaker
+            .record(self.agent.get(&url).call())
             .map_err(|e| DomainError::storage(format!("Health check failed: {}", e)))?;
 
         if response.status() == 200 {
@@ -0,0 +1,188 @@
+//! Consistency check between the embedded store and a running mcp-service.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{get_service_url, handle_http_error};
+use crate::domain::ContextSummary;
+
+/// Ids listed per mismatch category; the counts are always exact.
+pub const MAX_SAMPLE_IDS: usize = 20;
+
+/// Outcome of comparing the local store against a remote service.
+///
+/// A side that could not be read has no count and carries its error
+/// instead; the remaining fields are then left empty.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StoreComparison {
+    pub in_sync: bool,
+    pub local_count: Option<usize>,
+    pub remote_count: Option<usize>,
+    /// Ids present on both sides with identical summaries.
+    pub matching: usize,
+    pub differing_count: usize,
+    pub only_local_count: usize,
+    pub only_remote_count: usize,
+    /// Up to [`MAX_SAMPLE_IDS`] ids of each mismatch category.
+    pub differing: Vec<Uuid>,
+    pub only_local: Vec<Uuid>,
+    pub only_remote: Vec<Uuid>,
+    /// Why the local store was not read (e.g. held by another process).
+    pub local_error: Option<String>,
+    pub remote_error: Option<String>,
+}
+
+impl StoreComparison {
+    /// Compare top-level records by id and a fingerprint of their summary.
+    /// Chunk records are skipped; they follow their parent.
+    pub fn between(local: &[ContextSummary], remote: &[ContextSummary]) -> Self {
+        let local = fingerprints(local);
+        let remote = fingerprints(remote);
+        let mut report = Self {
+            local_count: Some(local.len()),
+            remote_count: Some(remote.len()),
+            ..Self::default()
+        };
+
+        for (id, hash) in &local {
+            match remote.get(id) {
+                Some(other) if other == hash => report.matching += 1,
+                Some(_) => {
+                    report.differing_count += 1;
+                    report.differing.push(*id);
+                }
+                None => {
+                    report.only_local_count += 1;
+                    report.only_local.push(*id);
+                }
+            }
+        }
+        for id in remote.keys().filter(|id| !local.contains_key(id)) {
+            report.only_remote_count += 1;
+            report.only_remote.push(*id);
+        }
+
+        for ids in [
+            &mut report.differing,
+            &mut report.only_local,
+            &mut report.only_remote,
+        ] {
+            ids.sort();
+            ids.truncate(MAX_SAMPLE_IDS);
+        }
+        report.in_sync =
+            report.differing_count + report.only_local_count + report.only_remote_count == 0;
+        report
+    }
+
+    /// Report for a comparison where at least one side could not be read.
+    pub fn unavailable(local: Result<usize, String>, remote: Result<usize, String>) -> Self {
+        Self {
+            local_count: local.as_ref().ok().copied(),
+            remote_count: remote.as_ref().ok().copied(),
+            local_error: local.err(),
+            remote_error: remote.err(),
+            ..Self::default()
+        }
+    }
+}
+
+fn fingerprints(summaries: &[ContextSummary]) -> HashMap<Uuid, u64> {
+    summaries
+        .iter()
+        .filter(|summary| summary.parent_id.is_none())
+        .map(|summary| {
+            let mut hasher = DefaultHasher::new();
+            summary.project.hash(&mut hasher);
+            summary.summary.hash(&mut hasher);
+            summary.kind.key().hash(&mut hasher);
+            summary.tags.hash(&mut hasher);
+            summary.created_at.hash(&mut hasher);
+            summary.updated_at.hash(&mut hasher);
+            summary.pinned.hash(&mut hasher);
+            summary.searchable.hash(&mut hasher);
+            (summary.id, hasher.finish())
+        })
+        .collect()
+}
+
+/// Read every summary from `/api/contexts/stream` of the service at `host:port`.
+pub fn fetch_remote_summaries(host: &str, port: u16) -> Result<Vec<ContextSummary>> {
+    let url = format!("{}/api/contexts/stream", get_service_url(host, port));
+    let response = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(2))
+        .timeout_read(Duration::from_secs(30))
+        .build()
+        .get(&url)
+        .call()
+        .map_err(handle_http_error)?;
+
+    let mut summaries = Vec::new();
+    for line in BufReader::new(response.into_reader()).lines() {
+        let line = line.context("failed to read summary stream")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        summaries.push(
+            serde_json::from_str(&line).with_context(|| format!("bad summary line from {url}"))?,
+        );
+    }
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ContextKind;
+    use chrono::Utc;
+
+    fn summary(id: Uuid, text: &str) -> ContextSummary {
+        ContextSummary {
+            id,
+            project: "ingat".into(),
+            summary: text.into(),
+            kind: ContextKind::CodeSnippet,
+            tags: vec!["sync".into()],
+            created_at: Utc::now(),
+            updated_at: None,
+            pinned: false,
+            searchable: true,
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn test_store_comparison_classifies_ids() {
+        let same = summary(Uuid::new_v4(), "same");
+        let changed = summary(Uuid::new_v4(), "before");
+        let mut changed_remote = changed.clone();
+        changed_remote.pinned = true;
+        let local_only = summary(Uuid::new_v4(), "local");
+        let remote_only = summary(Uuid::new_v4(), "remote");
+        let mut chunk = summary(Uuid::new_v4(), "chunk");
+        chunk.parent_id = Some(same.id);
+
+        let report = StoreComparison::between(
+            &[same.clone(), changed, local_only.clone(), chunk],
+            &[same, changed_remote.clone(), remote_only.clone()],
+        );
+        assert!(!report.in_sync);
+        assert_eq!(report.local_count, Some(3));
+        assert_eq!(report.remote_count, Some(3));
+        assert_eq!(report.matching, 1);
+        assert_eq!(report.differing, vec![changed_remote.id]);
+        assert_eq!(report.only_local, vec![local_only.id]);
+        assert_eq!(report.only_remote, vec![remote_only.id]);
+
+        let identical = StoreComparison::between(&[remote_only.clone()], &[remote_only]);
+        assert!(identical.in_sync);
+        assert_eq!(identical.matching, 1);
+    }
+}
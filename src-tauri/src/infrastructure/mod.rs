@@ -6,7 +6,12 @@ pub mod storage;
 
 #[cfg(feature = "fastembed-engine")]
 pub use embeddings::FastEmbedEngine;
-pub use embeddings::NoOpEmbeddingEngine;
 pub use embeddings::SimpleEmbedEngine;
-pub use http_client::{check_service_availability, RemoteVectorStore};
-pub use storage::SledVectorStore;
+pub use embeddings::{ChainMember, ChainedEmbeddingEngine, NoOpEmbeddingEngine};
+pub use http_client::{
+    check_service_availability, fetch_remote_summaries, test_service_connection, CircuitBreaker,
+    ConnectionReport, RemoteVectorStore, StoreComparison,
+};
+pub use storage::{
+    open_store, MemoryVectorStore, OpenedStore, SledVectorStore, StoreBackend, StoreOptions,
+};
@@ -0,0 +1,114 @@
+//! Coalescing flusher for synced sled writes.
+//!
+//! Instead of an fsync per `persist`, writes mark the database dirty and a
+//! background thread flushes once the coalescing window elapses or enough
+//! writes have piled up, so a burst of ingests costs a single fsync.
+
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use parking_lot::{Condvar, Mutex};
+use sled::Db;
+
+#[derive(Default)]
+struct FlushState {
+    /// Writes acknowledged since the last flush.
+    pending: usize,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<FlushState>,
+    wake: Condvar,
+    max_pending: usize,
+}
+
+pub(crate) struct CoalescingFlusher {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl CoalescingFlusher {
+    /// Flush `db` at most every `window` while writes are pending, or as soon
+    /// as `max_pending` writes have accumulated.
+    pub(crate) fn spawn(db: Db, window: Duration, max_pending: usize) -> std::io::Result<Self> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(FlushState::default()),
+            wake: Condvar::new(),
+            max_pending: max_pending.max(1),
+        });
+        let worker_shared = Arc::clone(&shared);
+        let worker = std::thread::Builder::new()
+            .name("ingat-flusher".into())
+            .spawn(move || run(&worker_shared, &db, window))?;
+        Ok(Self {
+            shared,
+            worker: Some(worker),
+        })
+    }
+
+    /// Note one write that still needs to reach disk.
+    pub(crate) fn mark_dirty(&self) {
+        let mut state = self.shared.state.lock();
+        state.pending += 1;
+        if state.pending == 1 || state.pending == self.shared.max_pending {
+            self.shared.wake.notify_one();
+        }
+    }
+
+    /// Forget `count` writes (a [`Self::pending`] snapshot taken before the
+    /// caller flushed) that are now on disk.
+    pub(crate) fn mark_flushed(&self, count: usize) {
+        let mut state = self.shared.state.lock();
+        state.pending = state.pending.saturating_sub(count);
+    }
+
+    /// Writes acknowledged but not flushed yet.
+    pub(crate) fn pending(&self) -> usize {
+        self.shared.state.lock().pending
+    }
+}
+
+impl Drop for CoalescingFlusher {
+    /// Stop the worker, which flushes whatever is still pending on its way out.
+    fn drop(&mut self) {
+        self.shared.state.lock().shutdown = true;
+        self.shared.wake.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run(shared: &Shared, db: &Db, window: Duration) {
+    let mut state = shared.state.lock();
+    loop {
+        while state.pending == 0 && !state.shutdown {
+            shared.wake.wait(&mut state);
+        }
+        if !state.shutdown && state.pending < shared.max_pending {
+            // Let the burst build up until the window closes or it grows
+            // past `max_pending`.
+            let deadline = std::time::Instant::now() + window;
+            while !state.shutdown && state.pending < shared.max_pending {
+                if shared.wake.wait_until(&mut state, deadline).timed_out() {
+                    break;
+                }
+            }
+        }
+
+        let shutdown = state.shutdown;
+        let flushed = std::mem::take(&mut state.pending);
+        drop(state);
+        if flushed > 0 {
+            if let Err(err) = db.flush() {
+                tracing::warn!(target: "ingat::storage", "coalesced flush of {flushed} writes failed: {err}");
+            }
+        }
+        if shutdown {
+            return;
+        }
+        state = shared.state.lock();
+    }
+}
@@ -0,0 +1,648 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use uuid::Uuid;
+
+use super::scoring;
+use crate::{
+    application::services::{
+        SearchHits, VectorStore, DEFAULT_QUERY_HISTORY_CAPACITY, SEARCH_EVENT_CAPACITY,
+    },
+    domain::{
+        tokenize_terms, ChangeEvent, ChangeKind, ContextEmbedding, ContextRecord, ContextSummary,
+        DomainError, EmbeddingComposition, HistoryOrder, QueryFilters, QueryHistoryEntry,
+        SearchEvent, SearchScope,
+    },
+};
+
+/// Document frequencies backing keyword search, mirroring sled's `term_stats` tree.
+#[derive(Default)]
+struct TermStats {
+    documents: u64,
+    frequencies: HashMap<String, u64>,
+}
+
+impl TermStats {
+    /// Undo `record_term_stats` for a deleted record.
+    fn forget(&mut self, terms: &BTreeSet<String>) {
+        self.documents = self.documents.saturating_sub(1);
+        for term in terms {
+            if let Some(count) = self.frequencies.get_mut(term) {
+                *count -= 1;
+                if *count == 0 {
+                    self.frequencies.remove(term);
+                }
+            }
+        }
+    }
+}
+
+/// Distinct summary and body terms of `record`, tokenized as the sled store
+/// does; chunk records are filtered out before this is called.
+fn record_terms(record: &ContextRecord) -> BTreeSet<String> {
+    tokenize_terms(&record.summary)
+        .into_iter()
+        .chain(tokenize_terms(&record.body))
+        .collect()
+}
+
+/// Non-persistent vector store kept entirely in memory.
+///
+/// Scoring matches [`super::SledVectorStore`], so it can stand in for the
+/// embedded store in tests and in ephemeral runs (`INGAT_STORE_BACKEND=memory`)
+/// where nothing should touch the disk. Everything is lost when it is dropped.
+pub struct MemoryVectorStore {
+    records: RwLock<HashMap<Uuid, ContextRecord>>,
+    /// Deletion time per removed id, replayed by `changes_since`.
+    tombstones: RwLock<HashMap<Uuid, DateTime<Utc>>>,
+    term_stats: RwLock<TermStats>,
+    /// Recorded searches, oldest first.
+    queries: Mutex<VecDeque<QueryHistoryEntry>>,
+    query_capacity: usize,
+    /// Search analytics events, oldest first.
+    search_events: Mutex<VecDeque<SearchEvent>>,
+}
+
+impl Default for MemoryVectorStore {
+    fn default() -> Self {
+        Self {
+            records: RwLock::default(),
+            tombstones: RwLock::default(),
+            term_stats: RwLock::default(),
+            queries: Mutex::default(),
+            query_capacity: DEFAULT_QUERY_HISTORY_CAPACITY,
+            search_events: Mutex::default(),
+        }
+    }
+}
+
+impl MemoryVectorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Searches kept in the query history; `0` stops recording.
+    pub fn with_query_history_capacity(mut self, capacity: usize) -> Self {
+        self.query_capacity = capacity;
+        self
+    }
+
+    /// Store pre-filled with `records`, e.g. a test fixture.
+    pub fn with_records(records: impl IntoIterator<Item = ContextRecord>) -> Self {
+        let store = Self::new();
+        store
+            .records
+            .write()
+            .extend(records.into_iter().map(|record| (record.id, record)));
+        store
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.read().is_empty()
+    }
+}
+
+impl VectorStore for MemoryVectorStore {
+    fn persist(&self, record: &ContextRecord) -> Result<(), DomainError> {
+        self.records.write().insert(record.id, record.clone());
+        Ok(())
+    }
+
+    fn persist_batch(&self, records: &[ContextRecord]) -> Result<(), DomainError> {
+        let mut stored = self.records.write();
+        for record in records {
+            stored.insert(record.id, record.clone());
+        }
+        Ok(())
+    }
+
+    fn search(
+        &self,
+        embedding: &ContextEmbedding,
+        limit: usize,
+        filters: &QueryFilters,
+        scope: SearchScope,
+    ) -> Result<SearchHits, DomainError> {
+        let records = self.records.read();
+        let mut scored: Vec<(ContextRecord, f32)> = Vec::new();
+        let mut mismatched = Vec::new();
+
+        for record in records.values() {
+            if !record.matches_filters(filters) {
+                continue;
+            }
+            let vector = &record.embedding_for(scope).vector;
+            if vector.len() != embedding.vector.len() {
+                mismatched.push(record.id);
+                continue;
+            }
+            let score = scoring::cosine_similarity(&embedding.vector, vector)?;
+            scored.push((record.clone(), score));
+        }
+
+        let matched_filters = scored.len() + mismatched.len();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+
+        Ok(SearchHits {
+            matches: scored,
+            scanned: records.len(),
+            matched_filters,
+            mismatched,
+        })
+    }
+
+    fn recent(
+        &self,
+        project: Option<&str>,
+        tag: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+        order: HistoryOrder,
+    ) -> Result<Vec<ContextSummary>, DomainError> {
+        let mut items: Vec<ContextSummary> = self
+            .records
+            .read()
+            .values()
+            .filter(|record| !record.is_chunk())
+            .filter(|record| project.map_or(true, |project| record.project == project))
+            .filter(|record| tag.map_or(true, |tag| record.tags.iter().any(|t| t == tag)))
+            .filter(|record| since.map_or(true, |since| record.created_at > since))
+            .map(ContextRecord::as_summary)
+            .collect();
+
+        items.sort_by(|a, b| order.compare(a, b));
+        items.truncate(limit);
+
+        Ok(items)
+    }
+
+    fn projects(&self) -> Result<Vec<String>, DomainError> {
+        let unique: BTreeSet<String> = self
+            .records
+            .read()
+            .values()
+            .map(|record| record.project.clone())
+            .collect();
+        Ok(unique.into_iter().collect())
+    }
+
+    fn iter_summaries(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<ContextSummary, DomainError>> + Send + '_> {
+        // Snapshot so callers never hold the lock while they consume the iterator.
+        let summaries: Vec<ContextSummary> = self
+            .records
+            .read()
+            .values()
+            .map(ContextRecord::as_summary)
+            .collect();
+        Box::new(summaries.into_iter().map(Ok))
+    }
+
+    fn set_pinned(&self, id: Uuid, pinned: bool) -> Result<ContextSummary, DomainError> {
+        let mut records = self.records.write();
+        let record = records
+            .get_mut(&id)
+            .ok_or_else(|| DomainError::not_found(format!("context {id}")))?;
+        record.pinned = pinned;
+        record.touch();
+        Ok(record.as_summary())
+    }
+
+    fn changes_since(
+        &self,
+        since: DateTime<Utc>,
+        include_deleted: bool,
+    ) -> Result<Vec<ChangeEvent>, DomainError> {
+        let mut events: Vec<ChangeEvent> = self
+            .records
+            .read()
+            .values()
+            .filter(|record| !record.is_chunk())
+            .filter_map(|record| {
+                let (change, at) = if record.created_at > since {
+                    (ChangeKind::Insert, record.created_at)
+                } else {
+                    (
+                        ChangeKind::Update,
+                        record.updated_at.filter(|updated_at| *updated_at > since)?,
+                    )
+                };
+                Some(ChangeEvent {
+                    id: record.id,
+                    change,
+                    at,
+                    summary: Some(record.as_summary()),
+                })
+            })
+            .collect();
+
+        if include_deleted {
+            events.extend(
+                self.tombstones
+                    .read()
+                    .iter()
+                    .filter(|(_, deleted_at)| **deleted_at > since)
+                    .map(|(id, deleted_at)| ChangeEvent {
+                        id: *id,
+                        change: ChangeKind::Delete,
+                        at: *deleted_at,
+                        summary: None,
+                    }),
+            );
+        }
+
+        events.sort_by_key(|event| event.at);
+        Ok(events)
+    }
+
+    fn scan_records(
+        &self,
+        visit: &mut dyn FnMut(Result<ContextRecord, DomainError>),
+    ) -> Result<(), DomainError> {
+        let records: Vec<ContextRecord> = self.records.read().values().cloned().collect();
+        for record in records {
+            visit(Ok(record));
+        }
+        Ok(())
+    }
+
+    fn fetch_records(&self, ids: &[Uuid]) -> Result<Vec<ContextRecord>, DomainError> {
+        let records = self.records.read();
+        Ok(ids
+            .iter()
+            .filter_map(|id| records.get(id).cloned())
+            .collect())
+    }
+
+    fn project_records(&self, project: &str) -> Result<Vec<ContextRecord>, DomainError> {
+        Ok(self
+            .records
+            .read()
+            .values()
+            .filter(|record| record.project == project)
+            .cloned()
+            .collect())
+    }
+
+    fn rebuild_term_stats(&self) -> Result<usize, DomainError> {
+        let mut stats = TermStats::default();
+        for record in self.records.read().values() {
+            if record.is_chunk() {
+                continue;
+            }
+            stats.documents += 1;
+            for term in record_terms(record) {
+                *stats.frequencies.entry(term).or_default() += 1;
+            }
+        }
+
+        let documents = stats.documents as usize;
+        *self.term_stats.write() = stats;
+        Ok(documents)
+    }
+
+    fn record_term_stats(&self, terms: &BTreeSet<String>) -> Result<(), DomainError> {
+        let mut stats = self.term_stats.write();
+        stats.documents += 1;
+        for term in terms {
+            *stats.frequencies.entry(term.clone()).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    fn keyword_search(
+        &self,
+        terms: &[String],
+        limit: usize,
+        filters: &QueryFilters,
+    ) -> Result<Vec<(ContextRecord, f32)>, DomainError> {
+        let weights: HashMap<&str, f32> = {
+            let stats = self.term_stats.read();
+            terms
+                .iter()
+                .map(|term| {
+                    let frequency = stats.frequencies.get(term).copied().unwrap_or(0);
+                    (
+                        term.as_str(),
+                        scoring::idf_weight(stats.documents, frequency),
+                    )
+                })
+                .collect()
+        };
+
+        let mut scored: Vec<(ContextRecord, f32)> = self
+            .records
+            .read()
+            .values()
+            .filter(|record| record.matches_filters(filters))
+            .filter_map(|record| {
+                scoring::keyword_score(record, &weights).map(|score| (record.clone(), score))
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+
+        Ok(scored)
+    }
+
+    fn delete_batch(&self, ids: &[Uuid]) -> Result<usize, DomainError> {
+        let deleted_at = Utc::now();
+        let mut records = self.records.write();
+        let mut tombstones = self.tombstones.write();
+        let mut term_stats = self.term_stats.write();
+
+        let mut deleted = 0;
+        for id in ids {
+            if let Some(record) = records.remove(id) {
+                if !record.is_chunk() {
+                    tombstones.insert(*id, deleted_at);
+                    term_stats.forget(&record_terms(&record));
+                }
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    fn rebuild_index(&self) -> Result<usize, DomainError> {
+        // There are no secondary indexes to rebuild; every query scans the map.
+        let records = self.records.read();
+        Ok(records
+            .values()
+            .filter(|record| record.parent_id.is_none())
+            .count())
+    }
+
+    fn prune_tombstones(&self, before: DateTime<Utc>) -> Result<usize, DomainError> {
+        let mut tombstones = self.tombstones.write();
+        let kept = tombstones.len();
+        tombstones.retain(|_, deleted_at| *deleted_at >= before);
+        Ok(kept - tombstones.len())
+    }
+
+    fn kind_counts(&self) -> Result<Vec<(String, usize)>, DomainError> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for record in self.records.read().values() {
+            if !record.is_chunk() {
+                *counts.entry(record.kind.key()).or_default() += 1;
+            }
+        }
+        Ok(counts.into_iter().collect())
+    }
+
+    fn store_composition(&self) -> Result<Vec<EmbeddingComposition>, DomainError> {
+        let mut histogram: BTreeMap<(String, Option<String>, usize), usize> = BTreeMap::new();
+        for record in self
+            .records
+            .read()
+            .values()
+            .filter(|record| record.searchable)
+        {
+            let embedding = &record.embedding;
+            *histogram
+                .entry((
+                    embedding.model.clone(),
+                    embedding.model_version.clone(),
+                    embedding.dims(),
+                ))
+                .or_default() += 1;
+        }
+
+        Ok(histogram
+            .into_iter()
+            .map(
+                |((model, model_version, dimensions), count)| EmbeddingComposition {
+                    model,
+                    model_version,
+                    dimensions,
+                    count,
+                },
+            )
+            .collect())
+    }
+
+    fn record_query(&self, entry: &QueryHistoryEntry) -> Result<(), DomainError> {
+        if self.query_capacity == 0 {
+            return Ok(());
+        }
+        let mut queries = self.queries.lock();
+        while queries.len() >= self.query_capacity {
+            queries.pop_front();
+        }
+        queries.push_back(entry.clone());
+        Ok(())
+    }
+
+    fn recent_queries(&self, limit: usize) -> Result<Vec<QueryHistoryEntry>, DomainError> {
+        Ok(self
+            .queries
+            .lock()
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn clear_query_history(&self) -> Result<usize, DomainError> {
+        let mut queries = self.queries.lock();
+        let cleared = queries.len();
+        queries.clear();
+        Ok(cleared)
+    }
+
+    fn record_search_event(&self, event: &SearchEvent) -> Result<(), DomainError> {
+        let mut events = self.search_events.lock();
+        while events.len() >= SEARCH_EVENT_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+        Ok(())
+    }
+
+    fn record_feedback(&self, query_id: Uuid, chosen_id: Uuid) -> Result<SearchEvent, DomainError> {
+        let mut events = self.search_events.lock();
+        let event = events
+            .iter_mut()
+            .rev()
+            .find(|event| event.query_id == query_id)
+            .ok_or_else(|| DomainError::not_found(format!("search {query_id}")))?;
+        event.choose(chosen_id);
+        Ok(event.clone())
+    }
+
+    fn search_events(&self) -> Result<Vec<SearchEvent>, DomainError> {
+        Ok(self.search_events.lock().iter().cloned().collect())
+    }
+
+    fn clear_search_events(&self) -> Result<usize, DomainError> {
+        let mut events = self.search_events.lock();
+        let cleared = events.len();
+        events.clear();
+        Ok(cleared)
+    }
+
+    fn ping(&self) -> Result<(), DomainError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ContextKind, OrderField, SortDirection};
+
+    fn record(project: &str, summary: &str, vector: Vec<f32>) -> ContextRecord {
+        ContextRecord::new(
+            project,
+            "vscode",
+            None::<String>,
+            None::<String>,
+            summary,
+            "body",
+            ["tag"],
+            ContextKind::CodeSnippet,
+            ContextEmbedding::new("ingat/simple-hash", vector),
+        )
+    }
+
+    #[test]
+    fn test_search_ranks_by_cosine_and_counts_filter_matches() {
+        let close = record("ingat", "close", vec![0.6, 0.8]);
+        let far = record("ingat", "far", vec![1.0, 0.0]);
+        let other = record("other", "other", vec![0.6, 0.8]);
+        let store = MemoryVectorStore::with_records([close.clone(), far.clone(), other]);
+
+        let filters = QueryFilters {
+            project: Some("ingat".into()),
+            ..QueryFilters::default()
+        };
+        let hits = store
+            .search(&close.embedding, 5, &filters, SearchScope::Both)
+            .unwrap();
+        let ids: Vec<Uuid> = hits.matches.iter().map(|(record, _)| record.id).collect();
+        assert_eq!(ids, vec![close.id, far.id]);
+        assert_eq!((hits.scanned, hits.matched_filters), (3, 2));
+        assert_eq!(store.projects().unwrap(), vec!["ingat", "other"]);
+    }
+
+    #[test]
+    fn test_recent_is_newest_first_and_deletes_leave_tombstones() {
+        let mut older = record("ingat", "older", vec![1.0, 0.0]);
+        older.created_at -= chrono::Duration::minutes(5);
+        let newer = record("ingat", "newer", vec![1.0, 0.0]);
+        let store = MemoryVectorStore::new();
+        store
+            .persist_batch(&[older.clone(), newer.clone()])
+            .unwrap();
+
+        let recent = store
+            .recent(
+                Some("ingat"),
+                Some("tag"),
+                None,
+                10,
+                HistoryOrder::default(),
+            )
+            .unwrap();
+        assert_eq!(recent[0].id, newer.id);
+        assert_eq!(recent[1].id, older.id);
+
+        let since = Utc::now() - chrono::Duration::seconds(1);
+        assert_eq!(store.delete_batch(&[older.id, Uuid::new_v4()]).unwrap(), 1);
+        assert_eq!(store.len(), 1);
+        let changes = store.changes_since(since, true).unwrap();
+        assert!(changes
+            .iter()
+            .any(|event| event.id == older.id && event.change == ChangeKind::Delete));
+        assert!(store.set_pinned(older.id, true).is_err());
+
+        // Compaction forgets deletions older than the horizon only.
+        assert_eq!(store.prune_tombstones(since).unwrap(), 0);
+        assert_eq!(
+            store
+                .prune_tombstones(Utc::now() + chrono::Duration::seconds(1))
+                .unwrap(),
+            1
+        );
+        assert!(store
+            .changes_since(since, true)
+            .unwrap()
+            .iter()
+            .all(|event| event.change != ChangeKind::Delete));
+    }
+
+    #[test]
+    fn test_recent_honours_requested_order() {
+        let beta = record("beta", "b", vec![1.0, 0.0]);
+        let alpha = record("alpha", "a", vec![1.0, 0.0]);
+        let store = MemoryVectorStore::with_records([beta.clone(), alpha.clone()]);
+
+        let by_project = HistoryOrder {
+            order_by: OrderField::Project,
+            direction: SortDirection::Asc,
+        };
+        let projects: Vec<String> = store
+            .recent(None, None, None, 10, by_project)
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.project)
+            .collect();
+        assert_eq!(projects, vec!["alpha", "beta"]);
+
+        store.set_pinned(beta.id, true).unwrap();
+        let by_update = HistoryOrder {
+            order_by: OrderField::UpdatedAt,
+            direction: SortDirection::Desc,
+        };
+        let recent = store.recent(None, None, None, 1, by_update).unwrap();
+        assert_eq!(recent[0].id, beta.id);
+    }
+
+    #[test]
+    fn test_since_excludes_older_records_and_future_matches_nothing() {
+        let mut old = record("ingat", "old", vec![1.0, 0.0]);
+        old.created_at -= chrono::Duration::hours(2);
+        let new = record("ingat", "new", vec![1.0, 0.0]);
+        let cutoff = Utc::now() - chrono::Duration::hours(1);
+        let store = MemoryVectorStore::with_records([old, new.clone()]);
+
+        let filters = QueryFilters {
+            since: Some(cutoff),
+            ..QueryFilters::default()
+        };
+        let hits = store
+            .search(&new.embedding, 5, &filters, SearchScope::Both)
+            .unwrap();
+        assert_eq!(hits.matches.len(), 1);
+        assert_eq!(hits.matches[0].0.id, new.id);
+
+        let recent = store
+            .recent(None, None, Some(cutoff), 10, HistoryOrder::default())
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].id, new.id);
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        let filters = QueryFilters {
+            since: Some(future),
+            ..QueryFilters::default()
+        };
+        let hits = store
+            .search(&new.embedding, 5, &filters, SearchScope::Both)
+            .unwrap();
+        assert!(hits.matches.is_empty());
+        assert!(store
+            .recent(None, None, Some(future), 10, HistoryOrder::default())
+            .unwrap()
+            .is_empty());
+    }
+}
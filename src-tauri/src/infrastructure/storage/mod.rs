@@ -1,8 +1,17 @@
 //! Storage adapters for Ingat.
 //!
-//! This module currently exposes the embedded sled-backed vector store
-//! that powers semantic retrieval and history listings.
+//! This module exposes the embedded sled-backed vector store that powers
+//! semantic retrieval and history listings, a non-persistent in-memory store,
+//! and the registry that picks between them (and the remote proxy).
 
+mod flusher;
+pub mod memory_store;
+pub mod quantize;
+pub mod registry;
+mod scoring;
 pub mod sled_store;
 
+pub use memory_store::MemoryVectorStore;
+pub use quantize::VectorPrecision;
+pub use registry::{open_store, OpenedStore, StoreBackend, StoreOptions, STORE_BACKEND_ENV};
 pub use sled_store::SledVectorStore;
@@ -0,0 +1,124 @@
+//! Optional lossy compression of stored embedding vectors.
+//!
+//! Vectors are always handed to callers (and scored) as `f32`; quantization only
+//! affects the bytes written to disk. Recall impact is small in practice: `f16`
+//! keeps ~3 significant digits, and `int8` with a per-vector scale keeps cosine
+//! scores within roughly ±0.01 of the full-precision value for unit-length
+//! vectors, which rarely changes the ordering of top results.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use half::f16;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::DomainError;
+
+/// On-disk precision for stored embedding vectors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorPrecision {
+    /// Full precision, stored inline as a JSON float array.
+    #[default]
+    F32,
+    /// Half precision (2 bytes per component).
+    F16,
+    /// Signed 8-bit integers scaled by the vector's largest magnitude (1 byte per component).
+    Int8,
+}
+
+/// Compressed vector payload. The tag doubles as the per-record precision marker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "precision", rename_all = "lowercase")]
+pub(crate) enum QuantizedVector {
+    F16 { data: String },
+    Int8 { scale: f32, data: String },
+}
+
+impl QuantizedVector {
+    /// Compress `vector` at `precision`, or `None` when it should stay as `f32`.
+    pub(crate) fn encode(vector: &[f32], precision: VectorPrecision) -> Option<Self> {
+        match precision {
+            VectorPrecision::F32 => None,
+            VectorPrecision::F16 => {
+                let bytes: Vec<u8> = vector
+                    .iter()
+                    .flat_map(|value| f16::from_f32(*value).to_le_bytes())
+                    .collect();
+                Some(Self::F16 {
+                    data: STANDARD.encode(bytes),
+                })
+            }
+            VectorPrecision::Int8 => {
+                let max = vector
+                    .iter()
+                    .fold(0.0f32, |acc, value| acc.max(value.abs()));
+                let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+                let bytes: Vec<u8> = vector
+                    .iter()
+                    .map(|value| (value / scale).round().clamp(-127.0, 127.0) as i8 as u8)
+                    .collect();
+                Some(Self::Int8 {
+                    scale,
+                    data: STANDARD.encode(bytes),
+                })
+            }
+        }
+    }
+
+    /// Expand back to `f32` for scoring.
+    pub(crate) fn decode(&self) -> Result<Vec<f32>, DomainError> {
+        let decode_bytes = |data: &str| {
+            STANDARD.decode(data).map_err(|err| {
+                DomainError::storage(format!("invalid quantized vector payload: {err}"))
+            })
+        };
+
+        match self {
+            Self::F16 { data } => {
+                let bytes = decode_bytes(data)?;
+                if bytes.len() % 2 != 0 {
+                    return Err(DomainError::storage(
+                        "invalid f16 vector payload: odd byte length",
+                    ));
+                }
+                Ok(bytes
+                    .chunks_exact(2)
+                    .map(|pair| f16::from_le_bytes([pair[0], pair[1]]).to_f32())
+                    .collect())
+            }
+            Self::Int8 { scale, data } => Ok(decode_bytes(data)?
+                .into_iter()
+                .map(|byte| byte as i8 as f32 * scale)
+                .collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(vector: &[f32], precision: VectorPrecision) -> Vec<f32> {
+        QuantizedVector::encode(vector, precision)
+            .expect("quantized")
+            .decode()
+            .expect("decoded")
+    }
+
+    #[test]
+    fn test_quantized_roundtrip_stays_close() {
+        let vector = vec![0.5, -0.25, 0.125, 0.0, -1.0, 0.333];
+
+        for precision in [VectorPrecision::F16, VectorPrecision::Int8] {
+            let decoded = roundtrip(&vector, precision);
+            assert_eq!(decoded.len(), vector.len());
+            for (original, restored) in vector.iter().zip(decoded) {
+                assert!((original - restored).abs() < 0.01, "{precision:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_f32_is_not_quantized() {
+        assert!(QuantizedVector::encode(&[1.0, 2.0], VectorPrecision::F32).is_none());
+    }
+}
@@ -0,0 +1,138 @@
+//! Builds the configured [`VectorStore`] behind a trait object so environment
+//! wiring never names a concrete store.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{MemoryVectorStore, SledVectorStore, VectorPrecision};
+use crate::{
+    application::services::VectorStore,
+    domain::DomainError,
+    infrastructure::http_client::{CircuitBreaker, RemoteVectorStore},
+};
+
+/// Environment variable that forces a store backend instead of auto-detection.
+pub const STORE_BACKEND_ENV: &str = "INGAT_STORE_BACKEND";
+
+/// Store implementations selectable through [`STORE_BACKEND_ENV`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreBackend {
+    /// Embedded sled database in the data directory.
+    Sled,
+    /// Proxy to a running mcp-service.
+    Remote,
+    /// Non-persistent in-memory store.
+    Memory,
+}
+
+impl StoreBackend {
+    pub const ALL: [StoreBackend; 3] = [Self::Sled, Self::Remote, Self::Memory];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Sled => "sled",
+            Self::Remote => "remote",
+            Self::Memory => "memory",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, DomainError> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|backend| backend.id().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                DomainError::validation(format!(
+                    "unknown store backend `{value}` (expected sled, remote or memory)"
+                ))
+            })
+    }
+
+    /// Backend forced by [`STORE_BACKEND_ENV`]; `None` when unset or empty,
+    /// leaving the choice to service detection.
+    pub fn from_env() -> Result<Option<Self>, DomainError> {
+        match std::env::var(STORE_BACKEND_ENV) {
+            Ok(value) if !value.trim().is_empty() => Self::parse(&value).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Everything any backend may need; each one reads only its own fields.
+#[derive(Debug, Clone)]
+pub struct StoreOptions {
+    /// Directory of the sled database.
+    pub store_dir: PathBuf,
+    pub sync_writes: bool,
+    /// Coalescing window for synced sled writes; `0` flushes each write.
+    pub flush_coalesce_ms: u64,
+    /// Pending writes that trigger a coalesced flush before the window ends.
+    pub flush_coalesce_writes: usize,
+    pub vector_precision: VectorPrecision,
+    pub separate_embeddings: bool,
+    /// Searches kept in the query history by local stores.
+    pub query_history: usize,
+    /// mcp-service endpoint for the remote backend.
+    pub host: String,
+    pub port: u16,
+}
+
+/// A constructed store plus the failure tracker of remote stores.
+pub struct OpenedStore {
+    pub store: Arc<dyn VectorStore>,
+    pub breaker: Option<Arc<CircuitBreaker>>,
+}
+
+pub fn open_store(
+    backend: StoreBackend,
+    options: &StoreOptions,
+) -> Result<OpenedStore, DomainError> {
+    let opened = match backend {
+        StoreBackend::Sled => {
+            std::fs::create_dir_all(&options.store_dir).map_err(|err| {
+                DomainError::storage(format!("failed to create store directory: {err}"))
+            })?;
+            let store = SledVectorStore::open(&options.store_dir)?
+                .with_sync_writes(options.sync_writes)
+                .with_vector_precision(options.vector_precision)
+                .with_separate_embeddings(options.separate_embeddings)
+                .with_query_history_capacity(options.query_history)
+                .with_flush_coalescing(options.flush_coalesce_ms, options.flush_coalesce_writes)?;
+            OpenedStore {
+                store: Arc::new(store),
+                breaker: None,
+            }
+        }
+        StoreBackend::Remote => {
+            let remote = RemoteVectorStore::new(&options.host, options.port);
+            OpenedStore {
+                breaker: Some(remote.breaker()),
+                store: Arc::new(remote),
+            }
+        }
+        StoreBackend::Memory => OpenedStore {
+            store: Arc::new(
+                MemoryVectorStore::new().with_query_history_capacity(options.query_history),
+            ),
+            breaker: None,
+        },
+    };
+    Ok(opened)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_backend_ids_roundtrip() {
+        for backend in StoreBackend::ALL {
+            assert_eq!(StoreBackend::parse(backend.id()).unwrap(), backend);
+        }
+        assert_eq!(
+            StoreBackend::parse(" Memory ").unwrap(),
+            StoreBackend::Memory
+        );
+        assert!(StoreBackend::parse("postgres").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! Ranking helpers shared by the store implementations so every backend
+//! scores the same record identically.
+
+use std::collections::HashMap;
+
+use crate::domain::{tokenize_terms, ContextRecord, DomainError};
+
+pub(crate) fn cosine_similarity(query: &[f32], candidate: &[f32]) -> Result<f32, DomainError> {
+    if query.len() != candidate.len() {
+        return Err(DomainError::embedding(format!(
+            "embedding dimension mismatch: query {} vs candidate {}",
+            query.len(),
+            candidate.len()
+        )));
+    }
+
+    let mut dot = 0.0f32;
+    let mut q_norm = 0.0f32;
+    let mut c_norm = 0.0f32;
+
+    for (q, c) in query.iter().zip(candidate.iter()) {
+        dot += q * c;
+        q_norm += q * q;
+        c_norm += c * c;
+    }
+
+    let denom = q_norm.sqrt() * c_norm.sqrt();
+    if denom == 0.0 {
+        return Err(DomainError::embedding(
+            "cannot compute cosine similarity with zero vector",
+        ));
+    }
+
+    // A corrupt vector must not poison the ranking: NaN sorts unpredictably
+    // under `total_cmp`, so score it as unrelated instead.
+    let score = dot / denom;
+    if score.is_nan() {
+        return Ok(0.0);
+    }
+
+    Ok(score.clamp(-1.0, 1.0))
+}
+
+/// Smoothed IDF; with no statistics yet (`documents == 0`) every term weighs the same.
+pub(crate) fn idf_weight(documents: u64, frequency: u64) -> f32 {
+    if documents == 0 {
+        return 1.0;
+    }
+    ((documents as f32 + 1.0) / (frequency as f32 + 1.0)).ln() + 1.0
+}
+
+/// Length-normalised TF-IDF of `record` against weighted query terms, or
+/// `None` when no query term occurs in it.
+pub(crate) fn keyword_score(record: &ContextRecord, weights: &HashMap<&str, f32>) -> Option<f32> {
+    let tokens: Vec<String> = tokenize_terms(&record.summary)
+        .into_iter()
+        .chain(tokenize_terms(&record.body))
+        .collect();
+    if tokens.is_empty() {
+        return None;
+    }
+
+    let score = tokens
+        .iter()
+        .filter_map(|token| weights.get(token.as_str()))
+        .sum::<f32>()
+        / tokens.len() as f32;
+    (score > 0.0).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity_scores_nan_as_zero() {
+        let score = cosine_similarity(&[1.0, 0.0], &[f32::NAN, 1.0]).unwrap();
+        assert_eq!(score, 0.0);
+
+        let score = cosine_similarity(&[1.0, 0.0], &[f32::INFINITY, 1.0]).unwrap();
+        assert_eq!(score, 0.0);
+    }
+}
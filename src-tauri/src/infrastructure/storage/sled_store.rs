@@ -1,23 +1,205 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 use bincode::Options;
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
-use serde::de::DeserializeOwned;
-use sled::{Config, Db, IVec, Tree};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sled::{
+    transaction::{
+        ConflictableTransactionError, TransactionError, TransactionalTree,
+        UnabortableTransactionError,
+    },
+    Batch, Config, Db, IVec, Transactional, Tree,
+};
 use uuid::Uuid;
 
+use super::flusher::CoalescingFlusher;
+use super::quantize::{QuantizedVector, VectorPrecision};
+use super::scoring;
 use crate::{
-    application::services::VectorStore,
-    domain::{ContextEmbedding, ContextRecord, ContextSummary, DomainError, QueryFilters},
+    application::services::{
+        SearchHits, VectorStore, DEFAULT_QUERY_HISTORY_CAPACITY, SEARCH_EVENT_CAPACITY,
+    },
+    domain::{
+        tokenize_terms, ChangeEvent, ChangeKind, ContextEmbedding, ContextKind, ContextRecord,
+        ContextSummary, DomainError, EmbeddingComposition, HistoryOrder, QueryFilters,
+        QueryHistoryEntry, SearchEvent, SearchScope,
+    },
 };
 
 const CONTEXTS_TREE: &str = "contexts";
+const TOMBSTONES_TREE: &str = "tombstones";
+const EMBEDDINGS_TREE: &str = "embeddings";
+const TERM_STATS_TREE: &str = "term_stats";
+/// Secondary index keyed `<kind key> 0x00 <record id>` with empty values.
+const KIND_INDEX_TREE: &str = "kind_index";
+/// Secondary index keyed `<parent id> <chunk id>` with empty values.
+const CHUNK_INDEX_TREE: &str = "chunk_index";
+/// Query history ring keyed by a contiguous big-endian sequence number.
+const QUERIES_TREE: &str = "queries";
+/// Search analytics events, keyed like the query history.
+const FEEDBACK_TREE: &str = "feedback";
+/// Secondary index from a search event's `query_id` to its feedback key.
+const FEEDBACK_IDS_TREE: &str = "feedback_ids";
+
+/// Key of the corpus document count inside the term stats tree. Terms never
+/// contain NUL, so it cannot collide with a real term.
+const TERM_STATS_DOCUMENTS_KEY: &[u8] = b"\0documents";
+
+/// Records are written as JSON objects so new fields can be added with serde defaults.
+/// Anything else is treated as the legacy fixed-layout bincode encoding.
+const JSON_RECORD_PREFIX: u8 = b'{';
+
+/// Key in the default tree holding the store's [`STORE_FORMAT_VERSION`].
+const FORMAT_VERSION_KEY: &[u8] = b"format_version";
+
+/// On-disk record format. Stores without the key predate it and hold
+/// bincode records (format 1), which format 2 still reads; each record is
+/// rewritten as JSON the next time it is written. Older releases cannot read
+/// JSON records, so a store marked with a newer format is refused.
+const STORE_FORMAT_VERSION: u64 = 2;
+
+/// Record layout written by releases that stored fixed-layout bincode payloads.
+#[derive(Deserialize)]
+struct LegacyContextRecord {
+    id: Uuid,
+    project: String,
+    ide: String,
+    file_path: Option<String>,
+    language: Option<String>,
+    summary: String,
+    body: String,
+    tags: Vec<String>,
+    kind: ContextKind,
+    embedding: LegacyContextEmbedding,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct LegacyContextEmbedding {
+    model: String,
+    vector: Vec<f32>,
+}
+
+/// JSON record envelope. When vectors are quantized, the record's own vectors are
+/// written empty and the compressed payloads travel alongside under `quantized`.
+#[derive(Serialize)]
+struct StoredRecordRef<'a> {
+    #[serde(flatten)]
+    record: &'a ContextRecord,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quantized: Option<QuantizedEmbeddings>,
+}
+
+#[derive(Deserialize)]
+struct StoredRecord {
+    #[serde(flatten)]
+    record: ContextRecord,
+    #[serde(default)]
+    quantized: Option<QuantizedEmbeddings>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct QuantizedEmbeddings {
+    embedding: QuantizedVector,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<QuantizedVector>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<QuantizedVector>,
+}
+
+impl StoredRecord {
+    fn into_record(self) -> Result<ContextRecord, DomainError> {
+        let mut record = self.record;
+        if let Some(quantized) = self.quantized {
+            record.embedding.vector = quantized.embedding.decode()?;
+            if let (Some(embedding), Some(vector)) =
+                (&mut record.summary_embedding, quantized.summary)
+            {
+                embedding.vector = vector.decode()?;
+            }
+            if let (Some(embedding), Some(vector)) = (&mut record.body_embedding, quantized.body) {
+                embedding.vector = vector.decode()?;
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// Vectors kept in the `embeddings` tree when records are written with
+/// separate embeddings; the matching `contexts` entry holds empty vectors.
+#[derive(Serialize, Deserialize)]
+struct StoredVectors {
+    embedding: StoredVector,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<StoredVector>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<StoredVector>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredVector {
+    Quantized(QuantizedVector),
+    Full(Vec<f32>),
+}
+
+impl StoredVector {
+    fn encode(vector: &[f32], precision: VectorPrecision) -> Self {
+        QuantizedVector::encode(vector, precision)
+            .map(Self::Quantized)
+            .unwrap_or_else(|| Self::Full(vector.to_vec()))
+    }
+
+    fn decode(self) -> Result<Vec<f32>, DomainError> {
+        match self {
+            Self::Quantized(quantized) => quantized.decode(),
+            Self::Full(vector) => Ok(vector),
+        }
+    }
+}
+
+/// Marker left behind when a record is deleted so sync consumers can replay it.
+#[derive(Serialize, Deserialize)]
+struct Tombstone {
+    id: Uuid,
+    project: String,
+    deleted_at: DateTime<Utc>,
+}
+
+impl From<LegacyContextRecord> for ContextRecord {
+    fn from(legacy: LegacyContextRecord) -> Self {
+        Self {
+            id: legacy.id,
+            project: legacy.project,
+            ide: legacy.ide,
+            file_path: legacy.file_path,
+            language: legacy.language,
+            summary: legacy.summary,
+            body: legacy.body,
+            tags: legacy.tags,
+            kind: legacy.kind,
+            embedding: ContextEmbedding::new(legacy.embedding.model, legacy.embedding.vector),
+            created_at: legacy.created_at,
+            pinned: false,
+            updated_at: None,
+            summary_embedding: None,
+            body_embedding: None,
+            source: None,
+            searchable: true,
+            parent_id: None,
+        }
+    }
+}
 
 /// Embedded vector store backed by `sled`.
 ///
 /// This adapter keeps the implementation intentionally simple by storing full
-/// `ContextRecord` payloads in a single tree. Vector similarity is performed
+/// `ContextRecord` payloads in a single tree (optionally with vectors split
+/// into a sibling `embeddings` tree). Vector similarity is performed
 /// in-memory using cosine similarity, which is acceptable for moderate data
 /// volumes and keeps the design embeddable without additional services.
 ///
@@ -26,8 +208,33 @@ const CONTEXTS_TREE: &str = "contexts";
 pub struct SledVectorStore {
     db: Db,
     contexts: Tree,
-    _data_dir: PathBuf,
+    tombstones: Tree,
+    embeddings: Tree,
+    term_stats: Tree,
+    kind_index: Tree,
+    chunk_index: Tree,
+    queries: Tree,
+    /// Key of the next query history entry.
+    query_next: AtomicU64,
+    /// Oldest query history key that may still be stored.
+    query_floor: AtomicU64,
+    query_capacity: usize,
+    feedback: Tree,
+    /// Key of the next search event.
+    feedback_next: AtomicU64,
+    /// Oldest search event key that may still be stored.
+    feedback_floor: AtomicU64,
+    feedback_ids: Tree,
+    data_dir: PathBuf,
     write_lock: Mutex<()>,
+    /// Serializes the query history and search event rings, which searches
+    /// append to, apart from record writes.
+    history_lock: Mutex<()>,
+    sync_writes: bool,
+    /// Batches synced single writes into one flush; `None` flushes each one.
+    flusher: Option<CoalescingFlusher>,
+    vector_precision: VectorPrecision,
+    separate_embeddings: bool,
 }
 
 impl SledVectorStore {
@@ -45,24 +252,538 @@ impl SledVectorStore {
             .open()
             .map_err(|err| DomainError::storage(format!("failed to open sled db: {err}")))?;
 
+        Self::check_format_version(&db)?;
+
         let contexts = db
             .open_tree(CONTEXTS_TREE)
             .map_err(|err| DomainError::storage(format!("failed to open contexts tree: {err}")))?;
 
-        Ok(Self {
+        let tombstones = db.open_tree(TOMBSTONES_TREE).map_err(|err| {
+            DomainError::storage(format!("failed to open tombstones tree: {err}"))
+        })?;
+
+        let embeddings = db.open_tree(EMBEDDINGS_TREE).map_err(|err| {
+            DomainError::storage(format!("failed to open embeddings tree: {err}"))
+        })?;
+
+        let term_stats = db.open_tree(TERM_STATS_TREE).map_err(|err| {
+            DomainError::storage(format!("failed to open term stats tree: {err}"))
+        })?;
+
+        let kind_index = db.open_tree(KIND_INDEX_TREE).map_err(|err| {
+            DomainError::storage(format!("failed to open kind index tree: {err}"))
+        })?;
+
+        let chunk_index_existed = db
+            .tree_names()
+            .iter()
+            .any(|name| name.as_ref() == CHUNK_INDEX_TREE.as_bytes());
+        let chunk_index = db.open_tree(CHUNK_INDEX_TREE).map_err(|err| {
+            DomainError::storage(format!("failed to open chunk index tree: {err}"))
+        })?;
+
+        let queries = db
+            .open_tree(QUERIES_TREE)
+            .map_err(|err| DomainError::storage(format!("failed to open queries tree: {err}")))?;
+        let query_key = |entry: Option<(IVec, IVec)>| entry.map(|(key, _)| decode_count(&key));
+        let read_error =
+            |err: sled::Error| DomainError::storage(format!("failed to read queries tree: {err}"));
+        let query_floor = query_key(queries.first().map_err(read_error)?).unwrap_or(0);
+        let query_next = query_key(queries.last().map_err(read_error)?).map_or(0, |key| key + 1);
+
+        let feedback = db
+            .open_tree(FEEDBACK_TREE)
+            .map_err(|err| DomainError::storage(format!("failed to open feedback tree: {err}")))?;
+        let read_error =
+            |err: sled::Error| DomainError::storage(format!("failed to read feedback tree: {err}"));
+        let feedback_floor = query_key(feedback.first().map_err(read_error)?).unwrap_or(0);
+        let feedback_next =
+            query_key(feedback.last().map_err(read_error)?).map_or(0, |key| key + 1);
+        let feedback_ids = db.open_tree(FEEDBACK_IDS_TREE).map_err(|err| {
+            DomainError::storage(format!("failed to open feedback index tree: {err}"))
+        })?;
+
+        let store = Self {
             db,
             contexts,
-            _data_dir: dir,
+            tombstones,
+            embeddings,
+            term_stats,
+            kind_index,
+            chunk_index,
+            queries,
+            query_next: AtomicU64::new(query_next),
+            query_floor: AtomicU64::new(query_floor),
+            query_capacity: DEFAULT_QUERY_HISTORY_CAPACITY,
+            feedback,
+            feedback_next: AtomicU64::new(feedback_next),
+            feedback_floor: AtomicU64::new(feedback_floor),
+            feedback_ids,
+            data_dir: dir,
             write_lock: Mutex::new(()),
+            history_lock: Mutex::new(()),
+            sync_writes: true,
+            flusher: None,
+            vector_precision: VectorPrecision::default(),
+            separate_embeddings: false,
+        };
+        store.backfill_secondary_indexes(chunk_index_existed)?;
+        store.backfill_feedback_ids()?;
+        Ok(store)
+    }
+
+    /// Refuse stores written by a newer format, and mark older or fresh ones
+    /// with the current one.
+    fn check_format_version(db: &Db) -> Result<(), DomainError> {
+        let stored = db
+            .get(FORMAT_VERSION_KEY)
+            .map_err(|err| DomainError::storage(format!("failed to read store format: {err}")))?
+            .map(|bytes| decode_count(&bytes));
+        match stored {
+            Some(version) if version > STORE_FORMAT_VERSION => Err(DomainError::storage(format!(
+                "store format {version} is newer than this release supports \
+                 ({STORE_FORMAT_VERSION}); upgrade ingat to open it"
+            ))),
+            Some(version) if version == STORE_FORMAT_VERSION => Ok(()),
+            _ => db
+                .insert(FORMAT_VERSION_KEY, &STORE_FORMAT_VERSION.to_be_bytes())
+                .map(|_| ())
+                .map_err(|err| {
+                    DomainError::storage(format!("failed to write store format: {err}"))
+                }),
+        }
+    }
+
+    /// Search events recorded before the query id index existed get it
+    /// built once on open.
+    fn backfill_feedback_ids(&self) -> Result<(), DomainError> {
+        if self.feedback.is_empty() || !self.feedback_ids.is_empty() {
+            return Ok(());
+        }
+        let mut batch = Batch::default();
+        for entry in self.feedback.iter() {
+            let (key, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read search events: {err}"))
+            })?;
+            let event: SearchEvent = serde_json::from_slice(&value)
+                .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))?;
+            batch.insert(&event.query_id.as_bytes()[..], key);
+        }
+        self.feedback_ids
+            .apply_batch(batch)
+            .map_err(|err| DomainError::storage(format!("failed to index search events: {err}")))
+    }
+
+    /// Stores written before the kind or chunk index existed (or whose kind
+    /// index was lost) get them rebuilt once on open.
+    fn backfill_secondary_indexes(&self, chunk_index_existed: bool) -> Result<(), DomainError> {
+        if self.contexts.is_empty() || (chunk_index_existed && !self.kind_index.is_empty()) {
+            return Ok(());
+        }
+        self.rebuild_secondary_indexes().map(|_| ())
+    }
+
+    /// Rebuild every secondary index tree from one pass over `contexts`. Stale
+    /// keys are dropped in the same transaction that writes the fresh ones, so
+    /// readers never see an empty or half-built index. Returns how many
+    /// contexts were indexed, not counting their chunks.
+    fn rebuild_secondary_indexes(&self) -> Result<usize, DomainError> {
+        let mut kinds = BTreeSet::new();
+        let mut chunks = BTreeSet::new();
+        let mut indexed = 0;
+        for entry in self.contexts.iter() {
+            let (_, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            let record = Self::decode_record(&value)?;
+            kinds.insert(Self::kind_index_key(&record.kind, &record.id));
+            match record.parent_id {
+                Some(parent) => {
+                    chunks.insert(Self::chunk_index_key(&parent, &record.id).to_vec());
+                }
+                None => indexed += 1,
+            }
+        }
+
+        let kind_batch = Self::index_batch(&self.kind_index, &kinds, "kind")?;
+        let chunk_batch = Self::index_batch(&self.chunk_index, &chunks, "chunk")?;
+        (&self.kind_index, &self.chunk_index)
+            .transaction(|(kind_index, chunk_index)| {
+                kind_index.apply_batch(&kind_batch)?;
+                chunk_index.apply_batch(&chunk_batch)?;
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|err: TransactionError<()>| {
+                DomainError::storage(format!("failed to rebuild secondary indexes: {err:?}"))
+            })?;
+        Ok(indexed)
+    }
+
+    /// A batch turning the index `tree` into exactly `keys`.
+    fn index_batch(
+        tree: &Tree,
+        keys: &BTreeSet<Vec<u8>>,
+        name: &str,
+    ) -> Result<Batch, DomainError> {
+        let mut batch = Batch::default();
+        for key in tree.iter().keys() {
+            let key = key.map_err(|err| {
+                DomainError::storage(format!("failed to read {name} index: {err}"))
+            })?;
+            if !keys.contains(key.as_ref()) {
+                batch.remove(key);
+            }
+        }
+        for key in keys {
+            batch.insert(key.as_slice(), &[]);
+        }
+        Ok(batch)
+    }
+
+    fn kind_index_key(kind: &ContextKind, id: &Uuid) -> Vec<u8> {
+        let mut key = kind.key().into_bytes();
+        key.push(0);
+        key.extend_from_slice(id.as_bytes());
+        key
+    }
+
+    fn chunk_index_key(parent: &Uuid, chunk: &Uuid) -> [u8; 32] {
+        let mut key = [0; 32];
+        key[..16].copy_from_slice(parent.as_bytes());
+        key[16..].copy_from_slice(chunk.as_bytes());
+        key
+    }
+
+    /// Ids of every stored chunk, for listings that leave chunks out.
+    fn chunk_id_set(&self) -> Result<HashSet<Uuid>, DomainError> {
+        self.chunk_index
+            .iter()
+            .keys()
+            .map(|key| {
+                let key = key.map_err(|err| {
+                    DomainError::storage(format!("failed to read chunk index: {err}"))
+                })?;
+                Uuid::from_slice(&key[16..])
+                    .map_err(|err| DomainError::storage(format!("corrupt chunk index key: {err}")))
+            })
+            .collect()
+    }
+
+    /// Raw context entries that may match `filters`. A kind filter is answered
+    /// from the kind index; everything else still needs a full scan.
+    fn candidates<'a>(
+        &'a self,
+        filters: &QueryFilters,
+    ) -> Box<dyn Iterator<Item = Result<IVec, DomainError>> + 'a> {
+        let read_error = |err: sled::Error| {
+            DomainError::storage(format!("failed to read context record: {err}"))
+        };
+        let Some(kind) = &filters.kind else {
+            return Box::new(
+                self.contexts
+                    .iter()
+                    .map(move |entry| entry.map(|(_, value)| value).map_err(read_error)),
+            );
+        };
+
+        let mut prefix = kind.key().into_bytes();
+        prefix.push(0);
+        let prefix_len = prefix.len();
+        Box::new(
+            self.kind_index
+                .scan_prefix(prefix)
+                .keys()
+                .filter_map(move |key| {
+                    let key = match key {
+                        Ok(key) => key,
+                        Err(err) => return Some(Err(read_error(err))),
+                    };
+                    self.contexts
+                        .get(&key[prefix_len..])
+                        .map_err(read_error)
+                        .transpose()
+                }),
+        )
+    }
+
+    /// Controls whether single writes are flushed immediately. Batch writes always flush.
+    pub fn with_sync_writes(mut self, sync_writes: bool) -> Self {
+        self.sync_writes = sync_writes;
+        self
+    }
+
+    /// Coalesce the flushes of synced single writes: a background thread
+    /// flushes at most every `window_ms` while writes are pending, or once
+    /// `max_writes` have accumulated. `0` flushes after every write again.
+    /// Explicit `flush` and `ping` calls still flush immediately.
+    pub fn with_flush_coalescing(
+        mut self,
+        window_ms: u64,
+        max_writes: usize,
+    ) -> Result<Self, DomainError> {
+        self.flusher = None;
+        if window_ms > 0 {
+            let flusher = CoalescingFlusher::spawn(
+                self.db.clone(),
+                Duration::from_millis(window_ms),
+                max_writes,
+            )
+            .map_err(|err| DomainError::storage(format!("failed to start flusher: {err}")))?;
+            self.flusher = Some(flusher);
+        }
+        Ok(self)
+    }
+
+    /// Synced writes acknowledged but still waiting for a coalesced flush.
+    pub fn unflushed_writes(&self) -> usize {
+        self.flusher.as_ref().map_or(0, CoalescingFlusher::pending)
+    }
+
+    /// Precision used for vectors written from now on. Existing records keep
+    /// their own precision until they are rewritten.
+    pub fn with_vector_precision(mut self, precision: VectorPrecision) -> Self {
+        self.vector_precision = precision;
+        self
+    }
+
+    /// Write vectors to the dedicated `embeddings` tree so metadata reads
+    /// (`recent`, `projects`, ...) never deserialize them. Applies to records
+    /// written from now on; both layouts are readable.
+    pub fn with_separate_embeddings(mut self, separate: bool) -> Self {
+        self.separate_embeddings = separate;
+        self
+    }
+
+    /// Searches kept in the query history; `0` stops recording. A smaller
+    /// capacity than the stored history trims it on the next recorded search.
+    pub fn with_query_history_capacity(mut self, capacity: usize) -> Self {
+        self.query_capacity = capacity;
+        self
+    }
+
+    fn flush_contexts(&self) -> Result<(), DomainError> {
+        let pending = self.unflushed_writes();
+        self.db
+            .flush()
+            .map_err(|err| DomainError::storage(format!("failed to flush contexts: {err}")))?;
+        if let Some(flusher) = &self.flusher {
+            flusher.mark_flushed(pending);
+        }
+        Ok(())
+    }
+
+    /// Make a single synced write durable, now or on the next coalesced flush.
+    fn flush_write(&self) -> Result<(), DomainError> {
+        if !self.sync_writes {
+            return Ok(());
+        }
+        match &self.flusher {
+            Some(flusher) => {
+                flusher.mark_dirty();
+                Ok(())
+            }
+            None => self.flush_contexts(),
+        }
+    }
+
+    /// Write records to `contexts` (and `embeddings`, `kind_index`,
+    /// `chunk_index`) in one transaction so the trees never disagree about a
+    /// record.
+    fn write_records(&self, records: &[&ContextRecord]) -> Result<(), DomainError> {
+        let mut entries = Vec::with_capacity(records.len());
+        for record in records {
+            let (context, vectors) = if self.separate_embeddings {
+                (
+                    Self::serialize_metadata(record)?,
+                    Some(Self::serialize_vectors(record, self.vector_precision)?),
+                )
+            } else {
+                (Self::serialize(record, self.vector_precision)?, None)
+            };
+            entries.push((Self::encode_key(&record.id), context, vectors));
+        }
+
+        (
+            &self.contexts,
+            &self.embeddings,
+            &self.kind_index,
+            &self.chunk_index,
+        )
+            .transaction(|(contexts, embeddings, kind_index, chunk_index)| {
+                for ((key, context, vectors), record) in entries.iter().zip(records) {
+                    let previous = contexts.insert(key, context.as_slice())?;
+                    match vectors {
+                        Some(vectors) => embeddings.insert(key, vectors.as_slice())?,
+                        None => embeddings.remove(key)?,
+                    };
+                    if let Some(previous) =
+                        previous.and_then(|bytes| Self::decode_record(&bytes).ok())
+                    {
+                        kind_index.remove(Self::kind_index_key(&previous.kind, &previous.id))?;
+                        if let Some(parent) = previous.parent_id {
+                            chunk_index
+                                .remove(&Self::chunk_index_key(&parent, &previous.id)[..])?;
+                        }
+                    }
+                    kind_index.insert(Self::kind_index_key(&record.kind, &record.id), &[])?;
+                    if let Some(parent) = record.parent_id {
+                        chunk_index.insert(&Self::chunk_index_key(&parent, &record.id)[..], &[])?;
+                    }
+                }
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|err: TransactionError<()>| {
+                DomainError::storage(format!("failed to persist context: {err:?}"))
+            })
+    }
+
+    /// Fill in vectors kept in the `embeddings` tree; inline records are left as-is.
+    fn load_vectors(&self, record: &mut ContextRecord) -> Result<(), DomainError> {
+        let Some(bytes) = self
+            .embeddings
+            .get(Self::encode_key(&record.id))
+            .map_err(|err| DomainError::storage(format!("failed to read embedding: {err}")))?
+        else {
+            return Ok(());
+        };
+
+        let vectors: StoredVectors = serde_json::from_slice(&bytes)
+            .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))?;
+
+        record.embedding.vector = vectors.embedding.decode()?;
+        if let (Some(embedding), Some(vector)) = (&mut record.summary_embedding, vectors.summary) {
+            embedding.vector = vector.decode()?;
+        }
+        if let (Some(embedding), Some(vector)) = (&mut record.body_embedding, vectors.body) {
+            embedding.vector = vector.decode()?;
+        }
+        Ok(())
+    }
+
+    fn read_count(&self, key: &[u8]) -> Result<u64, DomainError> {
+        let value = self
+            .term_stats
+            .get(key)
+            .map_err(|err| DomainError::storage(format!("failed to read term stats: {err}")))?;
+        Ok(value.map_or(0, |bytes| decode_count(&bytes)))
+    }
+
+    /// Terms `record` counts as one document for. Callers skip chunks, which
+    /// repeat their parent's text.
+    fn record_terms(record: &ContextRecord) -> BTreeSet<String> {
+        tokenize_terms(&record.summary)
+            .into_iter()
+            .chain(tokenize_terms(&record.body))
+            .collect()
+    }
+
+    /// Undo `record_term_stats` for a deleted record, dropping counts that
+    /// reach zero.
+    fn forget_terms(
+        term_stats: &TransactionalTree,
+        record: &ContextRecord,
+    ) -> Result<(), UnabortableTransactionError> {
+        let terms = Self::record_terms(record);
+        let keys = std::iter::once(TERM_STATS_DOCUMENTS_KEY)
+            .chain(terms.iter().map(|term| term.as_bytes()));
+        for key in keys {
+            let count = term_stats.get(key)?.map_or(0, |bytes| decode_count(&bytes));
+            match count.saturating_sub(1) {
+                0 => term_stats.remove(key)?,
+                count => term_stats.insert(key, &count.to_be_bytes()[..])?,
+            };
+        }
+        Ok(())
+    }
+
+    fn increment_count(&self, key: &[u8]) -> Result<(), DomainError> {
+        self.term_stats
+            .update_and_fetch(key, |old| {
+                Some((old.map_or(0, decode_count) + 1).to_be_bytes().to_vec())
+            })
+            .map_err(|err| DomainError::storage(format!("failed to update term stats: {err}")))?;
+        Ok(())
+    }
+
+    fn serialize_metadata(record: &ContextRecord) -> Result<Vec<u8>, DomainError> {
+        let mut stripped = record.clone();
+        stripped.embedding.vector.clear();
+        for embedding in [
+            &mut stripped.summary_embedding,
+            &mut stripped.body_embedding,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            embedding.vector.clear();
+        }
+
+        serde_json::to_vec(&StoredRecordRef {
+            record: &stripped,
+            quantized: None,
         })
+        .map_err(|err| DomainError::storage(format!("serialization error: {err}")))
     }
 
-    fn serialize<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, DomainError> {
-        bincode::options()
-            .with_fixint_encoding()
-            .allow_trailing_bytes()
-            .serialize(value)
-            .map_err(|err| DomainError::storage(format!("serialization error: {err}")))
+    fn serialize_vectors(
+        record: &ContextRecord,
+        precision: VectorPrecision,
+    ) -> Result<Vec<u8>, DomainError> {
+        let encode = |field: &Option<ContextEmbedding>| {
+            field
+                .as_ref()
+                .map(|embedding| StoredVector::encode(&embedding.vector, precision))
+        };
+
+        serde_json::to_vec(&StoredVectors {
+            embedding: StoredVector::encode(&record.embedding.vector, precision),
+            summary: encode(&record.summary_embedding),
+            body: encode(&record.body_embedding),
+        })
+        .map_err(|err| DomainError::storage(format!("serialization error: {err}")))
+    }
+
+    fn serialize(
+        record: &ContextRecord,
+        precision: VectorPrecision,
+    ) -> Result<Vec<u8>, DomainError> {
+        let result = match QuantizedVector::encode(&record.embedding.vector, precision) {
+            None => serde_json::to_vec(&StoredRecordRef {
+                record,
+                quantized: None,
+            }),
+            Some(embedding) => {
+                let quantize = |field: &Option<ContextEmbedding>| {
+                    field
+                        .as_ref()
+                        .and_then(|embedding| QuantizedVector::encode(&embedding.vector, precision))
+                };
+                let quantized = QuantizedEmbeddings {
+                    embedding,
+                    summary: quantize(&record.summary_embedding),
+                    body: quantize(&record.body_embedding),
+                };
+
+                let mut stripped = record.clone();
+                stripped.embedding.vector.clear();
+                for embedding in [
+                    &mut stripped.summary_embedding,
+                    &mut stripped.body_embedding,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    embedding.vector.clear();
+                }
+
+                serde_json::to_vec(&StoredRecordRef {
+                    record: &stripped,
+                    quantized: Some(quantized),
+                })
+            }
+        };
+
+        result.map_err(|err| DomainError::storage(format!("serialization error: {err}")))
     }
 
     fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DomainError> {
@@ -78,36 +799,12 @@ impl SledVectorStore {
     }
 
     fn decode_record(bytes: &IVec) -> Result<ContextRecord, DomainError> {
-        Self::deserialize(bytes.as_ref())
-    }
-
-    fn cosine_similarity(query: &[f32], candidate: &[f32]) -> Result<f32, DomainError> {
-        if query.len() != candidate.len() {
-            return Err(DomainError::embedding(format!(
-                "embedding dimension mismatch: query {} vs candidate {}",
-                query.len(),
-                candidate.len()
-            )));
+        if bytes.first() == Some(&JSON_RECORD_PREFIX) {
+            return serde_json::from_slice::<StoredRecord>(bytes.as_ref())
+                .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))?
+                .into_record();
         }
-
-        let mut dot = 0.0f32;
-        let mut q_norm = 0.0f32;
-        let mut c_norm = 0.0f32;
-
-        for (q, c) in query.iter().zip(candidate.iter()) {
-            dot += q * c;
-            q_norm += q * q;
-            c_norm += c * c;
-        }
-
-        let denom = q_norm.sqrt() * c_norm.sqrt();
-        if denom == 0.0 {
-            return Err(DomainError::embedding(
-                "cannot compute cosine similarity with zero vector",
-            ));
-        }
-
-        Ok((dot / denom).clamp(-1.0, 1.0))
+        Self::deserialize::<LegacyContextRecord>(bytes.as_ref()).map(ContextRecord::from)
     }
 
     fn record_matches_filters(record: &ContextRecord, filters: &QueryFilters) -> bool {
@@ -119,16 +816,86 @@ impl VectorStore for SledVectorStore {
     fn persist(&self, record: &ContextRecord) -> Result<(), DomainError> {
         let _guard = self.write_lock.lock();
 
-        let bytes = Self::serialize(record)?;
-        self.contexts
-            .insert(Self::encode_key(&record.id), bytes)
-            .map_err(|err| DomainError::storage(format!("failed to persist context: {err}")))?;
+        self.write_records(&[record])?;
+        self.flush_write()
+    }
+
+    fn persist_deferred(&self, record: &ContextRecord) -> Result<(), DomainError> {
+        let _guard = self.write_lock.lock();
+        self.write_records(&[record])
+    }
 
-        self.contexts
-            .flush()
-            .map_err(|err| DomainError::storage(format!("failed to flush contexts: {err}")))?;
+    fn delete_batch(&self, ids: &[Uuid]) -> Result<usize, DomainError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
 
-        Ok(())
+        let _guard = self.write_lock.lock();
+        let deleted_at = Utc::now();
+
+        let deleted = (
+            &self.contexts,
+            &self.embeddings,
+            &self.kind_index,
+            &self.chunk_index,
+            &self.tombstones,
+            &self.term_stats,
+        )
+            .transaction(
+                |(contexts, embeddings, kind_index, chunk_index, tombstones, term_stats)| {
+                    let mut deleted = 0;
+                    for id in ids {
+                        let key = Self::encode_key(id);
+                        let Some(previous) = contexts.remove(&key)? else {
+                            continue;
+                        };
+                        embeddings.remove(&key)?;
+                        deleted += 1;
+                        let project = match Self::decode_record(&previous) {
+                            Ok(record) => {
+                                kind_index.remove(Self::kind_index_key(&record.kind, id))?;
+                                if let Some(parent) = record.parent_id {
+                                    // Chunks never show up in listings, so clients
+                                    // have nothing to drop.
+                                    chunk_index.remove(&Self::chunk_index_key(&parent, id)[..])?;
+                                    continue;
+                                }
+                                Self::forget_terms(term_stats, &record)?;
+                                record.project
+                            }
+                            // Unreadable records have no trustworthy index entry; a
+                            // later `rebuild_index` drops whatever is left.
+                            Err(_) => String::new(),
+                        };
+                        let tombstone = serde_json::to_vec(&Tombstone {
+                            id: *id,
+                            project,
+                            deleted_at,
+                        })
+                        .map_err(|_| ConflictableTransactionError::Abort(()))?;
+                        tombstones.insert(&key, tombstone)?;
+                    }
+                    Ok::<_, ConflictableTransactionError<()>>(deleted)
+                },
+            )
+            .map_err(|err: TransactionError<()>| {
+                DomainError::storage(format!("failed to delete contexts: {err:?}"))
+            })?;
+
+        self.flush_contexts()?;
+        Ok(deleted)
+    }
+
+    fn persist_batch(&self, records: &[ContextRecord]) -> Result<(), DomainError> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let _guard = self.write_lock.lock();
+
+        self.write_records(&records.iter().collect::<Vec<_>>())?;
+
+        self.flush_contexts()
     }
 
     fn search(
@@ -136,34 +903,50 @@ impl VectorStore for SledVectorStore {
         embedding: &ContextEmbedding,
         limit: usize,
         filters: &QueryFilters,
-    ) -> Result<Vec<(ContextRecord, f32)>, DomainError> {
+        scope: SearchScope,
+    ) -> Result<SearchHits, DomainError> {
         let mut scored: Vec<(ContextRecord, f32)> = Vec::new();
+        let mut mismatched = Vec::new();
+        let mut scanned = 0;
 
-        for entry in self.contexts.iter() {
-            let (_, value) = entry.map_err(|err| {
-                DomainError::storage(format!("failed to read context record: {err}"))
-            })?;
-            let record = Self::decode_record(&value)?;
+        for value in self.candidates(filters) {
+            let mut record = Self::decode_record(&value?)?;
+            scanned += 1;
 
             if !Self::record_matches_filters(&record, filters) {
                 continue;
             }
+            self.load_vectors(&mut record)?;
 
-            let score = Self::cosine_similarity(&embedding.vector, &record.embedding.vector)?;
+            let vector = &record.embedding_for(scope).vector;
+            if vector.len() != embedding.vector.len() {
+                mismatched.push(record.id);
+                continue;
+            }
+            let score = scoring::cosine_similarity(&embedding.vector, vector)?;
 
             scored.push((record, score));
         }
 
+        let matched_filters = scored.len() + mismatched.len();
         scored.sort_by(|a, b| b.1.total_cmp(&a.1));
         scored.truncate(limit);
 
-        Ok(scored)
+        Ok(SearchHits {
+            matches: scored,
+            scanned,
+            matched_filters,
+            mismatched,
+        })
     }
 
     fn recent(
         &self,
         project: Option<&str>,
+        tag: Option<&str>,
+        since: Option<DateTime<Utc>>,
         limit: usize,
+        order: HistoryOrder,
     ) -> Result<Vec<ContextSummary>, DomainError> {
         let mut items: Vec<ContextSummary> = Vec::new();
 
@@ -172,6 +955,9 @@ impl VectorStore for SledVectorStore {
                 DomainError::storage(format!("failed to read context record: {err}"))
             })?;
             let record = Self::decode_record(&value)?;
+            if record.is_chunk() {
+                continue;
+            }
 
             if let Some(project_ref) = project {
                 if record.project != project_ref {
@@ -179,15 +965,36 @@ impl VectorStore for SledVectorStore {
                 }
             }
 
+            if let Some(tag_ref) = tag {
+                if !record.tags.iter().any(|t| t == tag_ref) {
+                    continue;
+                }
+            }
+
+            if since.is_some_and(|since| record.created_at <= since) {
+                continue;
+            }
+
             items.push(record.as_summary());
         }
 
-        items.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        items.sort_by(|a, b| order.compare(a, b));
         items.truncate(limit);
 
         Ok(items)
     }
 
+    fn iter_summaries(
+        &self,
+    ) -> Box<dyn Iterator<Item = Result<ContextSummary, DomainError>> + Send + '_> {
+        Box::new(self.contexts.iter().map(|entry| {
+            let (_, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            Ok(Self::decode_record(&value)?.as_summary())
+        }))
+    }
+
     fn projects(&self) -> Result<Vec<String>, DomainError> {
         let mut unique = BTreeSet::new();
 
@@ -202,11 +1009,1016 @@ impl VectorStore for SledVectorStore {
         Ok(unique.into_iter().collect())
     }
 
-    fn ping(&self) -> Result<(), DomainError> {
-        self.db
-            .flush()
-            .map_err(|err| DomainError::storage(format!("failed to flush db: {err}")))?;
+    fn flush(&self) -> Result<(), DomainError> {
+        self.flush_contexts()
+    }
+
+    fn chunk_ids(&self, parents: &[Uuid]) -> Result<Vec<Uuid>, DomainError> {
+        let mut ids = Vec::new();
+        for parent in parents {
+            for key in self.chunk_index.scan_prefix(parent.as_bytes()).keys() {
+                let key = key.map_err(|err| {
+                    DomainError::storage(format!("failed to read chunk index: {err}"))
+                })?;
+                ids.push(Uuid::from_slice(&key[16..]).map_err(|err| {
+                    DomainError::storage(format!("corrupt chunk index key: {err}"))
+                })?);
+            }
+        }
+        Ok(ids)
+    }
+
+    fn set_pinned(&self, id: Uuid, pinned: bool) -> Result<ContextSummary, DomainError> {
+        let _guard = self.write_lock.lock();
+
+        let key = Self::encode_key(&id);
+        let value = self
+            .contexts
+            .get(key)
+            .map_err(|err| DomainError::storage(format!("failed to read context record: {err}")))?
+            .ok_or_else(|| DomainError::not_found(format!("context {id}")))?;
+
+        let mut record = Self::decode_record(&value)?;
+        self.load_vectors(&mut record)?;
+        record.pinned = pinned;
+        record.touch();
+
+        self.write_records(&[&record])?;
+        self.flush_write()?;
 
+        Ok(record.as_summary())
+    }
+
+    fn changes_since(
+        &self,
+        since: DateTime<Utc>,
+        include_deleted: bool,
+    ) -> Result<Vec<ChangeEvent>, DomainError> {
+        let mut events = Vec::new();
+
+        for entry in self.contexts.iter() {
+            let (_, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            let record = Self::decode_record(&value)?;
+            if record.is_chunk() {
+                continue;
+            }
+
+            let event = if record.created_at > since {
+                Some((ChangeKind::Insert, record.created_at))
+            } else {
+                record
+                    .updated_at
+                    .filter(|updated_at| *updated_at > since)
+                    .map(|updated_at| (ChangeKind::Update, updated_at))
+            };
+
+            if let Some((change, at)) = event {
+                events.push(ChangeEvent {
+                    id: record.id,
+                    change,
+                    at,
+                    summary: Some(record.as_summary()),
+                });
+            }
+        }
+
+        if include_deleted {
+            for entry in self.tombstones.iter() {
+                let (_, value) = entry.map_err(|err| {
+                    DomainError::storage(format!("failed to read tombstone: {err}"))
+                })?;
+                let tombstone: Tombstone = serde_json::from_slice(&value)
+                    .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))?;
+
+                if tombstone.deleted_at > since {
+                    events.push(ChangeEvent {
+                        id: tombstone.id,
+                        change: ChangeKind::Delete,
+                        at: tombstone.deleted_at,
+                        summary: None,
+                    });
+                }
+            }
+        }
+
+        events.sort_by_key(|event| event.at);
+        Ok(events)
+    }
+
+    fn scan_records(
+        &self,
+        visit: &mut dyn FnMut(Result<ContextRecord, DomainError>),
+    ) -> Result<(), DomainError> {
+        for entry in self.contexts.iter() {
+            let (_, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            visit(Self::decode_record(&value).and_then(|mut record| {
+                self.load_vectors(&mut record)?;
+                Ok(record)
+            }));
+        }
         Ok(())
     }
+
+    fn fetch_records(&self, ids: &[Uuid]) -> Result<Vec<ContextRecord>, DomainError> {
+        let mut records = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let value = self.contexts.get(Self::encode_key(id)).map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            if let Some(value) = value {
+                let mut record = Self::decode_record(&value)?;
+                self.load_vectors(&mut record)?;
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn project_records(&self, project: &str) -> Result<Vec<ContextRecord>, DomainError> {
+        let mut records = Vec::new();
+
+        for entry in self.contexts.iter() {
+            let (_, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            let mut record = Self::decode_record(&value)?;
+            if record.project == project {
+                self.load_vectors(&mut record)?;
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn rebuild_term_stats(&self) -> Result<usize, DomainError> {
+        let _guard = self.write_lock.lock();
+
+        let mut documents = 0u64;
+        let mut frequencies: HashMap<String, u64> = HashMap::new();
+        for entry in self.contexts.iter() {
+            let (_, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            let record = Self::decode_record(&value)?;
+            if record.is_chunk() {
+                continue;
+            }
+            documents += 1;
+
+            for term in Self::record_terms(&record) {
+                *frequencies.entry(term).or_default() += 1;
+            }
+        }
+
+        // Stale terms are removed in the same batch, so readers see either
+        // the old statistics or the new ones.
+        let mut batch = Batch::default();
+        for key in self.term_stats.iter().keys() {
+            let key = key
+                .map_err(|err| DomainError::storage(format!("failed to read term stats: {err}")))?;
+            // The documents key is rewritten below.
+            let stale =
+                std::str::from_utf8(&key).map_or(true, |term| !frequencies.contains_key(term));
+            if stale {
+                batch.remove(key);
+            }
+        }
+        batch.insert(TERM_STATS_DOCUMENTS_KEY, &documents.to_be_bytes());
+        for (term, count) in frequencies {
+            batch.insert(term.as_bytes(), &count.to_be_bytes());
+        }
+
+        self.term_stats
+            .apply_batch(batch)
+            .map_err(|err| DomainError::storage(format!("failed to write term stats: {err}")))?;
+        self.flush_contexts()?;
+
+        Ok(documents as usize)
+    }
+
+    fn record_term_stats(&self, terms: &BTreeSet<String>) -> Result<(), DomainError> {
+        self.increment_count(TERM_STATS_DOCUMENTS_KEY)?;
+        for term in terms {
+            self.increment_count(term.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn keyword_search(
+        &self,
+        terms: &[String],
+        limit: usize,
+        filters: &QueryFilters,
+    ) -> Result<Vec<(ContextRecord, f32)>, DomainError> {
+        let documents = self.read_count(TERM_STATS_DOCUMENTS_KEY)?;
+        let weights = terms
+            .iter()
+            .map(|term| {
+                let frequency = if documents == 0 {
+                    0
+                } else {
+                    self.read_count(term.as_bytes())?
+                };
+                Ok((term.as_str(), scoring::idf_weight(documents, frequency)))
+            })
+            .collect::<Result<HashMap<&str, f32>, DomainError>>()?;
+
+        let mut scored: Vec<(ContextRecord, f32)> = Vec::new();
+        for value in self.candidates(filters) {
+            let record = Self::decode_record(&value?)?;
+
+            if !Self::record_matches_filters(&record, filters) {
+                continue;
+            }
+
+            if let Some(score) = scoring::keyword_score(&record, &weights) {
+                scored.push((record, score));
+            }
+        }
+
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+
+        Ok(scored)
+    }
+
+    fn rebuild_index(&self) -> Result<usize, DomainError> {
+        let _guard = self.write_lock.lock();
+        let indexed = self.rebuild_secondary_indexes()?;
+        self.flush_contexts()?;
+        Ok(indexed)
+    }
+
+    fn prune_tombstones(&self, before: DateTime<Utc>) -> Result<usize, DomainError> {
+        let _guard = self.write_lock.lock();
+        let mut batch = Batch::default();
+        let mut pruned = 0;
+        for entry in self.tombstones.iter() {
+            let (key, value) = entry
+                .map_err(|err| DomainError::storage(format!("failed to read tombstone: {err}")))?;
+            let tombstone: Tombstone = serde_json::from_slice(&value)
+                .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))?;
+            if tombstone.deleted_at < before {
+                batch.remove(key);
+                pruned += 1;
+            }
+        }
+        self.tombstones
+            .apply_batch(batch)
+            .map_err(|err| DomainError::storage(format!("failed to prune tombstones: {err}")))?;
+        self.flush_contexts()?;
+        Ok(pruned)
+    }
+
+    fn kind_counts(&self) -> Result<Vec<(String, usize)>, DomainError> {
+        // Chunks stay in the kind index so kind filters reach them, but they
+        // are not records of their own.
+        let chunks = self.chunk_id_set()?;
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for key in self.kind_index.iter().keys() {
+            let key = key
+                .map_err(|err| DomainError::storage(format!("failed to read kind index: {err}")))?;
+            let Some(end) = key.iter().position(|byte| *byte == 0) else {
+                continue;
+            };
+            if Uuid::from_slice(&key[end + 1..]).is_ok_and(|id| chunks.contains(&id)) {
+                continue;
+            }
+            let kind = String::from_utf8_lossy(&key[..end]);
+            // Keys are sorted, so equal kinds are adjacent.
+            match counts.last_mut() {
+                Some((last, count)) if *last == kind => *count += 1,
+                _ => counts.push((kind.into_owned(), 1)),
+            }
+        }
+        Ok(counts)
+    }
+
+    fn store_composition(&self) -> Result<Vec<EmbeddingComposition>, DomainError> {
+        let mut histogram: BTreeMap<(String, Option<String>, usize), usize> = BTreeMap::new();
+
+        for entry in self.contexts.iter() {
+            let (_, value) = entry.map_err(|err| {
+                DomainError::storage(format!("failed to read context record: {err}"))
+            })?;
+            let mut record = Self::decode_record(&value)?;
+            if !record.searchable {
+                continue;
+            }
+            self.load_vectors(&mut record)?;
+            let dims = record.embedding.dims();
+            *histogram
+                .entry((record.embedding.model, record.embedding.model_version, dims))
+                .or_default() += 1;
+        }
+
+        Ok(histogram
+            .into_iter()
+            .map(
+                |((model, model_version, dimensions), count)| EmbeddingComposition {
+                    model,
+                    model_version,
+                    dimensions,
+                    count,
+                },
+            )
+            .collect())
+    }
+
+    fn record_query(&self, entry: &QueryHistoryEntry) -> Result<(), DomainError> {
+        if self.query_capacity == 0 {
+            return Ok(());
+        }
+        let bytes = serde_json::to_vec(entry)
+            .map_err(|err| DomainError::storage(format!("serialization error: {err}")))?;
+        let write_error =
+            |err: sled::Error| DomainError::storage(format!("failed to record query: {err}"));
+
+        let _guard = self.history_lock.lock();
+        let key = self.query_next.fetch_add(1, Ordering::SeqCst);
+        self.queries
+            .insert(key.to_be_bytes(), bytes)
+            .map_err(write_error)?;
+
+        // Keys are contiguous, so each insert normally evicts exactly one
+        // entry; a lowered capacity drains the excess once.
+        let floor = (key + 1).saturating_sub(self.query_capacity as u64);
+        for stale in self.query_floor.load(Ordering::SeqCst)..floor {
+            self.queries
+                .remove(stale.to_be_bytes())
+                .map_err(write_error)?;
+        }
+        self.query_floor.fetch_max(floor, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn recent_queries(&self, limit: usize) -> Result<Vec<QueryHistoryEntry>, DomainError> {
+        self.queries
+            .iter()
+            .values()
+            .rev()
+            .take(limit)
+            .map(|value| {
+                let value = value.map_err(|err| {
+                    DomainError::storage(format!("failed to read query history: {err}"))
+                })?;
+                serde_json::from_slice(&value)
+                    .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))
+            })
+            .collect()
+    }
+
+    fn clear_query_history(&self) -> Result<usize, DomainError> {
+        let _guard = self.history_lock.lock();
+        let cleared = self.queries.len();
+        self.queries
+            .clear()
+            .map_err(|err| DomainError::storage(format!("failed to clear query history: {err}")))?;
+        self.query_floor
+            .store(self.query_next.load(Ordering::SeqCst), Ordering::SeqCst);
+        Ok(cleared)
+    }
+
+    fn record_search_event(&self, event: &SearchEvent) -> Result<(), DomainError> {
+        let bytes = serde_json::to_vec(event)
+            .map_err(|err| DomainError::storage(format!("serialization error: {err}")))?;
+
+        let _guard = self.history_lock.lock();
+        let key = self.feedback_next.fetch_add(1, Ordering::SeqCst);
+        let floor = (key + 1).saturating_sub(SEARCH_EVENT_CAPACITY as u64);
+        let stale = self.feedback_floor.load(Ordering::SeqCst)..floor;
+        (&self.feedback, &self.feedback_ids)
+            .transaction(|(feedback, feedback_ids)| {
+                feedback.insert(&key.to_be_bytes()[..], bytes.as_slice())?;
+                feedback_ids.insert(&event.query_id.as_bytes()[..], &key.to_be_bytes()[..])?;
+                for stale in stale.clone() {
+                    if let Some(evicted) = feedback.remove(&stale.to_be_bytes()[..])? {
+                        if let Ok(evicted) = serde_json::from_slice::<SearchEvent>(&evicted) {
+                            feedback_ids.remove(&evicted.query_id.as_bytes()[..])?;
+                        }
+                    }
+                }
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|err: TransactionError<()>| {
+                DomainError::storage(format!("failed to record search event: {err:?}"))
+            })?;
+        self.feedback_floor.fetch_max(floor, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn record_feedback(&self, query_id: Uuid, chosen_id: Uuid) -> Result<SearchEvent, DomainError> {
+        let read_error =
+            |err: sled::Error| DomainError::storage(format!("failed to read search events: {err}"));
+
+        let _guard = self.history_lock.lock();
+        let not_found = || DomainError::not_found(format!("search {query_id}"));
+        let key = self
+            .feedback_ids
+            .get(query_id.as_bytes())
+            .map_err(read_error)?
+            .ok_or_else(not_found)?;
+        let value = self
+            .feedback
+            .get(&key)
+            .map_err(read_error)?
+            .ok_or_else(not_found)?;
+        let mut event: SearchEvent = serde_json::from_slice(&value)
+            .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))?;
+        if event.choose(chosen_id) {
+            let bytes = serde_json::to_vec(&event)
+                .map_err(|err| DomainError::storage(format!("serialization error: {err}")))?;
+            self.feedback
+                .insert(key, bytes)
+                .map_err(|err| DomainError::storage(format!("failed to record feedback: {err}")))?;
+        }
+        Ok(event)
+    }
+
+    fn search_events(&self) -> Result<Vec<SearchEvent>, DomainError> {
+        self.feedback
+            .iter()
+            .values()
+            .map(|value| {
+                let value = value.map_err(|err| {
+                    DomainError::storage(format!("failed to read search events: {err}"))
+                })?;
+                serde_json::from_slice(&value)
+                    .map_err(|err| DomainError::storage(format!("deserialization error: {err}")))
+            })
+            .collect()
+    }
+
+    fn clear_search_events(&self) -> Result<usize, DomainError> {
+        let clear_error = |err: sled::Error| {
+            DomainError::storage(format!("failed to clear search events: {err}"))
+        };
+
+        let _guard = self.history_lock.lock();
+        let cleared = self.feedback.len();
+        self.feedback.clear().map_err(clear_error)?;
+        self.feedback_ids.clear().map_err(clear_error)?;
+        self.feedback_floor
+            .store(self.feedback_next.load(Ordering::SeqCst), Ordering::SeqCst);
+        Ok(cleared)
+    }
+
+    fn disk_usage(&self) -> Result<Option<u64>, DomainError> {
+        dir_size(&self.data_dir)
+            .map(Some)
+            .map_err(|err| DomainError::storage(format!("failed to measure store size: {err}")))
+    }
+
+    fn ping(&self) -> Result<(), DomainError> {
+        self.flush_contexts()
+    }
+}
+
+fn decode_count(bytes: &[u8]) -> u64 {
+    bytes.try_into().map(u64::from_be_bytes).unwrap_or(0)
+}
+
+/// Total size of the files under `dir`, recursively.
+fn dir_size(dir: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        total += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_legacy_bincode_record() {
+        #[derive(serde::Serialize)]
+        struct LegacyEmbedding<'a> {
+            model: &'a str,
+            vector: Vec<f32>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Legacy<'a> {
+            id: Uuid,
+            project: &'a str,
+            ide: &'a str,
+            file_path: Option<String>,
+            language: Option<String>,
+            summary: &'a str,
+            body: &'a str,
+            tags: Vec<String>,
+            kind: ContextKind,
+            embedding: LegacyEmbedding<'a>,
+            created_at: DateTime<Utc>,
+        }
+
+        let legacy = Legacy {
+            id: Uuid::new_v4(),
+            project: "ingat",
+            ide: "vscode",
+            file_path: None,
+            language: Some("rust".into()),
+            summary: "summary",
+            body: "body",
+            tags: vec!["storage".into()],
+            kind: ContextKind::FixHistory,
+            embedding: LegacyEmbedding {
+                model: "ingat/simple-hash",
+                vector: vec![0.5, 0.5],
+            },
+            created_at: Utc::now(),
+        };
+        let bytes = bincode::options()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .serialize(&legacy)
+            .unwrap();
+
+        let record = SledVectorStore::decode_record(&IVec::from(bytes)).unwrap();
+        assert_eq!(record.id, legacy.id);
+        assert_eq!(record.embedding.dims(), 2);
+        assert!(record.embedding.model_version.is_none());
+    }
+
+    #[test]
+    fn test_store_format_version_is_checked_on_open() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let store = SledVectorStore::open(&dir).unwrap();
+        let stored = store.db.get(FORMAT_VERSION_KEY).unwrap().unwrap();
+        assert_eq!(decode_count(&stored), STORE_FORMAT_VERSION);
+
+        // A store written by a newer release is refused rather than misread.
+        store
+            .db
+            .insert(
+                FORMAT_VERSION_KEY,
+                &(STORE_FORMAT_VERSION + 1).to_be_bytes(),
+            )
+            .unwrap();
+        store.db.flush().unwrap();
+        drop(store);
+        assert!(SledVectorStore::open(&dir).is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_json_record_roundtrip() {
+        let record = ContextRecord::new(
+            "ingat",
+            "vscode",
+            None::<String>,
+            None::<String>,
+            "summary",
+            "body",
+            ["tag"],
+            ContextKind::CodeSnippet,
+            ContextEmbedding::new("ingat/simple-hash", vec![1.0, 0.0])
+                .with_version(Some("ahash-v1".into())),
+        );
+
+        let bytes = SledVectorStore::serialize(&record, VectorPrecision::F32).unwrap();
+        let decoded = SledVectorStore::decode_record(&IVec::from(bytes)).unwrap();
+        assert_eq!(decoded.id, record.id);
+        assert_eq!(decoded.embedding.model_version.as_deref(), Some("ahash-v1"));
+
+        let bytes = SledVectorStore::serialize(&record, VectorPrecision::Int8).unwrap();
+        let decoded = SledVectorStore::decode_record(&IVec::from(bytes)).unwrap();
+        assert_eq!(decoded.embedding.vector, vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_kind_index_answers_kind_filters() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let store = SledVectorStore::open(&dir).unwrap();
+
+        for kind in [
+            ContextKind::CodeSnippet,
+            ContextKind::Other("release-notes".into()),
+            ContextKind::Other("release-notes".into()),
+        ] {
+            let record = ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                "summary",
+                "body",
+                ["tag"],
+                kind,
+                ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+            );
+            store.persist(&record).unwrap();
+        }
+
+        let mut counts = store.kind_counts().unwrap();
+        counts.sort();
+        assert_eq!(
+            counts,
+            vec![("code-snippet".into(), 1), ("release-notes".into(), 2)]
+        );
+
+        let filters = QueryFilters {
+            kind: Some(ContextKind::Other("Release Notes".into())),
+            ..QueryFilters::default()
+        };
+        let hits = store
+            .search(
+                &ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+                5,
+                &filters,
+                SearchScope::Both,
+            )
+            .unwrap();
+        assert_eq!(hits.matches.len(), 2);
+        // Only the indexed candidates are read, not the code snippet.
+        assert_eq!((hits.scanned, hits.matched_filters), (2, 2));
+
+        store.kind_index.clear().unwrap();
+        assert_eq!(store.rebuild_index().unwrap(), 3);
+        assert_eq!(store.kind_counts().unwrap().len(), 2);
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_delete_batch_removes_records_and_leaves_tombstones() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let store = SledVectorStore::open(&dir)
+            .unwrap()
+            .with_separate_embeddings(true);
+
+        let records: Vec<ContextRecord> = (0..3)
+            .map(|_| {
+                ContextRecord::new(
+                    "ingat",
+                    "vscode",
+                    None::<String>,
+                    None::<String>,
+                    "summary",
+                    "body",
+                    ["tag"],
+                    ContextKind::FixHistory,
+                    ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+                )
+            })
+            .collect();
+        store.persist_batch(&records).unwrap();
+        let since = Utc::now() - chrono::Duration::seconds(1);
+
+        let deleted = store
+            .delete_batch(&[records[0].id, Uuid::new_v4(), records[2].id])
+            .unwrap();
+        assert_eq!(deleted, 2);
+        assert_eq!(store.contexts.len(), 1);
+        assert_eq!(store.embeddings.len(), 1);
+        assert_eq!(
+            store.kind_counts().unwrap(),
+            vec![("fix-history".into(), 1)]
+        );
+
+        let deletes = store
+            .changes_since(since, true)
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.change == ChangeKind::Delete)
+            .count();
+        assert_eq!(deletes, 2);
+
+        // Compaction forgets deletions older than the horizon only.
+        assert_eq!(store.prune_tombstones(since).unwrap(), 0);
+        let horizon = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(store.prune_tombstones(horizon).unwrap(), 2);
+        assert!(store.tombstones.is_empty());
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_chunk_index_tracks_chunks_and_keeps_them_out_of_listings() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let store = SledVectorStore::open(&dir).unwrap();
+        let since = Utc::now() - chrono::Duration::seconds(1);
+
+        let record = |body: &str| {
+            ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                "summary",
+                body,
+                ["tag"],
+                ContextKind::FixHistory,
+                ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+            )
+        };
+        let parent = record("first half second half");
+        let chunks: Vec<ContextRecord> = ["first half", "second half"]
+            .into_iter()
+            .map(|body| {
+                let mut chunk = record(body);
+                chunk.parent_id = Some(parent.id);
+                chunk
+            })
+            .collect();
+        let mut batch = vec![parent.clone()];
+        batch.extend(chunks.iter().cloned());
+        store.persist_batch(&batch).unwrap();
+
+        let mut expected: Vec<Uuid> = chunks.iter().map(|chunk| chunk.id).collect();
+        expected.sort();
+        let mut found = store.chunk_ids(&[parent.id]).unwrap();
+        found.sort();
+        assert_eq!(found, expected);
+        assert_eq!(
+            store.kind_counts().unwrap(),
+            vec![("fix-history".into(), 1)]
+        );
+        let changes = store.changes_since(since, true).unwrap();
+        assert_eq!(changes.len(), 1);
+
+        store.chunk_index.clear().unwrap();
+        assert_eq!(store.rebuild_index().unwrap(), 1);
+        assert_eq!(store.chunk_ids(&[parent.id]).unwrap().len(), 2);
+
+        let mut ids = vec![parent.id];
+        ids.extend(expected);
+        assert_eq!(store.delete_batch(&ids).unwrap(), 3);
+        assert!(store.chunk_index.is_empty());
+        // Only the parent leaves a tombstone.
+        assert_eq!(store.tombstones.len(), 1);
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_separate_embeddings_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let store = SledVectorStore::open(&dir)
+            .unwrap()
+            .with_separate_embeddings(true);
+
+        let record = ContextRecord::new(
+            "ingat",
+            "vscode",
+            None::<String>,
+            None::<String>,
+            "summary",
+            "body",
+            ["tag"],
+            ContextKind::CodeSnippet,
+            ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+        );
+        store.persist(&record).unwrap();
+
+        let stored = store.contexts.get(record.id.as_bytes()).unwrap().unwrap();
+        assert!(SledVectorStore::decode_record(&stored)
+            .unwrap()
+            .embedding
+            .vector
+            .is_empty());
+
+        let results = store
+            .search(
+                &record.embedding,
+                5,
+                &QueryFilters::default(),
+                SearchScope::Both,
+            )
+            .unwrap()
+            .matches;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.embedding.vector, vec![0.6, 0.8]);
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_disk_usage_counts_store_files() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let store = SledVectorStore::open(&dir).unwrap();
+        store
+            .persist(&ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                "summary",
+                "body",
+                ["tag"],
+                ContextKind::CodeSnippet,
+                ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+            ))
+            .unwrap();
+
+        let bytes = store.disk_usage().unwrap().unwrap();
+        assert!(bytes > 0);
+        assert_eq!(bytes, dir_size(&dir).unwrap());
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_keyword_search_weights_rare_terms() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let store = SledVectorStore::open(&dir).unwrap();
+
+        let record = |summary: &str, body: &str| {
+            ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                summary,
+                body,
+                Vec::<String>::new(),
+                ContextKind::CodeSnippet,
+                ContextEmbedding::new("ingat/simple-hash", vec![1.0]),
+            )
+        };
+        let common = record("fix storage bug", "storage layer storage code");
+        let rare = record("fix tokenizer bug", "storage tokenizer code");
+        store.persist(&common).unwrap();
+        store.persist(&rare).unwrap();
+        assert_eq!(store.rebuild_term_stats().unwrap(), 2);
+
+        let results = store
+            .keyword_search(&["tokenizer".into()], 5, &QueryFilters::default())
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.id, rare.id);
+
+        // Deleting a record takes its terms out of the statistics.
+        store.delete_batch(&[rare.id]).unwrap();
+        assert_eq!(store.read_count(TERM_STATS_DOCUMENTS_KEY).unwrap(), 1);
+        assert_eq!(store.read_count(b"tokenizer").unwrap(), 0);
+        assert_eq!(store.read_count(b"storage").unwrap(), 1);
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_query_history_evicts_oldest_and_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let entry = |n: usize| QueryHistoryEntry {
+            prompt: format!("query {n}"),
+            results: n,
+            searched_at: Utc::now(),
+        };
+
+        let store = SledVectorStore::open(&dir)
+            .unwrap()
+            .with_query_history_capacity(3);
+        for n in 0..5 {
+            store.record_query(&entry(n)).unwrap();
+        }
+        let prompts: Vec<String> = store
+            .recent_queries(10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.prompt)
+            .collect();
+        assert_eq!(prompts, ["query 4", "query 3", "query 2"]);
+        drop(store);
+
+        let store = SledVectorStore::open(&dir)
+            .unwrap()
+            .with_query_history_capacity(2);
+        store.record_query(&entry(5)).unwrap();
+        let recent = store.recent_queries(10).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].prompt, "query 5");
+        assert_eq!(recent[1].prompt, "query 4");
+
+        assert_eq!(store.clear_query_history().unwrap(), 2);
+        assert!(store.recent_queries(10).unwrap().is_empty());
+        store.record_query(&entry(6)).unwrap();
+        assert_eq!(store.recent_queries(10).unwrap().len(), 1);
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_search_feedback_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let returned = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let event = SearchEvent {
+            query_id: Uuid::new_v4(),
+            prompt: "retry".into(),
+            returned: returned.clone(),
+            searched_at: Utc::now(),
+            chosen: Vec::new(),
+        };
+
+        let store = SledVectorStore::open(&dir).unwrap();
+        store.record_search_event(&event).unwrap();
+        store
+            .record_search_event(&SearchEvent {
+                query_id: Uuid::new_v4(),
+                ..event.clone()
+            })
+            .unwrap();
+        let updated = store.record_feedback(event.query_id, returned[1]).unwrap();
+        assert_eq!(updated.chosen[0].rank, Some(1));
+        assert!(store.record_feedback(Uuid::new_v4(), returned[0]).is_err());
+        drop(store);
+
+        let store = SledVectorStore::open(&dir).unwrap();
+        let events = store.search_events().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].query_id, event.query_id);
+        assert_eq!(events[0].chosen.len(), 1);
+        assert!(events[1].chosen.is_empty());
+        // The query id index is persisted along with the events.
+        let again = store.record_feedback(event.query_id, returned[0]).unwrap();
+        assert_eq!(again.chosen.len(), 2);
+
+        assert_eq!(store.clear_search_events().unwrap(), 2);
+        assert!(store.record_feedback(event.query_id, returned[0]).is_err());
+        store.record_search_event(&event).unwrap();
+        assert_eq!(store.search_events().unwrap().len(), 1);
+        assert!(store.record_feedback(event.query_id, returned[0]).is_ok());
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_flush_coalescing_batches_synced_writes() {
+        let dir = std::env::temp_dir().join(format!("ingat-sled-{}", Uuid::new_v4()));
+        let record = |n: usize| {
+            ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                format!("summary {n}"),
+                "body",
+                ["tag"],
+                ContextKind::CodeSnippet,
+                ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+            )
+        };
+
+        // A long window leaves the burst pending until something forces a flush.
+        let store = SledVectorStore::open(&dir)
+            .unwrap()
+            .with_flush_coalescing(60_000, 100)
+            .unwrap();
+        for n in 0..3 {
+            store.persist(&record(n)).unwrap();
+        }
+        assert_eq!(store.unflushed_writes(), 3);
+        store.ping().unwrap();
+        assert_eq!(store.unflushed_writes(), 0);
+
+        // Reaching the write threshold flushes without waiting for the window.
+        let store = store.with_flush_coalescing(60_000, 2).unwrap();
+        store.persist(&record(3)).unwrap();
+        store.persist(&record(4)).unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while store.unflushed_writes() > 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(store.unflushed_writes(), 0);
+
+        // An explicit flush does not wait for the window.
+        store.persist(&record(5)).unwrap();
+        assert_eq!(store.unflushed_writes(), 1);
+        store.flush().unwrap();
+        assert_eq!(store.unflushed_writes(), 0);
+
+        // Dropping the store flushes what is still pending.
+        store.persist(&record(6)).unwrap();
+        assert_eq!(store.unflushed_writes(), 1);
+        drop(store);
+        let store = SledVectorStore::open(&dir).unwrap();
+        assert_eq!(store.iter_summaries().count(), 7);
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,145 @@
+//! Process-level guard around the embedded store.
+//!
+//! sled refuses a second opener with an IO error, which used to surface as a
+//! startup crash when two windows (or the UI and mcp-service) raced into local
+//! mode. The first local opener writes its PID to `ingat.lock` in the data
+//! directory; later openers see a live holder and fall back to remote mode.
+//! Clean shutdowns remove the file with [`release`]; one left behind by a
+//! crash names a PID that is no longer running and is taken over as stale.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+pub const LOCK_FILENAME: &str = "ingat.lock";
+
+/// Outcome of trying to take the data directory lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceLock {
+    Acquired,
+    /// Another live process owns the store.
+    HeldBy(u32),
+}
+
+/// Take the lock for this process, replacing a stale one left by a dead process.
+pub fn acquire(data_dir: &Path) -> io::Result<InstanceLock> {
+    let path = data_dir.join(LOCK_FILENAME);
+    let own_pid = std::process::id();
+
+    // Two attempts: the second follows removal of a stale lock.
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                write!(file, "{own_pid}")?;
+                return Ok(InstanceLock::Acquired);
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                let holder = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|raw| raw.trim().parse::<u32>().ok());
+                match holder {
+                    Some(pid) if pid == own_pid => return Ok(InstanceLock::Acquired),
+                    Some(pid) if process_alive(pid) => return Ok(InstanceLock::HeldBy(pid)),
+                    _ => match fs::remove_file(&path) {
+                        Ok(()) => continue,
+                        Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                        Err(err) => return Err(err),
+                    },
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    // Lost a race with another process taking over the same stale lock.
+    let holder = fs::read_to_string(&path)?
+        .trim()
+        .parse::<u32>()
+        .unwrap_or(0);
+    Ok(InstanceLock::HeldBy(holder))
+}
+
+/// Drop the lock if this process holds it; a lock owned by anyone else is left alone.
+pub fn release(data_dir: &Path) -> io::Result<()> {
+    let path = data_dir.join(LOCK_FILENAME);
+    let holder = match fs::read_to_string(&path) {
+        Ok(raw) => raw.trim().parse::<u32>().ok(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if holder != Some(std::process::id()) {
+        return Ok(());
+    }
+    match fs::remove_file(&path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    use nix::{errno::Errno, sys::signal::kill, unistd::Pid};
+
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks existence; EPERM means it exists but isn't ours.
+    matches!(kill(Pid::from_raw(pid), None), Ok(()) | Err(Errno::EPERM))
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use windows::Win32::{
+        Foundation::{CloseHandle, STILL_ACTIVE},
+        System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+    };
+
+    unsafe {
+        let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return false;
+        };
+        let mut code = 0u32;
+        let alive = GetExitCodeProcess(handle, &mut code).is_ok() && code == STILL_ACTIVE.0 as u32;
+        let _ = CloseHandle(handle);
+        alive
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let dir = std::env::temp_dir().join(format!("ingat-lock-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(acquire(&dir).unwrap(), InstanceLock::Acquired);
+        // Re-acquiring from the same process is a no-op.
+        assert_eq!(acquire(&dir).unwrap(), InstanceLock::Acquired);
+
+        // PIDs this large are never handed out, so the holder counts as dead.
+        fs::write(dir.join(LOCK_FILENAME), "4294967294").unwrap();
+        assert_eq!(acquire(&dir).unwrap(), InstanceLock::Acquired);
+        assert_eq!(
+            fs::read_to_string(dir.join(LOCK_FILENAME)).unwrap(),
+            std::process::id().to_string()
+        );
+
+        release(&dir).unwrap();
+        assert!(!dir.join(LOCK_FILENAME).exists());
+        // Someone else's lock survives a release from this process.
+        fs::write(dir.join(LOCK_FILENAME), "4294967294").unwrap();
+        release(&dir).unwrap();
+        assert!(dir.join(LOCK_FILENAME).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
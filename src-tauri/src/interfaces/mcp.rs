@@ -1,24 +1,50 @@
-use std::{env, net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    env,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::{Context as AnyhowContext, Result};
-use parking_lot::RwLock;
+use http_body_util::{BodyExt, Either, Full, LengthLimitError, Limited};
+use hyper::body::Bytes;
+use parking_lot::{Mutex, RwLock};
 use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
-    model::{CallToolResult, ServerCapabilities, ServerInfo},
+    model::{
+        CallToolResult, ErrorCode, InitializeRequestParam, InitializeResult, ServerCapabilities,
+        ServerInfo,
+    },
+    service::{RequestContext, RoleServer},
     tool, tool_router,
     transport::sse_server::{SseServer, SseServerConfig},
-    ErrorData as McpError, ServerHandler,
+    ErrorData as McpError, ServerHandler, ServiceExt,
 };
 use serde_json::json;
-use tokio::task;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    task,
+};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
     application::{
-        dtos::{IngestContextRequest, SearchRequest},
+        dtos::{
+            DeleteContextRequest, DeleteContextsRequest, FeedbackRequest, IngestContextRequest,
+            RecentContextsRequest, SearchRequest,
+        },
         ContextService,
     },
-    domain::DomainError,
+    domain::{infer_project_from_path, DomainError},
+    interfaces::{
+        rate_limit::{
+            export_search_bucket, max_connections_from_env, rate_limit_from_env, ConnectionLimit,
+            TokenBucket, DEFAULT_MAX_CONNECTIONS, ENV_MAX_CONNECTIONS, ENV_RATE_LIMIT,
+        },
+        request_log::RequestLogger,
+    },
 };
 
 // Re-export for use in binaries
@@ -28,6 +54,7 @@ const ENV_BIND_ADDR: &str = "INGAT_MCP_BIND_ADDR";
 const ENV_SSE_PATH: &str = "INGAT_MCP_SSE_PATH";
 const ENV_POST_PATH: &str = "INGAT_MCP_POST_PATH";
 const ENV_KEEP_ALIVE_SECS: &str = "INGAT_MCP_KEEP_ALIVE_SECS";
+const ENV_UDS: &str = "INGAT_MCP_UDS";
 
 /// Static metadata describing the active MCP endpoints.
 #[derive(Debug, Clone)]
@@ -35,15 +62,24 @@ pub struct McpEndpointMetadata {
     pub bind_addr: SocketAddr,
     pub sse_path: String,
     pub post_path: String,
+    /// Unix socket the server listens on instead of `bind_addr`, when set.
+    pub uds_path: Option<PathBuf>,
 }
 
 impl McpEndpointMetadata {
     pub fn post_url(&self) -> String {
-        format!("http://{}{}", self.bind_addr, self.post_path)
+        self.url_for(&self.post_path)
     }
 
     pub fn sse_url(&self) -> String {
-        format!("http://{}{}", self.bind_addr, self.sse_path)
+        self.url_for(&self.sse_path)
+    }
+
+    fn url_for(&self, path: &str) -> String {
+        match &self.uds_path {
+            Some(socket) => format!("unix:{}:{}", socket.display(), path),
+            None => format!("http://{}{}", self.bind_addr, path),
+        }
     }
 }
 
@@ -54,6 +90,10 @@ pub struct McpServerConfig {
     pub sse_path: String,
     pub post_path: String,
     pub keep_alive: Duration,
+    /// Serve on this unix domain socket instead of TCP (unix platforms only).
+    pub uds_path: Option<PathBuf>,
+    /// Concurrent SSE clients; connections beyond it are closed on arrival.
+    pub max_connections: usize,
 }
 
 impl Default for McpServerConfig {
@@ -63,6 +103,8 @@ impl Default for McpServerConfig {
             sse_path: "/sse".into(),
             post_path: "/message".into(),
             keep_alive: Duration::from_secs(30),
+            uds_path: None,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
         }
     }
 }
@@ -91,6 +133,14 @@ impl McpServerConfig {
             }
         }
 
+        if let Ok(raw) = env::var(ENV_UDS) {
+            if !raw.trim().is_empty() {
+                cfg.uds_path = Some(PathBuf::from(raw.trim()));
+            }
+        }
+
+        cfg.max_connections = max_connections_from_env();
+
         cfg
     }
 
@@ -107,6 +157,7 @@ impl McpServerConfig {
                 bind_addr: self.bind_addr,
                 sse_path: self.sse_path,
                 post_path: self.post_path,
+                uds_path: self.uds_path,
             },
         )
     }
@@ -155,14 +206,20 @@ pub async fn spawn_mcp_server(
     config: McpServerConfig,
 ) -> Result<McpServerHandle> {
     let root_token = CancellationToken::new();
+    let connections = ConnectionLimit::new(config.max_connections);
     let (sse_config, metadata) = config.into_pair(root_token.clone());
 
-    let sse_server = SseServer::serve_with_config(sse_config)
-        .await
-        .context("failed to bind MCP SSE listener")?;
+    let listener = match &metadata.uds_path {
+        Some(socket) => bind_unix_socket(socket)?,
+        None => McpListener::Tcp(
+            tokio::net::TcpListener::bind(sse_config.bind)
+                .await
+                .context("failed to bind MCP SSE listener")?,
+        ),
+    };
+    let sse_server = serve_sse(sse_config, listener, metadata.uds_path.clone());
 
-    let worker_token =
-        sse_server.with_service(move || IngatMcpServer::new(Arc::clone(&service_cell)));
+    let worker_token = accept_connections(sse_server, service_cell, connections);
 
     Ok(McpServerHandle {
         root_token,
@@ -171,6 +228,239 @@ pub async fn spawn_mcp_server(
     })
 }
 
+/// Serve each SSE client with its own `IngatMcpServer`, closing connections
+/// that arrive while `connections` is full.
+fn accept_connections(
+    mut sse_server: SseServer,
+    service_cell: Arc<RwLock<Arc<ContextService>>>,
+    connections: Arc<ConnectionLimit>,
+) -> CancellationToken {
+    let ct = sse_server.config.ct.clone();
+    tokio::spawn(async move {
+        while let Some(transport) = sse_server.next_transport().await {
+            let Some(slot) = connections.try_acquire() else {
+                tracing::warn!(
+                    target: "ingat::mcp",
+                    max = connections.max(),
+                    "rejecting MCP connection; raise {ENV_MAX_CONNECTIONS} if this is expected"
+                );
+                drop(transport);
+                continue;
+            };
+            tracing::info!(
+                target: "ingat::mcp",
+                current = connections.current(),
+                peak = connections.peak(),
+                "MCP client connected"
+            );
+
+            let server = IngatMcpServer::new(Arc::clone(&service_cell));
+            let ct = sse_server.config.ct.child_token();
+            let connections = Arc::clone(&connections);
+            tokio::spawn(async move {
+                match server.serve_with_ct(transport, ct).await {
+                    Ok(running) => {
+                        let _ = running.waiting().await;
+                    }
+                    Err(err) => {
+                        tracing::warn!(target: "ingat::mcp", error = %err, "MCP handshake failed")
+                    }
+                }
+                drop(slot);
+                tracing::info!(
+                    target: "ingat::mcp",
+                    current = connections.current(),
+                    peak = connections.peak(),
+                    "MCP client disconnected"
+                );
+            });
+        }
+    });
+    ct
+}
+
+/// Listener the SSE router is served on.
+enum McpListener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener),
+}
+
+/// A connection accepted by [`McpListener`].
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+impl McpListener {
+    async fn accept(&self) -> std::io::Result<Box<dyn Connection>> {
+        match self {
+            Self::Tcp(listener) => Ok(Box::new(listener.accept().await?.0)),
+            #[cfg(unix)]
+            Self::Unix(listener) => Ok(Box::new(listener.accept().await?.0)),
+        }
+    }
+}
+
+/// Bind the unix socket, created owner-only. A leftover socket is replaced
+/// only when no server answers on it.
+#[cfg(unix)]
+fn bind_unix_socket(socket: &Path) -> Result<McpListener> {
+    use std::{
+        fs,
+        os::unix::{
+            fs::{FileTypeExt, PermissionsExt},
+            net::UnixStream,
+        },
+    };
+
+    // A socket left behind by an unclean exit would make `bind` fail. Only a
+    // socket nobody answers on is stale; anything else is left alone.
+    if let Ok(meta) = fs::symlink_metadata(socket) {
+        if !meta.file_type().is_socket() {
+            anyhow::bail!("{} exists and is not a socket", socket.display());
+        }
+        if UnixStream::connect(socket).is_ok() {
+            anyhow::bail!(
+                "another server is already listening on {}",
+                socket.display()
+            );
+        }
+        fs::remove_file(socket)
+            .with_context(|| format!("failed to remove stale socket {}", socket.display()))?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(socket)
+        .with_context(|| format!("failed to bind MCP socket {}", socket.display()))?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("failed to restrict MCP socket {}", socket.display()))?;
+    Ok(McpListener::Unix(listener))
+}
+
+#[cfg(not(unix))]
+fn bind_unix_socket(_socket: &Path) -> Result<McpListener> {
+    anyhow::bail!("{ENV_UDS} is only supported on unix platforms")
+}
+
+/// Serve the SSE router on `listener` until the server is cancelled, then
+/// remove `socket` if one was bound.
+fn serve_sse(config: SseServerConfig, listener: McpListener, socket: Option<PathBuf>) -> SseServer {
+    use hyper::service::Service as _;
+    use hyper_util::{rt::TokioIo, service::TowerToHyperService};
+
+    let (sse_server, router) = SseServer::new(config);
+    let router = TowerToHyperService::new(router);
+    let service = hyper::service::service_fn(move |request| {
+        let router = router.clone();
+        async move {
+            match lift_workspace_root(request).await {
+                Ok(request) => router
+                    .call(request)
+                    .await
+                    .map(|response| response.map(Either::Left)),
+                Err(rejection) => Ok(rejection.map(Either::Right)),
+            }
+        }
+    });
+    let ct = sse_server.config.ct.child_token();
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                _ = ct.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        tracing::error!(target: "ingat::mcp", error = %err, "MCP accept failed");
+                        continue;
+                    }
+                },
+            };
+            let service = service.clone();
+            let ct = ct.clone();
+            tokio::spawn(async move {
+                let connection = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service);
+                tokio::pin!(connection);
+                let result = tokio::select! {
+                    result = connection.as_mut() => result,
+                    _ = ct.cancelled() => {
+                        connection.as_mut().graceful_shutdown();
+                        connection.await
+                    }
+                };
+                if let Err(err) = result {
+                    tracing::debug!(target: "ingat::mcp", error = %err, "MCP connection ended");
+                }
+            });
+        }
+        if let Some(socket) = socket {
+            let _ = std::fs::remove_file(&socket);
+        }
+    });
+
+    sse_server
+}
+
+/// Largest SSE POST body read; far above any valid message (context bodies
+/// cap at 16k characters) and the same as axum's default limit.
+const MAX_MESSAGE_BYTES: usize = 2 * 1024 * 1024;
+
+/// rmcp keeps only the `initialize` params it models, so a `workspace_root`
+/// sent next to them is copied into `_meta`, where
+/// [`IngatMcpServer::initialize`] reads it. Bodies over
+/// [`MAX_MESSAGE_BYTES`] are answered with 413 instead of being buffered.
+async fn lift_workspace_root<B>(
+    request: hyper::Request<B>,
+) -> Result<hyper::Request<Full<Bytes>>, hyper::Response<Full<Bytes>>>
+where
+    B: hyper::body::Body<Data = Bytes>,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let (parts, body) = request.into_parts();
+    let body = match Limited::new(body, MAX_MESSAGE_BYTES).collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(err) => {
+            let status = if err.is::<LengthLimitError>() {
+                hyper::StatusCode::PAYLOAD_TOO_LARGE
+            } else {
+                hyper::StatusCode::BAD_REQUEST
+            };
+            let mut response = hyper::Response::new(Full::new(Bytes::new()));
+            *response.status_mut() = status;
+            return Err(response);
+        }
+    };
+    let body = workspace_root_in_meta(&body).map_or(body, Bytes::from);
+    Ok(hyper::Request::from_parts(parts, Full::new(body)))
+}
+
+/// The workspace a client announced in `initialize` params: `_meta` first,
+/// then a bare `workspace_root` next to the params rmcp models. Both
+/// transports resolve it here so they agree.
+fn announced_workspace_root(params: &serde_json::Value) -> Option<&serde_json::Value> {
+    params
+        .get("_meta")
+        .and_then(|meta| meta.get(WORKSPACE_ROOT_KEY))
+        .or_else(|| params.get(WORKSPACE_ROOT_KEY))
+}
+
+/// `body` with the [`announced_workspace_root`] stored in `params._meta`, if
+/// it is an `initialize` request carrying one outside `_meta`.
+fn workspace_root_in_meta(body: &[u8]) -> Option<Vec<u8>> {
+    let mut message: serde_json::Value = serde_json::from_slice(body).ok()?;
+    if message.get("method")?.as_str()? != "initialize" {
+        return None;
+    }
+    let params = message.get_mut("params")?;
+    let workspace_root = announced_workspace_root(params)?.clone();
+    params
+        .as_object_mut()?
+        .entry("_meta")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()?
+        .insert(WORKSPACE_ROOT_KEY.to_string(), workspace_root);
+    serde_json::to_vec(&message).ok()
+}
+
 pub struct McpRuntime {
     handle: McpServerHandle,
 }
@@ -194,10 +484,25 @@ impl McpRuntime {
     }
 }
 
+/// Key clients may send in `initialize` params (or `_meta`) naming their workspace.
+const WORKSPACE_ROOT_KEY: &str = "workspace_root";
+
+/// JSON-RPC server-error code returned when a client exceeds its request budget.
+const RATE_LIMITED: ErrorCode = ErrorCode(-32029);
+
+/// One server instance is created per SSE connection (and one for stdio), so the
+/// bucket it owns limits that client only.
 #[derive(Clone)]
 pub struct IngatMcpServer {
     service_cell: Arc<RwLock<Arc<ContextService>>>,
     tool_router: ToolRouter<Self>,
+    rate_limiter: Option<Arc<Mutex<TokenBucket>>>,
+    /// Process-wide budget for `export_mode` searches, shared with the REST route.
+    export_searches: Arc<Mutex<TokenBucket>>,
+    request_log: Option<RequestLogger>,
+    /// Project inferred from the connection's `workspace_root`; used only when
+    /// an `ingest_context` call leaves `project` empty.
+    workspace_project: Arc<RwLock<Option<String>>>,
 }
 
 impl IngatMcpServer {
@@ -205,6 +510,21 @@ impl IngatMcpServer {
         Self {
             service_cell,
             tool_router: Self::tool_router(),
+            rate_limiter: rate_limit_from_env()
+                .map(|rate| Arc::new(Mutex::new(TokenBucket::new(rate)))),
+            export_searches: export_search_bucket(),
+            request_log: RequestLogger::from_env(),
+            workspace_project: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Remember the workspace announced at `initialize` for this connection.
+    fn remember_workspace(&self, workspace_root: Option<&serde_json::Value>) {
+        if let Some(project) = workspace_root
+            .and_then(|value| value.as_str())
+            .and_then(infer_project_from_path)
+        {
+            *self.workspace_project.write() = Some(project);
         }
     }
 
@@ -212,7 +532,29 @@ impl IngatMcpServer {
         Arc::clone(&self.service_cell.read())
     }
 
-    async fn ingest(&self, payload: IngestContextRequest) -> Result<CallToolResult, McpError> {
+    /// Tool calls spend a token; `initialize` and `tools/list` never reach this.
+    fn check_rate_limit(&self) -> Result<(), McpError> {
+        match &self.rate_limiter {
+            Some(bucket) if !bucket.lock().try_acquire() => Err(McpError::new(
+                RATE_LIMITED,
+                "rate limit exceeded",
+                Some(json!({ "detail": format!("slow down; limit set by {ENV_RATE_LIMIT}") })),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    async fn ingest(&self, mut payload: IngestContextRequest) -> Result<CallToolResult, McpError> {
+        self.check_rate_limit()?;
+        // An explicit project always wins over the workspace default.
+        if payload.project.trim().is_empty() {
+            if let Some(project) = self.workspace_project.read().clone() {
+                payload.project = project;
+            }
+        }
+        if let Some(log) = &self.request_log {
+            log.ingest("mcp", &payload);
+        }
         let service = self.current_service();
         let summary = task::spawn_blocking(move || service.ingest(payload))
             .await
@@ -224,23 +566,114 @@ impl IngatMcpServer {
     }
 
     async fn search(&self, payload: SearchRequest) -> Result<CallToolResult, McpError> {
+        self.check_rate_limit()?;
+        if payload.export_mode && !self.export_searches.lock().try_acquire() {
+            return Err(McpError::new(
+                RATE_LIMITED,
+                "export searches are rate-limited",
+                Some(json!({ "detail": "retry in a few seconds" })),
+            ));
+        }
+        if let Some(log) = &self.request_log {
+            log.search("mcp", &payload);
+        }
+        let timestamp_format = payload.timestamp_format;
         let service = self.current_service();
         let response = task::spawn_blocking(move || service.search(payload))
             .await
             .map_err(|err| internal_error(err.to_string()))?
             .map_err(map_domain_error)?;
 
-        let value =
+        let mut value =
+            serde_json::to_value(response).map_err(|err| internal_error(err.to_string()))?;
+        timestamp_format.apply(&mut value);
+        Ok(CallToolResult::structured(value))
+    }
+
+    async fn recent(&self, payload: RecentContextsRequest) -> Result<CallToolResult, McpError> {
+        self.check_rate_limit()?;
+        let timestamp_format = payload.timestamp_format;
+        let service = self.current_service();
+        let response = task::spawn_blocking(move || {
+            service.history(
+                payload.project,
+                payload.tag,
+                payload.since,
+                payload.limit,
+                payload.order,
+            )
+        })
+        .await
+        .map_err(|err| internal_error(err.to_string()))?
+        .map_err(map_domain_error)?;
+
+        let mut value =
             serde_json::to_value(response).map_err(|err| internal_error(err.to_string()))?;
+        timestamp_format.apply(&mut value);
         Ok(CallToolResult::structured(value))
     }
+
+    async fn delete_one(&self, payload: DeleteContextRequest) -> Result<CallToolResult, McpError> {
+        self.check_rate_limit()?;
+        let service = self.current_service();
+        let id = payload.id;
+        task::spawn_blocking(move || service.delete(id))
+            .await
+            .map_err(|err| internal_error(err.to_string()))?
+            .map_err(map_domain_error)?;
+
+        Ok(CallToolResult::structured(json!({ "deleted": id })))
+    }
+
+    async fn delete(&self, payload: DeleteContextsRequest) -> Result<CallToolResult, McpError> {
+        self.check_rate_limit()?;
+        let service = self.current_service();
+        let deleted = task::spawn_blocking(move || service.delete_many(payload.ids))
+            .await
+            .map_err(|err| internal_error(err.to_string()))?
+            .map_err(map_domain_error)?;
+
+        Ok(CallToolResult::structured(json!({ "deleted": deleted })))
+    }
+
+    async fn feedback(&self, payload: FeedbackRequest) -> Result<CallToolResult, McpError> {
+        self.check_rate_limit()?;
+        let service = self.current_service();
+        let event = task::spawn_blocking(move || {
+            service.record_feedback(payload.query_id, payload.chosen_id)
+        })
+        .await
+        .map_err(|err| internal_error(err.to_string()))?
+        .map_err(map_domain_error)?;
+
+        Ok(CallToolResult::structured(json!({
+            "query_id": event.query_id,
+            "choices": event.chosen.len(),
+        })))
+    }
+
+    /// Keepalive probe; deliberately exempt from rate limiting. Flushes the
+    /// store first, so a successful ping means every acknowledged write is on
+    /// disk.
+    async fn ping(&self) -> Result<CallToolResult, McpError> {
+        let service = self.current_service();
+        task::spawn_blocking(move || service.flush())
+            .await
+            .map_err(|err| internal_error(err.to_string()))?
+            .map_err(map_domain_error)?;
+
+        Ok(CallToolResult::structured(json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "version": env!("CARGO_PKG_VERSION"),
+        })))
+    }
 }
 
 #[tool_router]
 impl IngatMcpServer {
     #[tool(
         name = "ingest_context",
-        description = "Persist a new Ingat context item."
+        description = "Persist a new Ingat context item. `project` defaults to the workspace root sent at initialize; an explicit value always takes precedence."
     )]
     async fn ingest_context(
         &self,
@@ -259,9 +692,73 @@ impl IngatMcpServer {
     ) -> Result<CallToolResult, McpError> {
         self.search(payload).await
     }
+
+    #[tool(
+        name = "recent_contexts",
+        description = "List the most recent contexts, optionally scoped to a project and/or tag."
+    )]
+    async fn recent_contexts(
+        &self,
+        Parameters(payload): Parameters<RecentContextsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.recent(payload).await
+    }
+
+    #[tool(
+        name = "delete_context",
+        description = "Delete one context (and any chunks split from it) by id. Fails with a not-found error if the id is unknown."
+    )]
+    async fn delete_context(
+        &self,
+        Parameters(payload): Parameters<DeleteContextRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.delete_one(payload).await
+    }
+
+    #[tool(
+        name = "delete_contexts",
+        description = "Delete several contexts by id in one atomic operation. Unknown ids are ignored; returns how many were deleted."
+    )]
+    async fn delete_contexts(
+        &self,
+        Parameters(payload): Parameters<DeleteContextsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.delete(payload).await
+    }
+
+    #[tool(
+        name = "record_feedback",
+        description = "Report which search result was actually used: `query_id` from the search response and the chosen context id. Only available when search analytics are enabled."
+    )]
+    async fn record_feedback(
+        &self,
+        Parameters(payload): Parameters<FeedbackRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.feedback(payload).await
+    }
+
+    #[tool(
+        name = "ping",
+        description = "Check that the connection and the Ingat service are alive and flush pending writes to disk; returns the server time and version."
+    )]
+    async fn ping_server(&self) -> Result<CallToolResult, McpError> {
+        self.ping().await
+    }
 }
 
 impl ServerHandler for IngatMcpServer {
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, McpError> {
+        self.remember_workspace(context.meta.get(WORKSPACE_ROOT_KEY));
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        Ok(self.get_info())
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: rmcp::model::ProtocolVersion::LATEST,
@@ -340,7 +837,14 @@ fn map_domain_error(err: DomainError) -> McpError {
         DomainError::NotFound(msg) => {
             McpError::resource_not_found("not found", Some(json!({ "detail": msg })))
         }
-        DomainError::Embedding(msg) | DomainError::Storage(msg) | DomainError::Other(msg) => {
+        DomainError::Busy(op) => McpError::internal_error(
+            "service in maintenance",
+            Some(json!({ "operation": op, "retryable": true })),
+        ),
+        DomainError::Embedding(msg)
+        | DomainError::Storage(msg)
+        | DomainError::Config(msg)
+        | DomainError::Other(msg) => {
             McpError::internal_error("internal error", Some(json!({ "detail": msg })))
         }
     }
@@ -356,20 +860,57 @@ fn internal_error(message: impl Into<String>) -> McpError {
 /// Run MCP server using stdio transport (stdin/stdout).
 /// This is compatible with VS Code, Cursor, Windsurf, and other process-spawning MCP clients.
 pub async fn run_mcp_stdio_server(service_cell: Arc<RwLock<Arc<ContextService>>>) -> Result<()> {
-    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-    use tracing::{debug, error, info};
+    use tracing::{error, info};
 
     info!(target: "ingat::mcp", "Starting MCP stdio server...");
 
-    let server = IngatMcpServer::new(service_cell);
-    let stdin = tokio::io::stdin();
-    let mut stdout = tokio::io::stdout();
-    let mut reader = BufReader::new(stdin);
+    let server = IngatMcpServer::new(Arc::clone(&service_cell));
+    serve_stdio(
+        &server,
+        tokio::io::BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+        shutdown_signal(),
+    )
+    .await;
+
+    let service = Arc::clone(&*service_cell.read());
+    if let Err(e) = tokio::task::spawn_blocking(move || service.flush()).await? {
+        error!(target: "ingat::mcp", "Failed to flush store on shutdown: {}", e);
+    }
+
+    info!(target: "ingat::mcp", "MCP stdio server terminated");
+    Ok(())
+}
+
+/// Newline-delimited JSON-RPC loop behind [`run_mcp_stdio_server`], over any
+/// reader/writer pair so [`mcp_stdio_roundtrip`] can drive it through pipes.
+/// Returns on EOF, a write failure or `shutdown`.
+async fn serve_stdio<R, W>(
+    server: &IngatMcpServer,
+    mut reader: R,
+    mut writer: W,
+    shutdown: impl std::future::Future<Output = &'static str>,
+) where
+    R: tokio::io::AsyncBufRead + Unpin,
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+    use tracing::{debug, error, info};
+
     let mut line = String::new();
+    tokio::pin!(shutdown);
 
     loop {
         line.clear();
-        match reader.read_line(&mut line).await {
+        // A request cut off by the signal is dropped; the client is going away.
+        let read = tokio::select! {
+            reason = &mut shutdown => {
+                info!(target: "ingat::mcp", "Received {reason}, shutting down");
+                break;
+            }
+            read = reader.read_line(&mut line) => read,
+        };
+        match read {
             Ok(0) => {
                 // EOF - client closed connection
                 info!(target: "ingat::mcp", "Client closed stdio connection");
@@ -387,21 +928,23 @@ pub async fn run_mcp_stdio_server(service_cell: Arc<RwLock<Arc<ContextService>>>
                 match serde_json::from_str::<serde_json::Value>(trimmed) {
                     Ok(request) => {
                         // Handle the request using rmcp's handler
-                        let response = handle_jsonrpc_request(&server, request).await;
+                        let Some(response) = handle_jsonrpc_request(server, request).await else {
+                            continue;
+                        };
 
                         // Write response to stdout
                         let response_json = serde_json::to_string(&response)
                             .unwrap_or_else(|e| format!(r#"{{"jsonrpc":"2.0","error":{{"code":-32603,"message":"Failed to serialize response: {}"}}}}"#, e));
 
-                        if let Err(e) = stdout.write_all(response_json.as_bytes()).await {
+                        if let Err(e) = writer.write_all(response_json.as_bytes()).await {
                             error!(target: "ingat::mcp", "Failed to write response: {}", e);
                             break;
                         }
-                        if let Err(e) = stdout.write_all(b"\n").await {
+                        if let Err(e) = writer.write_all(b"\n").await {
                             error!(target: "ingat::mcp", "Failed to write newline: {}", e);
                             break;
                         }
-                        if let Err(e) = stdout.flush().await {
+                        if let Err(e) = writer.flush().await {
                             error!(target: "ingat::mcp", "Failed to flush stdout: {}", e);
                             break;
                         }
@@ -412,16 +955,17 @@ pub async fn run_mcp_stdio_server(service_cell: Arc<RwLock<Arc<ContextService>>>
                         error!(target: "ingat::mcp", "Failed to parse JSON-RPC request: {}", e);
                         let error_response = json!({
                             "jsonrpc": "2.0",
+                            "id": null,
                             "error": {
                                 "code": -32700,
                                 "message": format!("Parse error: {}", e)
                             }
                         });
-                        let _ = stdout
+                        let _ = writer
                             .write_all(serde_json::to_string(&error_response).unwrap().as_bytes())
                             .await;
-                        let _ = stdout.write_all(b"\n").await;
-                        let _ = stdout.flush().await;
+                        let _ = writer.write_all(b"\n").await;
+                        let _ = writer.flush().await;
                     }
                 }
             }
@@ -431,22 +975,223 @@ pub async fn run_mcp_stdio_server(service_cell: Arc<RwLock<Arc<ContextService>>>
             }
         }
     }
+}
 
-    info!(target: "ingat::mcp", "MCP stdio server terminated");
-    Ok(())
+/// What the server answered during [`mcp_stdio_roundtrip`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StdioRoundtrip {
+    pub protocol_version: String,
+    pub server_name: String,
+    /// Names listed by `tools/list`.
+    pub tools: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+/// How long the round trip waits for any single response.
+const ROUNDTRIP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Drive the stdio JSON-RPC loop through in-memory pipes the way a client
+/// does: `initialize`, the `notifications/initialized` notification,
+/// `tools/list` and a `tools/call` of `ping`. Fails on a mismatched id, a
+/// response to the notification, a malformed result, or a stall.
+pub async fn mcp_stdio_roundtrip(
+    service_cell: Arc<RwLock<Arc<ContextService>>>,
+) -> Result<StdioRoundtrip> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let started = std::time::Instant::now();
+    let (mut client_out, server_in) = tokio::io::duplex(64 * 1024);
+    let (server_out, client_in) = tokio::io::duplex(64 * 1024);
+    let server = IngatMcpServer::new(service_cell);
+    let serving = tokio::spawn(async move {
+        serve_stdio(
+            &server,
+            BufReader::new(server_in),
+            server_out,
+            std::future::pending(),
+        )
+        .await
+    });
+
+    let mut lines = BufReader::new(client_in).lines();
+
+    send_line(
+        &mut client_out,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": rmcp::model::ProtocolVersion::LATEST,
+                "capabilities": {},
+                "clientInfo": { "name": "ingat-roundtrip", "version": env!("CARGO_PKG_VERSION") }
+            }
+        }),
+    )
+    .await?;
+    let initialized = expect_response(&mut lines, 1).await?;
+    let protocol_version = initialized
+        .pointer("/result/protocolVersion")
+        .and_then(|value| value.as_str())
+        .context("initialize result has no protocolVersion")?
+        .to_string();
+    let server_name = initialized
+        .pointer("/result/serverInfo/name")
+        .and_then(|value| value.as_str())
+        .context("initialize result has no serverInfo.name")?
+        .to_string();
+
+    // Answering this notification would put a response ahead of the
+    // `tools/list` reply below and fail its id check.
+    send_line(
+        &mut client_out,
+        json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+    )
+    .await?;
+    send_line(
+        &mut client_out,
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+    )
+    .await?;
+    let listed = expect_response(&mut lines, 2).await?;
+    let tools: Vec<String> = listed
+        .pointer("/result/tools")
+        .and_then(|value| value.as_array())
+        .context("tools/list result has no tools array")?
+        .iter()
+        .map(|tool| {
+            tool.get("name")
+                .and_then(|name| name.as_str())
+                .map(String::from)
+        })
+        .collect::<Option<_>>()
+        .context("tools/list returned a tool without a name")?;
+    anyhow::ensure!(
+        tools.iter().any(|name| name == "ping"),
+        "tools/list does not include ping"
+    );
+
+    send_line(
+        &mut client_out,
+        json!({
+            "jsonrpc": "2.0",
+            "id": "call-3",
+            "method": "tools/call",
+            "params": { "name": "ping", "arguments": {} }
+        }),
+    )
+    .await?;
+    let called = expect_response(&mut lines, "call-3").await?;
+    anyhow::ensure!(
+        called
+            .pointer("/result/content")
+            .is_some_and(|content| content.is_array()),
+        "tools/call result has no content array"
+    );
+    anyhow::ensure!(
+        called.pointer("/result/isError") != Some(&json!(true)),
+        "tools/call reported an error: {called}"
+    );
+
+    // Closing the pipe ends the loop; nothing may be left unread.
+    drop(client_out);
+    let trailing = tokio::time::timeout(ROUNDTRIP_TIMEOUT, lines.next_line())
+        .await
+        .context("server did not stop after the client hung up")??;
+    anyhow::ensure!(
+        trailing.is_none(),
+        "unexpected message after the last response: {}",
+        trailing.unwrap_or_default()
+    );
+    serving.await.context("stdio server task failed")?;
+
+    Ok(StdioRoundtrip {
+        protocol_version,
+        server_name,
+        tools,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+async fn send_line(
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+    message: serde_json::Value,
+) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    writer.write_all(format!("{message}\n").as_bytes()).await
+}
+
+/// Next line from the server, checked to be a JSON-RPC 2.0 success response to `id`.
+async fn expect_response<R>(
+    lines: &mut tokio::io::Lines<R>,
+    id: impl Into<serde_json::Value>,
+) -> Result<serde_json::Value>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let id = id.into();
+    let line = tokio::time::timeout(ROUNDTRIP_TIMEOUT, lines.next_line())
+        .await
+        .with_context(|| format!("no response to request {id}"))??
+        .with_context(|| format!("server hung up before answering request {id}"))?;
+    let response: serde_json::Value =
+        serde_json::from_str(&line).with_context(|| format!("response is not JSON: {line}"))?;
+    anyhow::ensure!(
+        response.get("jsonrpc") == Some(&json!("2.0")),
+        "response lacks jsonrpc 2.0: {line}"
+    );
+    anyhow::ensure!(
+        response.get("id") == Some(&id),
+        "expected a response to request {id}, got: {line}"
+    );
+    anyhow::ensure!(
+        response.get("error").is_none() && response.get("result").is_some(),
+        "request {id} failed: {line}"
+    );
+    Ok(response)
+}
+
+/// Resolves with the name of the first termination signal received.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = terminate.recv() => "SIGTERM",
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+            },
+            Err(e) => {
+                tracing::warn!(target: "ingat::mcp", "Cannot listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl+C"
+    }
 }
 
-/// Handle JSON-RPC requests for MCP
+/// Handle JSON-RPC requests for MCP. Notifications (messages without an
+/// `id`) get no response, not even an error.
 async fn handle_jsonrpc_request(
     server: &IngatMcpServer,
     request: serde_json::Value,
-) -> serde_json::Value {
-    // Extract request fields
-    let id = request.get("id").cloned();
+) -> Option<serde_json::Value> {
     let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
+    let Some(id) = request.get("id").cloned() else {
+        tracing::debug!(target: "ingat::mcp", "Notification: {}", method);
+        return None;
+    };
 
-    match method {
+    let response = match method {
         "initialize" => {
+            server.remember_workspace(request.get("params").and_then(announced_workspace_root));
             let info = server.get_info();
             json!({
                 "jsonrpc": "2.0",
@@ -505,6 +1250,43 @@ async fn handle_jsonrpc_request(
                                 )),
                             }
                         }
+                        "recent_contexts" => {
+                            match serde_json::from_value::<RecentContextsRequest>(arguments) {
+                                Ok(req) => server.recent(req).await,
+                                Err(e) => Err(McpError::invalid_params(
+                                    "Invalid recent_contexts arguments",
+                                    Some(json!({"detail": e.to_string()})),
+                                )),
+                            }
+                        }
+                        "delete_context" => {
+                            match serde_json::from_value::<DeleteContextRequest>(arguments) {
+                                Ok(req) => server.delete_one(req).await,
+                                Err(e) => Err(McpError::invalid_params(
+                                    "Invalid delete_context arguments",
+                                    Some(json!({"detail": e.to_string()})),
+                                )),
+                            }
+                        }
+                        "delete_contexts" => {
+                            match serde_json::from_value::<DeleteContextsRequest>(arguments) {
+                                Ok(req) => server.delete(req).await,
+                                Err(e) => Err(McpError::invalid_params(
+                                    "Invalid delete_contexts arguments",
+                                    Some(json!({"detail": e.to_string()})),
+                                )),
+                            }
+                        }
+                        "record_feedback" => {
+                            match serde_json::from_value::<FeedbackRequest>(arguments) {
+                                Ok(req) => server.feedback(req).await,
+                                Err(e) => Err(McpError::invalid_params(
+                                    "Invalid record_feedback arguments",
+                                    Some(json!({"detail": e.to_string()})),
+                                )),
+                            }
+                        }
+                        "ping" => server.ping().await,
                         _ => Err(McpError::invalid_params(
                             format!("Unknown tool: {}", tool_name),
                             None,
@@ -546,5 +1328,137 @@ async fn handle_jsonrpc_request(
                 "message": format!("Method not found: {}", method)
             }
         }),
+    };
+    Some(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::{
+        ingest_request, memory_service, service_over, ServiceConfig,
+    };
+    use crate::infrastructure::SledVectorStore;
+
+    #[test]
+    fn test_workspace_root_is_lifted_into_meta() {
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": { "protocolVersion": "2024-11-05", "workspace_root": "/work/ingat" },
+        });
+        let lifted = workspace_root_in_meta(&serde_json::to_vec(&initialize).unwrap()).unwrap();
+        let lifted: serde_json::Value = serde_json::from_slice(&lifted).unwrap();
+        assert_eq!(lifted["params"]["_meta"]["workspace_root"], "/work/ingat");
+
+        // An explicit `_meta` value wins, and other methods are left alone.
+        let explicit = json!({
+            "method": "initialize",
+            "params": { "workspace_root": "/a", "_meta": { "workspace_root": "/b" } },
+        });
+        let lifted = workspace_root_in_meta(&serde_json::to_vec(&explicit).unwrap()).unwrap();
+        let lifted: serde_json::Value = serde_json::from_slice(&lifted).unwrap();
+        assert_eq!(lifted["params"]["_meta"]["workspace_root"], "/b");
+        let call = json!({ "method": "tools/call", "params": { "workspace_root": "/a" } });
+        assert!(workspace_root_in_meta(&serde_json::to_vec(&call).unwrap()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_both_transports_prefer_meta_workspace_root() {
+        let params = json!({
+            "protocolVersion": "2024-11-05",
+            "workspace_root": "/work/bare",
+            "_meta": { "workspace_root": "/work/meta" },
+        });
+        assert_eq!(
+            announced_workspace_root(&params),
+            Some(&json!("/work/meta"))
+        );
+
+        // SSE: lifted into `_meta` for rmcp's `initialize`.
+        let initialize = json!({ "method": "initialize", "params": params });
+        let lifted = workspace_root_in_meta(&serde_json::to_vec(&initialize).unwrap()).unwrap();
+        let lifted: serde_json::Value = serde_json::from_slice(&lifted).unwrap();
+        assert_eq!(lifted["params"]["_meta"]["workspace_root"], "/work/meta");
+
+        // stdio: read directly by the JSON-RPC loop.
+        let server = IngatMcpServer::new(Arc::new(RwLock::new(Arc::new(memory_service()))));
+        let initialize =
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": params });
+        handle_jsonrpc_request(&server, initialize).await.unwrap();
+        assert_eq!(server.workspace_project.read().as_deref(), Some("meta"));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_sse_bodies_are_rejected() {
+        let request = |len| hyper::Request::new(Full::new(Bytes::from(vec![b' '; len])));
+        let rejected = lift_workspace_root(request(MAX_MESSAGE_BYTES + 1))
+            .await
+            .unwrap_err();
+        assert_eq!(rejected.status(), hyper::StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(lift_workspace_root(request(MAX_MESSAGE_BYTES))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_mcp_stdio_roundtrip_conforms() {
+        let service = memory_service();
+        let report = mcp_stdio_roundtrip(Arc::new(RwLock::new(Arc::new(service))))
+            .await
+            .unwrap();
+        assert_eq!(report.server_name, "ingat-mcp");
+        for tool in ["ingest_context", "search_contexts", "ping"] {
+            assert!(
+                report.tools.iter().any(|name| name == tool),
+                "{tool} missing"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifications_get_no_response() {
+        let service = memory_service();
+        let server = IngatMcpServer::new(Arc::new(RwLock::new(Arc::new(service))));
+        for method in [
+            "notifications/initialized",
+            "notifications/cancelled",
+            "tools/list",
+        ] {
+            let notification = json!({ "jsonrpc": "2.0", "method": method });
+            assert!(handle_jsonrpc_request(&server, notification)
+                .await
+                .is_none());
+        }
+        let unknown = json!({ "jsonrpc": "2.0", "id": 7, "method": "bogus" });
+        let response = handle_jsonrpc_request(&server, unknown).await.unwrap();
+        assert_eq!(response["id"], json!(7));
+        assert_eq!(response["error"]["code"], json!(-32601));
+    }
+
+    #[tokio::test]
+    async fn test_ping_flushes_pending_writes() {
+        let dir = std::env::temp_dir().join(format!("ingat-mcp-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(
+            SledVectorStore::open(&dir)
+                .unwrap()
+                .with_flush_coalescing(60_000, 100)
+                .unwrap(),
+        );
+        let service = service_over(store.clone(), ServiceConfig::default());
+        let server = IngatMcpServer::new(Arc::new(RwLock::new(Arc::new(service))));
+
+        server
+            .ingest(ingest_request("Flush on ping", "body"))
+            .await
+            .unwrap();
+        assert_eq!(store.unflushed_writes(), 1);
+        server.ping().await.unwrap();
+        assert_eq!(store.unflushed_writes(), 0);
+
+        drop(server);
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
     }
 }
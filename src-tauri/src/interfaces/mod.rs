@@ -4,3 +4,7 @@
 // Each submodule should be feature-gated by the capability it implements.
 #[cfg(feature = "mcp-server")]
 pub mod mcp;
+#[cfg(feature = "mcp-server")]
+pub mod rate_limit;
+#[cfg(feature = "mcp-server")]
+pub mod request_log;
@@ -0,0 +1,211 @@
+//! Token-bucket rate limiting shared by the MCP server and the HTTP service,
+//! plus the MCP server's cap on concurrent SSE connections.
+
+use std::{
+    collections::HashMap,
+    env,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::Instant,
+};
+
+use parking_lot::Mutex;
+
+pub const ENV_RATE_LIMIT: &str = "INGAT_MCP_RATE_LIMIT";
+pub const ENV_MAX_CONNECTIONS: &str = "INGAT_MCP_MAX_CONNECTIONS";
+
+/// Concurrent SSE connections allowed when `INGAT_MCP_MAX_CONNECTIONS` is unset.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 64;
+
+/// Keyed limiters stop tracking idle clients once they hold this many buckets.
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+/// Export-mode searches allowed per second across all clients.
+pub const EXPORT_SEARCHES_PER_SECOND: f64 = 0.2;
+
+/// Requests-per-second budget from `INGAT_MCP_RATE_LIMIT`; `None` disables limiting.
+pub fn rate_limit_from_env() -> Option<f64> {
+    env::var(ENV_RATE_LIMIT)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<f64>().ok())
+        .filter(|rate| rate.is_finite() && *rate > 0.0)
+}
+
+/// The process-wide budget for `export_mode` searches, shared by the HTTP
+/// route and every MCP connection.
+pub fn export_search_bucket() -> Arc<Mutex<TokenBucket>> {
+    static BUCKET: OnceLock<Arc<Mutex<TokenBucket>>> = OnceLock::new();
+    Arc::clone(
+        BUCKET.get_or_init(|| Arc::new(Mutex::new(TokenBucket::new(EXPORT_SEARCHES_PER_SECOND)))),
+    )
+}
+
+/// Connection cap from `INGAT_MCP_MAX_CONNECTIONS`; invalid or zero values use the default.
+pub fn max_connections_from_env() -> usize {
+    env::var(ENV_MAX_CONNECTIONS)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .filter(|max| *max > 0)
+        .unwrap_or(DEFAULT_MAX_CONNECTIONS)
+}
+
+/// Holds up to `capacity` tokens (one second of budget) refilled at `rate` per second.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64) -> Self {
+        let capacity = rate.max(1.0);
+        Self {
+            capacity,
+            tokens: capacity,
+            rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take one token if available.
+    pub fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.capacity
+    }
+}
+
+/// One token bucket per client key (connection, source address, token...).
+#[derive(Debug)]
+pub struct KeyedRateLimiter<K> {
+    rate: f64,
+    buckets: Mutex<HashMap<K, TokenBucket>>,
+}
+
+impl<K: Eq + Hash> KeyedRateLimiter<K> {
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn try_acquire(&self, key: K) -> bool {
+        let mut buckets = self.buckets.lock();
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| !bucket.is_full());
+        }
+        buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::new(self.rate))
+            .try_acquire()
+    }
+}
+
+/// Counts open connections against a fixed cap, remembering the peak.
+#[derive(Debug)]
+pub struct ConnectionLimit {
+    max: usize,
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl ConnectionLimit {
+    pub fn new(max: usize) -> Arc<Self> {
+        Arc::new(Self {
+            max,
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        })
+    }
+
+    /// Claim a slot for a new connection; `None` when the cap is reached. The
+    /// slot is released when the guard drops.
+    pub fn try_acquire(self: &Arc<Self>) -> Option<ConnectionGuard> {
+        let previous = self
+            .current
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+                (open < self.max).then_some(open + 1)
+            })
+            .ok()?;
+        self.peak.fetch_max(previous + 1, Ordering::Relaxed);
+        Some(ConnectionGuard(Arc::clone(self)))
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Acquire)
+    }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+}
+
+/// An open connection's slot in a [`ConnectionLimit`].
+#[derive(Debug)]
+pub struct ConnectionGuard(Arc<ConnectionLimit>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.current.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_exhausts_after_capacity() {
+        let mut bucket = TokenBucket::new(2.0);
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+    }
+
+    #[test]
+    fn test_keyed_limiter_isolates_clients() {
+        let limiter = KeyedRateLimiter::new(1.0);
+        assert!(limiter.try_acquire("a"));
+        assert!(!limiter.try_acquire("a"));
+        assert!(limiter.try_acquire("b"));
+    }
+
+    #[test]
+    fn test_connection_limit_frees_slots_on_drop() {
+        let limit = ConnectionLimit::new(2);
+        let first = limit.try_acquire().expect("first slot");
+        let _second = limit.try_acquire().expect("second slot");
+        assert!(limit.try_acquire().is_none());
+
+        drop(first);
+        assert_eq!(limit.current(), 1);
+        assert!(limit.try_acquire().is_some());
+        assert_eq!(limit.peak(), 2);
+    }
+}
@@ -0,0 +1,92 @@
+//! Opt-in logging of ingest/search payloads shared by the MCP server and the HTTP service.
+//!
+//! Only shape is logged by default (project, kind, tag count, text lengths);
+//! summaries, bodies and prompts may hold secrets and are redacted unless
+//! `INGAT_LOG_BODIES` is also set.
+
+use std::env;
+
+use tracing::info;
+
+use crate::application::dtos::{IngestContextRequest, SearchRequest};
+use crate::domain::{FilterField, QueryFilters};
+
+pub const ENV_LOG_REQUESTS: &str = "INGAT_LOG_REQUESTS";
+pub const ENV_LOG_BODIES: &str = "INGAT_LOG_BODIES";
+
+const REDACTED: &str = "<redacted>";
+
+/// Logs incoming requests under the `ingat::requests` target.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestLogger {
+    bodies: bool,
+}
+
+impl RequestLogger {
+    /// Enabled by `INGAT_LOG_REQUESTS`; `None` when request logging is off.
+    pub fn from_env() -> Option<Self> {
+        env_flag(ENV_LOG_REQUESTS).then(|| Self {
+            bodies: env_flag(ENV_LOG_BODIES),
+        })
+    }
+
+    /// `source` names the entry point, e.g. `"mcp"` or `"rest"`.
+    pub fn ingest(&self, source: &str, request: &IngestContextRequest) {
+        info!(
+            target: "ingat::requests",
+            source,
+            method = "ingest",
+            project = %request.project,
+            kind = request.kind.label(),
+            tags = request.tags.len(),
+            summary_len = request.summary.chars().count(),
+            body_len = request.body.chars().count(),
+            summary = self.reveal(&request.summary),
+            body = self.reveal(&request.body),
+        );
+    }
+
+    pub fn search(&self, source: &str, request: &SearchRequest) {
+        let filters = &request.filters;
+        let filter_expr = request.filter_expr.as_deref().unwrap_or_default();
+        info!(
+            target: "ingat::requests",
+            source,
+            method = "search",
+            project = filters.project.as_deref().unwrap_or("*"),
+            kind = filters.kind.as_ref().map_or("*", |kind| kind.label()),
+            tags = tag_predicates(filters),
+            filter_expr_len = filter_expr.chars().count(),
+            soft_filter = request.soft_filter,
+            limit = request.limit,
+            prompt_len = request.prompt.chars().count(),
+            prompt = self.reveal(&request.prompt),
+        );
+    }
+
+    fn reveal<'a>(&self, text: &'a str) -> &'a str {
+        if self.bodies {
+            text
+        } else {
+            REDACTED
+        }
+    }
+}
+
+/// Tag values a search filters on, the plain `tag` and every tag group's
+/// alternatives alike. The filter expression itself is only logged by length.
+fn tag_predicates(filters: &QueryFilters) -> usize {
+    let grouped: usize = filters
+        .groups
+        .iter()
+        .filter(|group| group.field == FilterField::Tag)
+        .map(|group| group.any_of.len())
+        .sum();
+    usize::from(filters.tag.is_some()) + grouped
+}
+
+fn env_flag(key: &str) -> bool {
+    env::var(key)
+        .map(|raw| matches!(raw.trim(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
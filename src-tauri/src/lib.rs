@@ -1,49 +1,76 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Result};
-use parking_lot::RwLock;
-use tauri::State;
+use parking_lot::{Mutex, RwLock};
+use tauri::ipc::Channel;
+use tauri::{Emitter, Manager, State};
 
 pub mod application;
+pub mod backup;
 pub mod domain;
 pub mod infrastructure;
+pub mod instance_lock;
 #[cfg(feature = "mcp-server")]
 pub mod interfaces;
+pub mod log_tail;
+pub mod mcp_config;
+pub mod migration;
 pub mod power_manager;
+pub mod profiles;
 pub mod service_manager;
 pub mod settings;
 
 use application::services::{EmbeddingEngine as EmbeddingEngineTrait, VectorStore};
 use application::{
-    ContextService, EmbeddingBackendListResponse, EmbeddingBackendOption, HealthStatusResponse,
-    IngestContextRequest, SearchRequest, SearchResponse, SummaryListResponse,
-    UpdateEmbeddingBackendRequest,
+    BuildInfo, ContextDiff, ContextService, DataMigrationResponse, EmbeddingBackendListResponse,
+    EmbeddingBackendOption, EmbeddingBenchmark, EmbeddingSelfTest, FeedbackStats,
+    HealthStatusResponse, IngestContextRequest, LabelCount, ProjectInfo, ProjectOverview,
+    ReembedProgress, RefineSearchRequest, RelatedProject, RuntimeConfigResponse, SearchRequest,
+    SearchResponse, SimulationScenario, SimulationTranscript, StorageStats,
+    StoreCompositionResponse, SummaryListResponse, UpdateEmbeddingBackendRequest, VerifyReport,
+};
+use domain::{
+    ChangeEvent, ContextEmbedding, ContextSummary, DomainError, EmbeddingComposition, HistoryOrder,
+    QueryHistoryEntry, SearchEvent,
 };
-use domain::{ContextSummary, DomainError};
 #[cfg(feature = "fastembed-engine")]
 use infrastructure::FastEmbedEngine;
+use instance_lock::InstanceLock;
+use log_tail::LogLine;
 
 use infrastructure::{
-    check_service_availability, NoOpEmbeddingEngine, RemoteVectorStore, SimpleEmbedEngine,
-    SledVectorStore,
+    check_service_availability, fetch_remote_summaries, open_store, test_service_connection,
+    ChainMember, ChainedEmbeddingEngine, CircuitBreaker, ConnectionReport, NoOpEmbeddingEngine,
+    OpenedStore, SimpleEmbedEngine, StoreBackend, StoreComparison, StoreOptions,
 };
 
 #[cfg(feature = "mcp-server")]
 use interfaces::mcp::{McpEndpointMetadata, McpRuntime, McpServerConfig};
 
 use power_manager::PowerManager;
-use service_manager::ServiceManager;
-use settings::{available_backends, ConfigManager, EmbeddingBackend};
+use service_manager::{OrphanCleanup, ServiceManager, ServiceProcess};
+use settings::{
+    available_backends, resolve_best_available_backend, AppConfig, ConfigManager, EmbeddingBackend,
+};
 #[cfg(feature = "mcp-server")]
 use tracing::info;
 
 /// Global state shared with Tauri commands.
 struct AppState {
     service: Arc<RwLock<Arc<ContextService>>>,
-    store: Arc<dyn VectorStore>,
-    config: Arc<ConfigManager>,
+    // Swapped together with `service` when the data directory is migrated.
+    store: RwLock<Arc<dyn VectorStore>>,
+    config: RwLock<Arc<ConfigManager>>,
+    data_dir: RwLock<std::path::PathBuf>,
+    // Flips to `Local` if mcp-service disappears mid-session.
+    mode: RwLock<RuntimeMode>,
     service_manager: Arc<ServiceManager>,
     power_manager: Arc<PowerManager>,
+    // Cancellation flag of the running `reembed_all`, if any.
+    reembed_cancel: Mutex<Option<Arc<AtomicBool>>>,
+    // Cancellation flag of the running `tail_service_logs`, if any.
+    log_tail_cancel: Mutex<Option<Arc<AtomicBool>>>,
 }
 
 impl AppState {
@@ -54,92 +81,888 @@ impl AppState {
     ) -> Self {
         Self {
             service: Arc::new(RwLock::new(handles.service)),
-            store: handles.store,
-            config: handles.config,
+            store: RwLock::new(handles.store),
+            config: RwLock::new(handles.config),
+            data_dir: RwLock::new(handles.data_dir),
+            mode: RwLock::new(handles.mode),
             service_manager,
             power_manager,
+            reembed_cancel: Mutex::new(None),
+            log_tail_cancel: Mutex::new(None),
+        }
+    }
+
+    fn service(&self) -> Arc<ContextService> {
+        Arc::clone(&self.service.read())
+    }
+
+    fn service_cell(&self) -> Arc<RwLock<Arc<ContextService>>> {
+        Arc::clone(&self.service)
+    }
+
+    fn store(&self) -> Arc<dyn VectorStore> {
+        Arc::clone(&self.store.read())
+    }
+
+    fn config(&self) -> Arc<ConfigManager> {
+        Arc::clone(&self.config.read())
+    }
+
+    fn data_dir(&self) -> std::path::PathBuf {
+        self.data_dir.read().clone()
+    }
+
+    fn mode(&self) -> RuntimeMode {
+        *self.mode.read()
+    }
+}
+
+pub struct AppHandles {
+    pub service: Arc<ContextService>,
+    pub store: Arc<dyn VectorStore>,
+    pub config: Arc<ConfigManager>,
+    pub data_dir: std::path::PathBuf,
+    pub mode: RuntimeMode,
+    /// Trips when a remote-mode app loses mcp-service; `None` in local mode.
+    pub breaker: Option<Arc<CircuitBreaker>>,
+}
+
+/// Whether this process owns the embedded store or proxies to mcp-service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeMode {
+    Local,
+    Remote,
+}
+
+impl RuntimeMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuntimeMode::Local => "local",
+            RuntimeMode::Remote => "remote",
+        }
+    }
+}
+
+/// Host and port of mcp-service from `INGAT_SERVICE_HOST` / `INGAT_SERVICE_PORT`.
+pub fn service_endpoint() -> (String, u16) {
+    let host = std::env::var("INGAT_SERVICE_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let port = std::env::var("INGAT_SERVICE_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(3200);
+    (host, port)
+}
+
+/// Snapshot of the effective runtime configuration of a running process.
+pub fn describe_runtime(
+    service: &ContextService,
+    config: &ConfigManager,
+    data_dir: &std::path::Path,
+    mode: RuntimeMode,
+) -> RuntimeConfigResponse {
+    let (service_host, service_port) = service_endpoint();
+    let backend = config.current().embedding;
+
+    RuntimeConfigResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        mode: mode.as_str().to_string(),
+        data_dir: data_dir.display().to_string(),
+        store_dir: (mode == RuntimeMode::Local)
+            .then(|| data_dir.join("store").display().to_string()),
+        active_backend: backend.id().to_string(),
+        embedding_model: service.embedding_model().to_string(),
+        dimensions: service.embedding_dimensions(),
+        service_host,
+        service_port,
+        features: compiled_features(),
+    }
+}
+
+/// Cargo features compiled into this binary.
+fn compiled_features() -> Vec<String> {
+    [
+        (cfg!(feature = "simple-embed"), "simple-embed"),
+        (cfg!(feature = "fastembed-engine"), "fastembed-engine"),
+        (cfg!(feature = "mcp-server"), "mcp-server"),
+        (cfg!(feature = "tauri-plugin"), "tauri-plugin"),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, name)| name.to_string())
+    .collect()
+}
+
+/// Version, features and key dependency versions of this build.
+pub fn describe_build() -> BuildInfo {
+    let dependencies = [
+        ("sled", env!("INGAT_DEP_SLED")),
+        ("fastembed", env!("INGAT_DEP_FASTEMBED")),
+        ("rmcp", env!("INGAT_DEP_RMCP")),
+        ("tauri", env!("INGAT_DEP_TAURI")),
+    ]
+    .into_iter()
+    .map(|(name, version)| (name.to_string(), version.to_string()))
+    .collect();
+
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: compiled_features(),
+        dependencies,
+        profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
         }
+        .to_string(),
+        target_os: std::env::consts::OS.to_string(),
+        target_arch: std::env::consts::ARCH.to_string(),
     }
+}
+
+#[tauri::command]
+fn build_info() -> BuildInfo {
+    describe_build()
+}
+
+#[tauri::command]
+async fn ingest_context(
+    state: State<'_, AppState>,
+    payload: IngestContextRequest,
+) -> Result<ContextSummary, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.ingest(payload))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn search_contexts(
+    state: State<'_, AppState>,
+    payload: SearchRequest,
+) -> Result<SearchResponse, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.search(payload))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn export_results_markdown(
+    state: State<'_, AppState>,
+    payload: SearchRequest,
+) -> Result<String, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.export_results_markdown(payload))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn keyword_search(
+    state: State<'_, AppState>,
+    payload: SearchRequest,
+) -> Result<SearchResponse, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.keyword_search(payload))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn rebuild_term_stats(state: State<'_, AppState>) -> Result<usize, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.rebuild_term_stats())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn recent_contexts(
+    state: State<'_, AppState>,
+    project: Option<String>,
+    tag: Option<String>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    limit: Option<usize>,
+    order: Option<HistoryOrder>,
+) -> Result<SummaryListResponse, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || {
+        service.history(project, tag, since, limit, order.unwrap_or_default())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn recent_queries(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<QueryHistoryEntry>, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.recent_queries(limit))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// Forgets every recorded search and returns how many were removed.
+#[tauri::command]
+async fn clear_query_history(state: State<'_, AppState>) -> Result<usize, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.clear_query_history())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// Notes that `chosen_id` was used after the search tagged `query_id`.
+#[tauri::command]
+async fn record_feedback(
+    state: State<'_, AppState>,
+    query_id: uuid::Uuid,
+    chosen_id: uuid::Uuid,
+) -> Result<SearchEvent, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.record_feedback(query_id, chosen_id))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn feedback_stats(state: State<'_, AppState>) -> Result<FeedbackStats, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.feedback_stats())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// Drops every recorded search event and returns how many were removed.
+#[tauri::command]
+async fn clear_feedback(state: State<'_, AppState>) -> Result<usize, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.clear_feedback())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// JSON snippet registering Ingat with `client`, using this install's
+/// `mcp_stdio` path and data directory.
+#[tauri::command]
+fn generate_mcp_config(state: State<'_, AppState>, client: String) -> Result<String, String> {
+    let client = mcp_config::McpClient::parse(&client).map_err(map_domain_error)?;
+    let stdio_binary = if client.launches_stdio() {
+        service_manager::locate_binary("mcp_stdio").map_err(|err| err.to_string())?
+    } else {
+        std::path::PathBuf::new()
+    };
+    let data_dir = state.data_dir();
+    let (service_host, service_port) = service_endpoint();
+
+    Ok(mcp_config::render(
+        client,
+        &mcp_config::McpLaunch {
+            stdio_binary: &stdio_binary,
+            data_dir: &data_dir,
+            service_host: &service_host,
+            service_port,
+        },
+    ))
+}
+
+#[tauri::command]
+async fn list_projects(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.projects())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// Projects with record counts and latest activity, most recently active first.
+#[tauri::command]
+async fn list_projects_with_activity(
+    state: State<'_, AppState>,
+) -> Result<Vec<ProjectInfo>, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.projects_with_activity())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// On-disk store size, record count and average bytes per record.
+#[tauri::command]
+async fn storage_stats(state: State<'_, AppState>) -> Result<StorageStats, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.storage_stats())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// Probe a remote service's health and stats without switching to it.
+#[tauri::command]
+async fn test_remote_connection(host: String, port: u16) -> Result<ConnectionReport, String> {
+    tauri::async_runtime::spawn_blocking(move || test_service_connection(&host, port))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Compare the local store with the service at `host:port` by id and summary
+/// fingerprint. A local store locked by another process is reported, not opened.
+#[tauri::command]
+async fn compare_stores(
+    state: State<'_, AppState>,
+    host: String,
+    port: u16,
+) -> Result<StoreComparison, String> {
+    let local_store = (state.mode() == RuntimeMode::Local).then(|| state.store());
+    let data_dir = state.data_dir();
+    let settings = state.config().current();
+    tauri::async_runtime::spawn_blocking(move || {
+        let local = match local_store {
+            Some(store) => collect_summaries(store.as_ref()),
+            None => read_unlocked_store(&data_dir, &settings),
+        };
+        let remote = fetch_remote_summaries(&host, port).map_err(|err| format!("{err:#}"));
+        match (local, remote) {
+            (Ok(local), Ok(remote)) => StoreComparison::between(&local, &remote),
+            (local, remote) => StoreComparison::unavailable(
+                local.map(|summaries| summaries.len()),
+                remote.map(|summaries| summaries.len()),
+            ),
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+fn collect_summaries(store: &dyn VectorStore) -> Result<Vec<ContextSummary>, String> {
+    store
+        .iter_summaries()
+        .collect::<Result<_, _>>()
+        .map_err(|err| err.to_string())
+}
+
+/// Open the sled store under `data_dir` just long enough to read its summaries,
+/// unless another process holds the instance lock.
+fn read_unlocked_store(
+    data_dir: &std::path::Path,
+    settings: &AppConfig,
+) -> Result<Vec<ContextSummary>, String> {
+    match instance_lock::acquire(data_dir) {
+        Ok(InstanceLock::Acquired) => {}
+        Ok(InstanceLock::HeldBy(pid)) => {
+            return Err(format!("local store is held by process {pid}"));
+        }
+        Err(err) => return Err(format!("failed to take data directory lock: {err}")),
+    }
+    let (host, port) = service_endpoint();
+    let summaries = open_store(
+        StoreBackend::Sled,
+        &store_options(data_dir, settings, host, port),
+    )
+    .map_err(|err| err.to_string())
+    .and_then(|opened| collect_summaries(opened.store.as_ref()));
+    if let Err(err) = instance_lock::release(data_dir) {
+        eprintln!("[ingat] Failed to release data directory lock: {err}");
+    }
+    summaries
+}
+
+#[tauri::command]
+async fn rebuild_index(state: State<'_, AppState>) -> Result<usize, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.rebuild_index())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn context_kinds(state: State<'_, AppState>) -> Result<Vec<LabelCount>, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.context_kinds())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn project_overview(
+    state: State<'_, AppState>,
+    project: String,
+    recent_limit: Option<usize>,
+) -> Result<ProjectOverview, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.project_overview(&project, recent_limit))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+/// Projects whose tags overlap most with `project`'s, best first.
+#[tauri::command]
+async fn related_projects(
+    state: State<'_, AppState>,
+    project: String,
+    limit: Option<usize>,
+) -> Result<Vec<RelatedProject>, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || {
+        service.related_projects(&project, limit.unwrap_or(10))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(map_domain_error)
+}
+
+/// The `k` most similar record pairs in the store, for weeding out redundancy.
+#[tauri::command]
+async fn top_similar_pairs(
+    state: State<'_, AppState>,
+    k: Option<usize>,
+) -> Result<Vec<(uuid::Uuid, uuid::Uuid, f32)>, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.top_similar_pairs(k.unwrap_or(20)))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn embed_text(state: State<'_, AppState>, text: String) -> Result<ContextEmbedding, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.embed_text(text))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+fn runtime_config(state: State<'_, AppState>) -> RuntimeConfigResponse {
+    describe_runtime(
+        &state.service(),
+        &state.config(),
+        &state.data_dir(),
+        state.mode(),
+    )
+}
+
+/// Move the store and config to `new_path`, verify the copy and reopen from it.
+/// Holds the service lock throughout so no write lands in the old store.
+#[tauri::command]
+async fn migrate_data_dir(
+    app: tauri::AppHandle,
+    new_path: String,
+) -> Result<DataMigrationResponse, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<DataMigrationResponse> {
+        let state = app.state::<AppState>();
+        if state.mode() == RuntimeMode::Remote {
+            return Err(anyhow!(
+                "migrating the data directory is not available in remote mode; stop mcp-service first"
+            ));
+        }
+
+        if profiles::active_profile(&resolve_data_root()?) != profiles::DEFAULT_PROFILE {
+            return Err(anyhow!(
+                "switch to the `{}` profile before migrating the data directory",
+                profiles::DEFAULT_PROFILE
+            ));
+        }
+
+        let mut service = state.service.write();
+        let from = state.data_dir();
+        let to = std::path::PathBuf::from(new_path.trim());
+        let default_dir = default_data_dir()?;
+        migration::validate_target(&from, &to)?;
+        let previous_location = migration::relocated_data_dir(&default_dir);
+
+        // sled keeps writing to its files while a Db is open, so the copy is
+        // taken from a closed store.
+        close_local_store(&state, &mut service)?;
+        let records = match migration::migrate_data_dir(&from, &to, &default_dir) {
+            Ok(records) => records,
+            Err(err) => {
+                reopen_local_store(&state, &mut service, from)?;
+                return Err(err);
+            }
+        };
+        // The copied directory is fresh, so this can only fail on IO errors.
+        instance_lock::acquire(&to).context("failed to lock the new data directory")?;
+
+        let handles = match open_local_environment(to.clone(), StoreBackend::Sled) {
+            Ok(handles) => handles,
+            Err(err) => {
+                let _ = instance_lock::release(&to);
+                migration::write_location(&default_dir, previous_location.as_deref())
+                    .context("failed to restore the previous data directory pointer")?;
+                reopen_local_store(&state, &mut service, from)?;
+                return Err(err.context("data was copied but the new location failed to open"));
+            }
+        };
+        *service = handles.service;
+        *state.store.write() = handles.store;
+        *state.config.write() = handles.config;
+        *state.data_dir.write() = handles.data_dir;
+
+        Ok(DataMigrationResponse {
+            from: from.display().to_string(),
+            to: to.display().to_string(),
+            records,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| format!("{err:#}"))
+}
+
+#[tauri::command]
+async fn list_profiles() -> Result<Vec<profiles::ProfileInfo>, String> {
+    tauri::async_runtime::spawn_blocking(|| profiles::list_profiles(&resolve_data_root()?))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| format!("{err:#}"))
+}
+
+#[tauri::command]
+async fn create_profile(name: String) -> Result<profiles::ProfileInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        profiles::create_profile(&resolve_data_root()?, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| format!("{err:#}"))
+}
+
+/// Reopen the app against profile `name`'s store and config and remember
+/// the choice. Holds the service lock so no write lands in the old store.
+#[tauri::command]
+async fn switch_profile(
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<profiles::ProfileInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<profiles::ProfileInfo> {
+        let state = app.state::<AppState>();
+        if state.mode() == RuntimeMode::Remote {
+            return Err(anyhow!(
+                "switching profiles is not available in remote mode; stop mcp-service first"
+            ));
+        }
+        let name = name.trim();
+        profiles::validate_name(name)?;
+        let root = resolve_data_root()?;
+        let to = profiles::profile_dir(&root, name);
+        if !to.is_dir() {
+            return Err(anyhow!("profile `{name}` does not exist"));
+        }
+
+        let mut service = state.service.write();
+        let from = state.data_dir();
+        if from != to {
+            if let InstanceLock::HeldBy(pid) =
+                instance_lock::acquire(&to).context("failed to lock the profile directory")?
+            {
+                return Err(anyhow!("profile `{name}` is in use by process {pid}"));
+            }
+            let handles = match open_local_environment(to.clone(), StoreBackend::Sled) {
+                Ok(handles) => handles,
+                Err(err) => {
+                    let _ = instance_lock::release(&to);
+                    return Err(err.context(format!("failed to open profile `{name}`")));
+                }
+            };
+            if let Err(err) = service.flush() {
+                eprintln!("[ingat] Failed to flush the previous profile: {err}");
+            }
+            *service = handles.service;
+            *state.store.write() = handles.store;
+            *state.config.write() = handles.config;
+            *state.data_dir.write() = handles.data_dir;
+            if let Err(err) = instance_lock::release(&from) {
+                eprintln!("[ingat] Failed to release data directory lock: {err}");
+            }
+        }
+        profiles::set_active_profile(&root, name)?;
+
+        Ok(profiles::ProfileInfo {
+            name: name.to_string(),
+            path: to.display().to_string(),
+            active: true,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| format!("{err:#}"))
+}
+
+/// Write every record to a single JSONL archive at `dest_path`.
+#[tauri::command]
+async fn backup_store(
+    state: State<'_, AppState>,
+    dest_path: String,
+) -> Result<backup::BackupReport, String> {
+    let store = state.store();
+    tauri::async_runtime::spawn_blocking(move || {
+        backup::backup_store(store.as_ref(), std::path::Path::new(dest_path.trim()))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| format!("{err:#}"))
+}
+
+/// Load an archive written by `backup_store` into the (empty) local store.
+/// Holds the service lock throughout so no write interleaves with the restore.
+#[tauri::command]
+async fn restore_store(
+    app: tauri::AppHandle,
+    src_path: String,
+) -> Result<backup::RestoreReport, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<backup::RestoreReport> {
+        let state = app.state::<AppState>();
+        if state.mode() == RuntimeMode::Remote {
+            return Err(anyhow!(
+                "restoring a backup is not available in remote mode; stop mcp-service first"
+            ));
+        }
+
+        let service = state.service.write();
+        let report = backup::restore_store(&*state.store(), std::path::Path::new(src_path.trim()))?;
+        service
+            .rebuild_index()
+            .and_then(|_| service.rebuild_term_stats())
+            .map_err(|err| anyhow!(err.to_string()))
+            .context("records were restored but rebuilding indexes failed")?;
+        Ok(report)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| format!("{err:#}"))
+}
+
+#[tauri::command]
+async fn refine_search(
+    state: State<'_, AppState>,
+    payload: RefineSearchRequest,
+) -> Result<SearchResponse, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || {
+        service.refine(payload.previous, payload.filters, payload.prompt)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn verify_store(state: State<'_, AppState>) -> Result<VerifyReport, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.verify())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn health(state: State<'_, AppState>) -> Result<HealthStatusResponse, String> {
+    let service = state.service();
+    let mut status = tauri::async_runtime::spawn_blocking(move || service.health())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)?;
+    status.mode = Some(state.mode().as_str().to_string());
+    Ok(status)
+}
+
+#[tauri::command]
+async fn pin_context(state: State<'_, AppState>, id: String) -> Result<ContextSummary, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.set_pinned(&id, true))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
 
-    fn service(&self) -> Arc<ContextService> {
-        Arc::clone(&self.service.read())
-    }
+#[tauri::command]
+async fn delete_contexts(
+    state: State<'_, AppState>,
+    ids: Vec<uuid::Uuid>,
+) -> Result<usize, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.delete_many(ids))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
 
-    fn service_cell(&self) -> Arc<RwLock<Arc<ContextService>>> {
-        Arc::clone(&self.service)
-    }
+/// Delete one context; fails with a not-found error for unknown ids.
+#[tauri::command]
+async fn delete_context(state: State<'_, AppState>, id: uuid::Uuid) -> Result<(), String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.delete(id))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
 
-    fn store(&self) -> Arc<dyn VectorStore> {
-        Arc::clone(&self.store)
-    }
+#[tauri::command]
+async fn unpin_context(state: State<'_, AppState>, id: String) -> Result<ContextSummary, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.set_pinned(&id, false))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
+}
 
-    fn config(&self) -> Arc<ConfigManager> {
-        Arc::clone(&self.config)
-    }
+/// Keep a context readable but drop its vectors so it no longer matches searches.
+#[tauri::command]
+async fn archive_context(state: State<'_, AppState>, id: String) -> Result<ContextSummary, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.archive(&id))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
 }
 
-pub struct AppHandles {
-    pub service: Arc<ContextService>,
-    pub store: Arc<dyn VectorStore>,
-    pub config: Arc<ConfigManager>,
-    pub data_dir: std::path::PathBuf,
+/// Line diff of two contexts' bodies plus their tag, kind and project differences.
+#[tauri::command]
+async fn diff_contexts(
+    state: State<'_, AppState>,
+    a: uuid::Uuid,
+    b: uuid::Uuid,
+) -> Result<ContextDiff, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.diff(a, b))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(map_domain_error)
 }
 
+/// Re-embed an archived context so searches find it again.
 #[tauri::command]
-async fn ingest_context(
+async fn unarchive_context(
     state: State<'_, AppState>,
-    payload: IngestContextRequest,
+    id: String,
 ) -> Result<ContextSummary, String> {
     let service = state.service();
-    tauri::async_runtime::spawn_blocking(move || service.ingest(payload))
+    tauri::async_runtime::spawn_blocking(move || service.unarchive(&id))
         .await
         .map_err(|err| err.to_string())?
         .map_err(map_domain_error)
 }
 
 #[tauri::command]
-async fn search_contexts(
+async fn changes_since(
     state: State<'_, AppState>,
-    payload: SearchRequest,
-) -> Result<SearchResponse, String> {
+    since: String,
+    include_deleted: Option<bool>,
+) -> Result<Vec<ChangeEvent>, String> {
     let service = state.service();
-    tauri::async_runtime::spawn_blocking(move || service.search(payload))
+    tauri::async_runtime::spawn_blocking(move || {
+        service.changes_since(&since, include_deleted.unwrap_or(false))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(map_domain_error)
+}
+
+#[tauri::command]
+async fn reembed_project(state: State<'_, AppState>, project: String) -> Result<usize, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || service.reembed_project(&project))
         .await
         .map_err(|err| err.to_string())?
         .map_err(map_domain_error)
 }
 
+/// Re-embed the whole store with the active model, streaming progress to
+/// `on_progress`. One run at a time; `cancel_reembed` stops it.
 #[tauri::command]
-async fn recent_contexts(
+async fn reembed_all(
     state: State<'_, AppState>,
-    project: Option<String>,
-    limit: Option<usize>,
-) -> Result<SummaryListResponse, String> {
+    on_progress: Channel<ReembedProgress>,
+) -> Result<ReembedProgress, String> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = state.reembed_cancel.lock();
+        if running.is_some() {
+            return Err("a re-embed is already running".into());
+        }
+        *running = Some(Arc::clone(&cancel));
+    }
+
     let service = state.service();
-    tauri::async_runtime::spawn_blocking(move || service.history(project, limit))
-        .await
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        service.reembed_all(&cancel, |progress| {
+            let _ = on_progress.send(progress);
+        })
+    })
+    .await;
+    *state.reembed_cancel.lock() = None;
+    result
         .map_err(|err| err.to_string())?
         .map_err(map_domain_error)
 }
 
+/// Stop a running `reembed_all` after the record in progress. Returns whether
+/// one was running.
 #[tauri::command]
-async fn list_projects(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+fn cancel_reembed(state: State<'_, AppState>) -> bool {
+    match state.reembed_cancel.lock().as_ref() {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+async fn store_composition(state: State<'_, AppState>) -> Result<StoreCompositionResponse, String> {
     let service = state.service();
-    tauri::async_runtime::spawn_blocking(move || service.projects())
+    tauri::async_runtime::spawn_blocking(move || service.store_composition())
         .await
         .map_err(|err| err.to_string())?
         .map_err(map_domain_error)
 }
 
 #[tauri::command]
-async fn health(state: State<'_, AppState>) -> Result<HealthStatusResponse, String> {
+async fn benchmark_embedding(
+    state: State<'_, AppState>,
+    samples: Option<usize>,
+) -> Result<EmbeddingBenchmark, String> {
     let service = state.service();
-    tauri::async_runtime::spawn_blocking(move || service.health())
+    tauri::async_runtime::spawn_blocking(move || service.benchmark_embedding(samples.unwrap_or(32)))
         .await
         .map_err(|err| err.to_string())?
         .map_err(map_domain_error)
 }
 
+/// Scripted ingest/search session against the live service; a smoke test for
+/// integrations. Not exposed in the UI.
+#[tauri::command]
+async fn simulate_session(
+    state: State<'_, AppState>,
+    scenario: Option<SimulationScenario>,
+) -> Result<SimulationTranscript, String> {
+    let service = state.service();
+    tauri::async_runtime::spawn_blocking(move || {
+        service.simulate_session(scenario.unwrap_or_default())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(map_domain_error)
+}
+
 #[tauri::command]
 async fn embedding_backends(
     state: State<'_, AppState>,
@@ -157,6 +980,7 @@ async fn set_embedding_backend(
     let service_cell = state.service_cell();
     let store = state.store();
     let config = state.config();
+    let mode = state.mode();
 
     tauri::async_runtime::spawn_blocking(move || -> Result<EmbeddingBackendListResponse> {
         let base_backend = EmbeddingBackend::with_default_model(&payload.backend_id)
@@ -164,13 +988,24 @@ async fn set_embedding_backend(
         let backend = apply_model_override(base_backend, payload.model_override);
 
         let (embedder, service_config) = init_embedder(&backend)?;
-        let new_service = Arc::new(ContextService::new(
-            embedder,
-            Arc::clone(&store),
-            service_config,
-        ));
+        let settings = config.current();
+        let service_config = tune_service_config(service_config, &settings);
+        let mut new_service = ContextService::new(embedder, Arc::clone(&store), service_config);
+        // The store is unchanged, so the outgoing service's (usually cached)
+        // composition spares the dimension check another full scan.
+        if !store.is_remote() {
+            let current = Arc::clone(&service_cell.read());
+            if let Ok(composition) = current.store_composition() {
+                new_service = new_service.with_composition(composition.entries);
+            }
+        }
+        // mcp-service searches its own legacy records in remote mode.
+        if mode != RuntimeMode::Remote {
+            new_service = attach_legacy_index(new_service, &backend, &settings);
+        }
+        let new_service = Arc::new(new_service);
 
-        let updated = config.set_backend(backend).map_err(|err| anyhow!(err))?;
+        let updated = config.set_backend(backend)?;
 
         {
             let mut guard = service_cell.write();
@@ -184,6 +1019,39 @@ async fn set_embedding_backend(
     .map_err(|err| err.to_string())
 }
 
+/// Start `backend_id` on the side, run the embedding self-test on it and drop
+/// it again. The active service and config are left untouched.
+#[tauri::command]
+async fn test_embedding_backend(
+    state: State<'_, AppState>,
+    backend_id: String,
+    model_override: Option<String>,
+) -> Result<EmbeddingSelfTest, String> {
+    let active = state.service();
+    tauri::async_runtime::spawn_blocking(move || -> Result<EmbeddingSelfTest> {
+        let base_backend = EmbeddingBackend::with_default_model(&backend_id)
+            .ok_or_else(|| anyhow!(format!("unknown backend '{backend_id}'")))?;
+        let backend = apply_model_override(base_backend, model_override);
+
+        let started = std::time::Instant::now();
+        let (embedder, service_config) = init_embedder(&backend)?;
+        let init_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        let mut report = application::services::embedding_selftest(
+            backend.id(),
+            embedder.as_ref(),
+            &service_config.embedding_model,
+        )
+        .map_err(|err| anyhow!(err.to_string()))?;
+        report.init_ms = init_ms;
+        report.active_dimensions = active.embedding_dimensions();
+        Ok(report)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| format!("{err:#}"))
+}
+
 /// Entry point invoked from `main.rs`.
 pub fn run() {
     #[cfg(feature = "mcp-server")]
@@ -205,11 +1073,7 @@ fn try_run() -> Result<()> {
     );
 
     // Check if service is already running before attempting to start
-    let host = std::env::var("INGAT_SERVICE_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let port = std::env::var("INGAT_SERVICE_PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(3200);
+    let (host, port) = service_endpoint();
 
     if !check_service_availability(&host, port) {
         eprintln!("[ingat] No running mcp-service detected");
@@ -232,6 +1096,7 @@ fn try_run() -> Result<()> {
     }
 
     let handles = build_environment().context("failed to bootstrap Ingat environment")?;
+    let breaker = handles.breaker.clone();
     let app_state = AppState::new(
         handles,
         Arc::clone(&service_manager),
@@ -261,21 +1126,82 @@ fn try_run() -> Result<()> {
             // Initialize power monitoring after app setup
             power_manager::init_power_monitoring(app.handle(), power_manager_for_setup)
                 .context("failed to initialize power monitoring")?;
+            if let Some(breaker) = breaker {
+                watch_remote_failures(app.handle(), &breaker);
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             ingest_context,
             search_contexts,
+            keyword_search,
+            export_results_markdown,
+            refine_search,
+            rebuild_term_stats,
+            rebuild_index,
             recent_contexts,
             list_projects,
+            list_projects_with_activity,
+            storage_stats,
+            test_remote_connection,
+            context_kinds,
+            project_overview,
+            related_projects,
+            top_similar_pairs,
+            embed_text,
+            runtime_config,
+            build_info,
+            verify_store,
+            migrate_data_dir,
+            backup_store,
+            restore_store,
+            list_profiles,
+            create_profile,
+            switch_profile,
             health,
+            store_composition,
+            reembed_project,
+            reembed_all,
+            cancel_reembed,
+            changes_since,
+            pin_context,
+            unpin_context,
+            archive_context,
+            unarchive_context,
+            diff_contexts,
+            delete_context,
+            delete_contexts,
+            recent_queries,
+            clear_query_history,
+            record_feedback,
+            feedback_stats,
+            clear_feedback,
+            generate_mcp_config,
+            benchmark_embedding,
+            simulate_session,
             embedding_backends,
             set_embedding_backend,
+            test_embedding_backend,
             service_status,
             start_service,
+            list_service_processes,
+            compare_stores,
+            kill_orphan_services,
+            tail_service_logs,
+            stop_service_log_tail,
             stop_service
         ])
-        .run(tauri::generate_context!())
+        .build(tauri::generate_context!())
+        .map(|app| {
+            app.run(|handle, event| {
+                if let tauri::RunEvent::Exit = event {
+                    // Coalesced writes may still be pending; make them durable.
+                    if let Err(err) = handle.state::<AppState>().store().flush() {
+                        eprintln!("[ingat] Failed to flush the store on exit: {err}");
+                    }
+                }
+            })
+        })
         .map_err(|err| anyhow!(err));
 
     #[cfg(feature = "mcp-server")]
@@ -291,6 +1217,83 @@ fn try_run() -> Result<()> {
     Ok(())
 }
 
+/// Emitted with a [`RuntimeModeChanged`] payload when the app switches modes.
+pub const RUNTIME_MODE_EVENT: &str = "runtime-mode-changed";
+
+#[derive(Clone, serde::Serialize)]
+struct RuntimeModeChanged {
+    mode: &'static str,
+    reason: String,
+}
+
+/// Fall back to local mode once `breaker` trips. The switch runs on its own
+/// thread because the tripping call may come from a thread holding the
+/// service lock for reading.
+fn watch_remote_failures(app: &tauri::AppHandle, breaker: &Arc<CircuitBreaker>) {
+    let app = app.clone();
+    let weak = Arc::downgrade(breaker);
+    breaker.on_trip(move || {
+        let app = app.clone();
+        let weak = weak.clone();
+        std::thread::spawn(move || {
+            if let Some(breaker) = weak.upgrade() {
+                fail_over_to_local(&app, &breaker);
+            }
+        });
+    });
+}
+
+fn fail_over_to_local(app: &tauri::AppHandle, breaker: &CircuitBreaker) {
+    let state = app.state::<AppState>();
+    if state.mode() == RuntimeMode::Local {
+        return;
+    }
+    eprintln!("[ingat] ✗ mcp-service stopped responding - attempting LOCAL MODE");
+
+    let data_dir = state.data_dir();
+    match instance_lock::acquire(&data_dir) {
+        Ok(InstanceLock::Acquired) => {}
+        Ok(InstanceLock::HeldBy(pid)) => {
+            eprintln!(
+                "[ingat] Database is still held by process {} - staying in REMOTE MODE",
+                pid
+            );
+            breaker.reset();
+            return;
+        }
+        Err(err) => {
+            eprintln!("[ingat] Failed to take data directory lock: {err}");
+            breaker.reset();
+            return;
+        }
+    }
+    let handles = match open_local_environment(data_dir, StoreBackend::Sled) {
+        Ok(handles) => handles,
+        Err(err) => {
+            eprintln!("[ingat] Failed to open local database: {err:#}");
+            breaker.reset();
+            return;
+        }
+    };
+
+    {
+        let mut service = state.service.write();
+        *service = handles.service;
+        *state.store.write() = handles.store;
+        *state.config.write() = handles.config;
+        *state.mode.write() = RuntimeMode::Local;
+    }
+    eprintln!("[ingat] → Using LOCAL MODE - will open database directly");
+
+    let payload = RuntimeModeChanged {
+        mode: RuntimeMode::Local.as_str(),
+        reason: "mcp-service stopped responding".into(),
+    };
+    if let Err(err) = app.emit(RUNTIME_MODE_EVENT, payload) {
+        eprintln!("[ingat] Failed to emit {RUNTIME_MODE_EVENT}: {err}");
+    }
+}
+
 #[tauri::command]
 async fn service_status(state: State<'_, AppState>) -> Result<ServiceStatusResponse, String> {
     let manager = &state.service_manager;
@@ -319,6 +1322,92 @@ async fn stop_service(state: State<'_, AppState>) -> Result<String, String> {
     Ok("Service stopped".to_string())
 }
 
+#[tauri::command]
+async fn list_service_processes(state: State<'_, AppState>) -> Result<Vec<ServiceProcess>, String> {
+    let manager = Arc::clone(&state.service_manager);
+    tauri::async_runtime::spawn_blocking(move || manager.list_processes())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn kill_orphan_services(state: State<'_, AppState>) -> Result<OrphanCleanup, String> {
+    let manager = Arc::clone(&state.service_manager);
+    tauri::async_runtime::spawn_blocking(move || manager.kill_orphans())
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())
+}
+
+/// Stream the service log file to `on_line`: the last `backlog` lines (200 by
+/// default), then new ones as they are written, until `stop_service_log_tail`
+/// is called or another tail replaces this one. `level` keeps lines at or
+/// above it, e.g. `warn` for warnings and errors.
+#[tauri::command]
+async fn tail_service_logs(
+    state: State<'_, AppState>,
+    on_line: Channel<LogLine>,
+    level: Option<String>,
+    backlog: Option<usize>,
+) -> Result<(), String> {
+    let path = state
+        .service_manager
+        .log_file()
+        .map(std::path::Path::to_path_buf)
+        .ok_or_else(|| {
+            "file logging is off; set INGAT_SERVICE_LOG_FILE and restart mcp-service".to_string()
+        })?;
+    let max_level = match level
+        .as_deref()
+        .map(str::trim)
+        .filter(|raw| !raw.is_empty())
+    {
+        Some(raw) => Some(
+            raw.parse::<tracing::Level>()
+                .map_err(|_| format!("unknown log level `{raw}`"))?,
+        ),
+        None => None,
+    };
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = state.log_tail_cancel.lock().replace(Arc::clone(&cancel)) {
+        previous.store(true, Ordering::Relaxed);
+    }
+    let flag = Arc::clone(&cancel);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        log_tail::follow(&path, max_level, backlog.unwrap_or(200), &flag, |line| {
+            if on_line.send(line).is_err() {
+                flag.store(true, Ordering::Relaxed);
+            }
+        })
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))
+    })
+    .await;
+    {
+        let mut running = state.log_tail_cancel.lock();
+        if running
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, &cancel))
+        {
+            *running = None;
+        }
+    }
+    result.map_err(|err| err.to_string())?
+}
+
+/// Stop a running `tail_service_logs`. Returns whether one was running.
+#[tauri::command]
+fn stop_service_log_tail(state: State<'_, AppState>) -> bool {
+    match state.log_tail_cancel.lock().take() {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
 #[derive(serde::Serialize)]
 struct ServiceStatusResponse {
     is_running: bool,
@@ -347,9 +1436,26 @@ pub async fn run_mcp_bridge(config: Option<McpServerConfig>) -> Result<()> {
         .context("failed to listen for shutdown signal")?;
 
     runtime.shutdown();
+    if let Err(err) = handles.store.flush() {
+        tracing::warn!(target: "ingat::mcp", "Failed to flush the store: {err}");
+    }
     Ok(())
 }
 
+/// Exercise the stdio JSON-RPC handshake end to end against a throwaway
+/// in-memory service, leaving the real store untouched.
+#[cfg(feature = "mcp-server")]
+pub async fn check_mcp_stdio() -> Result<interfaces::mcp::StdioRoundtrip> {
+    let model = "ingat/simple-hash";
+    let engine = SimpleEmbedEngine::try_new(model, 64).map_err(|err| anyhow!(err.to_string()))?;
+    let service = ContextService::new(
+        Arc::new(engine),
+        Arc::new(infrastructure::MemoryVectorStore::new()),
+        application::services::ServiceConfig::with_model(model),
+    );
+    interfaces::mcp::mcp_stdio_roundtrip(Arc::new(RwLock::new(Arc::new(service)))).await
+}
+
 /// Run MCP server using stdio transport (stdin/stdout).
 /// This is designed for VS Code, Cursor, Windsurf, and other IDEs that spawn MCP processes.
 #[cfg(feature = "mcp-server")]
@@ -364,11 +1470,22 @@ pub async fn run_mcp_stdio() -> Result<()> {
         "Starting MCP stdio server (stdin/stdout transport)..."
     );
 
-    interfaces::mcp::run_mcp_stdio_server(service_cell)
+    let served = interfaces::mcp::run_mcp_stdio_server(service_cell)
         .await
-        .context("MCP stdio server failed")?;
+        .context("MCP stdio server failed");
 
-    Ok(())
+    // Close the store before giving up the lock so the next opener can take it.
+    let (mode, data_dir) = (handles.mode, handles.data_dir);
+    if let Err(err) = handles.store.flush() {
+        tracing::warn!(target: "ingat::mcp", "Failed to flush the store: {err}");
+    }
+    drop(handles.store);
+    if mode == RuntimeMode::Local {
+        if let Err(err) = instance_lock::release(&data_dir) {
+            tracing::warn!(target: "ingat::mcp", "Failed to release data directory lock: {err}");
+        }
+    }
+    served
 }
 
 #[cfg(feature = "mcp-server")]
@@ -378,6 +1495,7 @@ fn log_mcp_startup(metadata: &McpEndpointMetadata) {
     info!(
         target: "ingat::mcp",
         bind = %metadata.bind_addr,
+        socket = ?metadata.uds_path,
         sse = %sse_url,
         post = %post_url,
         "MCP runtime listening"
@@ -408,13 +1526,37 @@ where
 }
 
 pub fn build_environment() -> Result<AppHandles> {
-    // Check if mcp-service is running
-    let host = std::env::var("INGAT_SERVICE_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let port = std::env::var("INGAT_SERVICE_PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(3200);
+    let (host, port) = service_endpoint();
+
+    match StoreBackend::from_env().map_err(|err| anyhow!(err.to_string()))? {
+        Some(StoreBackend::Remote) => {
+            eprintln!(
+                "[ingat] ✓ INGAT_STORE_BACKEND=remote - proxying to {}:{}",
+                host, port
+            );
+            return build_environment_remote(&host, port);
+        }
+        Some(StoreBackend::Memory) => {
+            eprintln!("[ingat] → INGAT_STORE_BACKEND=memory - nothing will be persisted");
+            return open_local_environment(resolve_data_dir()?, StoreBackend::Memory);
+        }
+        Some(StoreBackend::Sled) => {
+            let data_dir = resolve_data_dir()?;
+            if let InstanceLock::HeldBy(pid) =
+                instance_lock::acquire(&data_dir).context("failed to take data directory lock")?
+            {
+                return Err(anyhow!(
+                    "INGAT_STORE_BACKEND=sled but the database is held by running process {}",
+                    pid
+                ));
+            }
+            eprintln!("[ingat] → INGAT_STORE_BACKEND=sled - opening database directly");
+            return open_local_environment(data_dir, StoreBackend::Sled);
+        }
+        None => {}
+    }
 
+    // Check if mcp-service is running
     eprintln!("[ingat] Checking for mcp-service at {}:{}...", host, port);
 
     if check_service_availability(&host, port) {
@@ -428,41 +1570,192 @@ pub fn build_environment() -> Result<AppHandles> {
     }
 
     eprintln!("[ingat] ✗ No mcp-service detected at {}:{}", host, port);
+
+    let data_dir = resolve_data_dir()?;
+    match instance_lock::acquire(&data_dir).context("failed to take data directory lock")? {
+        InstanceLock::Acquired => {}
+        InstanceLock::HeldBy(pid) => {
+            // The holder may still be binding its port; remote calls retry on their own.
+            eprintln!(
+                "[ingat] ✓ Database is held by running process {} - using REMOTE MODE",
+                pid
+            );
+            return build_environment_remote(&host, port);
+        }
+    }
+
     eprintln!("[ingat] → Using LOCAL MODE - will open database directly");
     eprintln!("[ingat] → This may conflict if mcp-service starts later");
-    build_environment_local()
+    open_local_environment(data_dir, StoreBackend::Sled)
 }
 
-/// Build environment using local database
-fn build_environment_local() -> Result<AppHandles> {
-    let data_dir = resolve_data_dir()?;
+/// How long `close_local_store` waits for in-flight calls to let go of the store.
+const STORE_CLOSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Swap the local service and store for stand-ins and wait until every
+/// in-flight call has dropped the old ones, which closes the sled database.
+/// On timeout the old store is put back into service.
+fn close_local_store(state: &AppState, service: &mut Arc<ContextService>) -> Result<()> {
+    service
+        .flush()
+        .map_err(|err| anyhow!(err.to_string()))
+        .context("failed to flush store before migration")?;
+
+    let placeholder: Arc<dyn VectorStore> = Arc::new(infrastructure::MemoryVectorStore::new());
+    let store = std::mem::replace(&mut *state.store.write(), Arc::clone(&placeholder));
+    *service = Arc::new(ContextService::new(
+        Arc::new(NoOpEmbeddingEngine::default()),
+        placeholder,
+        application::services::ServiceConfig::default(),
+    ));
+
+    let deadline = std::time::Instant::now() + STORE_CLOSE_TIMEOUT;
+    while Arc::strong_count(&store) > 1 {
+        if std::time::Instant::now() >= deadline {
+            let handles = local_environment(state.data_dir(), state.config(), store)?;
+            *service = handles.service;
+            *state.store.write() = handles.store;
+            return Err(anyhow!(
+                "the store is still in use by a running operation; try again once it finishes"
+            ));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(25));
+    }
+    Ok(())
+}
+
+/// Reopen the sled store under `data_dir` after `close_local_store`.
+fn reopen_local_store(
+    state: &AppState,
+    service: &mut Arc<ContextService>,
+    data_dir: std::path::PathBuf,
+) -> Result<()> {
+    let handles = open_local_environment(data_dir, StoreBackend::Sled)
+        .context("failed to reopen the current data directory")?;
+    *service = handles.service;
+    *state.store.write() = handles.store;
+    *state.config.write() = handles.config;
+    *state.data_dir.write() = handles.data_dir;
+    Ok(())
+}
 
+/// Open `backend` (sled or memory) in-process; config still comes from `data_dir`.
+fn open_local_environment(
+    data_dir: std::path::PathBuf,
+    backend: StoreBackend,
+) -> Result<AppHandles> {
     let config = Arc::new(ConfigManager::load(&data_dir).context("failed to load config file")?);
+    resolve_best_available_backend(&config).context("failed to persist embedding backend")?;
     let active_config = config.current();
 
-    let store_path = data_dir.join("store");
-    std::fs::create_dir_all(&store_path).context("failed to create store directory")?;
-    let store_impl = SledVectorStore::open(&store_path)
-        .map_err(|err| anyhow!(err.to_string()))
-        .context("failed to open embedded store")?;
-    let store: Arc<dyn VectorStore> = Arc::new(store_impl);
+    let (host, port) = service_endpoint();
+    let store = open_store(
+        backend,
+        &store_options(&data_dir, &active_config, host, port),
+    )
+    .map_err(|err| anyhow!(err.to_string()))
+    .context("failed to open embedded store")?
+    .store;
 
-    let (embedder, service_config) = init_embedder(&active_config.embedding)
-        .context("failed to initialise embedding backend")?;
-    let service = Arc::new(ContextService::new(
-        embedder,
-        Arc::clone(&store),
-        service_config,
-    ));
+    local_environment(data_dir, config, store)
+}
+
+/// Build the local service around an already open `store`.
+fn local_environment(
+    data_dir: std::path::PathBuf,
+    config: Arc<ConfigManager>,
+    store: Arc<dyn VectorStore>,
+) -> Result<AppHandles> {
+    let active_config = config.current();
+    let mut embedding = active_config.embedding.clone();
+    // Scanned once here and handed to the service, which reuses it for the
+    // dimension check below and later health polls.
+    let mut composition = None;
+    // Simple is the only variant without `fastembed-engine`.
+    #[allow(irrefutable_let_patterns)]
+    if let EmbeddingBackend::Simple { model, dimensions } = &mut embedding {
+        // Trust what is on disk over a possibly stale config.
+        let scanned = composition.insert(
+            store
+                .store_composition()
+                .map_err(|err| anyhow!(err.to_string()))
+                .context("failed to read store composition")?,
+        );
+        if let Some(stored) = dominant_dimensions(scanned, model) {
+            if stored != *dimensions {
+                eprintln!(
+                    "[ingat] Simple backend configured for {} dimensions but stored `{}` records use {}; using {}",
+                    dimensions, model, stored, stored
+                );
+                *dimensions = stored;
+            }
+        }
+    }
+
+    let (embedder, service_config) = if active_config.backend_chain.is_empty() {
+        init_embedder(&embedding)
+    } else {
+        init_embedder_chain(&active_config.backend_chain, &config)
+    }
+    .context("failed to initialise embedding backend")?;
+    let service_config = tune_service_config(service_config, &active_config);
+    let mut service = ContextService::new(embedder, Arc::clone(&store), service_config);
+    if let Some(composition) = composition {
+        service = service.with_composition(composition);
+    }
+    let service = Arc::new(attach_legacy_index(service, &embedding, &active_config));
+
+    if matches!(active_config.embedding, EmbeddingBackend::Simple { .. }) {
+        if let Ok(Some(mismatch)) = service.dimension_mismatch() {
+            eprintln!(
+                "[ingat] Warning: simple backend is configured for {} dimensions but {} stored record(s) of `{}` use {:?}; they will not match new searches until re-embedded",
+                mismatch.configured, mismatch.affected_records, mismatch.model, mismatch.stored
+            );
+        }
+    }
 
     Ok(AppHandles {
         service,
         store,
         config,
         data_dir,
+        mode: RuntimeMode::Local,
+        breaker: None,
     })
 }
 
+/// Registry options for every store backend, from persisted storage settings.
+fn store_options(
+    data_dir: &std::path::Path,
+    settings: &AppConfig,
+    host: String,
+    port: u16,
+) -> StoreOptions {
+    StoreOptions {
+        store_dir: data_dir.join("store"),
+        sync_writes: settings.storage.sync_writes,
+        flush_coalesce_ms: settings.storage.flush_coalesce_ms,
+        flush_coalesce_writes: settings.storage.flush_coalesce_writes,
+        vector_precision: settings.storage.vector_precision,
+        separate_embeddings: settings.storage.separate_embeddings,
+        query_history: settings.search.query_history,
+        host,
+        port,
+    }
+}
+
+/// Dimension shared by most stored records of `model`, if any exist.
+fn dominant_dimensions(composition: &[EmbeddingComposition], model: &str) -> Option<usize> {
+    let mut by_dimensions: std::collections::HashMap<usize, usize> = Default::default();
+    for entry in composition.iter().filter(|entry| entry.model == model) {
+        *by_dimensions.entry(entry.dimensions).or_default() += entry.count;
+    }
+    by_dimensions
+        .into_iter()
+        .max_by_key(|&(dimensions, count)| (count, dimensions))
+        .map(|(dimensions, _)| dimensions)
+}
+
 /// Build environment using remote mcp-service
 fn build_environment_remote(host: &str, port: u16) -> Result<AppHandles> {
     let data_dir = resolve_data_dir()?;
@@ -471,15 +1764,22 @@ fn build_environment_remote(host: &str, port: u16) -> Result<AppHandles> {
     let active_config = config.current();
 
     // Use remote implementations
-    let store: Arc<dyn VectorStore> = Arc::new(RemoteVectorStore::new(host, port));
+    let OpenedStore { store, breaker } = open_store(
+        StoreBackend::Remote,
+        &store_options(&data_dir, &active_config, host.to_string(), port),
+    )
+    .map_err(|err| anyhow!(err.to_string()))?;
 
     // Use a no-op embedder since embedding happens on the remote service
     // The RemoteVectorStore handles all operations including embedding via HTTP proxy
     let embedder: Arc<dyn EmbeddingEngineTrait> = Arc::new(NoOpEmbeddingEngine::for_remote_mode());
     let default_limit = application::services::ServiceConfig::default().default_limit;
-    let service_config = application::services::ServiceConfig::new(
-        active_config.embedding.model_name(),
-        default_limit,
+    let service_config = tune_service_config(
+        application::services::ServiceConfig::new(
+            active_config.embedding.model_name(),
+            default_limit,
+        ),
+        &active_config,
     );
 
     let service = Arc::new(ContextService::new(
@@ -493,6 +1793,8 @@ fn build_environment_remote(host: &str, port: u16) -> Result<AppHandles> {
         store,
         config,
         data_dir,
+        mode: RuntimeMode::Remote,
+        breaker,
     })
 }
 
@@ -512,13 +1814,141 @@ fn init_embedder(
         }
         #[cfg(feature = "fastembed-engine")]
         EmbeddingBackend::FastEmbed { model } => {
-            let engine = FastEmbedEngine::try_new(model).map_err(|err| anyhow!(err.to_string()))?;
+            let instances = infrastructure::embeddings::fastembed_engine::instances_from_env();
+            let engine = FastEmbedEngine::try_new_pool(model, instances)
+                .map_err(|err| anyhow!(err.to_string()))?;
             let config = application::services::ServiceConfig::new(model.clone(), default_limit);
             Ok((Arc::new(engine), config))
         }
     }
 }
 
+/// Initialise every backend in `chain` that can start and settle on the first
+/// one that actually embeds; later members are only fallbacks for that probe.
+/// The member an earlier run settled on goes first, and the one that serves
+/// this run is saved for the next.
+fn init_embedder_chain(
+    chain: &[EmbeddingBackend],
+    config: &ConfigManager,
+) -> Result<(
+    Arc<dyn EmbeddingEngineTrait>,
+    application::services::ServiceConfig,
+)> {
+    let previous = config.current().settled_backend;
+    let mut ordered: Vec<&EmbeddingBackend> = chain.iter().collect();
+    if let Some(position) = ordered
+        .iter()
+        .position(|backend| Some(*backend) == previous.as_ref())
+    {
+        let preferred = ordered.remove(position);
+        ordered.insert(0, preferred);
+    }
+
+    let mut members = Vec::new();
+    let mut backends = Vec::new();
+    for backend in ordered {
+        match init_embedder(backend) {
+            Ok((engine, service_config)) => {
+                members.push(ChainMember {
+                    backend_id: backend.id().to_string(),
+                    model: service_config.embedding_model,
+                    engine,
+                });
+                backends.push(backend);
+            }
+            Err(err) => eprintln!("[ingat] Skipping {} backend: {err}", backend.id()),
+        }
+    }
+
+    let engine =
+        ChainedEmbeddingEngine::try_new(members).map_err(|err| anyhow!(err.to_string()))?;
+    let served = engine.settle().map_err(|err| anyhow!(err.to_string()))?;
+    eprintln!(
+        "[ingat] Embedding chain settled on {} ({})",
+        served.backend_id, served.model
+    );
+    let served_backend = backends
+        .into_iter()
+        .find(|backend| backend.id() == served.backend_id && backend.model_name() == served.model);
+    if let Some(backend) = served_backend.filter(|backend| Some(*backend) != previous.as_ref()) {
+        if let Err(err) = config.set_settled_backend(backend.clone()) {
+            eprintln!("[ingat] Failed to save the settled embedding backend: {err}");
+        }
+    }
+
+    let default_limit = application::services::ServiceConfig::default().default_limit;
+    let config = application::services::ServiceConfig::new(served.model.clone(), default_limit);
+    Ok((Arc::new(engine), config))
+}
+
+/// Apply persisted settings on top of the backend-derived service config.
+fn tune_service_config(
+    service_config: application::services::ServiceConfig,
+    settings: &AppConfig,
+) -> application::services::ServiceConfig {
+    service_config
+        .with_field_embeddings(settings.search.field_embeddings)
+        .with_search_cache_ttl_ms(settings.search.search_cache_ttl_ms)
+        .with_truncation(
+            settings.embedding_input.truncation,
+            settings.embedding_input.max_chars,
+        )
+        .with_embed_scope(settings.embedding_input.scope)
+        .with_embed_file_path(settings.embedding_input.include_file_path)
+        .with_preprocess(
+            settings
+                .embedding_input
+                .preprocess
+                .get(settings.embedding.id())
+                .cloned()
+                .unwrap_or_default(),
+        )
+        .with_auto_summary(settings.ingest.auto_summary)
+        .with_sanitize_unicode(settings.ingest.sanitize_unicode)
+        .with_auto_tag_language(settings.ingest.auto_tag_language)
+        .with_infer_kind(settings.ingest.infer_kind)
+        .with_chunking(settings.ingest.chunk_size, settings.ingest.chunk_overlap)
+        .with_tag_boost(settings.search.tag_boost)
+        .with_lazy_reembed(settings.search.lazy_reembed)
+        .with_empty_results_message(&settings.search.empty_results_message)
+        .with_search_analytics(settings.search.search_analytics)
+        .with_legacy_penalty(settings.search.legacy_penalty)
+        .with_tombstone_retention_days(settings.storage.tombstone_retention_days)
+        .with_ide_default_kinds(&settings.ide_default_kind)
+}
+
+/// Search the configured `legacy_embedding` backend's records alongside the
+/// active ones. Remote stores can't filter by model, so this is local only.
+fn attach_legacy_index(
+    service: ContextService,
+    active: &EmbeddingBackend,
+    settings: &AppConfig,
+) -> ContextService {
+    let Some(legacy) = settings
+        .legacy_embedding
+        .as_ref()
+        .filter(|legacy| *legacy != active)
+    else {
+        return service;
+    };
+    match init_embedder(legacy) {
+        Ok((engine, config)) => {
+            eprintln!(
+                "[ingat] Searching legacy `{}` records alongside the active model",
+                config.embedding_model
+            );
+            service.with_legacy_index(engine, config.embedding_model)
+        }
+        Err(err) => {
+            eprintln!(
+                "[ingat] Legacy {} backend unavailable, its records won't be searched: {err:#}",
+                legacy.id()
+            );
+            service
+        }
+    }
+}
+
 fn build_backend_response(
     active: EmbeddingBackend,
     service: Arc<ContextService>,
@@ -542,9 +1972,18 @@ fn build_backend_response(
             .or_else(|| active.expected_dimensions());
     }
 
+    // Only the simple backend lets users pick an arbitrary dimension, so it is
+    // the one that can silently end up with an unsearchable mix.
+    let dimension_mismatch = if matches!(active, EmbeddingBackend::Simple { .. }) {
+        service.dimension_mismatch().ok().flatten()
+    } else {
+        None
+    };
+
     EmbeddingBackendListResponse {
         active: active.id().to_string(),
         options,
+        dimension_mismatch,
     }
 }
 
@@ -570,7 +2009,7 @@ fn apply_model_override(
     backend
 }
 
-fn resolve_data_dir() -> Result<std::path::PathBuf> {
+fn default_data_dir() -> Result<std::path::PathBuf> {
     let dirs = directories::ProjectDirs::from("dev", "ingat", "Ingat")
         .ok_or_else(|| anyhow!("unable to determine OS data dir"))?;
     let dir = dirs.data_dir().to_path_buf();
@@ -578,6 +2017,25 @@ fn resolve_data_dir() -> Result<std::path::PathBuf> {
     Ok(dir)
 }
 
+/// `INGAT_DATA_DIR` when set, else the OS data directory or wherever
+/// `migrate_data_dir` last moved it. Profiles live underneath.
+fn resolve_data_root() -> Result<std::path::PathBuf> {
+    if let Some(dir) = std::env::var_os("INGAT_DATA_DIR").filter(|dir| !dir.is_empty()) {
+        let dir = std::path::PathBuf::from(dir);
+        std::fs::create_dir_all(&dir).context("failed to create data directory")?;
+        return Ok(dir);
+    }
+    let dir = default_data_dir()?;
+    Ok(migration::relocated_data_dir(&dir).unwrap_or(dir))
+}
+
+/// Store and config directory of the active profile.
+fn resolve_data_dir() -> Result<std::path::PathBuf> {
+    let root = resolve_data_root()?;
+    let profile = profiles::active_profile(&root);
+    Ok(profiles::profile_dir(&root, &profile))
+}
+
 fn map_domain_error(err: DomainError) -> String {
     err.to_string()
 }
@@ -0,0 +1,310 @@
+//! Following the detached mcp-service's log file.
+//!
+//! [`LogTail`] remembers how far into the file it has read and picks up
+//! whatever was appended since. A file that shrinks (copy-truncate) or is
+//! replaced by a new one (rename-and-recreate, as [`rotate_log_file`] does on
+//! every service start) is read again from the top, so rotation never loses
+//! the tail or replays stale lines.
+
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tracing::Level;
+
+/// How often [`follow`] checks the file for new lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Bytes read back from the end of the file for the initial backlog.
+const BACKLOG_WINDOW: u64 = 256 * 1024;
+
+/// Log files past this size are moved aside when the service starts.
+pub const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// One line of the service log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogLine {
+    /// `ERROR` through `TRACE`; `None` for lines that carry no level, such as
+    /// panic messages or the continuation of a multi-line event.
+    pub level: Option<String>,
+    pub line: String,
+}
+
+/// Move `path` to `<path>.1` (replacing an older one) once it has grown past
+/// `max_bytes`, so the next writer starts a fresh file.
+pub fn rotate_log_file(path: &Path, max_bytes: u64) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(meta) if meta.len() > max_bytes => fs::rename(path, rotated_path(path)),
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    path.with_file_name(name)
+}
+
+/// The level of a `tracing_subscriber::fmt` line, which comes right after the
+/// timestamp.
+pub fn parse_level(line: &str) -> Option<Level> {
+    line.split_whitespace()
+        .take(2)
+        .find_map(|token| match token {
+            "ERROR" => Some(Level::ERROR),
+            "WARN" => Some(Level::WARN),
+            "INFO" => Some(Level::INFO),
+            "DEBUG" => Some(Level::DEBUG),
+            "TRACE" => Some(Level::TRACE),
+            _ => None,
+        })
+}
+
+/// Incremental reader over a log file that may be rotated underneath it.
+pub struct LogTail {
+    path: PathBuf,
+    file: Option<File>,
+    identity: Option<FileIdentity>,
+    offset: u64,
+    /// Bytes after the last newline, held until the line is complete.
+    partial: Vec<u8>,
+}
+
+impl LogTail {
+    /// Start following `path`, returning up to `backlog` of its most recent
+    /// lines. A missing file is not an error; it is picked up once created.
+    pub fn open(path: impl Into<PathBuf>, backlog: usize) -> io::Result<(Self, Vec<String>)> {
+        let mut tail = Self {
+            path: path.into(),
+            file: None,
+            identity: None,
+            offset: 0,
+            partial: Vec::new(),
+        };
+        let Some(meta) = tail.reopen()? else {
+            return Ok((tail, Vec::new()));
+        };
+        tail.offset = meta.len().saturating_sub(BACKLOG_WINDOW);
+        let skip_first = tail.offset > 0;
+        let mut lines = tail.read_new()?;
+        if skip_first && !lines.is_empty() {
+            // The window most likely starts mid-line.
+            lines.remove(0);
+        }
+        let keep = lines.len().saturating_sub(backlog);
+        Ok((tail, lines.split_off(keep)))
+    }
+
+    /// Complete lines appended since the last call.
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let meta = match fs::metadata(&self.path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                // Rotated away and not recreated yet; the old file may still
+                // have lines we have not read.
+                return self.read_new();
+            }
+            Err(err) => return Err(err),
+        };
+        let replaced = self.file.is_none() || Some(FileIdentity::of(&meta)) != self.identity;
+        if replaced {
+            // The old file stays readable through the open handle, so drain
+            // it before moving on.
+            let mut lines = self.read_new()?;
+            lines.extend(self.flush_partial());
+            lines.extend(self.restart()?);
+            return Ok(lines);
+        }
+        if meta.len() < self.offset {
+            self.partial.clear();
+            self.offset = 0;
+        }
+        if meta.len() == self.offset {
+            return Ok(Vec::new());
+        }
+        self.read_new()
+    }
+
+    /// Read the replacement file from the top.
+    fn restart(&mut self) -> io::Result<Vec<String>> {
+        self.offset = 0;
+        self.partial.clear();
+        match self.reopen()? {
+            Some(_) => self.read_new(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn reopen(&mut self) -> io::Result<Option<fs::Metadata>> {
+        match File::open(&self.path) {
+            Ok(file) => {
+                let meta = file.metadata()?;
+                self.identity = Some(FileIdentity::of(&meta));
+                self.file = Some(file);
+                Ok(Some(meta))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                self.file = None;
+                self.identity = None;
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(Vec::new());
+        };
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut chunk = Vec::new();
+        let read = file.read_to_end(&mut chunk)?;
+        self.offset += read as u64;
+        self.partial.extend_from_slice(&chunk);
+
+        let Some(last_newline) = self.partial.iter().rposition(|byte| *byte == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let rest = self.partial.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        Ok(String::from_utf8_lossy(&complete)
+            .lines()
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+
+    /// The unterminated last line of a file that was rotated away.
+    fn flush_partial(&mut self) -> Vec<String> {
+        let partial = std::mem::take(&mut self.partial);
+        let line = String::from_utf8_lossy(&partial).trim_end().to_string();
+        if line.is_empty() {
+            Vec::new()
+        } else {
+            vec![line]
+        }
+    }
+}
+
+/// Distinguishes a recreated file from the one already open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity {
+    #[cfg(unix)]
+    inode: (u64, u64),
+    #[cfg(not(unix))]
+    created: Option<std::time::SystemTime>,
+}
+
+impl FileIdentity {
+    fn of(meta: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Self {
+                inode: (meta.dev(), meta.ino()),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            Self {
+                created: meta.created().ok(),
+            }
+        }
+    }
+}
+
+/// Stream `path` to `emit` until `cancel` is set: first up to `backlog`
+/// recent lines, then new ones as they are written. Lines above
+/// `max_level` are dropped; lines without a level always pass.
+pub fn follow(
+    path: &Path,
+    max_level: Option<Level>,
+    backlog: usize,
+    cancel: &AtomicBool,
+    mut emit: impl FnMut(LogLine),
+) -> io::Result<()> {
+    let (mut tail, recent) = LogTail::open(path, backlog)?;
+    let mut send = |lines: Vec<String>| {
+        for line in lines {
+            let level = parse_level(&line);
+            if matches!((level, max_level), (Some(level), Some(max)) if level > max) {
+                continue;
+            }
+            emit(LogLine {
+                level: level.map(|level| level.to_string()),
+                line,
+            });
+        }
+    };
+    send(recent);
+    while !cancel.load(Ordering::Relaxed) {
+        std::thread::sleep(POLL_INTERVAL);
+        send(tail.poll()?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_log_tail_follows_appends_and_rotation() {
+        let dir = std::env::temp_dir().join(format!("ingat-logs-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mcp-service.log");
+
+        append(&path, "one\ntwo\nthree\n");
+        let (mut tail, recent) = LogTail::open(&path, 2).unwrap();
+        assert_eq!(recent, vec!["two", "three"]);
+
+        // Partial lines wait for their newline.
+        append(&path, "fo");
+        assert!(tail.poll().unwrap().is_empty());
+        append(&path, "ur\n");
+        assert_eq!(tail.poll().unwrap(), vec!["four"]);
+
+        // Rename-and-recreate: what reached the old file is read first, then
+        // the new file from the top.
+        append(&path, "five\nsi");
+        rotate_log_file(&path, 0).unwrap();
+        assert!(dir.join("mcp-service.log.1").exists());
+        assert_eq!(tail.poll().unwrap(), vec!["five"]);
+        assert!(tail.poll().unwrap().is_empty());
+        append(&path, "fresh\n");
+        assert_eq!(tail.poll().unwrap(), vec!["si", "fresh"]);
+
+        // Copy-truncate: a shorter file restarts at the beginning.
+        fs::write(&path, "").unwrap();
+        append(&path, "ok\n");
+        assert_eq!(tail.poll().unwrap(), vec!["ok"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_level_reads_fmt_lines() {
+        let line = "2024-05-01T10:00:00.000000Z  WARN ThreadId(02) ingat::storage: slow flush";
+        assert_eq!(parse_level(line), Some(Level::WARN));
+        assert_eq!(parse_level("thread 'main' panicked at src/lib.rs"), None);
+        assert!(Level::DEBUG > Level::INFO);
+    }
+}
@@ -0,0 +1,142 @@
+//! Ready-to-paste MCP client configuration.
+//!
+//! Hand-written `mcpServers` blocks were the most common setup mistake: each
+//! editor nests the server under a different key, and paths copied from the
+//! docs never match the local install. Snippets produced here use the real
+//! `mcp_stdio` path and data directory of the running app.
+
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::domain::DomainError;
+
+/// Editors with a known MCP configuration layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum McpClient {
+    VsCode,
+    Cursor,
+    Windsurf,
+    ClaudeDesktop,
+    Sublime,
+    /// Connects to mcp-service over HTTP instead of launching `mcp_stdio`.
+    Zed,
+}
+
+impl McpClient {
+    pub const ALL: [McpClient; 6] = [
+        Self::VsCode,
+        Self::Cursor,
+        Self::Windsurf,
+        Self::ClaudeDesktop,
+        Self::Sublime,
+        Self::Zed,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::VsCode => "vscode",
+            Self::Cursor => "cursor",
+            Self::Windsurf => "windsurf",
+            Self::ClaudeDesktop => "claude-desktop",
+            Self::Sublime => "sublime",
+            Self::Zed => "zed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, DomainError> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|client| client.id().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::ALL.iter().map(McpClient::id).collect();
+                DomainError::validation(format!(
+                    "unknown MCP client `{value}` (expected one of: {})",
+                    known.join(", ")
+                ))
+            })
+    }
+
+    /// Whether the snippet launches `mcp_stdio` (everything but Zed).
+    pub fn launches_stdio(&self) -> bool {
+        *self != Self::Zed
+    }
+
+    /// Top-level key the client reads its server table from.
+    fn servers_key(&self) -> &'static str {
+        match self {
+            Self::VsCode => "mcp.servers",
+            Self::Cursor | Self::Windsurf | Self::ClaudeDesktop => "mcpServers",
+            Self::Sublime => "mcp_servers",
+            Self::Zed => "context_servers",
+        }
+    }
+}
+
+/// Paths and endpoint of this install that a client needs to reach Ingat.
+#[derive(Debug, Clone)]
+pub struct McpLaunch<'a> {
+    pub stdio_binary: &'a Path,
+    pub data_dir: &'a Path,
+    pub service_host: &'a str,
+    pub service_port: u16,
+}
+
+/// Pretty-printed JSON snippet registering Ingat with `client`.
+pub fn render(client: McpClient, launch: &McpLaunch<'_>) -> String {
+    let server = match client {
+        McpClient::Zed => json!({
+            "settings": {
+                "url": format!("http://{}:{}", launch.service_host, launch.service_port),
+            },
+        }),
+        _ => json!({
+            "command": launch.stdio_binary.display().to_string(),
+            "args": [],
+            "env": {
+                "INGAT_DATA_DIR": launch.data_dir.display().to_string(),
+                "INGAT_SERVICE_HOST": launch.service_host,
+                "INGAT_SERVICE_PORT": launch.service_port.to_string(),
+            },
+        }),
+    };
+
+    let snippet = json!({ client.servers_key(): { "ingat": server } });
+    // Serializing a `Value` cannot fail.
+    serde_json::to_string_pretty(&snippet).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippets_use_client_key_and_real_paths() {
+        let launch = McpLaunch {
+            stdio_binary: Path::new("/opt/ingat/mcp_stdio"),
+            data_dir: Path::new("/home/dev/.ingat"),
+            service_host: "127.0.0.1",
+            service_port: 3200,
+        };
+
+        let cursor: serde_json::Value =
+            serde_json::from_str(&render(McpClient::parse("Cursor").unwrap(), &launch)).unwrap();
+        let server = &cursor["mcpServers"]["ingat"];
+        assert_eq!(server["command"], "/opt/ingat/mcp_stdio");
+        assert_eq!(server["env"]["INGAT_DATA_DIR"], "/home/dev/.ingat");
+
+        let vscode: serde_json::Value =
+            serde_json::from_str(&render(McpClient::VsCode, &launch)).unwrap();
+        assert!(vscode["mcp.servers"]["ingat"]["command"].is_string());
+
+        let zed: serde_json::Value =
+            serde_json::from_str(&render(McpClient::Zed, &launch)).unwrap();
+        assert_eq!(
+            zed["context_servers"]["ingat"]["settings"]["url"],
+            "http://127.0.0.1:3200"
+        );
+
+        assert!(McpClient::parse("notepad").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Moving the data directory (sled store + config) to a new location.
+//!
+//! The store must be closed while it is copied, since an open sled database
+//! keeps writing to its files. The copy is staged next to the target and only
+//! renamed into place once the copied store opens and holds the same number of
+//! records as the source. The
+//! new location is then recorded in a pointer file inside the default data
+//! directory, which `resolve_data_dir` follows on the next start. The source
+//! directory is left untouched so it can serve as a backup.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use uuid::Uuid;
+
+use crate::{
+    application::services::VectorStore, infrastructure::SledVectorStore, settings::CONFIG_FILENAME,
+};
+
+/// File in the default data directory naming a relocated data directory.
+pub const LOCATION_FILENAME: &str = "data_location";
+
+const STORE_DIRNAME: &str = "store";
+
+/// Follow the pointer left by a previous migration, if any.
+pub fn relocated_data_dir(default_dir: &Path) -> Option<PathBuf> {
+    let raw = fs::read_to_string(default_dir.join(LOCATION_FILENAME)).ok()?;
+    let path = PathBuf::from(raw.trim());
+    (path.is_absolute() && path.is_dir()).then_some(path)
+}
+
+/// Copy the store and config from `from` to `to`, verify the copy and point
+/// `default_dir` at it. The caller must close the store under `from` first;
+/// opening it here fails while another handle holds it. Returns the number
+/// of records moved.
+pub fn migrate_data_dir(from: &Path, to: &Path, default_dir: &Path) -> Result<usize> {
+    validate_target(from, to)?;
+
+    let expected = {
+        let source = SledVectorStore::open(from.join(STORE_DIRNAME))
+            .map_err(|err| anyhow!(err.to_string()))
+            .context("the current store is still open")?;
+        count_records(&source).context("failed to count source records")?
+    };
+
+    let name = to
+        .file_name()
+        .ok_or_else(|| anyhow!("target path has no directory name"))?
+        .to_string_lossy();
+    let staging = to.with_file_name(format!(".{name}.migrating-{}", Uuid::new_v4()));
+
+    let staged = stage_copy(from, &staging, expected);
+    if let Err(err) = staged {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(err);
+    }
+
+    if to.exists() {
+        // Validated empty above; `rename` cannot replace a directory on every platform.
+        fs::remove_dir(to).context("failed to replace empty target directory")?;
+    }
+    if let Err(err) = fs::rename(&staging, to) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(err).context("failed to move staged copy into place");
+    }
+
+    if let Err(err) = write_location(default_dir, Some(to)) {
+        let _ = fs::remove_dir_all(to);
+        return Err(err).context("failed to record the new data directory");
+    }
+
+    Ok(expected)
+}
+
+/// Reject targets that are relative, overlap `from` or already hold files.
+pub fn validate_target(from: &Path, to: &Path) -> Result<()> {
+    if !to.is_absolute() {
+        bail!("target directory must be an absolute path");
+    }
+    if to.starts_with(from) || from.starts_with(to) {
+        bail!("target directory must not overlap the current data directory");
+    }
+    if to.exists() {
+        if !to.is_dir() {
+            bail!("target {} is not a directory", to.display());
+        }
+        if fs::read_dir(to)?.next().is_some() {
+            bail!("target directory {} is not empty", to.display());
+        }
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("target parent {} is not writable", parent.display()))?;
+    }
+    Ok(())
+}
+
+fn stage_copy(from: &Path, staging: &Path, expected: usize) -> Result<()> {
+    fs::create_dir(staging)
+        .with_context(|| format!("target location {} is not writable", staging.display()))?;
+    copy_dir(&from.join(STORE_DIRNAME), &staging.join(STORE_DIRNAME))
+        .context("failed to copy store")?;
+    let config = from.join(CONFIG_FILENAME);
+    if config.exists() {
+        fs::copy(&config, staging.join(CONFIG_FILENAME)).context("failed to copy config")?;
+    }
+
+    let copied = SledVectorStore::open(staging.join(STORE_DIRNAME))
+        .map_err(|err| anyhow!(err.to_string()))
+        .context("copied store does not open")?;
+    let actual = count_records(&copied).context("failed to count copied records")?;
+    if actual != expected {
+        bail!("copied store holds {actual} records, expected {expected}");
+    }
+    Ok(())
+}
+
+fn count_records(store: &dyn VectorStore) -> Result<usize> {
+    store.iter_summaries().try_fold(0, |count, summary| {
+        summary
+            .map(|_| count + 1)
+            .map_err(|err| anyhow!(err.to_string()))
+    })
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Point `default_dir` at `to`, or back at itself when `None`. The pointer is
+/// written via a temp file + rename so a crash never leaves it half-written.
+pub fn write_location(default_dir: &Path, to: Option<&Path>) -> Result<()> {
+    let pointer = default_dir.join(LOCATION_FILENAME);
+    let Some(to) = to else {
+        return match fs::remove_file(&pointer) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        };
+    };
+    let temp = default_dir.join(format!("{LOCATION_FILENAME}.tmp"));
+    fs::write(&temp, to.display().to_string())?;
+    fs::rename(&temp, &pointer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_rejects_non_empty_or_overlapping_targets() {
+        let root = std::env::temp_dir().join(format!("ingat-migrate-{}", Uuid::new_v4()));
+        let from = root.join("data");
+        fs::create_dir_all(&from).unwrap();
+
+        assert!(validate_target(&from, &from.join("nested")).is_err());
+        assert!(validate_target(&from, Path::new("relative")).is_err());
+
+        let occupied = root.join("occupied");
+        fs::create_dir_all(&occupied).unwrap();
+        fs::write(occupied.join("file"), b"x").unwrap();
+        assert!(validate_target(&from, &occupied).is_err());
+
+        assert!(validate_target(&from, &root.join("fresh")).is_ok());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_migration_copies_a_closed_store_and_restores_the_pointer() {
+        let root = std::env::temp_dir().join(format!("ingat-migrate-{}", Uuid::new_v4()));
+        let from = root.join("data");
+        let to = root.join("moved");
+
+        let store = SledVectorStore::open(from.join(STORE_DIRNAME)).unwrap();
+        store
+            .persist(&crate::domain::ContextRecord::new(
+                "ingat",
+                "vscode",
+                None::<String>,
+                None::<String>,
+                "summary",
+                "body",
+                ["tag"],
+                crate::domain::ContextKind::CodeSnippet,
+                crate::domain::ContextEmbedding::new("ingat/simple-hash", vec![0.6, 0.8]),
+            ))
+            .unwrap();
+        // Still open: the copy is refused.
+        assert!(migrate_data_dir(&from, &to, &root).is_err());
+        assert!(!to.exists());
+
+        drop(store);
+        assert_eq!(migrate_data_dir(&from, &to, &root).unwrap(), 1);
+        assert_eq!(relocated_data_dir(&root), Some(to.clone()));
+
+        write_location(&root, None).unwrap();
+        assert_eq!(relocated_data_dir(&root), None);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
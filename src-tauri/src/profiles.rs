@@ -0,0 +1,174 @@
+//! Named data profiles: separate stores and configs under one data directory.
+//!
+//! The `default` profile is the data directory itself, so installs without
+//! profiles keep working unchanged. Every other profile lives in
+//! `profiles/<name>/` with its own `store/` and `config.json`. The active
+//! profile is recorded in a pointer file in the data directory, which
+//! `resolve_data_dir` follows on every start.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+/// Profile backed by the data directory itself.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// File in the data directory naming the active profile.
+pub const ACTIVE_PROFILE_FILENAME: &str = "active_profile";
+
+const PROFILES_DIRNAME: &str = "profiles";
+const MAX_PROFILE_NAME_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub path: String,
+    pub active: bool,
+}
+
+/// Directory holding `name`'s store and config; `root` for the default profile.
+pub fn profile_dir(root: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        root.to_path_buf()
+    } else {
+        root.join(PROFILES_DIRNAME).join(name)
+    }
+}
+
+/// Name of the active profile. A pointer to a profile that no longer exists
+/// falls back to the default one.
+pub fn active_profile(root: &Path) -> String {
+    fs::read_to_string(root.join(ACTIVE_PROFILE_FILENAME))
+        .ok()
+        .map(|raw| raw.trim().to_string())
+        .filter(|name| validate_name(name).is_ok() && profile_dir(root, name).is_dir())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// The default profile first, then the others by name.
+pub fn list_profiles(root: &Path) -> Result<Vec<ProfileInfo>> {
+    let active = active_profile(root);
+    let mut names = Vec::new();
+    match fs::read_dir(root.join(PROFILES_DIRNAME)) {
+        Ok(entries) => {
+            for entry in entries {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.file_type()?.is_dir() && validate_name(&name).is_ok() {
+                    names.push(name);
+                }
+            }
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err).context("failed to list profiles"),
+    }
+    names.sort();
+
+    Ok(std::iter::once(DEFAULT_PROFILE.to_string())
+        .chain(names)
+        .map(|name| ProfileInfo {
+            path: profile_dir(root, &name).display().to_string(),
+            active: name == active,
+            name,
+        })
+        .collect())
+}
+
+/// Create an empty profile; its store and config appear on first switch.
+pub fn create_profile(root: &Path, name: &str) -> Result<ProfileInfo> {
+    let name = name.trim();
+    validate_name(name)?;
+    if name == DEFAULT_PROFILE {
+        bail!("`{DEFAULT_PROFILE}` always exists");
+    }
+    let dir = profile_dir(root, name);
+    if dir.exists() {
+        bail!("profile `{name}` already exists");
+    }
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create profile directory {}", dir.display()))?;
+    Ok(ProfileInfo {
+        name: name.to_string(),
+        path: dir.display().to_string(),
+        active: false,
+    })
+}
+
+/// Record `name` as the profile to open on the next start. Written via a
+/// temp file + rename so a crash never leaves the pointer half-written.
+pub fn set_active_profile(root: &Path, name: &str) -> Result<()> {
+    validate_name(name)?;
+    let pointer = root.join(ACTIVE_PROFILE_FILENAME);
+    if name == DEFAULT_PROFILE {
+        return match fs::remove_file(&pointer) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        };
+    }
+    let temp = root.join(format!("{ACTIVE_PROFILE_FILENAME}.tmp"));
+    fs::write(&temp, name)?;
+    fs::rename(&temp, &pointer)?;
+    Ok(())
+}
+
+/// Names double as directory names: ASCII letters, digits, `-` and `_`.
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_PROFILE_NAME_CHARS {
+        bail!("profile name must be 1-{MAX_PROFILE_NAME_CHARS} characters");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("profile name may only contain letters, digits, `-` and `_`");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_create_list_and_switch() {
+        let root = std::env::temp_dir().join(format!("ingat-profiles-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+
+        assert_eq!(active_profile(&root), DEFAULT_PROFILE);
+        assert!(create_profile(&root, "../escape").is_err());
+        assert!(create_profile(&root, DEFAULT_PROFILE).is_err());
+
+        let work = create_profile(&root, "work").unwrap();
+        assert!(Path::new(&work.path).is_dir());
+        assert!(create_profile(&root, "work").is_err());
+        create_profile(&root, "personal").unwrap();
+
+        set_active_profile(&root, "work").unwrap();
+        assert_eq!(active_profile(&root), "work");
+        let listed: Vec<(String, bool)> = list_profiles(&root)
+            .unwrap()
+            .into_iter()
+            .map(|profile| (profile.name, profile.active))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (DEFAULT_PROFILE.to_string(), false),
+                ("personal".to_string(), false),
+                ("work".to_string(), true),
+            ]
+        );
+
+        // A pointer to a removed profile falls back to the default.
+        fs::remove_dir_all(profile_dir(&root, "work")).unwrap();
+        assert_eq!(active_profile(&root), DEFAULT_PROFILE);
+
+        set_active_profile(&root, DEFAULT_PROFILE).unwrap();
+        assert!(!root.join(ACTIVE_PROFILE_FILENAME).exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -3,18 +3,57 @@
 //! This module handles starting the mcp-service as a child process when the
 //! Tauri UI launches, and ensures it's properly shut down when the UI closes.
 
-use std::process::{Child, Command};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
-use tracing::{debug, error, info};
+use serde::Serialize;
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+use tracing::{debug, error, info, warn};
+
+use crate::log_tail::{rotate_log_file, MAX_LOG_BYTES};
+
+/// Port the service listens on when `INGAT_SERVICE_PORT` is unset.
+const DEFAULT_PORT: u16 = 3200;
+/// How long `start` waits for a spawned service to report healthy when
+/// `INGAT_SERVICE_START_TIMEOUT` is unset. First runs may download a model.
+const DEFAULT_START_TIMEOUT: Duration = Duration::from_secs(30);
+/// Pause between health checks while the service starts.
+const START_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// An `mcp_service` process running on this machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServiceProcess {
+    pub pid: u32,
+    /// Port the process was started to serve (its `INGAT_SERVICE_PORT`);
+    /// `None` when its environment could not be read.
+    pub port: Option<u16>,
+    /// Serves the configured port, i.e. the service this app talks to.
+    pub current: bool,
+}
+
+/// Outcome of [`ServiceManager::kill_orphans`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrphanCleanup {
+    pub found: Vec<ServiceProcess>,
+    pub killed: Vec<u32>,
+    /// PIDs that could not be terminated, with the reason.
+    pub failed: Vec<(u32, String)>,
+}
 
 /// Manages the lifecycle of the mcp-service child process.
 pub struct ServiceManager {
     child: Arc<Mutex<Option<Child>>>,
+    /// PID of the service this manager spawned, which is never an orphan.
+    spawned_pid: Arc<Mutex<Option<u32>>>,
     port: u16,
     host: String,
+    start_timeout: Duration,
+    /// Where the spawned service's stdout/stderr go; discarded when unset.
+    log_file: Option<PathBuf>,
 }
 
 impl ServiceManager {
@@ -22,8 +61,11 @@ impl ServiceManager {
     pub fn new() -> Self {
         Self {
             child: Arc::new(Mutex::new(None)),
+            spawned_pid: Arc::new(Mutex::new(None)),
             port: Self::resolve_port(),
             host: Self::resolve_host(),
+            start_timeout: Self::resolve_start_timeout(),
+            log_file: Self::resolve_log_file(),
         }
     }
 
@@ -31,8 +73,11 @@ impl ServiceManager {
     pub fn with_config(port: u16, host: String) -> Self {
         Self {
             child: Arc::new(Mutex::new(None)),
+            spawned_pid: Arc::new(Mutex::new(None)),
             port,
             host,
+            start_timeout: Self::resolve_start_timeout(),
+            log_file: Self::resolve_log_file(),
         }
     }
 
@@ -41,7 +86,7 @@ impl ServiceManager {
     /// This method will:
     /// 1. Check if the service is already running on the configured port
     /// 2. If not, spawn the mcp-service binary
-    /// 3. Wait briefly to ensure it starts successfully
+    /// 3. Poll its health endpoint until it answers or the start timeout passes
     pub fn start(&self) -> Result<()> {
         // Check if service is already running
         if self.is_running() {
@@ -54,10 +99,11 @@ impl ServiceManager {
 
         info!("Starting mcp-service at {}", binary_path.display());
         debug!("Configuration: {}:{}", self.host, self.port);
+        let (stdout, stderr) = self.log_stdio()?;
 
         // Spawn the service process as a detached background process
         #[cfg(windows)]
-        let child = {
+        let mut child = {
             use std::os::windows::process::CommandExt;
             const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
             const DETACHED_PROCESS: u32 = 0x00000008;
@@ -67,45 +113,63 @@ impl ServiceManager {
                 .env("INGAT_SERVICE_PORT", self.port.to_string())
                 .env("INGAT_LOG", Self::resolve_log_level())
                 .env("RUST_LOG_STYLE", "never")
+                .stdout(stdout)
+                .stderr(stderr)
                 .creation_flags(CREATE_NEW_PROCESS_GROUP | DETACHED_PROCESS)
                 .spawn()
                 .context("Failed to spawn mcp-service process")?
         };
 
         #[cfg(unix)]
-        let child = {
+        let mut child = {
             Command::new(&binary_path)
                 .env("INGAT_SERVICE_HOST", &self.host)
                 .env("INGAT_SERVICE_PORT", self.port.to_string())
                 .env("INGAT_LOG", Self::resolve_log_level())
                 .env("RUST_LOG_STYLE", "never")
-                .stdin(std::process::Stdio::null())
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null())
+                .stdin(Stdio::null())
+                .stdout(stdout)
+                .stderr(stderr)
                 .spawn()
                 .context("Failed to spawn mcp-service process")?
         };
 
         let pid = child.id();
         info!("mcp-service started with PID: {} (detached)", pid);
+        if let Ok(mut spawned) = self.spawned_pid.lock() {
+            *spawned = Some(pid);
+        }
+
+        // Loading (or downloading) an embedding model can take a while, so
+        // keep polling until the timeout rather than checking once.
+        let started = Instant::now();
+        while !self.is_running() {
+            if let Ok(Some(status)) = child.try_wait() {
+                error!("mcp-service exited during startup: {}", status);
+                return Err(anyhow::anyhow!("Service exited during startup ({status})"));
+            }
+            if started.elapsed() >= self.start_timeout {
+                error!(
+                    "mcp-service failed to start - not healthy on port {} after {}s",
+                    self.port,
+                    self.start_timeout.as_secs()
+                );
+                return Err(anyhow::anyhow!(
+                    "Service failed to start within {}s",
+                    self.start_timeout.as_secs()
+                ));
+            }
+            std::thread::sleep(START_POLL_INTERVAL);
+        }
 
         // Don't store the child process - it's detached and will persist independently
         // This allows the UI to close without stopping the service
         drop(child);
 
-        // Wait a moment for the service to start
-        std::thread::sleep(Duration::from_millis(1500));
-
-        // Verify it started successfully
-        if !self.is_running() {
-            error!(
-                "mcp-service failed to start - not listening on port {}",
-                self.port
-            );
-            return Err(anyhow::anyhow!("Service failed to start"));
-        }
-
-        info!("mcp-service is now running and accepting connections");
+        info!(
+            "mcp-service is now running and accepting connections ({}ms)",
+            started.elapsed().as_millis()
+        );
         Ok(())
     }
 
@@ -135,58 +199,103 @@ impl ServiceManager {
         }
     }
 
-    /// Get the service URL.
-    pub fn service_url(&self) -> String {
-        format!("http://{}:{}", self.host, self.port)
+    /// Every running `mcp_service` process with the port it serves.
+    pub fn list_processes(&self) -> Result<Vec<ServiceProcess>> {
+        Ok(self.scan_processes().1)
     }
 
-    /// Find the mcp-service binary.
-    ///
-    /// Search order:
-    /// 1. Next to the current executable (for bundled apps)
-    /// 2. In target/release (for development)
-    /// 3. In target/debug (for development)
-    fn find_binary(&self) -> Result<std::path::PathBuf> {
-        let binary_name = if cfg!(windows) {
-            "mcp_service.exe"
-        } else {
-            "mcp_service"
-        };
-
-        // 1. Check next to current executable (bundled)
-        if let Ok(exe_path) = std::env::current_exe() {
-            if let Some(exe_dir) = exe_path.parent() {
-                let bundled_path = exe_dir.join(binary_name);
-                if bundled_path.exists() {
-                    debug!("Found mcp-service at: {}", bundled_path.display());
-                    return Ok(bundled_path);
+    /// Terminate `mcp_service` processes serving other ports than the
+    /// configured one, e.g. leftovers from older builds. The service this
+    /// manager spawned and processes whose port is unknown are left alone.
+    pub fn kill_orphans(&self) -> Result<OrphanCleanup> {
+        let (system, found) = self.scan_processes();
+        let spawned = self.spawned_pid.lock().ok().and_then(|pid| *pid);
+        let mut cleanup = OrphanCleanup::default();
+        for process in found.iter().filter(|process| is_orphan(process, spawned)) {
+            match terminate(&system, process.pid) {
+                Ok(()) => {
+                    info!(
+                        "Terminated orphaned mcp-service {} (port {:?})",
+                        process.pid, process.port
+                    );
+                    cleanup.killed.push(process.pid);
+                }
+                Err(err) => {
+                    warn!("Failed to terminate mcp-service {}: {}", process.pid, err);
+                    cleanup.failed.push((process.pid, err.to_string()));
                 }
             }
         }
+        cleanup.found = found;
+        Ok(cleanup)
+    }
 
-        // 2. Check target/release (development)
-        let release_path = std::path::PathBuf::from("target/release").join(binary_name);
-        if release_path.exists() {
-            debug!("Found mcp-service at: {}", release_path.display());
-            return Ok(release_path);
-        }
+    /// Snapshot the process table, keeping it around so the processes found
+    /// can be signalled afterwards.
+    fn scan_processes(&self) -> (System, Vec<ServiceProcess>) {
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_environ(UpdateKind::OnlyIfNotSet),
+        );
+        let own_pid = std::process::id();
+        let found = system
+            .processes()
+            .values()
+            .filter(|process| process.thread_kind().is_none())
+            .filter(|process| process.pid().as_u32() != own_pid)
+            .filter(|process| is_service_binary(process.name(), process.exe()))
+            .map(|process| self.describe(process))
+            .collect();
+        (system, found)
+    }
 
-        // 3. Check target/debug (development)
-        let debug_path = std::path::PathBuf::from("target/debug").join(binary_name);
-        if debug_path.exists() {
-            debug!("Found mcp-service at: {}", debug_path.display());
-            return Ok(debug_path);
+    fn describe(&self, process: &Process) -> ServiceProcess {
+        let port = configured_port(process.environ());
+        ServiceProcess {
+            pid: process.pid().as_u32(),
+            current: port == Some(self.port),
+            port,
         }
+    }
+
+    /// Get the service URL.
+    pub fn service_url(&self) -> String {
+        format!("http://{}:{}", self.host, self.port)
+    }
 
-        // 4. Check in PATH
-        if let Ok(path) = which::which(binary_name) {
-            debug!("Found mcp-service in PATH: {}", path.display());
-            return Ok(path);
+    /// The service's log file, if file logging is enabled.
+    pub fn log_file(&self) -> Option<&Path> {
+        self.log_file.as_deref()
+    }
+
+    /// Output handles for a new service process: the log file (rotated once
+    /// it outgrows [`MAX_LOG_BYTES`]) if one is configured, else nothing.
+    fn log_stdio(&self) -> Result<(Stdio, Stdio)> {
+        let Some(path) = &self.log_file else {
+            return Ok((Stdio::null(), Stdio::null()));
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create log directory {}", parent.display()))?;
+        }
+        if let Err(err) = rotate_log_file(path, MAX_LOG_BYTES) {
+            warn!("Failed to rotate {}: {}", path.display(), err);
         }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        Ok((Stdio::from(file.try_clone()?), Stdio::from(file)))
+    }
 
-        Err(anyhow::anyhow!(
-            "Could not find mcp_service binary. Please ensure it's built and accessible."
-        ))
+    /// Find the mcp-service binary (see [`locate_binary`]).
+    fn find_binary(&self) -> Result<std::path::PathBuf> {
+        locate_binary("mcp_service")
     }
 
     /// Resolve port from environment or use default.
@@ -194,7 +303,7 @@ impl ServiceManager {
         std::env::var("INGAT_SERVICE_PORT")
             .ok()
             .and_then(|p| p.parse().ok())
-            .unwrap_or(3200)
+            .unwrap_or(DEFAULT_PORT)
     }
 
     /// Resolve host from environment or use default.
@@ -202,6 +311,18 @@ impl ServiceManager {
         std::env::var("INGAT_SERVICE_HOST").unwrap_or_else(|_| "127.0.0.1".to_string())
     }
 
+    /// Resolve the startup timeout (whole seconds) from `INGAT_SERVICE_START_TIMEOUT`.
+    fn resolve_start_timeout() -> Duration {
+        parse_start_timeout(std::env::var("INGAT_SERVICE_START_TIMEOUT").ok().as_deref())
+    }
+
+    /// Resolve the service log file from `INGAT_SERVICE_LOG_FILE`.
+    fn resolve_log_file() -> Option<PathBuf> {
+        std::env::var_os("INGAT_SERVICE_LOG_FILE")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    }
+
     /// Resolve log level from environment or use default.
     fn resolve_log_level() -> String {
         std::env::var("INGAT_LOG").unwrap_or_else(|_| "error".to_string())
@@ -222,6 +343,110 @@ impl Drop for ServiceManager {
     }
 }
 
+fn parse_start_timeout(raw: Option<&str>) -> Duration {
+    raw.and_then(|raw| raw.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map_or(DEFAULT_START_TIMEOUT, Duration::from_secs)
+}
+
+fn is_orphan(process: &ServiceProcess, spawned: Option<u32>) -> bool {
+    !process.current && process.port.is_some() && Some(process.pid) != spawned
+}
+
+/// Whether a process runs the `mcp_service` binary, by executable path when
+/// it is readable and by process name otherwise.
+fn is_service_binary(name: &OsStr, exe: Option<&Path>) -> bool {
+    let stem = exe
+        .and_then(Path::file_stem)
+        .unwrap_or_else(|| Path::new(name).file_stem().unwrap_or(name));
+    stem == "mcp_service"
+}
+
+/// The port a service process serves, resolved from its environment the same
+/// way the service resolves it. An empty environment means it could not be
+/// read (another user's process), so the port is unknown.
+fn configured_port<S: AsRef<OsStr>>(environ: &[S]) -> Option<u16> {
+    if environ.is_empty() {
+        return None;
+    }
+    let port = environ.iter().find_map(|entry| {
+        entry
+            .as_ref()
+            .to_str()?
+            .strip_prefix("INGAT_SERVICE_PORT=")?
+            .trim()
+            .parse()
+            .ok()
+    });
+    Some(port.unwrap_or(DEFAULT_PORT))
+}
+
+/// Find one of the bundled binaries by its cargo name (e.g. `mcp_stdio`).
+///
+/// Search order:
+/// 1. Next to the current executable (for bundled apps)
+/// 2. In target/release (for development)
+/// 3. In target/debug (for development)
+/// 4. In PATH
+pub fn locate_binary(name: &str) -> Result<std::path::PathBuf> {
+    let binary_name = if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_string()
+    };
+
+    // 1. Check next to current executable (bundled)
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            let bundled_path = exe_dir.join(&binary_name);
+            if bundled_path.exists() {
+                debug!("Found {} at: {}", name, bundled_path.display());
+                return Ok(bundled_path);
+            }
+        }
+    }
+
+    // 2. Check target/release (development)
+    let release_path = std::path::PathBuf::from("target/release").join(&binary_name);
+    if release_path.exists() {
+        debug!("Found {} at: {}", name, release_path.display());
+        return Ok(release_path);
+    }
+
+    // 3. Check target/debug (development)
+    let debug_path = std::path::PathBuf::from("target/debug").join(&binary_name);
+    if debug_path.exists() {
+        debug!("Found {} at: {}", name, debug_path.display());
+        return Ok(debug_path);
+    }
+
+    // 4. Check in PATH
+    if let Ok(path) = which::which(&binary_name) {
+        debug!("Found {} in PATH: {}", name, path.display());
+        return Ok(path);
+    }
+
+    Err(anyhow::anyhow!(
+        "Could not find {name} binary. Please ensure it's built and accessible."
+    ))
+}
+
+/// Ask `pid` to exit, falling back to a hard kill where SIGTERM does not
+/// exist (Windows).
+fn terminate(system: &System, pid: u32) -> Result<()> {
+    let process = system
+        .process(Pid::from_u32(pid))
+        .with_context(|| format!("Process {pid} already exited"))?;
+    let signalled = process
+        .kill_with(Signal::Term)
+        .unwrap_or_else(|| process.kill());
+    if signalled {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Failed to terminate process {pid}"))
+    }
+}
+
 /// Check if a port is available (not in use).
 #[cfg(windows)]
 pub fn is_port_available(port: u16) -> bool {
@@ -254,6 +479,64 @@ mod tests {
         assert_eq!(manager.host, "localhost");
     }
 
+    #[test]
+    fn test_start_timeout_parsing() {
+        assert_eq!(parse_start_timeout(None), DEFAULT_START_TIMEOUT);
+        assert_eq!(parse_start_timeout(Some(" 90 ")), Duration::from_secs(90));
+        assert_eq!(parse_start_timeout(Some("0")), DEFAULT_START_TIMEOUT);
+        assert_eq!(parse_start_timeout(Some("soon")), DEFAULT_START_TIMEOUT);
+    }
+
+    #[test]
+    fn test_service_processes_are_matched_by_binary_and_environment() {
+        let name = OsStr::new;
+        assert!(is_service_binary(name("mcp_service"), None));
+        assert!(is_service_binary(
+            name("mcp_service.exe"),
+            Some(Path::new(r"C:\Ingat\mcp_service.exe"))
+        ));
+        assert!(is_service_binary(
+            name("mcp-service"),
+            Some(Path::new(
+                "/Applications/Ingat.app/Contents/MacOS/mcp_service"
+            ))
+        ));
+        assert!(!is_service_binary(name("mcp_stdio"), None));
+        assert!(!is_service_binary(
+            name("mcp_service"),
+            Some(Path::new("/usr/bin/bash"))
+        ));
+
+        assert_eq!(configured_port::<&str>(&[]), None);
+        assert_eq!(configured_port(&["HOME=/root"]), Some(DEFAULT_PORT));
+        assert_eq!(
+            configured_port(&["INGAT_SERVICE_PORT=3100", "HOME=/root"]),
+            Some(3100)
+        );
+        assert_eq!(
+            configured_port(&["INGAT_SERVICE_PORT=soon"]),
+            Some(DEFAULT_PORT)
+        );
+    }
+
+    #[test]
+    fn test_process_scan_skips_this_process() {
+        let manager = ServiceManager::with_config(3201, "localhost".to_string());
+        let found = manager.list_processes().unwrap();
+        assert!(found
+            .iter()
+            .all(|process| process.pid != std::process::id()));
+    }
+
+    #[test]
+    fn test_orphans_exclude_current_spawned_and_portless() {
+        let process = |pid, port, current| ServiceProcess { pid, port, current };
+        assert!(is_orphan(&process(1, Some(3100), false), Some(2)));
+        assert!(!is_orphan(&process(1, Some(3200), true), None));
+        assert!(!is_orphan(&process(1, Some(3100), false), Some(1)));
+        assert!(!is_orphan(&process(1, None, false), None));
+    }
+
     #[test]
     fn test_service_url() {
         let manager = ServiceManager::new();
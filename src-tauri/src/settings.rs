@@ -1,11 +1,20 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
 
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
+use crate::application::services::{
+    EmbedScope, PreprocessStep, TruncationStrategy, DEFAULT_EMPTY_RESULTS_MESSAGE,
+    DEFAULT_LEGACY_PENALTY, DEFAULT_QUERY_HISTORY_CAPACITY, DEFAULT_TAG_BOOST,
+    DEFAULT_TOMBSTONE_RETENTION_DAYS,
+};
+use crate::domain::{ContextKind, DomainError};
+use crate::infrastructure::storage::VectorPrecision;
+
 /// Default filename used to persist configuration within the data directory.
-const CONFIG_FILENAME: &str = "config.json";
+pub const CONFIG_FILENAME: &str = "config.json";
 
 /// Declarative list of embedding backends compiled into the binary.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -76,7 +85,9 @@ impl EmbeddingBackend {
         match self {
             EmbeddingBackend::Simple { dimensions, .. } => Some(*dimensions),
             #[cfg(feature = "fastembed-engine")]
-            EmbeddingBackend::FastEmbed { .. } => None,
+            EmbeddingBackend::FastEmbed { model } => {
+                crate::infrastructure::FastEmbedEngine::known_dimensions(model)
+            }
         }
     }
 
@@ -113,17 +124,212 @@ impl Default for EmbeddingBackend {
     }
 }
 
+/// Tuning knobs for the embedded sled store.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StorageSettings {
+    /// Flush to disk after every single write. When disabled, sled flushes in the
+    /// background and only batch writes force a flush.
+    #[serde(default = "default_sync_writes")]
+    pub sync_writes: bool,
+    /// With `sync_writes`, gather the flushes of writes arriving within this
+    /// many milliseconds into one, so ingest bursts cost a single fsync. `0`
+    /// flushes after every write.
+    #[serde(default = "default_flush_coalesce_ms")]
+    pub flush_coalesce_ms: u64,
+    /// Pending writes that trigger the coalesced flush early.
+    #[serde(default = "default_flush_coalesce_writes")]
+    pub flush_coalesce_writes: usize,
+    /// On-disk precision for new embedding vectors (`f32`, `f16` or `int8`).
+    /// Scoring always runs on `f32`; lower precisions trade a little recall for
+    /// a 2-4x smaller embedding footprint.
+    #[serde(default)]
+    pub vector_precision: VectorPrecision,
+    /// Keep vectors in a dedicated tree so listing and metadata reads skip them.
+    #[serde(default)]
+    pub separate_embeddings: bool,
+    /// Sync horizon in days: an index rebuild drops deletions older than this
+    /// from the change feed.
+    #[serde(default = "default_tombstone_retention_days")]
+    pub tombstone_retention_days: u32,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            sync_writes: default_sync_writes(),
+            flush_coalesce_ms: default_flush_coalesce_ms(),
+            flush_coalesce_writes: default_flush_coalesce_writes(),
+            vector_precision: VectorPrecision::default(),
+            separate_embeddings: false,
+            tombstone_retention_days: default_tombstone_retention_days(),
+        }
+    }
+}
+
+/// Search behaviour toggles.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchSettings {
+    /// Store separate summary and body vectors so searches can target a single
+    /// field. Triples embedding work at ingest, so it is off by default.
+    #[serde(default)]
+    pub field_embeddings: bool,
+    /// Serve repeated identical searches from memory for this many
+    /// milliseconds. Entries are tied to the store version, so any write makes
+    /// them stale; `0` (the default) disables the cache.
+    #[serde(default)]
+    pub search_cache_ttl_ms: u64,
+    /// Score bonus per matching tag when a search asks for `soft_filter`.
+    #[serde(default = "default_tag_boost")]
+    pub tag_boost: f32,
+    /// Recent searches kept for `recent_queries`; the oldest are dropped
+    /// first. `0` stops recording.
+    #[serde(default = "default_query_history")]
+    pub query_history: usize,
+    /// When a search meets records embedded with another dimension (e.g.
+    /// after a backend change), skip them and re-embed them in the background
+    /// instead of failing. Off by default.
+    #[serde(default)]
+    pub lazy_reembed: bool,
+    /// `message` attached to searches that return nothing, so agents don't
+    /// claim an answer came from memory. Empty leaves it out.
+    #[serde(default = "default_empty_results_message")]
+    pub empty_results_message: String,
+    /// Keep each search's returned ids (never bodies) so `record_feedback`
+    /// can note which result was used; see `feedback_stats`. Off by default.
+    #[serde(default)]
+    pub search_analytics: bool,
+    /// Subtracted from scores of records still on `legacy_embedding`, so
+    /// equally similar hits from the active model rank first.
+    #[serde(default = "default_legacy_penalty")]
+    pub legacy_penalty: f32,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            field_embeddings: false,
+            search_cache_ttl_ms: 0,
+            tag_boost: default_tag_boost(),
+            query_history: default_query_history(),
+            lazy_reembed: false,
+            empty_results_message: default_empty_results_message(),
+            search_analytics: false,
+            legacy_penalty: default_legacy_penalty(),
+        }
+    }
+}
+
+/// How text longer than the model's input window is cut before embedding.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EmbeddingInputSettings {
+    /// `head`, `tail` or `head_tail`. Applies to the embedded text only.
+    #[serde(default)]
+    pub truncation: TruncationStrategy,
+    /// Character budget for embedding input; `0` (the default) passes text
+    /// through and lets the backend truncate on its own.
+    #[serde(default)]
+    pub max_chars: usize,
+    /// `summary_plus_body` (default) or `summary_only`, which embeds summary
+    /// and tags and leaves the body out of the vector. Re-embed after changing it.
+    #[serde(default)]
+    pub scope: EmbedScope,
+    /// Append each record's `file_path` to its embedded text (and a search's
+    /// `path_hint` to the query). Applies to records embedded after enabling it.
+    #[serde(default)]
+    pub include_file_path: bool,
+    /// Preprocessing steps per backend id (`simple`, `fastembed`), applied in
+    /// order to ingest and query text. Backends without an entry get none.
+    #[serde(default)]
+    pub preprocess: BTreeMap<String, Vec<PreprocessStep>>,
+}
+
+/// Ingest validation behaviour.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IngestSettings {
+    /// Derive a missing summary from the first sentence of the body instead of
+    /// rejecting the request. Off by default so validation stays strict.
+    #[serde(default)]
+    pub auto_summary: bool,
+    /// Drop control characters, zero-width characters and BOMs from summary,
+    /// body and tags before storage. Newlines and tabs in the body are kept.
+    #[serde(default = "default_sanitize_unicode")]
+    pub sanitize_unicode: bool,
+    /// Add the record's `language` (normalized) to its tags when present and
+    /// not already tagged, so it shows up in tag filters. Off by default.
+    #[serde(default)]
+    pub auto_tag_language: bool,
+    /// Guess the kind of ingests that leave it unspecified (and match no
+    /// `ide_default_kind`) from their content. Off by default.
+    #[serde(default)]
+    pub infer_kind: bool,
+    /// Also store bodies longer than this many chars as overlapping chunks,
+    /// each with its own vector, so searches can match part of a long body.
+    /// `0` (the default) keeps one vector per record.
+    #[serde(default)]
+    pub chunk_size: usize,
+    /// Chars shared by neighbouring chunks.
+    #[serde(default)]
+    pub chunk_overlap: usize,
+}
+
+impl Default for IngestSettings {
+    fn default() -> Self {
+        Self {
+            auto_summary: false,
+            sanitize_unicode: default_sanitize_unicode(),
+            auto_tag_language: false,
+            infer_kind: false,
+            chunk_size: 0,
+            chunk_overlap: 0,
+        }
+    }
+}
+
 /// Complete persisted configuration payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     #[serde(default)]
     pub embedding: EmbeddingBackend,
+    #[serde(default)]
+    pub storage: StorageSettings,
+    #[serde(default)]
+    pub search: SearchSettings,
+    #[serde(default)]
+    pub embedding_input: EmbeddingInputSettings,
+    #[serde(default)]
+    pub ingest: IngestSettings,
+    /// Backends to try in priority order at startup. The first one that
+    /// embeds successfully is used for the whole run (vectors from different
+    /// backends can't be mixed). Empty means use `embedding` alone.
+    #[serde(default)]
+    pub backend_chain: Vec<EmbeddingBackend>,
+    /// Chain member the last run settled on. It is tried first on the next
+    /// start so restarts keep writing into the same vector space.
+    #[serde(default)]
+    pub settled_backend: Option<EmbeddingBackend>,
+    /// Kind given to ingests from an IDE (matched case-insensitively) that
+    /// leave `kind` unspecified, e.g. `{"wezterm": "ToolLog"}`.
+    #[serde(default)]
+    pub ide_default_kind: BTreeMap<String, ContextKind>,
+    /// Backend the store is migrating away from. While set, records it
+    /// embedded stay searchable in their own similarity space until they are
+    /// re-embedded; unset it once the migration is done.
+    #[serde(default)]
+    pub legacy_embedding: Option<EmbeddingBackend>,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             embedding: EmbeddingBackend::default(),
+            storage: StorageSettings::default(),
+            search: SearchSettings::default(),
+            embedding_input: EmbeddingInputSettings::default(),
+            ingest: IngestSettings::default(),
+            backend_chain: Vec::new(),
+            settled_backend: None,
+            ide_default_kind: BTreeMap::new(),
+            legacy_embedding: None,
         }
     }
 }
@@ -136,16 +342,17 @@ pub struct ConfigManager {
 
 impl ConfigManager {
     /// Create a manager rooted at `data_dir`. The JSON file will be located at
-    /// `<data_dir>/config.json`.
-    pub fn load(data_dir: impl AsRef<Path>) -> std::io::Result<Self> {
+    /// `<data_dir>/config.json`. An unreadable file is an error; one that no
+    /// longer parses (e.g. it names a backend this build lacks) falls back to
+    /// the defaults so startup still succeeds.
+    pub fn load(data_dir: impl AsRef<Path>) -> Result<Self, DomainError> {
         let path = data_dir.as_ref().join(CONFIG_FILENAME);
-        let config = if path.exists() {
-            fs::read(&path)
-                .ok()
-                .and_then(|bytes| serde_json::from_slice::<AppConfig>(&bytes).ok())
-                .unwrap_or_default()
-        } else {
-            AppConfig::default()
+        let config = match read_config_bytes(&path)? {
+            Some(bytes) => parse_config(&path, &bytes).unwrap_or_else(|err| {
+                eprintln!("[ingat] {err}; using default settings");
+                AppConfig::default()
+            }),
+            None => AppConfig::default(),
         };
 
         Ok(Self {
@@ -154,31 +361,76 @@ impl ConfigManager {
         })
     }
 
+    /// Re-read the config file, e.g. after it was edited by hand. On failure
+    /// the current configuration stays in effect.
+    pub fn reload(&self) -> Result<AppConfig, DomainError> {
+        let config = match read_config_bytes(&self.path)? {
+            Some(bytes) => parse_config(&self.path, &bytes)?,
+            None => AppConfig::default(),
+        };
+        *self.state.write() = config.clone();
+        Ok(config)
+    }
+
     /// Snapshot of the current configuration.
     pub fn current(&self) -> AppConfig {
-        self.state.read().expect("config poisoned").clone()
+        self.state.read().clone()
+    }
+
+    /// Whether a config file has been written for this data directory.
+    pub fn is_persisted(&self) -> bool {
+        self.path.exists()
     }
 
     /// Update the active embedding backend and persist to disk.
-    pub fn set_backend(&self, backend: EmbeddingBackend) -> std::io::Result<AppConfig> {
+    pub fn set_backend(&self, backend: EmbeddingBackend) -> Result<AppConfig, DomainError> {
         {
-            let mut guard = self.state.write().expect("config poisoned");
+            let mut guard = self.state.write();
             guard.embedding = backend;
             self.persist_locked(&guard)?;
         }
         Ok(self.current())
     }
 
+    /// Remember which `backend_chain` member served this run and persist it.
+    pub fn set_settled_backend(&self, backend: EmbeddingBackend) -> Result<(), DomainError> {
+        let mut guard = self.state.write();
+        guard.settled_backend = Some(backend);
+        self.persist_locked(&guard)
+    }
+
     /// Ensure the backing directory exists and write the JSON payload.
-    fn persist_locked(&self, config: &AppConfig) -> std::io::Result<()> {
-        if let Some(parent) = self.path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let payload = serde_json::to_vec_pretty(config)?;
-        fs::write(&self.path, payload)
+    fn persist_locked(&self, config: &AppConfig) -> Result<(), DomainError> {
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let payload = serde_json::to_vec_pretty(config)?;
+            fs::write(&self.path, payload)
+        };
+        write().map_err(|err| {
+            DomainError::config(format!("failed to write {}: {err}", self.path.display()))
+        })
     }
 }
 
+/// Raw config file contents; `None` when no file has been written yet.
+fn read_config_bytes(path: &Path) -> Result<Option<Vec<u8>>, DomainError> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(DomainError::config(format!(
+            "failed to read {}: {err}",
+            path.display()
+        ))),
+    }
+}
+
+fn parse_config(path: &Path, bytes: &[u8]) -> Result<AppConfig, DomainError> {
+    serde_json::from_slice(bytes)
+        .map_err(|err| DomainError::config(format!("invalid {}: {err}", path.display())))
+}
+
 pub fn available_backends() -> Vec<EmbeddingBackend> {
     #[cfg(feature = "fastembed-engine")]
     {
@@ -198,6 +450,82 @@ pub fn available_backends() -> Vec<EmbeddingBackend> {
     }
 }
 
+/// Pick the embedding backend to run with.
+///
+/// Once a config file exists its backend is used as-is so runs stay stable. On a
+/// fresh install each compiled backend is probed in preference order (FastEmbed
+/// first, when compiled in) and the first one that initialises is persisted, so a
+/// failed model download falls back to `simple` instead of breaking startup.
+pub fn resolve_best_available_backend(
+    manager: &ConfigManager,
+) -> Result<EmbeddingBackend, DomainError> {
+    if manager.is_persisted() {
+        return Ok(manager.current().embedding);
+    }
+
+    let backend = available_backends()
+        .into_iter()
+        .find(probe_backend)
+        .unwrap_or_else(|| EmbeddingBackend::Simple {
+            model: default_simple_model(),
+            dimensions: default_simple_dim(),
+        });
+
+    manager.set_backend(backend.clone())?;
+    Ok(backend)
+}
+
+/// Try to initialise `backend`; FastEmbed loads from the local model cache when present.
+fn probe_backend(backend: &EmbeddingBackend) -> bool {
+    match backend {
+        EmbeddingBackend::Simple { .. } => true,
+        #[cfg(feature = "fastembed-engine")]
+        EmbeddingBackend::FastEmbed { model } => {
+            match crate::infrastructure::FastEmbedEngine::try_new(model) {
+                Ok(_) => true,
+                Err(err) => {
+                    eprintln!(
+                        "[ingat] FastEmbed unavailable ({err}); falling back to simple embeddings"
+                    );
+                    false
+                }
+            }
+        }
+    }
+}
+
+const fn default_sync_writes() -> bool {
+    true
+}
+
+const fn default_flush_coalesce_ms() -> u64 {
+    250
+}
+
+const fn default_flush_coalesce_writes() -> usize {
+    64
+}
+
+const fn default_tombstone_retention_days() -> u32 {
+    DEFAULT_TOMBSTONE_RETENTION_DAYS
+}
+
+const fn default_sanitize_unicode() -> bool {
+    true
+}
+
+const fn default_tag_boost() -> f32 {
+    DEFAULT_TAG_BOOST
+}
+
+const fn default_legacy_penalty() -> f32 {
+    DEFAULT_LEGACY_PENALTY
+}
+
+const fn default_query_history() -> usize {
+    DEFAULT_QUERY_HISTORY_CAPACITY
+}
+
 const fn default_simple_dim() -> usize {
     256
 }
@@ -206,7 +534,71 @@ fn default_simple_model() -> String {
     "ingat/simple-hash".to_string()
 }
 
+fn default_empty_results_message() -> String {
+    DEFAULT_EMPTY_RESULTS_MESSAGE.to_string()
+}
+
 #[cfg(feature = "fastembed-engine")]
 fn default_fastembed_model() -> String {
     "BAAI/bge-small-en-v1.5".to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_panic_while_writing_does_not_brick_config() {
+        let dir = std::env::temp_dir().join(format!("ingat-config-{}", uuid::Uuid::new_v4()));
+        let manager = Arc::new(ConfigManager::load(&dir).expect("load"));
+
+        let writer = Arc::clone(&manager);
+        let result = std::thread::spawn(move || {
+            let _guard = writer.state.write();
+            panic!("simulated failure while holding the config lock");
+        })
+        .join();
+        assert!(result.is_err());
+
+        let backend = EmbeddingBackend::with_default_model("simple").expect("simple backend");
+        let updated = manager.set_backend(backend.clone()).expect("persist");
+        assert_eq!(updated.embedding, backend);
+        assert_eq!(manager.current().embedding, backend);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(feature = "fastembed-engine")]
+    #[test]
+    fn test_fastembed_dimensions_known_before_init() {
+        let dims = |model: &str| {
+            EmbeddingBackend::FastEmbed {
+                model: model.to_string(),
+            }
+            .expected_dimensions()
+        };
+        assert_eq!(dims("BAAI/bge-small-en-v1.5"), Some(384));
+        assert_eq!(dims("bge-large-en-v1.5"), Some(1024));
+        assert_eq!(dims("NomicEmbedTextV15"), Some(768));
+        assert_eq!(dims("not-a-model"), None);
+    }
+
+    #[test]
+    fn test_reload_reports_invalid_config_and_keeps_current() {
+        let dir = std::env::temp_dir().join(format!("ingat-config-{}", uuid::Uuid::new_v4()));
+        let manager = ConfigManager::load(&dir).expect("load");
+        let backend = EmbeddingBackend::with_default_model("simple").expect("simple backend");
+        manager.set_backend(backend.clone()).expect("persist");
+
+        fs::write(dir.join(CONFIG_FILENAME), b"{ not json").expect("corrupt config");
+        assert!(matches!(manager.reload(), Err(DomainError::Config(_))));
+        assert_eq!(manager.current().embedding, backend);
+
+        // Startup stays lenient about a file it can read but not parse.
+        let reloaded = ConfigManager::load(&dir).expect("load falls back to defaults");
+        assert_eq!(reloaded.current().embedding, EmbeddingBackend::default());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
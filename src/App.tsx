@@ -36,6 +36,7 @@ function App() {
   const [searchPayload, setSearchPayload] =
     useState<SearchRequest>(initialSearchPayload);
   const [searchResults, setSearchResults] = useState<SearchResult[]>([]);
+  const [searchQueryId, setSearchQueryId] = useState<string | null>(null);
   const [isSearching, setIsSearching] = useState(false);
 
   const [projects, setProjects] = useState<string[]>([]);
@@ -105,10 +106,13 @@ function App() {
       setStatusMessage("Searching contexts…");
       const response = await memoryClient.searchContexts(searchPayload);
       setSearchResults(response.results);
+      setSearchQueryId(response.query_id ?? null);
       setStatusMessage(
-        response.results.length
-          ? `Found ${response.results.length} relevant matches.`
-          : "No semantic matches yet.",
+        response.has_more
+          ? `Showing ${response.results.length} of ${response.total_matched} matches; refine the prompt to narrow them down.`
+          : response.results.length
+            ? `Found ${response.results.length} relevant matches.`
+            : "No semantic matches yet.",
       );
     } catch (error) {
       const message =
@@ -137,6 +141,16 @@ function App() {
     refreshHealth();
   }, [refreshHistory, refreshProjects, refreshHealth]);
 
+  // Analytics are opt-in and best effort; a failed report never bothers the user.
+  const reportChosen = useCallback(
+    (result: SearchResult) => {
+      if (searchQueryId) {
+        memoryClient.recordFeedback(searchQueryId, result.id).catch(() => {});
+      }
+    },
+    [searchQueryId],
+  );
+
   const handleCopy = useCallback(
     async (text: string) => {
       try {
@@ -219,10 +233,14 @@ function App() {
               results={searchResults}
               isSearching={isSearching}
               emptyMessage={statusMessage}
-              onSelect={(result) =>
-                setSelection({ ...result, source: "search" })
-              }
-              onCopyBody={(result) => handleCopy(result.body)}
+              onSelect={(result) => {
+                reportChosen(result);
+                setSelection({ ...result, source: "search" });
+              }}
+              onCopyBody={(result) => {
+                reportChosen(result);
+                handleCopy(result.body);
+              }}
             />
           </div>
 
@@ -0,0 +1,84 @@
+import { useEffect, useRef, useState } from "react";
+
+import { memoryClient } from "../lib/memoryClient";
+import type { LogLevel, LogLine } from "../types/context";
+
+// Lines kept on screen; older ones scroll away.
+const MAX_LINES = 500;
+
+const LEVELS: LogLevel[] = ["ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+
+const LEVEL_COLORS: Record<LogLevel, string> = {
+  ERROR: "text-red-300",
+  WARN: "text-yellow-300",
+  INFO: "text-slate-200",
+  DEBUG: "text-slate-400",
+  TRACE: "text-slate-500",
+};
+
+export default function ServiceLogs() {
+  const [lines, setLines] = useState<LogLine[]>([]);
+  const [level, setLevel] = useState<LogLevel>("INFO");
+  const [error, setError] = useState<string | null>(null);
+  const bottomRef = useRef<HTMLDivElement>(null);
+
+  useEffect(() => {
+    setLines([]);
+    setError(null);
+    memoryClient
+      .tailServiceLogs((line) => {
+        setLines((previous) => [...previous, line].slice(-MAX_LINES));
+      }, level)
+      .catch((err) => {
+        setError(err instanceof Error ? err.message : String(err));
+      });
+    return () => {
+      void memoryClient.stopServiceLogTail();
+    };
+  }, [level]);
+
+  useEffect(() => {
+    bottomRef.current?.scrollIntoView({ block: "nearest" });
+  }, [lines]);
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center justify-between">
+        <h4 className="text-sm font-semibold text-white">Service Logs</h4>
+        <select
+          value={level}
+          onChange={(event) => setLevel(event.target.value as LogLevel)}
+          className="rounded border border-white/20 bg-surface-300/50 px-2 py-1 text-xs text-slate-200"
+        >
+          {LEVELS.map((option) => (
+            <option key={option} value={option}>
+              {option} and above
+            </option>
+          ))}
+        </select>
+      </div>
+      {error ? (
+        <div className="rounded-md bg-red-500/20 p-3 text-xs text-red-200">
+          {error}
+        </div>
+      ) : (
+        <div className="h-64 overflow-y-auto rounded bg-black/60 p-3 font-mono text-xs">
+          {lines.length === 0 && (
+            <p className="text-slate-500">Waiting for log lines...</p>
+          )}
+          {lines.map((entry, index) => (
+            <div
+              key={index}
+              className={`whitespace-pre-wrap break-all ${
+                entry.level ? LEVEL_COLORS[entry.level] : "text-slate-300"
+              }`}
+            >
+              {entry.line}
+            </div>
+          ))}
+          <div ref={bottomRef} />
+        </div>
+      )}
+    </div>
+  );
+}
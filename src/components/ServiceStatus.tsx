@@ -1,6 +1,8 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 
+import ServiceLogs from "./ServiceLogs";
+
 interface ServiceStatus {
   is_running: boolean;
   service_url: string;
@@ -11,6 +13,7 @@ export default function ServiceStatus() {
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [showGuide, setShowGuide] = useState(false);
+  const [showLogs, setShowLogs] = useState(false);
 
   const checkStatus = async () => {
     try {
@@ -167,8 +170,21 @@ export default function ServiceStatus() {
         >
           {showGuide ? "Hide Guide" : "Setup Guide"}
         </button>
+        <button
+          onClick={() => setShowLogs(!showLogs)}
+          className="rounded-md border border-white/20 bg-surface-300/50 px-4 py-2 text-sm font-medium text-slate-200 hover:bg-surface-300"
+        >
+          {showLogs ? "Hide Logs" : "Logs"}
+        </button>
       </div>
 
+      {/* Live service log */}
+      {showLogs && (
+        <div className="mb-4">
+          <ServiceLogs />
+        </div>
+      )}
+
       {/* Setup Guide */}
       {showGuide && (
         <div className="space-y-4 rounded-md border border-white/10 bg-surface-300/50 p-4">
@@ -1,12 +1,32 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 
 import type {
+  BackupReport,
+  BuildInfo,
+  ConnectionReport,
+  ContextDiff,
   ContextSummary,
   EmbeddingBackendListResponse,
+  EmbeddingSelfTest,
+  FeedbackStats,
   HealthStatusResponse,
+  HistoryOrder,
   IngestContextRequest,
+  LogLevel,
+  LogLine,
+  OrphanCleanup,
+  ProfileInfo,
+  ProjectInfo,
+  QueryHistoryEntry,
+  ReembedProgress,
+  RelatedProject,
+  RestoreReport,
+  SearchEvent,
   SearchRequest,
   SearchResponse,
+  ServiceProcess,
+  StorageStats,
+  StoreComparison,
   SummaryListResponse,
   UpdateEmbeddingBackendRequest,
 } from "../types/context";
@@ -15,15 +35,43 @@ const COMMANDS = {
   ingest: "ingest_context",
 
   search: "search_contexts",
+  recentQueries: "recent_queries",
+  clearQueryHistory: "clear_query_history",
+  recordFeedback: "record_feedback",
+  feedbackStats: "feedback_stats",
+  clearFeedback: "clear_feedback",
 
   recent: "recent_contexts",
+  archive: "archive_context",
+  unarchive: "unarchive_context",
+  deleteContext: "delete_context",
+  diff: "diff_contexts",
 
   projects: "list_projects",
+  projectsWithActivity: "list_projects_with_activity",
+  relatedProjects: "related_projects",
+  topSimilarPairs: "top_similar_pairs",
 
   health: "health",
+  buildInfo: "build_info",
+  storageStats: "storage_stats",
+  backupStore: "backup_store",
+  restoreStore: "restore_store",
+  listProfiles: "list_profiles",
+  createProfile: "create_profile",
+  switchProfile: "switch_profile",
+  reembedAll: "reembed_all",
+  cancelReembed: "cancel_reembed",
+  testRemoteConnection: "test_remote_connection",
+  compareStores: "compare_stores",
+  listServiceProcesses: "list_service_processes",
+  killOrphanServices: "kill_orphan_services",
+  tailServiceLogs: "tail_service_logs",
+  stopServiceLogTail: "stop_service_log_tail",
 
   embeddingBackends: "embedding_backends",
   setEmbeddingBackend: "set_embedding_backend",
+  testEmbeddingBackend: "test_embedding_backend",
 } as const;
 
 type CommandKey = keyof typeof COMMANDS;
@@ -50,21 +98,146 @@ export const memoryClient = {
     return invokeOrThrow("search", { payload });
   },
 
+  recentQueries(limit?: number): Promise<QueryHistoryEntry[]> {
+    return invokeOrThrow("recentQueries", { limit });
+  },
+
+  clearQueryHistory(): Promise<number> {
+    return invokeOrThrow("clearQueryHistory");
+  },
+
+  recordFeedback(queryId: string, chosenId: string): Promise<SearchEvent> {
+    return invokeOrThrow("recordFeedback", { queryId, chosenId });
+  },
+
+  feedbackStats(): Promise<FeedbackStats> {
+    return invokeOrThrow("feedbackStats");
+  },
+
+  clearFeedback(): Promise<number> {
+    return invokeOrThrow("clearFeedback");
+  },
+
   fetchRecent(options: {
     project?: string;
+    tag?: string;
+    since?: string;
     limit?: number;
+    order?: HistoryOrder;
   }): Promise<SummaryListResponse> {
     return invokeOrThrow("recent", options);
   },
 
+  archiveContext(id: string): Promise<ContextSummary> {
+    return invokeOrThrow("archive", { id });
+  },
+
+  unarchiveContext(id: string): Promise<ContextSummary> {
+    return invokeOrThrow("unarchive", { id });
+  },
+
+  /** Rejects with a "not found" message for unknown ids. */
+  deleteContext(id: string): Promise<void> {
+    return invokeOrThrow("deleteContext", { id });
+  },
+
+  diffContexts(a: string, b: string): Promise<ContextDiff> {
+    return invokeOrThrow("diff", { a, b });
+  },
+
   listProjects(): Promise<string[]> {
     return invokeOrThrow("projects");
   },
 
+  /** Most recently active project first. */
+  listProjectsWithActivity(): Promise<ProjectInfo[]> {
+    return invokeOrThrow("projectsWithActivity");
+  },
+
+  relatedProjects(project: string, limit?: number): Promise<RelatedProject[]> {
+    return invokeOrThrow("relatedProjects", { project, limit });
+  },
+
+  /** `[id, id, cosine]` for the `k` most similar pairs, best first. */
+  topSimilarPairs(k?: number): Promise<[string, string, number][]> {
+    return invokeOrThrow("topSimilarPairs", { k });
+  },
+
   health(): Promise<HealthStatusResponse> {
     return invokeOrThrow("health");
   },
 
+  buildInfo(): Promise<BuildInfo> {
+    return invokeOrThrow("buildInfo");
+  },
+
+  storageStats(): Promise<StorageStats> {
+    return invokeOrThrow("storageStats");
+  },
+
+  backupStore(destPath: string): Promise<BackupReport> {
+    return invokeOrThrow("backupStore", { destPath });
+  },
+
+  restoreStore(srcPath: string): Promise<RestoreReport> {
+    return invokeOrThrow("restoreStore", { srcPath });
+  },
+
+  listProfiles(): Promise<ProfileInfo[]> {
+    return invokeOrThrow("listProfiles");
+  },
+
+  createProfile(name: string): Promise<ProfileInfo> {
+    return invokeOrThrow("createProfile", { name });
+  },
+
+  switchProfile(name: string): Promise<ProfileInfo> {
+    return invokeOrThrow("switchProfile", { name });
+  },
+
+  reembedAll(
+    onProgress: (progress: ReembedProgress) => void,
+  ): Promise<ReembedProgress> {
+    const channel = new Channel<ReembedProgress>();
+    channel.onmessage = onProgress;
+    return invokeOrThrow("reembedAll", { onProgress: channel });
+  },
+
+  cancelReembed(): Promise<boolean> {
+    return invokeOrThrow("cancelReembed");
+  },
+
+  testRemoteConnection(host: string, port: number): Promise<ConnectionReport> {
+    return invokeOrThrow("testRemoteConnection", { host, port });
+  },
+
+  compareStores(host: string, port: number): Promise<StoreComparison> {
+    return invokeOrThrow("compareStores", { host, port });
+  },
+
+  listServiceProcesses(): Promise<ServiceProcess[]> {
+    return invokeOrThrow("listServiceProcesses");
+  },
+
+  killOrphanServices(): Promise<OrphanCleanup> {
+    return invokeOrThrow("killOrphanServices");
+  },
+
+  /** Resolves once the tail is stopped or replaced by another one. */
+  tailServiceLogs(
+    onLine: (line: LogLine) => void,
+    level?: LogLevel,
+    backlog?: number,
+  ): Promise<void> {
+    const channel = new Channel<LogLine>();
+    channel.onmessage = onLine;
+    return invokeOrThrow("tailServiceLogs", { onLine: channel, level, backlog });
+  },
+
+  stopServiceLogTail(): Promise<boolean> {
+    return invokeOrThrow("stopServiceLogTail");
+  },
+
   listEmbeddingBackends(): Promise<EmbeddingBackendListResponse> {
     return invokeOrThrow("embeddingBackends");
   },
@@ -74,4 +247,11 @@ export const memoryClient = {
   ): Promise<EmbeddingBackendListResponse> {
     return invokeOrThrow("setEmbeddingBackend", { payload });
   },
+
+  testEmbeddingBackend(
+    backendId: string,
+    modelOverride?: string,
+  ): Promise<EmbeddingSelfTest> {
+    return invokeOrThrow("testEmbeddingBackend", { backendId, modelOverride });
+  },
 };
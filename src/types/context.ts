@@ -13,6 +13,129 @@ export interface ContextSummary {
   kind: ContextKind;
   tags: string[];
   created_at: string;
+  updated_at?: string | null;
+  pinned?: boolean;
+  /** False once archived: kept in history, skipped by search. */
+  searchable?: boolean;
+  /** Set on chunks of a long body; points at the full record. */
+  parent_id?: string | null;
+}
+
+export interface ProjectInfo {
+  name: string;
+  count: number;
+  last_activity: string;
+}
+
+export interface StorageStats {
+  /** Null in remote mode. */
+  disk_bytes?: number | null;
+  records: number;
+  bytes_per_record?: number | null;
+}
+
+export interface DiffLine {
+  /** `removed`: only in `a`; `added`: only in `b`. */
+  op: "same" | "removed" | "added";
+  text: string;
+}
+
+export interface ContextDiff {
+  a: ContextSummary;
+  b: ContextSummary;
+  /** Null when either record is archived or their dimensions differ. */
+  similarity?: number | null;
+  same_project: boolean;
+  same_kind: boolean;
+  shared_tags: string[];
+  tags_only_in_a: string[];
+  tags_only_in_b: string[];
+  body: DiffLine[];
+}
+
+export interface RelatedProject {
+  name: string;
+  /** Jaccard similarity of the two projects' tag sets, in (0, 1]. */
+  score: number;
+  shared_tags: string[];
+}
+
+export interface ReembedProgress {
+  /** Records visited so far, including ones already on the active model. */
+  processed: number;
+  total: number;
+  /** Records whose vectors were actually replaced. */
+  reembedded: number;
+  cancelled: boolean;
+}
+
+export interface ConnectionReport {
+  ok: boolean;
+  url: string;
+  /** Round trip of the `/health` probe. */
+  latency_ms?: number | null;
+  version?: string | null;
+  total_contexts?: number | null;
+  /** `transport`: nothing answered; `protocol`: something answered, but not an ingat service. */
+  failure?: "transport" | "protocol" | null;
+  error?: string | null;
+}
+
+export interface StoreComparison {
+  in_sync: boolean;
+  /** Null when that side could not be read; see the matching `*_error`. */
+  local_count?: number | null;
+  remote_count?: number | null;
+  matching: number;
+  differing_count: number;
+  only_local_count: number;
+  only_remote_count: number;
+  /** Up to 20 ids per category; the counts are exact. */
+  differing: string[];
+  only_local: string[];
+  only_remote: string[];
+  /** e.g. the local store is held by another process. */
+  local_error?: string | null;
+  remote_error?: string | null;
+}
+
+export interface ServiceProcess {
+  pid: number;
+  /** Port the process was started to serve; null if its environment is unreadable. */
+  port: number | null;
+  /** Serves the configured port; never killed by `killOrphanServices`. */
+  current: boolean;
+}
+
+export interface OrphanCleanup {
+  found: ServiceProcess[];
+  killed: number[];
+  /** `[pid, reason]` for processes that could not be terminated. */
+  failed: [number, string][];
+}
+
+export type LogLevel = "ERROR" | "WARN" | "INFO" | "DEBUG" | "TRACE";
+
+/** One line of the mcp-service log file. */
+export interface LogLine {
+  /** Missing for lines without a level, e.g. panic messages. */
+  level: LogLevel | null;
+  line: string;
+}
+
+export type OrderField = "CreatedAt" | "UpdatedAt" | "Project" | "Kind";
+export type SortDirection = "Asc" | "Desc";
+
+/** History ordering; newest first when omitted. */
+export interface HistoryOrder {
+  order_by?: OrderField;
+  direction?: SortDirection;
+}
+
+export interface SourceMeta {
+  ide: string;
+  ide_version?: string | null;
+  workspace?: string | null;
 }
 
 export interface ContextRecord extends ContextSummary {
@@ -20,6 +143,7 @@ export interface ContextRecord extends ContextSummary {
   file_path?: string | null;
   language?: string | null;
   body: string;
+  source?: SourceMeta | null;
 }
 
 export interface QueryFilters {
@@ -27,6 +151,16 @@ export interface QueryFilters {
   kind?: ContextKind;
   tag?: string;
   ide?: string;
+  ide_version?: string;
+  groups?: FilterGroup[];
+  since?: string;
+}
+
+export type FilterField = "project" | "kind" | "tag" | "ide";
+
+export interface FilterGroup {
+  field: FilterField;
+  any_of: string[];
 }
 
 export interface IngestContextRequest {
@@ -38,12 +172,36 @@ export interface IngestContextRequest {
   body: string;
   tags: string[];
   kind: ContextKind;
+  pinned?: boolean;
+  model_override?: string;
+  source?: SourceMeta;
+  /** true waits for the disk; omitted lets the store batch the flush; false defers it (bulk imports). */
+  flush?: boolean;
 }
 
+export type SearchScope = "SummaryOnly" | "BodyOnly" | "Both";
+
 export interface SearchRequest {
   prompt: string;
   filters?: QueryFilters;
   limit?: number;
+  min_score?: number;
+  scope?: SearchScope;
+  model_override?: string;
+  /** e.g. `tag:rust|go tag:async`; replaces `filters` when set. */
+  filter_expr?: string;
+  /** Boost records carrying the requested tags instead of excluding the rest. */
+  soft_filter?: boolean;
+  /** RFC 3339; only records created after it are considered. */
+  since?: string;
+  /** Bulk export: allows `limit` up to 1000. Slow; not for interactive search. */
+  export_mode?: boolean;
+  /** File the query is about; used when the backend embeds file paths. */
+  path_hint?: string | null;
+  /** One hit per chunked record instead of one per matching chunk. */
+  collapse_by_parent?: boolean;
+  /** At most this many results from any one project; unset means no cap. */
+  max_per_project?: number | null;
 }
 
 export interface SearchResult {
@@ -55,23 +213,120 @@ export interface SearchResult {
   kind: ContextKind;
   score: number;
   created_at: string;
+  pinned?: boolean;
+  source?: SourceMeta | null;
+  /** Set on chunk hits: `body` is the matched window of this record. */
+  parent_id?: string | null;
+  /** Hits folded into this one by `collapse_by_parent`. */
+  chunk_matches?: number | null;
+}
+
+export interface DeleteContextsRequest {
+  ids: string[];
+}
+
+export interface RefineSearchRequest {
+  previous: string[];
+  filters?: QueryFilters;
+  prompt?: string | null;
 }
 
 export interface SearchResponse {
   query: string;
   results: SearchResult[];
+  scanned?: number;
+  matched_filters?: number;
+  /** Results available before `limit` cut the list (a lower bound for keyword searches). */
+  total_matched?: number;
+  /** More matches exist beyond the returned ones. */
+  has_more?: boolean;
+  /** False when no result came back. */
+  found: boolean;
+  /** Guidance for agents when nothing was found. */
+  message?: string | null;
+  /** Set when search analytics are on; pass to `recordFeedback`. */
+  query_id?: string;
 }
 
 export interface SummaryListResponse {
   items: ContextSummary[];
 }
 
+export interface QueryHistoryEntry {
+  prompt: string;
+  results: number;
+  searched_at: string;
+}
+
+export interface ChosenResult {
+  id: string;
+  /** Position in the returned results; absent when it wasn't returned. */
+  rank?: number | null;
+  chosen_at: string;
+}
+
+export interface SearchEvent {
+  query_id: string;
+  prompt: string;
+  returned: string[];
+  searched_at: string;
+  chosen: ChosenResult[];
+}
+
+export interface FeedbackStats {
+  enabled: boolean;
+  searches: number;
+  with_feedback: number;
+  choices: number;
+  /** Uses of the result at each position (index 0 = top result). */
+  rank_counts: number[];
+  outside_results: number;
+  mean_reciprocal_rank: number;
+}
+
+export interface EmbeddingComposition {
+  model: string;
+  model_version?: string | null;
+  dimensions: number;
+  count: number;
+}
+
 export interface HealthStatusResponse {
   ok: boolean;
 
   message: string;
 
   details?: string;
+
+  composition?: EmbeddingComposition[];
+
+  /** Reported by the desktop app; switches to "local" if mcp-service goes away. */
+  mode?: "local" | "remote";
+
+  /** Records re-embedded in the background by searches (`lazy_reembed`). */
+  lazy_reembedded?: number;
+
+  /** Writes are rejected while a maintenance operation runs. */
+  state?: ServiceState;
+}
+
+export type MaintenanceOp = "reembed" | "rebuild_index" | "rebuild_term_stats";
+
+export type ServiceState =
+  | { status: "idle" }
+  | { status: "maintenance"; op: MaintenanceOp };
+
+/** Payload of the `runtime-mode-changed` event. */
+export interface RuntimeModeChanged {
+  mode: "local" | "remote";
+  reason: string;
+}
+
+export interface StoreCompositionResponse {
+  active_model: string;
+  active_dimensions?: number | null;
+  total: number;
+  entries: EmbeddingComposition[];
 }
 
 export interface EmbeddingBackendOption {
@@ -83,12 +338,90 @@ export interface EmbeddingBackendOption {
   feature_gated: boolean;
 }
 
+export interface DimensionMismatch {
+  model: string;
+  configured: number;
+  stored: number[];
+  affected_records: number;
+}
+
 export interface EmbeddingBackendListResponse {
   active: string;
   options: EmbeddingBackendOption[];
+  dimension_mismatch?: DimensionMismatch | null;
+}
+
+export type VerifyProblem =
+  | { problem: "wrong_dimensions"; expected: number; actual: number }
+  | { problem: "zero_vector" }
+  | { problem: "non_finite" };
+
+export interface EmbeddingSelfTest {
+  backend: string;
+  model: string;
+  dimensions: number;
+  active_dimensions?: number | null;
+  init_ms: number;
+  embed_ms: number;
+  deterministic: boolean;
+  related_score: number;
+  unrelated_score: number;
+  problems: VerifyProblem[];
+  passed: boolean;
 }
 
 export interface UpdateEmbeddingBackendRequest {
   backend_id: string;
   model_override?: string;
 }
+
+export interface RuntimeConfigResponse {
+  version: string;
+  mode: "local" | "remote";
+  data_dir: string;
+  store_dir?: string | null;
+  active_backend: string;
+  embedding_model: string;
+  dimensions?: number | null;
+  service_host: string;
+  service_port: number;
+  features: string[];
+}
+
+export interface BuildInfo {
+  version: string;
+  features: string[];
+  /** Resolved versions from `Cargo.lock`, e.g. `{ sled: "0.34.7" }`. */
+  dependencies: Record<string, string>;
+  profile: "debug" | "release";
+  target_os: string;
+  target_arch: string;
+}
+
+export interface DataMigrationResponse {
+  from: string;
+  to: string;
+  records: number;
+}
+
+export interface ProfileInfo {
+  name: string;
+  /** Directory holding the profile's store and config. */
+  path: string;
+  active: boolean;
+}
+
+export interface BackupReport {
+  path: string;
+  records: number;
+  /** Records that failed to decode and were left out of the archive. */
+  skipped: number;
+  bytes: number;
+}
+
+export interface RestoreReport {
+  path: string;
+  records: number;
+  /** When the backup was taken. */
+  created_at: string;
+}